@@ -0,0 +1,140 @@
+//! A [`Future`] wrapper that can be aborted from elsewhere through an [`AbortHandle`].
+
+use crate::atomic_waker::AtomicWaker;
+use alloc::sync::Arc;
+use core::{
+	fmt::{self, Display, Formatter},
+	future::Future,
+	pin::Pin,
+	sync::atomic::{AtomicBool, Ordering},
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// The error returned by [`Abortable`] if it was aborted before the inner [`Future`] completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Aborted;
+
+impl Display for Aborted {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("`Future` was aborted")
+	}
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+	aborted: AtomicBool,
+	waker: AtomicWaker,
+}
+
+/// Wraps `future` into an [`Abortable`] and returns it along with an [`AbortHandle`] for it.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{
+///     abortable::{abortable, Aborted},
+///     block_on::block_on,
+/// };
+///
+/// let (future, handle) = abortable(core::future::pending::<()>());
+/// handle.clone().abort();
+/// assert_eq!(block_on(future), Err(Aborted));
+/// ```
+pub fn abortable<F: Future>(future: F) -> (Abortable<F>, AbortHandle) {
+	let shared = Arc::new(Shared::default());
+	(
+		Abortable {
+			future: Some(future),
+			shared: Arc::clone(&shared),
+		},
+		AbortHandle { shared },
+	)
+}
+
+/// Aborts an [`Abortable`]. Clones refer to the same one.
+#[derive(Debug, Clone)]
+pub struct AbortHandle {
+	shared: Arc<Shared>,
+}
+
+impl AbortHandle {
+	/// Makes the [`Abortable`] complete with [`Aborted`] next time it's polled, unless it completed already, and wakes it.
+	///
+	/// The inner [`Future`] is dropped then, on the task that polls it.
+	pub fn abort(&self) {
+		self.shared.aborted.store(true, Ordering::Release);
+		self.shared.waker.wake();
+	}
+
+	/// Whether [`.abort()`](`AbortHandle::abort`) was called on this or a clone.
+	#[must_use]
+	pub fn is_aborted(&self) -> bool {
+		self.shared.aborted.load(Ordering::Acquire)
+	}
+}
+
+/// A [`Future`] that completes with the output of `future`, or with [`Aborted`] once its [`AbortHandle`] is used.
+///
+/// Abortion is checked before each poll of the inner [`Future`], which is dropped in place on completion.
+///
+/// > Supervisors can keep the [`AbortHandle`]s of their children's [`Abortable`]s,
+/// > for example while the children themselves run in a [`PinnedFutureSet`](`crate::pinned_future_set::PinnedFutureSet`).
+#[pin_project]
+pub struct Abortable<F> {
+	/// [`None`] once completed.
+	#[pin]
+	future: Option<F>,
+	shared: Arc<Shared>,
+}
+
+impl<F> Abortable<F> {
+	/// Whether this [`Abortable`] was aborted, even if it wasn't polled since.
+	#[must_use]
+	pub fn is_aborted(&self) -> bool {
+		self.shared.aborted.load(Ordering::Acquire)
+	}
+}
+
+impl<F: Future> Future for Abortable<F> {
+	type Output = Result<F::Output, Aborted>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		let future = this
+			.future
+			.as_mut()
+			.as_pin_mut()
+			.expect("`Abortable` was previously completed.");
+
+		// Registered first, so that an abort after the check below isn't missed.
+		this.shared.waker.register(cx.waker());
+		let output = if this.shared.aborted.load(Ordering::Acquire) {
+			Poll::Ready(Err(Aborted))
+		} else {
+			future.poll(cx).map(Ok)
+		};
+		if output.is_ready() {
+			this.future.set(None);
+		}
+		output
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for Abortable<F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+}
+
+/// Shows only the progress, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F> core::fmt::Debug for Abortable<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Abortable")
+			.field("done", &self.future.is_none())
+			.field("aborted", &self.is_aborted())
+			.finish_non_exhaustive()
+	}
+}
@@ -1,9 +1,13 @@
 //! A minimal always-[`Unpin`] wrapper.
 
-use alloc::{boxed::Box, rc::Rc, sync::Arc};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, rc::Rc};
+#[cfg(feature = "alloc")]
+use core::mem;
 use core::{
 	borrow::{Borrow, BorrowMut},
-	mem,
 	ops::{Deref, DerefMut},
 	pin::Pin,
 };
@@ -65,12 +69,14 @@ impl<T> From<T> for AntiPinned<T> {
 }
 
 /// Implementing this for [`Box`] is specifically allowed.
+#[cfg(feature = "alloc")]
 impl<T: ?Sized> From<Box<T>> for Box<AntiPinned<T>> {
 	fn from(boxed: Box<T>) -> Self {
 		AntiPinned::wrap_boxed(boxed)
 	}
 }
 
+#[cfg(feature = "alloc")]
 macro_rules! boxed_conversions {
 	// There's a bit of syntax noise here. In short:
 	// `{$(…),*$(,)?} => {$(…)}` cleanly makes a macro variadic over a comma-separated pattern.
@@ -121,9 +127,14 @@ macro_rules! boxed_conversions {
 	)*};
 }
 
+#[cfg(feature = "alloc")]
 boxed_conversions! {
 	Box(wrap_boxed, unwrap_boxed, wrap_pinned_boxed),
 	Rc(wrap_rced, unwrap_rced, wrap_pinned_rced, unwrap_pinned_rced),
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+boxed_conversions! {
 	Arc(wrap_arced, unwrap_arced, wrap_pinned_arced, unwrap_pinned_arced),
 }
 
@@ -131,6 +142,7 @@ boxed_conversions! {
 /// we don't need `where T: Unpin` to unwrap the instance for free.
 ///
 /// Instead, the instance of `T` may become irreversibly pinned in the process.
+#[cfg(feature = "alloc")]
 impl<T: ?Sized> AntiPinned<T> {
 	/// Unwraps a pinned boxed [`AntiPinned<_>`] in place.
 	#[must_use]
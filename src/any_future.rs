@@ -1,27 +1,80 @@
 //! A [`Future`] that interlaces [`Future`]s, until one completes.
 
-use crate::pinned_pin::PinnedPin;
-use alloc::boxed::Box;
+#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+use crate::poll_stats::PollObserver;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use crate::waker_dispatch::WakerDispatch;
+use crate::{
+	either::{
+		Either10, Either11, Either12, Either2, Either3, Either4, Either5, Either6, Either7,
+		Either8, Either9,
+	},
+	map::Map,
+	pinned_pin::PinnedPin,
+};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
 use core::{
-	future::Future,
-	mem,
+	future::{Future, IntoFuture},
+	num::NonZeroUsize,
+	ops::DerefMut,
 	pin::Pin,
 	task::{Context, Poll},
 };
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
 use pin_project::pin_project;
-use tap::Pipe;
 
 /// Creates a [`Future`] that completes when any [`Future`] in `futures` completes.
 ///
+/// `futures` may also contain [`IntoFuture`]s, which are converted up front. See [`IntoFutures`].
+///
 /// The output is the output of that [`Future`].
-pub fn any<Fs: Futures>(futures: Fs) -> AnyFuture<Fs> {
-	AnyFuture::new(futures)
+pub fn any<Fs: IntoFutures>(futures: Fs) -> AnyFuture<Fs::Futures> {
+	AnyFuture::new(futures.into_futures())
+}
+
+/// Creates a [`Future`] that completes when any [`Future`] in the dynamically-sized `futures` completes.
+///
+/// The [`Future`]s are pinned in place behind the box, so they don't have to be [`Unpin`].
+///
+/// The output is the output of that [`Future`].
+#[cfg(feature = "alloc")]
+pub fn any_boxed<F: Future>(futures: impl Into<Box<[F]>>) -> AnyFuture<Pin<Box<[F]>>> {
+	AnyFuture::new_boxed(futures.into())
+}
+
+/// Creates a [`Future`] that completes when any [`Future`] in the borrowed `futures` completes.
+///
+/// The [`Future`]s stay where they are, so they can be raced again afterwards.
+/// (Those that completed generally mustn't be polled again, though, so replace them in place first.)
+///
+/// The output is the index of that [`Future`] along with its output.
+#[must_use]
+pub fn any_borrowed<F: Future>(futures: Pin<&mut [F]>) -> AnyIndexed<Pin<&mut [F]>> {
+	AnyIndexed(AnyFuture::new(futures))
+}
+
+/// Creates a [`Future`] that completes when any [`Future`] in the tuple `futures` completes,
+/// even if their output types differ.
+///
+/// The tuple may also contain [`IntoFuture`]s, which are converted up front.
+///
+/// The output is an [`Either2`], [`Either3`], … that contains the output of that [`Future`] at its position.
+pub fn any_heterogeneous<Fs>(futures: Fs) -> AnyFuture<<Heterogeneous<Fs> as IntoFutures>::Futures>
+where
+	Heterogeneous<Fs>: IntoFutures,
+{
+	AnyFuture::new(Heterogeneous(futures).into_futures())
 }
 
 /// A [`Future`] that completes when any [`Future`] in `futures` completes.
 ///
-/// Note that this type can't implement [`futures_core::FusedFuture`] without storing an additional completion flag,
-/// at which point composing that externally only when needed is generally better.
+/// With the `"futures-core"` feature, this implements [`FusedFuture`](`futures_core::FusedFuture`)
+/// through a completion flag, regardless of the constituent [`Future`]s.
+/// Polling it again after it completed still polls the constituents, though.
+/// Use [`AnyFuture::fused`] or wrap it in a [`Fuse`](`crate::fuse::Fuse`) to prevent that.
+/// (See [`crate::fused::Fused`] for how to skip polling terminated constituents.)
 ///
 /// > It's pretty neat that we can do this also without a macro,
 /// > since that *may* lead to lower compile times due to less total emitted code.
@@ -29,107 +82,480 @@ pub fn any<Fs: Futures>(futures: Fs) -> AnyFuture<Fs> {
 /// > It's not as versatile as a macro if we don't control storage for the composed futures, though.
 ///
 /// Compare and contrast [`crate::join_future::JoinFuture`].
+///
+/// # Fairness
+///
+/// Each poll starts at the constituent [`Future`] after the one the previous poll started at, wrapping around.
+/// That way, a [`Future`] that is frequently ready can't starve those after it.
+///
+/// Use [`AnyFuture::biased`] to always start at the first constituent [`Future`] instead.
+///
+/// (See [`AnyFuture::new_dispatching`] for a way to only poll those that were woken,
+/// and [`AnyFuture::with_budget`] for a way to bound the work done per poll.)
 #[derive(Debug)]
 #[pin_project]
-#[repr(transparent)]
 pub struct AnyFuture<Fs: Futures + ?Sized> {
-	/// We can actually implement this entire type in safe Rust (except for one constructor),
+	/// Where the next poll starts.
+	cursor: usize,
+	biased: bool,
+	budget: Option<NonZeroUsize>,
+	/// Set iff the previous poll ran out of budget, in which case the next one continues from `cursor` even if biased.
+	resuming: bool,
+	/// Set once a poll returned [`Poll::Ready`].
+	done: bool,
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	dispatch: Option<WakerDispatch>,
+	#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+	observer: Option<&'static dyn PollObserver>,
+	/// We can actually implement this entire type in safe Rust,
 	/// by using the item-pinning [`PinnedPin`] here.
 	#[pin]
 	futures: PinnedPin<Fs>,
 }
 
-impl<Fs: Futures + ?Sized> AnyFuture<Fs> {
+impl<Fs: Futures> AnyFuture<Fs> {
 	/// Creates a new instance of [`AnyFuture`] from the given `futures`.
 	#[must_use]
-	pub fn new(futures: Fs) -> Self
-	where
-		Fs: Sized,
-	{
+	pub fn new(futures: Fs) -> Self {
 		Self {
+			cursor: 0,
+			biased: false,
+			budget: None,
+			resuming: false,
+			done: false,
+			#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+			dispatch: None,
+			#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+			observer: None,
 			futures: futures.into(),
 		}
 	}
 
-	/// Creates a new instance of [`AnyFuture`] from the given `futures`.
+	/// Creates a new instance of [`AnyFuture`] from the given `futures`,
+	/// which polls each inner [`Future`] only after it was woken (and once initially).
+	///
+	/// This costs a few allocations up front, but avoids redundant polls when racing many [`Future`]s.
+	///
+	/// > See [`JoinFuture::new_dispatching`](`crate::join_future::JoinFuture::new_dispatching`),
+	/// > which works the same way.
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
 	#[must_use]
-	pub fn new_boxed(futures: Box<Fs>) -> Box<Self> {
-		// *Technically* it's legal to transmute directly here
-		// (and that's what this code should do when compiler-optimised)
-		// but I prefer to let the compiler check what's documented elsewhere, if possible.
-		let futures: Box<PinnedPin<Fs>> = futures.into();
-		unsafe {
-			//SAFETY:`AnyFuture` and `PinnedPin` have the same memory representation.
-			mem::transmute(futures)
+	pub fn new_dispatching(futures: Fs) -> Self {
+		Self {
+			dispatch: Some(WakerDispatch::new(futures.len())),
+			..Self::new(futures)
 		}
 	}
 
-	/// Creates a new instance of [`AnyFuture`] from the given `futures`.
+	/// Creates a new instance of [`AnyFuture`] from the given `futures`
+	/// that always polls them in order, starting with the first.
+	///
+	/// > This is slightly cheaper and deterministic, which is what you want if the order expresses priority.
 	#[must_use]
-	pub fn new_pinned(futures: Pin<Box<Fs>>) -> Pin<Box<Self>> {
-		unsafe {
-			//SAFETY:
-			// This is sound because `Self` is dependently `!Unpin` on `Fs`
-			// (through `PinnedPin`) and …
-			Pin::into_inner_unchecked(futures)
-				.pipe(Self::new_boxed)
-				// because we "repin" the result here:
-				// (Technically the data remained pinned of course,
-				// so we just fix up the return type.)
-				.into()
+	pub fn biased(futures: Fs) -> Self {
+		Self {
+			biased: true,
+			..Self::new(futures)
+		}
+	}
+
+	/// Limits how many constituent [`Future`]s are polled each time this [`AnyFuture`] is polled.
+	///
+	/// When the budget runs out, the [`AnyFuture`] remembers where it stopped, wakes itself and returns [`Poll::Pending`].
+	/// The next poll resumes from the following constituent [`Future`] (even if [biased](`AnyFuture::biased`)),
+	/// so all of them get their turn and the work per poll is bounded independently of their number.
+	///
+	/// > See [`JoinFuture::with_budget`](`crate::join_future::JoinFuture::with_budget`),
+	/// > which works the same way.
+	#[must_use]
+	pub fn with_budget(mut self, budget: NonZeroUsize) -> Self {
+		self.budget = Some(budget);
+		self
+	}
+
+	/// Records each poll of this [`AnyFuture`] into `observer`, along with how many constituent [`Future`]s it polled.
+	///
+	/// See [`PollStats`](`crate::poll_stats::PollStats`).
+	///
+	/// > See [`JoinFuture::observed_by`](`crate::join_future::JoinFuture::observed_by`),
+	/// > which works the same way.
+	#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+	#[must_use]
+	pub fn observed_by(mut self, observer: &'static dyn PollObserver) -> Self {
+		self.observer = Some(observer);
+		self
+	}
+}
+
+/// Boxed `futures` can be of unsized types like slices, and stay in place.
+///
+/// > These used to be transmutes of the box into a `Box<AnyFuture<Fs>>`,
+/// > but that doesn't work anymore with the fairness cursor stored alongside `futures`.
+#[cfg(feature = "alloc")]
+impl<Fs: Futures + ?Sized> AnyFuture<Pin<Box<Fs>>> {
+	/// Creates a new instance of [`AnyFuture`] from the given boxed `futures`.
+	#[must_use]
+	pub fn new_boxed(futures: Box<Fs>) -> Self {
+		Self::new(futures.into())
+	}
+
+	/// Creates a new instance of [`AnyFuture`] from the given pinned boxed `futures`.
+	#[must_use]
+	pub fn new_pinned(futures: Pin<Box<Fs>>) -> Self {
+		Self::new(futures)
+	}
+}
+
+impl<Fs: Futures + ?Sized> AnyFuture<Fs> {
+	/// Like [`Future::poll`], but also returns the index of the constituent [`Future`] that completed.
+	///
+	/// See [`AnyIndexed`] for a [`Future`] with this output.
+	pub fn poll_indexed(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<(usize, Fs::Output)> {
+		let this = self.project();
+		let mut futures = this.futures;
+		let len = Fs::len(&futures.0);
+
+		let start = if (*this.biased && !*this.resuming) || *this.cursor >= len {
+			0
+		} else {
+			*this.cursor
+		};
+		*this.cursor = start + 1;
+
+		let initial_budget = this.budget.map_or(usize::MAX, NonZeroUsize::get);
+		let mut budget = initial_budget;
+		let mut exhausted = false;
+		let mut next = start;
+		let mut ready = None;
+
+		// Returns `false` iff `index` completed or the budget ran out before it could be polled.
+		let mut poll_at = |index: usize, cx: &mut Context<'_>| {
+			if budget == 0 {
+				exhausted = true;
+				return false;
+			}
+			budget -= 1;
+			next = index + 1;
+			if let Poll::Ready(output) = Fs::poll_at(futures.as_mut(), index, cx) {
+				ready = Some((index, output));
+				return false;
+			}
+			true
+		};
+
+		#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+		if let Some(dispatch) = this.dispatch {
+			// Registering first means no wake-up can slip through between the two steps.
+			dispatch.register(cx.waker());
+			dispatch.drain_dirty_from(start, |index| {
+				index >= len || poll_at(index, &mut Context::from_waker(dispatch.waker(index)))
+			});
+		} else {
+			(start..len).chain(0..start).all(|index| poll_at(index, cx));
+		}
+		#[cfg(not(all(feature = "alloc", target_has_atomic = "ptr")))]
+		(start..len).chain(0..start).all(|index| poll_at(index, cx));
+
+		*this.resuming = exhausted;
+		#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+		if let Some(observer) = this.observer {
+			observer.record_poll(initial_budget - budget);
+		}
+		if let Some(ready) = ready {
+			*this.done = true;
+			Poll::Ready(ready)
+		} else {
+			if exhausted {
+				*this.cursor = if next < len { next } else { 0 };
+				cx.waker().wake_by_ref();
+			}
+			Poll::Pending
 		}
 	}
 }
 
+impl<Fs: Futures> AnyFuture<Fs> {
+	/// Makes this [`AnyFuture`] return [`Poll::Pending`] instead of polling its constituent [`Future`]s again once it completed.
+	#[must_use]
+	pub fn fused(self) -> FusedAnyFuture<Fs> {
+		FusedAnyFuture { inner: self }
+	}
+
+	/// Transforms this [`AnyFuture`]'s output with `f`, without a hand-written wrapper [`Future`].
+	#[must_use]
+	pub fn map<F: FnOnce(Fs::Output) -> T, T>(self, f: F) -> Map<Self, F> {
+		Map::new(self, f)
+	}
+}
+
 impl<Fs: Futures + Sized> Future for AnyFuture<Fs> {
 	type Output = Fs::Output;
 
 	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		Fs::poll(self.project().futures, cx)
+		self.poll_indexed(cx).map(|(_, output)| output)
+	}
+}
+
+/// An [`AnyFuture`] that stays pending once completed. See [`AnyFuture::fused`].
+///
+/// Once completed, it returns [`Poll::Pending`] without polling the constituent [`Future`]s again,
+/// which is fine inside a combinator that is driven by its other constituents (or a `select!`-style loop).
+#[derive(Debug)]
+#[pin_project]
+pub struct FusedAnyFuture<Fs: Futures> {
+	#[pin]
+	inner: AnyFuture<Fs>,
+}
+
+impl<Fs: Futures> FusedAnyFuture<Fs> {
+	/// Whether this [`FusedAnyFuture`] has completed.
+	#[must_use]
+	pub fn is_terminated(&self) -> bool {
+		self.inner.done
+	}
+
+	/// Gives pinning exclusive access to the wrapped [`AnyFuture`].
+	#[must_use]
+	pub fn inner_pin_mut(self: Pin<&mut Self>) -> Pin<&mut AnyFuture<Fs>> {
+		self.project().inner
+	}
+}
+
+impl<Fs: Futures> Future for FusedAnyFuture<Fs> {
+	type Output = Fs::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.inner.done {
+			return Poll::Pending;
+		}
+		self.project().inner.poll(cx)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for FusedAnyFuture<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.inner.done
+	}
+}
+
+/// Creates a [`Future`] that completes when any [`Future`] in `futures` completes.
+///
+/// `futures` may also contain [`IntoFuture`]s, which are converted up front. See [`IntoFutures`].
+///
+/// The output is the index of that [`Future`] along with its output.
+pub fn any_indexed<Fs: IntoFutures>(futures: Fs) -> AnyIndexed<Fs::Futures> {
+	AnyIndexed(AnyFuture::new(futures.into_futures()))
+}
+
+/// An [`AnyFuture`] that also outputs the index of the [`Future`] that completed.
+///
+/// > This is useful for select-style control flow over arrays of [`Future`]s with the same type.
+#[derive(Debug)]
+#[pin_project]
+#[repr(transparent)]
+pub struct AnyIndexed<Fs: Futures + ?Sized>(#[pin] pub AnyFuture<Fs>);
+
+impl<Fs: Futures> AnyIndexed<Fs> {
+	/// Transforms this [`AnyIndexed`]'s output with `f`, without a hand-written wrapper [`Future`].
+	#[must_use]
+	pub fn map<F: FnOnce((usize, Fs::Output)) -> T, T>(self, f: F) -> Map<Self, F> {
+		Map::new(self, f)
+	}
+}
+
+impl<Fs: Futures + Sized> Future for AnyIndexed<Fs> {
+	type Output = (usize, Fs::Output);
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.project().0.poll_indexed(cx)
 	}
 }
 
 /// Types that can be used with [`AnyFuture`].
 ///
-/// Compare and contrast [`crate::join_future::futures`].
+/// Compare and contrast [`crate::join_future::Futures`].
 pub trait Futures {
 	/// The combined output type.
 	type Output;
 
-	/// Like [`Future::poll`], with [`PinnedPin`] just for convenience.
+	/// The number of constituent [`Future`]s.
+	fn len(&self) -> usize;
+
+	/// Whether there are no constituent [`Future`]s.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Like [`Future::poll`], but for only the constituent [`Future`] at `index`,
+	/// with [`PinnedPin`] just for convenience.
 	///
 	/// This *should* use `self`, but that's not here supported by Rust (as of Rust 1.57).
-	fn poll(futures: Pin<&mut PinnedPin<Self>>, cx: &mut Context<'_>) -> Poll<Self::Output>;
+	///
+	/// # Panics
+	///
+	/// Iff `index >= futures.len()`.
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output>;
 }
 
-impl<F: Future, const N: usize> Futures for [F; N] {
-	type Output = F::Output;
+/// Types that can be converted into [`Futures`] up front, like collections of [`IntoFuture`]s.
+///
+/// This is what [`any`], [`any_indexed`] and [`any_heterogeneous`] accept,
+/// so that for example builder types can be raced directly.
+///
+/// > There can't be a blanket implementation for all [`Futures`], since that would overlap with the ones for collections of [`IntoFuture`]s.
+/// > Instead, the remaining [`Futures`] implement this trait as identity, where that makes sense.
+pub trait IntoFutures {
+	/// The [`Futures`] this converts into.
+	type Futures: Futures;
+
+	/// Converts `self` into [`Futures`], converting each [`IntoFuture`] in turn.
+	fn into_futures(self) -> Self::Futures;
+}
+
+impl<F: IntoFuture, const N: usize> IntoFutures for [F; N] {
+	type Futures = [F::IntoFuture; N];
+
+	fn into_futures(self) -> Self::Futures {
+		self.map(IntoFuture::into_future)
+	}
+}
+
+impl IntoFutures for () {
+	type Futures = ();
 
-	fn poll(futures: Pin<&mut PinnedPin<Self>>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		for future in futures {
-			if let ready @ Poll::Ready(_) = future.poll(cx) {
-				return ready;
+	fn into_futures(self) -> Self::Futures {}
+}
+
+impl<F0: IntoFuture> IntoFutures for (F0,) {
+	type Futures = (F0::IntoFuture,);
+
+	fn into_futures(self) -> Self::Futures {
+		(self.0.into_future(),)
+	}
+}
+
+impl<F0, F1> IntoFutures for (F0, F1)
+where
+	F0: IntoFuture,
+	F1: IntoFuture<Output = F0::Output>,
+{
+	type Futures = (F0::IntoFuture, F1::IntoFuture);
+
+	fn into_futures(self) -> Self::Futures {
+		(self.0.into_future(), self.1.into_future())
+	}
+}
+
+impl<F: IntoFuture> IntoFutures for Option<F> {
+	type Futures = Option<F::IntoFuture>;
+
+	fn into_futures(self) -> Self::Futures {
+		self.map(IntoFuture::into_future)
+	}
+}
+
+impl<A, B> IntoFutures for Either2<A, B>
+where
+	A: IntoFutures,
+	B: IntoFutures,
+	B::Futures: Futures<Output = <A::Futures as Futures>::Output>,
+{
+	type Futures = Either2<A::Futures, B::Futures>;
+
+	fn into_futures(self) -> Self::Futures {
+		match self {
+			Either2::A(a) => Either2::A(a.into_futures()),
+			Either2::B(b) => Either2::B(b.into_futures()),
+		}
+	}
+}
+
+macro_rules! identity_into_futures {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$(#[$attr:meta])*
+		impl<$($T:ident),*> for $Fs:ty $(where $($bound:tt)*)?
+	),*$(,)?} => {$(
+		$(#[$attr])*
+		impl<$($T),*> IntoFutures for $Fs
+		where
+			$Fs: Futures,
+			$($($bound)*)?
+		{
+			type Futures = Self;
+
+			fn into_futures(self) -> Self::Futures {
+				self
 			}
 		}
-		Poll::Pending
+	)*};
+}
+
+identity_into_futures! {
+	#[cfg(feature = "alloc")]
+	impl<F> for Box<[F]>,
+	#[cfg(feature = "alloc")]
+	impl<F> for Vec<F>,
+	impl<P> for Pin<P>,
+	impl<Fs> for PinnedPin<Fs>,
+}
+
+impl<F: Future, const N: usize> Futures for [F; N] {
+	type Output = F::Output;
+
+	fn len(&self) -> usize {
+		N
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		// The unsizing coercion also works through `Pin<_>` and `PinnedPin<_>`.
+		let futures: Pin<&mut PinnedPin<[F]>> = futures;
+		PinnedPin::get_mut(futures, index)
+			.unwrap_or_else(|| {
+				panic!(
+					"Index {} out of bounds for an array of length {}.",
+					index, N
+				)
+			})
+			.poll(cx)
 	}
 }
 
 /// The implementation for slices is the same as for arrays,
 /// but the compiled result is different:
 ///
-/// The `.poll` method won't be monomorphised for each slice length (so the output text size is smaller),
-/// but in exchange the loop can't be unrolled (as well), which means this will run slightly slower in SOME cases.
+/// The `.poll_at` method won't be monomorphised for each slice length (so the output text size is smaller),
+/// but in exchange [`AnyFuture`]'s loop can't be unrolled (as well), which means this will run slightly slower in SOME cases.
 impl<F: Future> Futures for [F] {
 	type Output = F::Output;
 
-	fn poll(futures: Pin<&mut PinnedPin<Self>>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		for future in futures {
-			if let ready @ Poll::Ready(_) = future.poll(cx) {
-				return ready;
-			}
-		}
-		Poll::Pending
+	fn len(&self) -> usize {
+		<[F]>::len(self)
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		let len = futures.0.len();
+		PinnedPin::get_mut(futures, index)
+			.unwrap_or_else(|| {
+				panic!(
+					"Index {} out of bounds for a slice of length {}.",
+					index, len
+				)
+			})
+			.poll(cx)
 	}
 }
 
@@ -137,17 +563,36 @@ impl Futures for () {
 	/// This *should* be `!`, but the never type isn't stable yet as of Rust 1.57.
 	type Output = core::convert::Infallible;
 
-	fn poll(_: Pin<&mut PinnedPin<Self>>, _: &mut Context<'_>) -> Poll<Self::Output> {
-		Poll::Pending
+	fn len(&self) -> usize {
+		0
+	}
+
+	fn poll_at(
+		_: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		_: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		panic!("Index {} out of bounds for `()`.", index)
 	}
 }
 
 impl<F0: Future> Futures for (F0,) {
 	type Output = F0::Output;
 
-	fn poll(futures: Pin<&mut PinnedPin<Self>>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+	fn len(&self) -> usize {
+		1
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
 		//TODO: Implement that projection in [`PinnedPin`] instead.
-		unsafe { futures.map_unchecked_mut(|this| &mut this.0 .0) }.poll(cx)
+		match index {
+			0 => unsafe { futures.map_unchecked_mut(|this| &mut this.0 .0) }.poll(cx),
+			_ => panic!("Index {} out of bounds for a 1-tuple.", index),
+		}
 	}
 }
 
@@ -158,20 +603,293 @@ where
 {
 	type Output = F0::Output;
 
-	fn poll(mut futures: Pin<&mut PinnedPin<Self>>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+	fn len(&self) -> usize {
+		2
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
 		//TODO: Implement that projection in [`PinnedPin`] instead.
-		if let ready @ Poll::Ready(_) =
-			unsafe { futures.as_mut().map_unchecked_mut(|this| &mut this.0 .0) }.poll(cx)
-		{
-			return ready;
+		match index {
+			0 => unsafe { futures.map_unchecked_mut(|this| &mut this.0 .0) }.poll(cx),
+			1 => unsafe { futures.map_unchecked_mut(|this| &mut this.0 .1) }.poll(cx),
+			_ => panic!("Index {} out of bounds for a 2-tuple.", index),
 		}
-		if let ready @ Poll::Ready(_) =
-			unsafe { futures.map_unchecked_mut(|this| &mut this.0 .1) }.poll(cx)
-		{
-			return ready;
-		}
-		Poll::Pending
 	}
 }
 
 // etc.
+
+/// Only for [`Unpin`] [`Future`]s, as [`Futures::poll_at`] can't guarantee that the box isn't unpinned afterwards.
+///
+/// Use [`any_boxed`] for a version that pins the [`Future`]s instead.
+#[cfg(feature = "alloc")]
+impl<F: Future + Unpin> Futures for Box<[F]> {
+	type Output = F::Output;
+
+	fn len(&self) -> usize {
+		<[F]>::len(self)
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		let len = futures.0.len();
+		Pin::new(
+			Pin::into_inner(futures)
+				.0
+				.get_mut(index)
+				.unwrap_or_else(|| {
+					panic!(
+						"Index {} out of bounds for a slice of length {}.",
+						index, len
+					)
+				}),
+		)
+		.poll(cx)
+	}
+}
+
+/// Only for [`Unpin`] [`Future`]s, as [`Futures::poll_at`] can't guarantee that the [`Vec`] isn't unpinned afterwards.
+///
+/// Use [`any_boxed`] for a version that pins the [`Future`]s instead.
+#[cfg(feature = "alloc")]
+impl<F: Future + Unpin> Futures for Vec<F> {
+	type Output = F::Output;
+
+	fn len(&self) -> usize {
+		Vec::len(self)
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		let len = futures.0.len();
+		Pin::new(
+			Pin::into_inner(futures)
+				.0
+				.get_mut(index)
+				.unwrap_or_else(|| {
+					panic!(
+						"Index {} out of bounds for a `Vec` of length {}.",
+						index, len
+					)
+				}),
+		)
+		.poll(cx)
+	}
+}
+
+/// Pinned pointers to [`Futures`] are [`Futures`] themselves, which is how [`AnyFuture::new_pinned`] works.
+///
+/// This includes pinning references like `Pin<&mut [F]>`, to race [`Future`]s in long-lived (for example `static`) storage
+/// repeatedly without moving them into the [`AnyFuture`]. See [`any_borrowed`].
+impl<P> Futures for Pin<P>
+where
+	P: DerefMut + Unpin,
+	P::Target: Futures,
+{
+	type Output = <P::Target as Futures>::Output;
+
+	fn len(&self) -> usize {
+		(**self).len()
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		// `Pin<P>` is `Unpin` here, so this doesn't need any `unsafe`.
+		let futures = PinnedPin::from_pin_mut(Pin::into_inner(futures).0.as_mut());
+		<P::Target as Futures>::poll_at(futures, index, cx)
+	}
+}
+
+/// Item-pinning [`PinnedPin`]s of [`Futures`] are [`Futures`] themselves,
+/// so that for example `Pin<&mut PinnedPin<[F]>>` can be raced.
+impl<Fs: Futures + ?Sized> Futures for PinnedPin<Fs> {
+	type Output = Fs::Output;
+
+	fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		Fs::poll_at(futures.as_mut_pinned(), index, cx)
+	}
+}
+
+/// An optional [`Future`], which counts as no constituent [`Future`]s at all if absent.
+///
+/// On its own, that means the [`AnyFuture`] never completes if it's [`None`].
+impl<F: Future> Futures for Option<F> {
+	type Output = F::Output;
+
+	fn len(&self) -> usize {
+		usize::from(self.is_some())
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		match (index, futures.as_mut_pinned().as_pin_mut()) {
+			(0, Some(future)) => future.poll(cx),
+			_ => panic!("Index {} out of bounds for an optional `Future`.", index),
+		}
+	}
+}
+
+/// Races the constituent [`Future`]s of whichever side is active.
+impl<A, B> Futures for Either2<A, B>
+where
+	A: Futures,
+	B: Futures<Output = A::Output>,
+{
+	type Output = A::Output;
+
+	fn len(&self) -> usize {
+		match self {
+			Either2::A(a) => a.len(),
+			Either2::B(b) => b.len(),
+		}
+	}
+
+	fn poll_at(
+		futures: Pin<&mut PinnedPin<Self>>,
+		index: usize,
+		cx: &mut Context<'_>,
+	) -> Poll<Self::Output> {
+		unsafe {
+			//SAFETY: Plain structural pin projection.
+			match &mut Pin::into_inner_unchecked(futures).0 {
+				Either2::A(a) => A::poll_at(Pin::new_unchecked(PinnedPin::from_mut(a)), index, cx),
+				Either2::B(b) => B::poll_at(Pin::new_unchecked(PinnedPin::from_mut(b)), index, cx),
+			}
+		}
+	}
+}
+
+/// Marks a tuple of [`Future`]s with (possibly) different output types, to be raced by [`AnyFuture`].
+///
+/// The combined output is an [`Either2`], [`Either3`], … with a variant for each position.
+///
+/// > This needs a separate wrapper since the plain tuples already race [`Future`]s with a shared output type.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Heterogeneous<Fs>(pub Fs);
+
+macro_rules! heterogeneous_futures {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$Either:ident($($F:ident.$index:tt),*$(,)?)
+	),*$(,)?} => {$(
+		impl<$($F: Future),*> Futures for Heterogeneous<($($F,)*)> {
+			type Output = $Either<$($F::Output),*>;
+
+			fn len(&self) -> usize {
+				[$($index),*].len()
+			}
+
+			fn poll_at(
+				futures: Pin<&mut PinnedPin<Self>>,
+				index: usize,
+				cx: &mut Context<'_>,
+			) -> Poll<Self::Output> {
+				match index {
+					$($index => unsafe {
+						//SAFETY: Plain structural pin projection.
+						futures.map_unchecked_mut(|this| &mut (this.0).0.$index)
+					}.poll(cx).map($Either::$F),)*
+					_ => panic!("Index {} out of bounds for a {}-tuple.", index, [$($index),*].len()),
+				}
+			}
+		}
+
+		impl<$($F: IntoFuture),*> IntoFutures for Heterogeneous<($($F,)*)> {
+			type Futures = Heterogeneous<($($F::IntoFuture,)*)>;
+
+			fn into_futures(self) -> Self::Futures {
+				Heterogeneous(($((self.0).$index.into_future(),)*))
+			}
+		}
+	)*};
+}
+
+heterogeneous_futures! {
+	Either2(A.0, B.1),
+	Either3(A.0, B.1, C.2),
+	Either4(A.0, B.1, C.2, D.3),
+	Either5(A.0, B.1, C.2, D.3, E.4),
+	Either6(A.0, B.1, C.2, D.3, E.4, F.5),
+	Either7(A.0, B.1, C.2, D.3, E.4, F.5, G.6),
+	Either8(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7),
+	Either9(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8),
+	Either10(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9),
+	Either11(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10),
+	Either12(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10, L.11),
+}
+
+/// Like [`JoinFuture`](`crate::join_future::JoinFuture`), this only reflects whether the [`AnyFuture`] itself completed.
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for AnyFuture<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for AnyIndexed<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.0.done
+	}
+}
+
+#[cfg(all(test, feature = "futures-core"))]
+mod tests {
+	use super::AnyFuture;
+	use crate::waker::noop_context;
+	use core::{
+		future::{ready, Future},
+		pin::Pin,
+		task::Poll,
+	};
+	use futures_core::FusedFuture;
+
+	#[test]
+	fn terminated_constituents_dont_terminate_the_whole() {
+		let mut done = AnyFuture::new([ready(0)]);
+		assert_eq!(
+			Pin::new(&mut done).poll(&mut noop_context()),
+			Poll::Ready(0)
+		);
+		assert!(done.is_terminated());
+
+		let mut any = AnyFuture::biased([AnyFuture::new([ready(1)]), done]);
+		assert!(!any.is_terminated());
+		assert_eq!(Pin::new(&mut any).poll(&mut noop_context()), Poll::Ready(1));
+		assert!(any.is_terminated());
+	}
+
+	#[test]
+	fn fused_stays_pending() {
+		let mut any = AnyFuture::new((ready(0),)).fused();
+		assert!(!any.is_terminated());
+		assert_eq!(Pin::new(&mut any).poll(&mut noop_context()), Poll::Ready(0));
+		assert!(FusedFuture::is_terminated(&any));
+		assert_eq!(Pin::new(&mut any).poll(&mut noop_context()), Poll::Pending);
+	}
+}
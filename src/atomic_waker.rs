@@ -0,0 +1,243 @@
+//! A [`Waker`] slot that can be registered and woken concurrently.
+
+use core::{
+	cell::UnsafeCell,
+	hint,
+	sync::atomic::{AtomicUsize, Ordering},
+	task::Waker,
+};
+
+const WAITING: usize = 0;
+const REGISTERING: usize = 0b01;
+const WAKING: usize = 0b10;
+
+/// A [`Waker`] slot with a single registering side and any number of waking sides.
+///
+/// The state is a tiny lock: Whichever side flips it away from `WAITING` gets to access the slot.
+/// If a wake arrives while a [`Waker`] is being registered, the registering side wakes on its behalf.
+///
+/// This doesn't need a global allocator, but it does need pointer-sized atomic compare-and-swap,
+/// so it's only available where `target_has_atomic = "ptr"`.
+///
+/// # Usage
+///
+/// The waiting side must [`.register(…)`](`AtomicWaker::register`) *before* checking its condition,
+/// and the signalling side must update the condition *before* calling [`.wake()`](`AtomicWaker::wake`).
+/// That way, a signal is never missed between the check and the registration.
+///
+/// The [`Waker`] is taken out of the slot before it's woken, so that it can re-register itself from within `wake`.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::{
+///     sync::atomic::{AtomicBool, Ordering},
+///     task::Poll,
+/// };
+/// use unpin_choices_dsa::{atomic_waker::AtomicWaker, waker::noop_context};
+///
+/// static WAKER: AtomicWaker = AtomicWaker::new();
+/// static SIGNALLED: AtomicBool = AtomicBool::new(false);
+///
+/// let mut cx = noop_context();
+/// let mut poll_signal = || {
+///     WAKER.register(cx.waker());
+///     if SIGNALLED.load(Ordering::Acquire) {
+///         Poll::Ready(())
+///     } else {
+///         Poll::Pending
+///     }
+/// };
+///
+/// assert_eq!(poll_signal(), Poll::Pending);
+///
+/// SIGNALLED.store(true, Ordering::Release);
+/// WAKER.wake();
+///
+/// assert_eq!(poll_signal(), Poll::Ready(()));
+/// ```
+pub struct AtomicWaker {
+	state: AtomicUsize,
+	waker: UnsafeCell<Option<Waker>>,
+}
+
+/// # Safety notes
+///
+/// Access to `waker` is synchronised through `state`.
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+	/// Creates a new empty [`AtomicWaker`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			state: AtomicUsize::new(WAITING),
+			waker: UnsafeCell::new(None),
+		}
+	}
+
+	/// Registers `waker` to be woken by the next call to [`.wake()`](`AtomicWaker::wake`).
+	///
+	/// Concurrent calls to this method don't panic, but only one of them will be registered.
+	pub fn register(&self, waker: &Waker) {
+		match self
+			.state
+			.compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire)
+			.unwrap_or_else(|state| state)
+		{
+			WAITING => unsafe {
+				//SAFETY: The `REGISTERING` flag gives exclusive access to the slot.
+				let slot = &mut *self.waker.get();
+				match slot {
+					Some(old) if old.will_wake(waker) => (),
+					_ => *slot = Some(waker.clone()),
+				}
+
+				if let Err(state) = self.state.compare_exchange(
+					REGISTERING,
+					WAITING,
+					Ordering::AcqRel,
+					Ordering::Acquire,
+				) {
+					// A wake arrived in the meantime, which couldn't take the waker.
+					debug_assert_eq!(state, REGISTERING | WAKING);
+					let waker = slot.take();
+					self.state.swap(WAITING, Ordering::AcqRel);
+					if let Some(waker) = waker {
+						waker.wake();
+					}
+				}
+			},
+			WAKING => {
+				// A wake is in progress and may or may not see the new waker, so wake it directly.
+				waker.wake_by_ref();
+				hint::spin_loop();
+			}
+			state => {
+				// Concurrent registration, which is a caller error but not unsound.
+				debug_assert!(state == REGISTERING || state == REGISTERING | WAKING);
+			}
+		}
+	}
+
+	/// Wakes the registered [`Waker`], if any, and clears the slot.
+	pub fn wake(&self) {
+		if let Some(waker) = self.take() {
+			waker.wake()
+		}
+	}
+
+	/// Takes the registered [`Waker`], if any and if the slot isn't currently being accessed otherwise.
+	#[must_use]
+	pub fn take(&self) -> Option<Waker> {
+		match self.state.fetch_or(WAKING, Ordering::AcqRel) {
+			WAITING => {
+				let waker = unsafe {
+					//SAFETY: The `WAKING` flag gives exclusive access to the slot.
+					(*self.waker.get()).take()
+				};
+				self.state.fetch_and(!WAKING, Ordering::Release);
+				waker
+			}
+			// Either another wake is already in progress, or the registering side will wake.
+			_ => None,
+		}
+	}
+}
+
+impl Default for AtomicWaker {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl core::fmt::Debug for AtomicWaker {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("AtomicWaker").finish_non_exhaustive()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::AtomicWaker;
+	use crate::waker::counting_waker;
+	use core::{
+		ptr,
+		sync::atomic::{AtomicUsize, Ordering},
+		task::{RawWaker, RawWakerVTable, Waker},
+	};
+
+	#[test]
+	fn register_after_wake_still_wakes() {
+		static WAKES: AtomicUsize = AtomicUsize::new(0);
+		let waker = AtomicWaker::new();
+
+		waker.register(&counting_waker(&WAKES));
+		waker.wake();
+		assert_eq!(WAKES.load(Ordering::Acquire), 1);
+
+		// The slot is empty now, so this wake does nothing...
+		waker.wake();
+		assert_eq!(WAKES.load(Ordering::Acquire), 1);
+
+		// ...but it's usable again after registering.
+		waker.register(&counting_waker(&WAKES));
+		waker.wake();
+		assert_eq!(WAKES.load(Ordering::Acquire), 2);
+	}
+
+	#[test]
+	fn re_registering_the_same_waker_does_not_clone_it() {
+		static CLONES: AtomicUsize = AtomicUsize::new(0);
+		static VTABLE: RawWakerVTable = RawWakerVTable::new(
+			|data| {
+				CLONES.fetch_add(1, Ordering::AcqRel);
+				RawWaker::new(data, &VTABLE)
+			},
+			|_| (),
+			|_| (),
+			|_| (),
+		);
+		let counted = unsafe {
+			//SAFETY: The vtable functions don't access `data`.
+			Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE))
+		};
+
+		let waker = AtomicWaker::new();
+		waker.register(&counted);
+		assert_eq!(CLONES.load(Ordering::Acquire), 1);
+		waker.register(&counted);
+		waker.register(&counted);
+		assert_eq!(CLONES.load(Ordering::Acquire), 1);
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn a_wake_racing_a_register_is_not_lost() {
+		extern crate std;
+		use core::sync::atomic::AtomicBool;
+		use std::{sync::Arc, thread};
+
+		static WAKES: AtomicUsize = AtomicUsize::new(0);
+		for _ in 0..1000 {
+			let waker = Arc::new(AtomicWaker::new());
+			let signalled = Arc::new(AtomicBool::new(false));
+			let wakes_before = WAKES.load(Ordering::Acquire);
+
+			let signaller = thread::spawn({
+				let waker = waker.clone();
+				let signalled = signalled.clone();
+				move || {
+					signalled.store(true, Ordering::Release);
+					waker.wake();
+				}
+			});
+			// As in the usage notes: register first, then check.
+			waker.register(&counting_waker(&WAKES));
+			let seen = signalled.load(Ordering::Acquire);
+			signaller.join().unwrap();
+
+			assert!(seen || WAKES.load(Ordering::Acquire) > wakes_before);
+		}
+	}
+}
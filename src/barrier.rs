@@ -0,0 +1,159 @@
+//! An asynchronous barrier, which releases a group of tasks once all of them have arrived.
+
+use crate::{spin_lock::SpinLock, waker_set::WakerSet};
+use core::{
+	future::Future,
+	mem,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Lets a fixed number of tasks [wait](`Barrier::wait`) until all of them have arrived, then releases them together.
+///
+/// It can be reused, as each completed group starts a new one.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{barrier::Barrier, block_on::block_on, join_future::join};
+///
+/// let barrier = Barrier::new(2);
+/// let (a, b) = block_on(join((barrier.wait(), barrier.wait())));
+/// assert!(a.is_leader() != b.is_leader());
+/// ```
+///
+/// > Waiting tasks are tracked in a [`WakerSet`]. Without `"alloc"`, more than a few at once cause spurious wake-ups.
+pub struct Barrier {
+	n: usize,
+	state: SpinLock<State>,
+}
+
+struct State {
+	arrived: usize,
+	/// Incremented each time a group is released.
+	generation: u64,
+	wakers: WakerSet,
+}
+
+impl Barrier {
+	/// Creates a new [`Barrier`] that releases groups of `n` tasks.
+	///
+	/// `n == 0` behaves like `n == 1`, so every [`.wait()`](`Barrier::wait`) completes right away.
+	#[must_use]
+	pub const fn new(n: usize) -> Self {
+		Self {
+			n,
+			state: SpinLock::new(State {
+				arrived: 0,
+				generation: 0,
+				wakers: WakerSet::new(),
+			}),
+		}
+	}
+
+	/// Returns a [`Future`] that arrives at this [`Barrier`] when first polled, and completes once the group is complete.
+	///
+	/// Dropping it before then withdraws it from the group again.
+	#[must_use]
+	pub fn wait(&self) -> Wait<'_> {
+		Wait {
+			barrier: self,
+			state: WaitState::Idle,
+		}
+	}
+}
+
+impl core::fmt::Debug for Barrier {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let arrived = self.state.lock().arrived;
+		f.debug_struct("Barrier")
+			.field("n", &self.n)
+			.field("arrived", &arrived)
+			.finish_non_exhaustive()
+	}
+}
+
+/// The output of [`Wait`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BarrierWaitResult {
+	is_leader: bool,
+}
+
+impl BarrierWaitResult {
+	/// Whether this was the last task to arrive, which is true for exactly one in each group.
+	#[must_use]
+	pub fn is_leader(&self) -> bool {
+		self.is_leader
+	}
+}
+
+/// A [`Future`] that completes once all tasks of its group have arrived. See [`Barrier::wait`].
+#[derive(Debug)]
+pub struct Wait<'a> {
+	barrier: &'a Barrier,
+	state: WaitState,
+}
+
+#[derive(Debug)]
+enum WaitState {
+	Idle,
+	/// Arrived during this generation.
+	Waiting(u64),
+	Done,
+}
+
+impl Future for Wait<'_> {
+	type Output = BarrierWaitResult;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut state = self.barrier.state.lock();
+		match self.state {
+			WaitState::Idle => {
+				state.arrived += 1;
+				if state.arrived >= self.barrier.n {
+					state.arrived = 0;
+					state.generation += 1;
+					let mut wakers = mem::take(&mut state.wakers);
+					drop(state);
+					self.state = WaitState::Done;
+					wakers.wake_all();
+					return Poll::Ready(BarrierWaitResult { is_leader: true });
+				}
+				let generation = state.generation;
+				self.state = WaitState::Waiting(generation);
+			}
+			WaitState::Waiting(generation) => {
+				if state.generation != generation {
+					drop(state);
+					self.state = WaitState::Done;
+					return Poll::Ready(BarrierWaitResult { is_leader: false });
+				}
+			}
+			WaitState::Done => panic!("`Wait` was previously completed."),
+		}
+		state.wakers.insert(cx.waker());
+		Poll::Pending
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl FusedFuture for Wait<'_> {
+	fn is_terminated(&self) -> bool {
+		matches!(self.state, WaitState::Done)
+	}
+}
+
+impl Drop for Wait<'_> {
+	/// Withdraws from the group if it wasn't released yet.
+	///
+	/// > The [`Waker`](`core::task::Waker`) stays registered, as it may be shared with another [`Wait`] in the same task.
+	fn drop(&mut self) {
+		if let WaitState::Waiting(generation) = self.state {
+			let mut state = self.barrier.state.lock();
+			if state.generation == generation {
+				state.arrived -= 1;
+			}
+		}
+	}
+}
@@ -0,0 +1,40 @@
+//! A spinning `block_on` that needs neither the standard library nor a global allocator.
+
+use crate::waker::noop_context;
+use core::{future::Future, hint, pin::Pin, task::Poll};
+
+/// Drives `future` to completion on the current thread by polling it in a busy loop.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::block_on::block_on;
+///
+/// assert_eq!(block_on(async { 1 + 1 }), 2);
+/// ```
+///
+/// > This is fine for doctests and start-up code, but burns CPU time while waiting.
+/// > See [`block_on_with`] to sleep between polls instead.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+	block_on_with(future, hint::spin_loop)
+}
+
+/// Drives `future` to completion on the current thread, calling `relax` after each poll that returns [`Poll::Pending`].
+///
+/// The [`Waker`](`core::task::Waker`) passed to `future` does nothing,
+/// so `relax` decides when to poll again. On microcontrollers, this is usually a wait-for-interrupt instruction,
+/// like `cortex_m::asm::wfi`, since only interrupts can make progress possible then.
+pub fn block_on_with<F: Future>(future: F, mut relax: impl FnMut()) -> F::Output {
+	let mut future = future;
+	let mut future = unsafe {
+		//SAFETY: Shadowed, so it can't be moved again.
+		Pin::new_unchecked(&mut future)
+	};
+
+	let mut cx = noop_context();
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+			return output;
+		}
+		relax()
+	}
+}
@@ -0,0 +1,398 @@
+//! A broadcast channel, where each [`Receiver`] reads every value from its own position in a shared ring buffer.
+//!
+//! Sending never waits. Instead, the oldest value is overwritten once the buffer is full,
+//! and [`Receiver`]s that fall behind by more than its capacity are told how many values they missed.
+
+use crate::{
+	spin_lock::SpinLock,
+	state_ref::StateRef,
+	waiter_list::{Waiter, WaiterList},
+};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::{
+	fmt::{self, Display, Formatter},
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+/// The error returned by [`Receiver::recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecvError {
+	/// The [`Receiver`] fell behind and missed this many values, which were overwritten.
+	///
+	/// It continues with the oldest value still available.
+	Lagged(u64),
+	/// All [`Sender`]s were dropped and the [`Receiver`] has seen all values.
+	Closed,
+}
+
+impl Display for RecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			RecvError::Lagged(missed) => write!(f, "the `Receiver` missed {missed} values"),
+			RecvError::Closed => f.write_str("all `Sender`s were dropped"),
+		}
+	}
+}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TryRecvError {
+	/// There is no new value yet.
+	Empty,
+	/// See [`RecvError::Lagged`].
+	Lagged(u64),
+	/// See [`RecvError::Closed`].
+	Closed,
+}
+
+impl Display for TryRecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			TryRecvError::Empty => f.write_str("there is no new value yet"),
+			TryRecvError::Lagged(missed) => write!(f, "the `Receiver` missed {missed} values"),
+			TryRecvError::Closed => f.write_str("all `Sender`s were dropped"),
+		}
+	}
+}
+
+/// The state of a broadcast channel that keeps the last `N` values, which [`Sender`]s and [`Receiver`]s borrow.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{
+///     block_on::block_on,
+///     broadcast::{Broadcast, RecvError},
+/// };
+///
+/// let mut broadcast = Broadcast::<_, 2>::new();
+/// let (sender, mut a) = broadcast.split();
+/// let mut b = sender.subscribe();
+///
+/// sender.send(1);
+/// assert_eq!(block_on(a.recv()), Ok(1));
+///
+/// sender.send(2);
+/// sender.send(3);
+/// assert_eq!(block_on(b.recv()), Err(RecvError::Lagged(1)));
+/// assert_eq!(block_on(b.recv()), Ok(2));
+///
+/// drop(sender);
+/// assert_eq!(block_on(a.recv()), Ok(2));
+/// assert_eq!(block_on(a.recv()), Ok(3));
+/// assert_eq!(block_on(a.recv()), Err(RecvError::Closed));
+/// ```
+pub struct Broadcast<T, const N: usize> {
+	state: SpinLock<State<T, N>>,
+}
+
+struct State<T, const N: usize> {
+	/// The value with sequence number `s` is at `buffer[s % N]`, if it's not older than `N`.
+	buffer: [Option<T>; N],
+	/// The sequence number of the next value to be sent.
+	next: u64,
+	senders: usize,
+	waiters: WaiterList,
+}
+
+impl<T, const N: usize> State<T, N> {
+	/// The sequence number of the oldest value still available.
+	fn oldest(&self) -> u64 {
+		self.next.saturating_sub(N as u64)
+	}
+
+	/// Where the value with sequence number `seq` is stored.
+	fn index(seq: u64) -> usize {
+		usize::try_from(seq % N as u64).expect("unreachable")
+	}
+}
+
+impl<T: Clone, const N: usize> State<T, N> {
+	/// Clones the value at `*next` and advances it, or skips it ahead to the oldest value if it lagged.
+	fn read(&self, next: &mut u64) -> Result<T, TryRecvError> {
+		let oldest = self.oldest();
+		if *next < oldest {
+			let missed = oldest - *next;
+			*next = oldest;
+			Err(TryRecvError::Lagged(missed))
+		} else if *next < self.next {
+			let value = self.buffer[Self::index(*next)].clone();
+			*next += 1;
+			Ok(value.expect("unreachable"))
+		} else if self.senders == 0 {
+			Err(TryRecvError::Closed)
+		} else {
+			Err(TryRecvError::Empty)
+		}
+	}
+}
+
+impl<T, const N: usize> Broadcast<T, N> {
+	const NONE: Option<T> = None;
+
+	/// Creates a new empty [`Broadcast`].
+	///
+	/// > A capacity of `0` is allowed, but then all [`Receiver`]s always lag.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			state: SpinLock::new(State {
+				buffer: [Self::NONE; N],
+				next: 0,
+				senders: 0,
+				waiters: WaiterList::new(),
+			}),
+		}
+	}
+
+	/// Resets this [`Broadcast`], dropping any buffered values, and splits it into a [`Sender`] and a [`Receiver`].
+	///
+	/// Use [`Sender::subscribe`] or clone the [`Receiver`] for more consumers.
+	pub fn split(&mut self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+		*self = Self::new();
+		self.state.lock().senders = 1;
+		let this = &*self;
+		(
+			Sender {
+				broadcast: StateRef::Borrowed(this),
+			},
+			Receiver {
+				broadcast: StateRef::Borrowed(this),
+				next: 0,
+			},
+		)
+	}
+}
+
+impl<T, const N: usize> Default for Broadcast<T, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only how many values were sent, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Broadcast<T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Broadcast")
+			.field("sent", &self.state.lock().next)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a heap-allocated broadcast channel that keeps the last `N` values, whose halves can be moved anywhere independently.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn channel<T, const N: usize>() -> (Sender<'static, T, N>, Receiver<'static, T, N>) {
+	let broadcast = Arc::new(Broadcast::new());
+	broadcast.state.lock().senders = 1;
+	(
+		Sender {
+			broadcast: StateRef::Shared(Arc::clone(&broadcast)),
+		},
+		Receiver {
+			broadcast: StateRef::Shared(broadcast),
+			next: 0,
+		},
+	)
+}
+
+/// A sending half of a broadcast channel. Clone it for more producers.
+pub struct Sender<'a, T, const N: usize> {
+	broadcast: StateRef<'a, Broadcast<T, N>>,
+}
+
+impl<'a, T, const N: usize> Sender<'a, T, N> {
+	/// Sends `value` to all [`Receiver`]s, overwriting the oldest value if the buffer is full, and wakes them.
+	pub fn send(&self, value: T) {
+		let mut state = self.broadcast.state.lock();
+		let overwritten = if N == 0 {
+			None
+		} else {
+			let index = State::<T, N>::index(state.next);
+			state.buffer[index].replace(value)
+		};
+		state.next += 1;
+		let waiting = state.waiters.len();
+		drop(state);
+		drop(overwritten);
+		self.broadcast.wake(waiting);
+	}
+
+	/// Creates a new [`Receiver`] that receives only values sent after this call.
+	#[must_use]
+	pub fn subscribe(&self) -> Receiver<'a, T, N> {
+		Receiver {
+			next: self.broadcast.state.lock().next,
+			broadcast: self.broadcast.clone(),
+		}
+	}
+}
+
+impl<T, const N: usize> Broadcast<T, N> {
+	/// Wakes up to `count` waiting [`Receiver`]s, one by one outside the lock.
+	///
+	/// > Limited to those that were waiting before, since woken ones may re-register right away.
+	fn wake(&self, count: usize) {
+		for _ in 0..count {
+			match self.state.lock().waiters.notify_one() {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+}
+
+impl<T, const N: usize> Clone for Sender<'_, T, N> {
+	fn clone(&self) -> Self {
+		self.broadcast.state.lock().senders += 1;
+		Self {
+			broadcast: self.broadcast.clone(),
+		}
+	}
+}
+
+impl<T, const N: usize> Drop for Sender<'_, T, N> {
+	fn drop(&mut self) {
+		let mut state = self.broadcast.state.lock();
+		state.senders -= 1;
+		let waiting = if state.senders == 0 {
+			state.waiters.len()
+		} else {
+			0
+		};
+		drop(state);
+		self.broadcast.wake(waiting);
+	}
+}
+
+/// Shows only how many values were sent, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Sender<'_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Sender")
+			.field("sent", &self.broadcast.state.lock().next)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A receiving half of a broadcast channel, with its own read position.
+///
+/// Cloning it clones the read position too.
+pub struct Receiver<'a, T, const N: usize> {
+	broadcast: StateRef<'a, Broadcast<T, N>>,
+	/// The sequence number of the next value to read.
+	next: u64,
+}
+
+impl<'a, T: Clone, const N: usize> Receiver<'a, T, N> {
+	/// Clones the next value without waiting, if there is one.
+	///
+	/// # Errors
+	///
+	/// See [`TryRecvError`].
+	pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+		self.broadcast.state.lock().read(&mut self.next)
+	}
+
+	/// Returns a [`Future`] that completes with a clone of the next value, or with a [`RecvError`].
+	#[must_use]
+	pub fn recv(&mut self) -> Recv<'_, 'a, T, N> {
+		Recv {
+			receiver: self,
+			waiter: Waiter::new(),
+			registered: false,
+		}
+	}
+}
+
+impl<T, const N: usize> Clone for Receiver<'_, T, N> {
+	fn clone(&self) -> Self {
+		Self {
+			broadcast: self.broadcast.clone(),
+			next: self.next,
+		}
+	}
+}
+
+/// Shows only the read position, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Receiver<'_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Receiver")
+			.field("next", &self.next)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes with a clone of the next value. See [`Receiver::recv`].
+pub struct Recv<'r, 'a, T, const N: usize> {
+	receiver: &'r mut Receiver<'a, T, N>,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked, to skip locking on drop otherwise.
+	registered: bool,
+}
+
+impl<T: Clone, const N: usize> Future for Recv<'_, '_, T, N> {
+	type Output = Result<T, RecvError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+
+		let receiver = &mut *this.receiver;
+		let mut state = receiver.broadcast.state.lock();
+		let output = match state.read(&mut receiver.next) {
+			Ok(value) => Ok(value),
+			Err(TryRecvError::Lagged(missed)) => Err(RecvError::Lagged(missed)),
+			Err(TryRecvError::Closed) => Err(RecvError::Closed),
+			Err(TryRecvError::Empty) => {
+				unsafe {
+					//SAFETY: `waiter` is only used with this list, and removed on drop.
+					state.waiters.register(waiter, cx.waker());
+				}
+				this.registered = true;
+				return Poll::Pending;
+			}
+		};
+
+		if this.registered {
+			unsafe {
+				//SAFETY: As above.
+				state.waiters.remove(waiter);
+			}
+			this.registered = false;
+		}
+		Poll::Ready(output)
+	}
+}
+
+impl<T, const N: usize> Drop for Recv<'_, '_, T, N> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				self.receiver.broadcast.state.lock().waiters.remove(waiter);
+			}
+		}
+	}
+}
+
+/// Shows only the read position, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Recv<'_, '_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Recv")
+			.field("next", &self.receiver.next)
+			.finish_non_exhaustive()
+	}
+}
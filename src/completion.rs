@@ -0,0 +1,228 @@
+//! Lightweight completion flag storage for the joining combinators.
+//!
+//! > Most joins have only a handful of constituents, so a single small integer is plenty.
+//! > Larger fixed-size joins use arrays of words, and dynamically-sized ones a boxed slice of them.
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::fmt::{self, Debug, Formatter};
+
+/// Storage for one completion flag per constituent [`Future`](`core::future::Future`), all initially unset.
+///
+/// This trait is sealed. It's implemented for [`u8`], [`u16`], [`u32`], [`u64`] and arrays thereof
+/// (and boxed slices thereof with the `"alloc"` feature).
+pub trait Completion: sealed::Sealed {
+	/// The number of flags that fit into this storage.
+	fn capacity(&self) -> usize {
+		self.words().len() * <Self::Word as sealed::Word>::BITS
+	}
+
+	/// Whether the flag at `index` is set.
+	///
+	/// # Panics
+	///
+	/// Iff `index >= self.capacity()`.
+	fn get(&self, index: usize) -> bool {
+		use sealed::Word;
+		let (word, bit) = Self::Word::locate(index);
+		self.words()[word].and(bit) != Self::Word::ZERO
+	}
+
+	/// Sets or unsets the flag at `index`.
+	///
+	/// # Panics
+	///
+	/// Iff `index >= self.capacity()`.
+	fn set(&mut self, index: usize, value: bool) {
+		use sealed::Word;
+		let (word, bit) = Self::Word::locate(index);
+		let word = &mut self.words_mut()[word];
+		*word = if value {
+			word.or(bit)
+		} else {
+			word.and_not(bit)
+		};
+	}
+
+	/// Unsets all flags.
+	fn clear(&mut self) {
+		use sealed::Word;
+		self.words_mut().fill(Self::Word::ZERO);
+	}
+
+	/// The number of set flags.
+	fn count_ones(&self) -> usize {
+		use sealed::Word;
+		self.words().iter().map(|word| word.count_ones()).sum()
+	}
+
+	/// Whether all flags in `0..len` are set.
+	///
+	/// # Panics
+	///
+	/// Iff `len > self.capacity()`.
+	fn all(&self, len: usize) -> bool {
+		use sealed::Word;
+		assert!(len <= self.capacity());
+		let (full, rest) = (len / Self::Word::BITS, len % Self::Word::BITS);
+		let words = self.words();
+		words[..full].iter().all(|word| *word == Self::Word::MAX)
+			&& (rest == 0 || {
+				let mask = Self::Word::low_mask(rest);
+				words[full].and(mask) == mask
+			})
+	}
+}
+
+impl<C: sealed::Sealed + ?Sized> Completion for C {}
+
+mod sealed {
+	/// The actual sealing trait, which also hides the storage details.
+	pub trait Sealed {
+		type Word: Word;
+
+		fn words(&self) -> &[Self::Word];
+		fn words_mut(&mut self) -> &mut [Self::Word];
+	}
+
+	pub trait Word: Copy + Eq {
+		const BITS: usize;
+		const ZERO: Self;
+		const MAX: Self;
+
+		fn and(self, other: Self) -> Self;
+		fn or(self, other: Self) -> Self;
+		fn and_not(self, other: Self) -> Self;
+		fn count_ones(self) -> usize;
+		fn low_mask(bits: usize) -> Self;
+
+		/// Returns the word index and bit mask for `index`.
+		fn locate(index: usize) -> (usize, Self);
+	}
+}
+
+macro_rules! words {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$($word:ident),*$(,)?} => {$(
+		impl sealed::Word for $word {
+			const BITS: usize = $word::BITS as usize;
+			const ZERO: Self = 0;
+			const MAX: Self = $word::MAX;
+
+			fn and(self, other: Self) -> Self {
+				self & other
+			}
+
+			fn or(self, other: Self) -> Self {
+				self | other
+			}
+
+			fn and_not(self, other: Self) -> Self {
+				self & !other
+			}
+
+			fn count_ones(self) -> usize {
+				$word::count_ones(self) as usize
+			}
+
+			fn low_mask(bits: usize) -> Self {
+				debug_assert!(bits < <Self as sealed::Word>::BITS);
+				(1 << bits) - 1
+			}
+
+			fn locate(index: usize) -> (usize, Self) {
+				let bits = <Self as sealed::Word>::BITS;
+				(index / bits, 1 << (index % bits))
+			}
+		}
+
+		impl sealed::Sealed for $word {
+			type Word = Self;
+
+			fn words(&self) -> &[Self::Word] {
+				core::slice::from_ref(self)
+			}
+
+			fn words_mut(&mut self) -> &mut [Self::Word] {
+				core::slice::from_mut(self)
+			}
+		}
+	)*};
+}
+
+words!(u8, u16, u32, u64);
+
+impl<W: sealed::Word, const N: usize> sealed::Sealed for [W; N] {
+	type Word = W;
+
+	fn words(&self) -> &[Self::Word] {
+		self
+	}
+
+	fn words_mut(&mut self) -> &mut [Self::Word] {
+		self
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<W: sealed::Word> sealed::Sealed for Box<[W]> {
+	type Word = W;
+
+	fn words(&self) -> &[Self::Word] {
+		self
+	}
+
+	fn words_mut(&mut self) -> &mut [Self::Word] {
+		self
+	}
+}
+
+/// Formats the first `len` flags of a [`Completion`] like `[0110]`, in index order from left to right.
+pub(crate) struct Bitmap<'a, C: ?Sized>(pub(crate) &'a C, pub(crate) usize);
+
+impl<C: Completion + ?Sized> Debug for Bitmap<'_, C> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("[")?;
+		for index in 0..self.1 {
+			f.write_str(if self.0.get(index) { "1" } else { "0" })?;
+		}
+		f.write_str("]")
+	}
+}
+
+/// Names the smallest [`Completion`] for `N` flags, through [`SmallestCompletion`].
+///
+/// > This indirection is necessary because a const generic can't be used in a type calculation directly,
+/// > as of Rust 1.57.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct CompletionFor<const N: usize>;
+
+/// Selects the smallest [`Completion`] type, implemented for [`CompletionFor<N>`] where `N <= 256`.
+pub trait SmallestCompletion {
+	/// The [`Completion`] type.
+	type Completion: Completion + Default + Copy + Debug;
+}
+
+macro_rules! smallest_completion {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$completion:ty: $($n:literal)*
+	),*$(,)?} => {$($(
+		impl SmallestCompletion for CompletionFor<$n> {
+			type Completion = $completion;
+		}
+	)*)*};
+}
+
+smallest_completion! {
+	u8: 0 1 2 3 4 5 6 7 8,
+	u16: 9 10 11 12 13 14 15 16,
+	u32: 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32,
+	u64: 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64,
+	[u64; 2]: 65 66 67 68 69 70 71 72 73 74 75 76 77 78 79 80 81 82 83 84 85 86 87 88 89 90 91 92 93 94 95 96
+		97 98 99 100 101 102 103 104 105 106 107 108 109 110 111 112 113 114 115 116 117 118 119 120 121 122 123 124 125 126 127 128,
+	[u64; 3]: 129 130 131 132 133 134 135 136 137 138 139 140 141 142 143 144 145 146 147 148 149 150 151 152 153 154 155 156 157 158 159 160
+		161 162 163 164 165 166 167 168 169 170 171 172 173 174 175 176 177 178 179 180 181 182 183 184 185 186 187 188 189 190 191 192,
+	[u64; 4]: 193 194 195 196 197 198 199 200 201 202 203 204 205 206 207 208 209 210 211 212 213 214 215 216 217 218 219 220 221 222 223 224
+		225 226 227 228 229 230 231 232 233 234 235 236 237 238 239 240 241 242 243 244 245 246 247 248 249 250 251 252 253 254 255 256,
+}
@@ -0,0 +1,165 @@
+//! A [`Stream`](`futures_core::Stream`)-like adaptor that yields the outputs of [`Future`]s in the order they complete.
+
+use crate::{
+	any_future::{Futures, Heterogeneous},
+	completion::{Bitmap, Completion, CompletionFor, SmallestCompletion},
+	pinned_pin::PinnedPin,
+};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec};
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+use pin_project::pin_project;
+
+/// Creates a [`Completions`] that yields the index and output of each [`Future`] in `futures` as it completes.
+pub fn completions<F: Future, const N: usize>(
+	futures: [F; N],
+) -> Completions<[F; N], <CompletionFor<N> as SmallestCompletion>::Completion>
+where
+	CompletionFor<N>: SmallestCompletion,
+{
+	Completions::new(futures, Default::default())
+}
+
+/// Creates a [`Completions`] that yields the index and output of each [`Future`] in the tuple `futures` as it completes,
+/// even if their output types differ.
+///
+/// Each output is wrapped in an [`Either2`](`crate::either::Either2`), [`Either3`](`crate::either::Either3`), …
+/// with the variant matching its position.
+pub fn completions_heterogeneous<Fs>(futures: Fs) -> Completions<Heterogeneous<Fs>, u16>
+where
+	Heterogeneous<Fs>: Futures,
+{
+	Completions::new(Heterogeneous(futures), 0)
+}
+
+/// Creates a [`Completions`] that yields the index and output of each [`Future`] in the dynamically-sized `futures` as it completes.
+///
+/// The [`Future`]s are pinned in place behind the box, so they don't have to be [`Unpin`].
+#[cfg(feature = "alloc")]
+pub fn completions_boxed<F: Future>(
+	futures: impl Into<Box<[F]>>,
+) -> Completions<Pin<Box<[F]>>, Box<[u64]>> {
+	let futures = futures.into();
+	let completion = vec![0; (futures.len() + 63) / 64].into_boxed_slice();
+	Completions::new(futures.into(), completion)
+}
+
+/// Yields the index and output of each constituent [`Future`] as it completes, and then [`None`].
+///
+/// This is the streaming dual of [`JoinFuture`](`crate::join_future::JoinFuture`):
+/// Each output is available as soon as possible, and doesn't have to be stored until all of them are.
+///
+/// Like [`AnyFuture`](`crate::any_future::AnyFuture`), each poll starts after the [`Future`] the previous poll started at.
+#[pin_project]
+pub struct Completions<Fs: Futures, C> {
+	completion: C,
+	/// Where the next poll starts.
+	cursor: usize,
+	#[pin]
+	futures: PinnedPin<Fs>,
+}
+
+impl<Fs: Futures, C: Completion> Completions<Fs, C> {
+	/// Creates a new instance of [`Completions`] from the given `futures`,
+	/// tracking their completion in `completion`.
+	///
+	/// # Panics
+	///
+	/// Iff `completion` has too little capacity for `futures` or has any flag set.
+	#[must_use]
+	pub fn new(futures: Fs, completion: C) -> Self {
+		assert!(
+			completion.capacity() >= futures.len(),
+			"`completion` has room for only {} of {} flags.",
+			completion.capacity(),
+			futures.len()
+		);
+		assert_eq!(completion.count_ones(), 0, "`completion` isn't clear.");
+		Self {
+			completion,
+			cursor: 0,
+			futures: futures.into(),
+		}
+	}
+
+	/// The number of constituent [`Future`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.futures.0.len()
+	}
+
+	/// Whether there are no constituent [`Future`]s.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.futures.0.is_empty()
+	}
+
+	/// The number of constituent [`Future`]s whose output hasn't been yielded yet.
+	#[must_use]
+	pub fn remaining(&self) -> usize {
+		self.len() - self.completion.count_ones()
+	}
+
+	/// Yields the index and output of the next constituent [`Future`] that completed.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) once all of them have.
+	pub fn poll_next(
+		self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+	) -> Poll<Option<(usize, Fs::Output)>> {
+		let this = self.project();
+		let mut futures = this.futures;
+		let len = futures.0.len();
+		if this.completion.all(len) {
+			return Poll::Ready(None);
+		}
+
+		let start = if *this.cursor >= len { 0 } else { *this.cursor };
+		*this.cursor = start + 1;
+
+		for index in (start..len).chain(0..start) {
+			if !this.completion.get(index) {
+				if let Poll::Ready(output) = Fs::poll_at(futures.as_mut(), index, cx) {
+					this.completion.set(index, true);
+					return Poll::Ready(Some((index, output)));
+				}
+			}
+		}
+		Poll::Pending
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures, C: Completion> Stream for Completions<Fs, C> {
+	type Item = (usize, Fs::Output);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		Completions::poll_next(self, cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining(), Some(self.remaining()))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures, C: Completion> FusedStream for Completions<Fs, C> {
+	fn is_terminated(&self) -> bool {
+		self.completion.all(self.len())
+	}
+}
+
+/// Shows the completion bitmap, like [`JoinFuture`](`crate::join_future::JoinFuture`)'s [`Debug`](`core::fmt::Debug`) implementation.
+impl<Fs: Futures, C: Completion> core::fmt::Debug for Completions<Fs, C> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Completions")
+			.field("completion", &Bitmap(&self.completion, self.len()))
+			.finish_non_exhaustive()
+	}
+}
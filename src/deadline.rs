@@ -0,0 +1,114 @@
+//! A [`Future`] that races another against a deadline, dropping it if the deadline wins.
+
+use crate::{
+	any_future::AnyFuture,
+	map::Map,
+	sleep::{self, Delay, Sleep},
+	timer::{TimedOut, Timer},
+};
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// The error returned by [`Deadline`] if its deadline passed first.
+pub type DeadlineExceeded = TimedOut;
+
+/// Creates a [`Deadline`] that completes with the output of `future`,
+/// or with [`DeadlineExceeded`] once `deadline` passes on the driver registered with [`set_driver`](`crate::sleep::set_driver`).
+///
+/// # Panics
+///
+/// Iff no driver was registered.
+pub fn deadline<F: Future>(future: F, deadline: Duration) -> Deadline<F> {
+	Deadline::new(future, sleep::expect_driver(), deadline)
+}
+
+/// Creates a [`Deadline`] that completes with the output of `future`,
+/// or with [`DeadlineExceeded`] once `deadline` passes on `timer`.
+pub fn deadline_on<F: Future, T: Timer>(
+	timer: T,
+	future: F,
+	deadline: T::Instant,
+) -> Deadline<F, T> {
+	Deadline::new(future, timer, deadline)
+}
+
+type Succeed<T> = fn(T) -> Result<T, DeadlineExceeded>;
+type Exceed<T> = fn(()) -> Result<T, DeadlineExceeded>;
+type Race<F, T> = AnyFuture<(
+	Map<F, Succeed<<F as Future>::Output>>,
+	Map<Sleep<T>, Exceed<<F as Future>::Output>>,
+)>;
+
+/// A [`Future`] that completes with the output of `future`, or with [`DeadlineExceeded`] once `deadline` has passed.
+///
+/// Both the inner [`Future`] and the [`Sleep`] are dropped in place as soon as either completes,
+/// so resources held by the inner [`Future`] are released right when the deadline fires.
+///
+/// > This is a [biased](`AnyFuture::biased`) [`AnyFuture`] over the inner [`Future`] and a [`Sleep`],
+/// > so an inner [`Future`] that is ready in time wins even if polled late.
+/// > Compare [`Timeout`](`crate::timeout::Timeout`), which keeps the inner [`Future`] around.
+#[pin_project]
+pub struct Deadline<F: Future, T: Timer = &'static dyn Delay> {
+	/// [`None`] once completed.
+	#[pin]
+	race: Option<Race<F, T>>,
+}
+
+impl<F: Future, T: Timer> Deadline<F, T> {
+	/// Creates a new instance of [`Deadline`] that gives up on `future` once `deadline` passes on `timer`.
+	#[must_use]
+	pub fn new(future: F, timer: T, deadline: T::Instant) -> Self {
+		Self {
+			race: Some(AnyFuture::biased((
+				Map::new(future, Ok),
+				Map::new(Sleep::new(timer, deadline), |()| Err(TimedOut)),
+			))),
+		}
+	}
+
+	/// Whether this [`Deadline`] has completed, in which case the inner [`Future`] was dropped.
+	#[must_use]
+	pub fn is_terminated(&self) -> bool {
+		self.race.is_none()
+	}
+}
+
+impl<F: Future, T: Timer> Future for Deadline<F, T> {
+	type Output = Result<F::Output, DeadlineExceeded>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut race = self.project().race;
+		let output = race
+			.as_mut()
+			.as_pin_mut()
+			.expect("`Deadline` was previously completed.")
+			.poll(cx);
+		if output.is_ready() {
+			race.set(None);
+		}
+		output
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, T: Timer> FusedFuture for Deadline<F, T> {
+	fn is_terminated(&self) -> bool {
+		self.race.is_none()
+	}
+}
+
+/// Shows only the progress, since `F` and `T` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F: Future, T: Timer> core::fmt::Debug for Deadline<F, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Deadline")
+			.field("done", &self.race.is_none())
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,200 @@
+//! A dynamic set of pinned [`Future`]s with optional per-entry deadlines.
+
+use crate::{
+	pinned_future_set::PinnedFutureSet,
+	timer::{TimedOut, Timer},
+};
+use alloc::{collections::BinaryHeap, vec::Vec};
+#[cfg(feature = "futures-core")]
+use core::pin::Pin;
+use core::{
+	cmp::Reverse,
+	future::Future,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+
+/// A supervised set of [`Future`]s, each of which may have a deadline.
+///
+/// Entries are identified by the key returned when pushing them, which is reused only after the entry's result was yielded.
+///
+/// [`.poll_next(…)`](`DeadlineFutureSet::poll_next`) first expires overdue entries, yielding [`TimedOut`] errors for them,
+/// and then polls only those entries that were woken since their last poll.
+///
+/// Each [`Future`] is boxed individually, so it stays in place even as the set grows, and may be [`!Unpin`](`Unpin`).
+///
+/// > Expired deadlines are removed from the queue lazily, which keeps both pushing and completion cheap.
+/// >
+/// > The [`Future`]s themselves are kept in a [`PinnedFutureSet`], with the deadline bookkeeping alongside by key.
+pub struct DeadlineFutureSet<F: Future, T: Timer> {
+	timer: T,
+	futures: PinnedFutureSet<F>,
+	/// Deadline bookkeeping, by key.
+	slots: Vec<Slot<T::Instant>>,
+	/// Deadlines along with the slot index and generation they were registered for.
+	deadlines: BinaryHeap<Reverse<(T::Instant, usize, usize)>>,
+}
+
+/// The key and result of an entry that either completed or expired.
+pub type Entry<F> = (usize, Result<<F as Future>::Output, TimedOut>);
+
+struct Slot<I> {
+	deadline: Option<I>,
+	/// Incremented each time the slot is vacated, to invalidate stale deadlines.
+	/// (So a deadline with a matching generation always belongs to an occupied slot.)
+	generation: usize,
+}
+
+impl<F: Future, T: Timer> DeadlineFutureSet<F, T> {
+	/// Creates a new empty [`DeadlineFutureSet`] that consults `timer`.
+	#[must_use]
+	pub fn new(timer: T) -> Self {
+		Self {
+			timer,
+			futures: PinnedFutureSet::new(),
+			slots: Vec::new(),
+			deadlines: BinaryHeap::new(),
+		}
+	}
+
+	/// The [`Timer`] this [`DeadlineFutureSet`] consults.
+	#[must_use]
+	pub fn timer(&self) -> &T {
+		&self.timer
+	}
+
+	/// The number of entries that haven't been yielded yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.futures.len()
+	}
+
+	/// Whether there are no entries left.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.futures.is_empty()
+	}
+
+	/// Adds `future` without a deadline and returns its key.
+	pub fn push(&mut self, future: F) -> usize {
+		self.insert(future, None)
+	}
+
+	/// Adds `future` to expire at `deadline` and returns its key.
+	pub fn push_with_deadline(&mut self, future: F, deadline: T::Instant) -> usize {
+		self.insert(future, Some(deadline))
+	}
+
+	fn insert(&mut self, future: F, deadline: Option<T::Instant>) -> usize {
+		let index = self.futures.push(future);
+		if index == self.slots.len() {
+			self.slots.push(Slot {
+				deadline: None,
+				generation: 0,
+			});
+		}
+
+		let slot = &mut self.slots[index];
+		slot.deadline = deadline;
+		if let Some(deadline) = deadline {
+			self.deadlines
+				.push(Reverse((deadline, index, slot.generation)));
+		}
+		index
+	}
+
+	/// Clears the bookkeeping for `index`, whose entry has already left the [`PinnedFutureSet`].
+	fn vacate(&mut self, index: usize) {
+		let slot = &mut self.slots[index];
+		slot.deadline = None;
+		slot.generation = slot.generation.wrapping_add(1);
+	}
+
+	/// The deadline of the entry at `key`, if there is such an entry and it has a deadline.
+	#[must_use]
+	pub fn deadline(&self, key: usize) -> Option<T::Instant> {
+		self.slots.get(key).and_then(|slot| slot.deadline)
+	}
+
+	/// Drops the entry at `key` early, without yielding a result for it.
+	///
+	/// Returns whether there was such an entry.
+	pub fn cancel(&mut self, key: usize) -> bool {
+		let removed = self.futures.remove(key);
+		if removed {
+			self.vacate(key);
+		}
+		removed
+	}
+
+	/// Yields the key and result of the next entry that either completed or expired.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) iff the set is empty.
+	pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Entry<F>>> {
+		if self.is_empty() {
+			return Poll::Ready(None);
+		}
+
+		let now = self.timer.now();
+		while let Some(&Reverse((deadline, index, generation))) = self.deadlines.peek() {
+			if deadline > now {
+				break;
+			}
+			self.deadlines.pop();
+			if self.slots[index].generation == generation {
+				self.futures.remove(index);
+				self.vacate(index);
+				return Poll::Ready(Some((index, Err(TimedOut))));
+			}
+		}
+
+		if let Poll::Ready(Some((index, output))) = self.futures.poll_next(cx) {
+			self.vacate(index);
+			return Poll::Ready(Some((index, Ok(output))));
+		}
+
+		// Stale deadlines at the front would only cause spurious wake-ups.
+		while let Some(&Reverse((deadline, index, generation))) = self.deadlines.peek() {
+			if self.slots[index].generation == generation {
+				self.timer.wake_at(deadline, cx.waker());
+				break;
+			}
+			self.deadlines.pop();
+		}
+		Poll::Pending
+	}
+}
+
+impl<F: Future, T: Timer> Unpin for DeadlineFutureSet<F, T> {}
+
+/// Yields [`None`] whenever the set is empty, but can be resumed after pushing more entries.
+#[cfg(feature = "futures-core")]
+impl<F: Future, T: Timer> Stream for DeadlineFutureSet<F, T> {
+	type Item = Entry<F>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		DeadlineFutureSet::poll_next(self.get_mut(), cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len(), Some(self.len()))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, T: Timer> FusedStream for DeadlineFutureSet<F, T> {
+	fn is_terminated(&self) -> bool {
+		self.is_empty()
+	}
+}
+
+impl<F: Future, T: Timer> core::fmt::Debug for DeadlineFutureSet<F, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("DeadlineFutureSet")
+			.field("len", &self.len())
+			.field("capacity", &self.slots.len())
+			.field("queued_deadlines", &self.deadlines.len())
+			.finish_non_exhaustive()
+	}
+}
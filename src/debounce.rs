@@ -0,0 +1,155 @@
+//! A [`Stream`] adaptor that only lets items through once its source has settled.
+
+use crate::{
+	sleep::{self, Delay},
+	timer::Timer,
+};
+use core::{
+	ops::Add,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+use futures_core::{FusedStream, Stream};
+use pin_project::pin_project;
+
+/// Creates a [`Debounce`] that yields an item of `stream` only once `quiet_period` has passed without a newer one,
+/// measured on the driver registered with [`set_driver`](`crate::sleep::set_driver`).
+///
+/// # Panics
+///
+/// Iff no driver was registered.
+pub fn debounce<S: Stream>(stream: S, quiet_period: Duration) -> Debounce<S> {
+	Debounce::new(stream, sleep::expect_driver(), quiet_period)
+}
+
+/// Creates a [`Debounce`] that yields an item of `stream` only once `quiet_period` has passed without a newer one,
+/// measured on `timer`.
+pub fn debounce_on<S: Stream, T: Timer, D: Copy>(
+	timer: T,
+	stream: S,
+	quiet_period: D,
+) -> Debounce<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	Debounce::new(stream, timer, quiet_period)
+}
+
+/// A [`Stream`] that yields an item of `stream` only once `quiet_period` has passed without a newer one.
+///
+/// Superseded items are dropped. The last item is yielded right away once `stream` ends.
+///
+/// > The inner [`Stream`] is drained eagerly whenever this is polled, so it can't apply backpressure.
+/// > Use [`Throttle`](`crate::throttle::Throttle`) where that matters.
+#[pin_project]
+pub struct Debounce<S: Stream, T: Timer = &'static dyn Delay, D = Duration> {
+	#[pin]
+	stream: S,
+	timer: T,
+	quiet_period: D,
+	/// The latest item along with when it's due.
+	pending: Option<(S::Item, T::Instant)>,
+	/// Set once `stream` has ended.
+	done: bool,
+}
+
+impl<S: Stream, T: Timer, D> Debounce<S, T, D> {
+	/// Creates a new instance of [`Debounce`] that yields an item of `stream` only once `quiet_period` has passed without a newer one,
+	/// measured on `timer`.
+	#[must_use]
+	pub fn new(stream: S, timer: T, quiet_period: D) -> Self {
+		Self {
+			stream,
+			timer,
+			quiet_period,
+			pending: None,
+			done: false,
+		}
+	}
+
+	/// Gives pinning exclusive access to the inner [`Stream`].
+	#[must_use]
+	pub fn stream_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+		self.project().stream
+	}
+
+	/// The item that will be yielded next unless superseded, if any.
+	#[must_use]
+	pub fn pending(&self) -> Option<&S::Item> {
+		self.pending.as_ref().map(|(item, _)| item)
+	}
+}
+
+impl<S: Stream, T: Timer, D: Copy> Stream for Debounce<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		while !*this.done {
+			match this.stream.as_mut().poll_next(cx) {
+				Poll::Ready(Some(item)) => {
+					*this.pending = Some((item, this.timer.now() + *this.quiet_period));
+				}
+				Poll::Ready(None) => *this.done = true,
+				Poll::Pending => break,
+			}
+		}
+
+		match this.pending.take() {
+			None => {
+				if *this.done {
+					Poll::Ready(None)
+				} else {
+					Poll::Pending
+				}
+			}
+			Some((item, _)) if *this.done => Poll::Ready(Some(item)),
+			Some((item, due)) => {
+				if this.timer.now() >= due {
+					Poll::Ready(Some(item))
+				} else {
+					this.timer.wake_at(due, cx.waker());
+					*this.pending = Some((item, due));
+					Poll::Pending
+				}
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let pending = usize::from(self.pending.is_some());
+		if self.done {
+			(pending, Some(pending))
+		} else {
+			let (_, upper) = self.stream.size_hint();
+			(0, upper.and_then(|upper| upper.checked_add(pending)))
+		}
+	}
+}
+
+impl<S: Stream, T: Timer, D: Copy> FusedStream for Debounce<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	fn is_terminated(&self) -> bool {
+		self.done && self.pending.is_none()
+	}
+}
+
+/// Shows only the progress, since `S` and `T` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<S: Stream, T: Timer, D: core::fmt::Debug> core::fmt::Debug for Debounce<S, T, D>
+where
+	T::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Debounce")
+			.field("quiet_period", &self.quiet_period)
+			.field("due", &self.pending.as_ref().map(|(_, due)| due))
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
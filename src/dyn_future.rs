@@ -0,0 +1,26 @@
+//! Type-erased boxed [`Future`]s, for dynamically-built sets of [`Future`]s with different types.
+//!
+//! These are [`Unpin`] regardless of the erased [`Future`], so they work with all combinators in this crate,
+//! including the [`Vec`](`alloc::vec::Vec`)-based ones like [`any`](`crate::any_future::any`)
+//! and [`join_all`](`crate::join_all::join_all`).
+//!
+//! > The combinators are then monomorphised only once per output type, rather than once per combination of [`Future`] types.
+
+use alloc::boxed::Box;
+use core::{future::Future, pin::Pin};
+
+/// A pinned, boxed and type-erased [`Future`].
+pub type DynFuture<'a, T> = Pin<Box<dyn 'a + Future<Output = T>>>;
+
+/// A pinned, boxed and type-erased [`Future`] that can be sent across threads.
+pub type SendDynFuture<'a, T> = Pin<Box<dyn 'a + Send + Future<Output = T>>>;
+
+/// Pins, boxes and type-erases `future`.
+pub fn dyn_future<'a, F: 'a + Future>(future: F) -> DynFuture<'a, F::Output> {
+	Box::pin(future)
+}
+
+/// Pins, boxes and type-erases `future`, keeping it [`Send`].
+pub fn send_dyn_future<'a, F: 'a + Send + Future>(future: F) -> SendDynFuture<'a, F::Output> {
+	Box::pin(future)
+}
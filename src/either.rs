@@ -0,0 +1,82 @@
+//! Anonymous sum types, for combined outputs of [`Future`](`core::future::Future`)s with different output types.
+//!
+//! > These are named by arity rather than by position (like `Left` and `Right`),
+//! > since there is no obvious way to name a third position.
+
+macro_rules! either {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$(#[$attr:meta])*
+		$Either:ident($($V:ident.$index:tt),*$(,)?)
+	),*$(,)?} => {$(
+		$(#[$attr])*
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+		pub enum $Either<$($V),*> {
+			$(
+				#[allow(missing_docs)]
+				$V($V),
+			)*
+		}
+
+		impl<$($V),*> $Either<$($V),*> {
+			/// The position of the active variant, starting at `0`.
+			#[must_use]
+			pub fn index(&self) -> usize {
+				match self {
+					$(Self::$V(_) => $index,)*
+				}
+			}
+		}
+
+		impl<T> $Either<$(same!($V, T)),*> {
+			/// Unwraps the value of whichever variant is active, if they all have the same type.
+			#[must_use]
+			pub fn into_inner(self) -> T {
+				match self {
+					$(Self::$V(value))|* => value,
+				}
+			}
+		}
+	)*};
+}
+
+/// Substitutes `$T` for `$_`, to repeat a type once per variant.
+macro_rules! same {
+	($_:ident, $T:ty) => {
+		$T
+	};
+}
+
+/// One of two values, for conditional composition of [`Future`](`core::future::Future`)s.
+///
+/// Both [`any_future::Futures`](`crate::any_future::Futures`) and [`join_future::Futures`](`crate::join_future::Futures`)
+/// are implemented for it, delegating to whichever side is active.
+pub type Either<A, B> = Either2<A, B>;
+
+either! {
+	/// One of two values.
+	///
+	/// > This one is `#[repr(C)]`, so that [`JoinFuture`](`crate::join_future::JoinFuture`) can write outputs into its payload in place.
+	#[repr(C)]
+	Either2(A.0, B.1),
+	/// One of three values.
+	Either3(A.0, B.1, C.2),
+	/// One of four values.
+	Either4(A.0, B.1, C.2, D.3),
+	/// One of five values.
+	Either5(A.0, B.1, C.2, D.3, E.4),
+	/// One of six values.
+	Either6(A.0, B.1, C.2, D.3, E.4, F.5),
+	/// One of seven values.
+	Either7(A.0, B.1, C.2, D.3, E.4, F.5, G.6),
+	/// One of eight values.
+	Either8(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7),
+	/// One of nine values.
+	Either9(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8),
+	/// One of ten values.
+	Either10(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9),
+	/// One of eleven values.
+	Either11(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10),
+	/// One of twelve values.
+	Either12(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10, L.11),
+}
@@ -0,0 +1,95 @@
+//! A [`Future`] that is one of two [`Future`]s with the same output type, chosen at runtime.
+
+use crate::either::Either2;
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// One of two [`Future`]s, so that conditional code paths can return a single concrete type without boxing:
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::future::{ready, Future};
+/// use unpin_choices_dsa::either_future::EitherFuture;
+///
+/// fn maybe_cached(cached: Option<u8>) -> impl Future<Output = u8> {
+///     match cached {
+///         Some(value) => EitherFuture::A(ready(value)),
+///         None => EitherFuture::B(async { 1 + 1 }),
+///     }
+/// }
+/// ```
+///
+/// > This is distinct from [`Either2`], which [`AnyFuture`](`crate::any_future::AnyFuture`) and [`JoinFuture`](`crate::join_future::JoinFuture`)
+/// > treat as collection of (at most) one [`Future`] rather than as [`Future`] by itself.
+#[pin_project(project = EitherFutureProj)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EitherFuture<A, B> {
+	#[allow(missing_docs)]
+	A(#[pin] A),
+	#[allow(missing_docs)]
+	B(#[pin] B),
+}
+
+impl<A, B> EitherFuture<A, B> {
+	/// Gives pinning exclusive access to whichever side is active.
+	#[must_use]
+	pub fn as_pin_mut(self: Pin<&mut Self>) -> EitherFuture<Pin<&mut A>, Pin<&mut B>> {
+		match self.project() {
+			EitherFutureProj::A(a) => EitherFuture::A(a),
+			EitherFutureProj::B(b) => EitherFuture::B(b),
+		}
+	}
+
+	/// Converts this [`EitherFuture`] into the equivalent [`Either2`].
+	#[must_use]
+	pub fn into_either(self) -> Either2<A, B> {
+		match self {
+			Self::A(a) => Either2::A(a),
+			Self::B(b) => Either2::B(b),
+		}
+	}
+}
+
+impl<A, B> From<Either2<A, B>> for EitherFuture<A, B> {
+	fn from(either: Either2<A, B>) -> Self {
+		match either {
+			Either2::A(a) => Self::A(a),
+			Either2::B(b) => Self::B(b),
+		}
+	}
+}
+
+impl<A, B> Future for EitherFuture<A, B>
+where
+	A: Future,
+	B: Future<Output = A::Output>,
+{
+	type Output = A::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		match self.project() {
+			EitherFutureProj::A(a) => a.poll(cx),
+			EitherFutureProj::B(b) => b.poll(cx),
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<A, B> FusedFuture for EitherFuture<A, B>
+where
+	A: FusedFuture,
+	B: FusedFuture<Output = A::Output>,
+{
+	fn is_terminated(&self) -> bool {
+		match self {
+			Self::A(a) => a.is_terminated(),
+			Self::B(b) => b.is_terminated(),
+		}
+	}
+}
@@ -0,0 +1,90 @@
+//! A [`Future`] adaptor that drops its inner [`Future`] on completion and stays pending afterwards.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Wraps `future` in a [`Fuse`].
+pub fn fuse<F: Future>(future: F) -> Fuse<F> {
+	Fuse::new(future)
+}
+
+/// A [`Future`] that completes with the output of `future`, dropping it in place right away,
+/// and returns [`Poll::Pending`] whenever it's polled after that.
+///
+/// This makes any [`Future`] safe to poll in `select!`-style loops and lets it implement [`FusedFuture`](`futures_core::FusedFuture`).
+///
+/// > [`Fused`](`crate::fused::Fused`) goes the other way, bridging [`Future`]s that are fused already into this crate's combinators.
+/// > Some combinators, like [`AnyFuture::fused`](`crate::any_future::AnyFuture::fused`), have a built-in completion flag instead,
+/// > which keeps their state around for inspection.
+#[pin_project]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fuse<F> {
+	/// [`None`] once completed.
+	#[pin]
+	future: Option<F>,
+}
+
+impl<F> Fuse<F> {
+	/// Creates a new instance of [`Fuse`] that wraps `future`.
+	#[must_use]
+	pub fn new(future: F) -> Self {
+		Self {
+			future: Some(future),
+		}
+	}
+
+	/// Creates a [`Fuse`] that is terminated already.
+	///
+	/// This is handy as initial value of a [`Fuse`] that is [set](`Pin::set`) to a new [`Future`] later.
+	#[must_use]
+	pub fn terminated() -> Self {
+		Self { future: None }
+	}
+
+	/// Whether this [`Fuse`] has completed (or was created [terminated](`Fuse::terminated`)).
+	#[must_use]
+	pub fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+
+	/// Gives pinning exclusive access to the inner [`Future`], if it wasn't dropped yet.
+	#[must_use]
+	pub fn future_pin_mut(self: Pin<&mut Self>) -> Option<Pin<&mut F>> {
+		self.project().future.as_pin_mut()
+	}
+}
+
+impl<F> Default for Fuse<F> {
+	fn default() -> Self {
+		Self::terminated()
+	}
+}
+
+impl<F: Future> Future for Fuse<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut future = self.project().future;
+		let output = match future.as_mut().as_pin_mut() {
+			Some(inner) => inner.poll(cx),
+			None => return Poll::Pending,
+		};
+		if output.is_ready() {
+			future.set(None);
+		}
+		output
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for Fuse<F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+}
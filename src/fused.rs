@@ -0,0 +1,133 @@
+//! A bridging adaptor for [`FusedFuture`]s and [`FusedStream`]s from elsewhere.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use futures_core::{FusedFuture, FusedStream, Stream};
+
+/// Wraps `inner` into a [`Fused`], which skips polling it once it is terminated.
+pub fn fused<T>(inner: T) -> Fused<T> {
+	Fused(inner)
+}
+
+/// A transparent wrapper around a [`FusedFuture`] or [`FusedStream`],
+/// which consults [`FusedFuture::is_terminated`] or [`FusedStream::is_terminated`] before each poll.
+///
+/// Use this to place already-fused [`Future`]s into this crate's combinators instead of wrapping them in another completion flag.
+///
+/// The combinators can't tell whether a constituent is fused by themselves (without specialisation),
+/// so the check happens in here: Once the inner [`Future`] is terminated, this returns [`Poll::Pending`]
+/// without polling it *and without registering the [`Waker`](`core::task::Waker`)*.
+///
+/// - In an [`AnyFuture`](`crate::any_future::AnyFuture`), that skips the terminated constituent,
+///   and the race is decided by the others.
+/// - A [`JoinFuture`](`crate::join_future::JoinFuture`) needs each constituent's output,
+///   so joining an already-terminated [`Fused`] **never completes**. Don't do that.
+///
+/// Neither combinator's own [`FusedFuture`] implementation looks at its constituents.
+///
+/// > In short, this is how to opt into the `is_terminated()` check per constituent,
+/// > which isn't free either, though usually cheaper than a redundant poll.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Fused<T>(pub T);
+
+impl<T> Fused<T> {
+	/// Gives pinning exclusive access to the inner value
+	/// inside this pinned [`Fused<T>`].
+	#[must_use]
+	pub fn as_mut_pinned(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: Plain structural pin projection. `Fused` doesn't implement `Drop` or `Unpin` by itself.
+			self.map_unchecked_mut(|this| &mut this.0)
+		}
+	}
+}
+
+/// Returns [`Poll::Pending`] without polling the inner [`Future`] once it's terminated,
+/// which is fine inside a combinator that is driven by its other constituents.
+///
+/// Nothing wakes the task on behalf of a terminated [`Fused`], see above.
+impl<F: FusedFuture> Future for Fused<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.0.is_terminated() {
+			Poll::Pending
+		} else {
+			self.as_mut_pinned().poll(cx)
+		}
+	}
+}
+
+impl<F: FusedFuture> FusedFuture for Fused<F> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_terminated()
+	}
+}
+
+/// Returns [`Poll::Ready(None)`](`Poll::Ready`) without polling the inner [`Stream`] once it's terminated.
+impl<S: FusedStream> Stream for Fused<S> {
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		if self.0.is_terminated() {
+			Poll::Ready(None)
+		} else {
+			self.as_mut_pinned().poll_next(cx)
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		if self.0.is_terminated() {
+			(0, Some(0))
+		} else {
+			self.0.size_hint()
+		}
+	}
+}
+
+impl<S: FusedStream> FusedStream for Fused<S> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_terminated()
+	}
+}
+
+#[cfg(all(test, feature = "combinators", target_has_atomic = "ptr"))]
+mod tests {
+	use super::fused;
+	use crate::{
+		any_future::any, join_future::join, test_futures::panic_on_second_poll, waker::noop_context,
+	};
+	use core::{
+		future::{ready, Future},
+		pin::Pin,
+		task::Poll,
+	};
+
+	#[test]
+	fn any_skips_terminated_constituents() {
+		// Terminated up front, and panics if polled again.
+		let mut terminated = panic_on_second_poll(ready(1));
+		let mut cx = noop_context();
+		assert_eq!(Pin::new(&mut terminated).poll(&mut cx), Poll::Ready(1));
+
+		let mut any = any([fused(terminated), fused(panic_on_second_poll(ready(2)))]);
+		assert_eq!(Pin::new(&mut any).poll(&mut cx), Poll::Ready(2));
+	}
+
+	#[test]
+	fn join_with_a_terminated_constituent_never_completes() {
+		let mut terminated = panic_on_second_poll(ready(1));
+		let mut cx = noop_context();
+		assert_eq!(Pin::new(&mut terminated).poll(&mut cx), Poll::Ready(1));
+
+		let mut join = join([fused(terminated), fused(panic_on_second_poll(ready(2)))]);
+		for _ in 0..3 {
+			assert!(Pin::new(&mut join).poll(&mut cx).is_pending());
+		}
+		assert_eq!(join.completed_count(), 1);
+	}
+}
@@ -0,0 +1,196 @@
+//! A fixed-capacity FIFO queue of [`Future`]s that are pinned in place and yield their outputs in order, without allocation.
+
+use crate::maybe_done::MaybeDone;
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+
+/// A ring buffer of up to `N` [`Future`]s that are driven together, yielding their outputs in the order they were pushed.
+///
+/// The [`Future`]s are stored inline, so the queue has to be pinned before use, but they may be [`!Unpin`](`Unpin`).
+/// Each keeps being driven until it completes, even if earlier ones are still pending.
+/// Its output is then held in its slot until it's yielded, after which the slot is reused.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::pin::Pin;
+/// use unpin_choices_dsa::{block_on::block_on, future_queue::FutureQueue};
+///
+/// async fn task(value: u32) -> u32 {
+///     value
+/// }
+///
+/// let mut queue = Box::pin(FutureQueue::<_, 2>::new());
+/// queue.as_mut().try_push(task(1)).ok().unwrap();
+/// queue.as_mut().try_push(task(2)).ok().unwrap();
+/// assert!(queue.as_mut().try_push(task(3)).is_err());
+///
+/// assert_eq!(block_on(queue.as_mut().next()), Some(1));
+/// queue.as_mut().try_push(task(3)).ok().unwrap();
+/// assert_eq!(block_on(queue.as_mut().next()), Some(2));
+/// assert_eq!(block_on(queue.as_mut().next()), Some(3));
+/// assert_eq!(block_on(queue.as_mut().next()), None);
+/// ```
+///
+/// > This is the ordered counterpart to [`StaticFutureSet`](`crate::static_future_set::StaticFutureSet`),
+/// > and like it polls all running [`Future`]s each time.
+pub struct FutureQueue<F: Future, const N: usize> {
+	/// The slot of the oldest entry.
+	head: usize,
+	len: usize,
+	/// Pinned structurally. [`MaybeDone::Gone`] marks a vacant slot.
+	slots: [MaybeDone<F>; N],
+}
+
+impl<F: Future, const N: usize> FutureQueue<F, N> {
+	/// Creates a new empty [`FutureQueue`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			head: 0,
+			len: 0,
+			slots: [(); N].map(|()| MaybeDone::Gone),
+		}
+	}
+
+	/// The number of entries that haven't been yielded yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether there are no entries left.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Whether all slots are occupied.
+	#[must_use]
+	pub fn is_full(&self) -> bool {
+		self.len == N
+	}
+
+	/// The maximum number of entries this queue can hold at once, `N`.
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		N
+	}
+
+	fn slot(self: Pin<&mut Self>, index: usize) -> Pin<&mut MaybeDone<F>> {
+		unsafe {
+			//SAFETY: Plain structural pin projection. Slots are only ever dropped in place.
+			self.map_unchecked_mut(|this| &mut this.slots[index])
+		}
+	}
+
+	/// Appends `future` at the back of the queue.
+	///
+	/// # Errors
+	///
+	/// Iff the queue is full, in which case `future` is handed back.
+	pub fn try_push(mut self: Pin<&mut Self>, future: F) -> Result<(), F> {
+		if self.is_full() {
+			return Err(future);
+		}
+		let index = (self.head + self.len) % N;
+		self.as_mut().slot(index).set(MaybeDone::new(future));
+		unsafe {
+			//SAFETY: Not a pinned field.
+			self.get_unchecked_mut().len += 1
+		}
+		Ok(())
+	}
+
+	/// Drives all entries and yields the output of the oldest one once it's available, vacating its slot.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) iff the queue is empty.
+	pub fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<F::Output>> {
+		if self.is_empty() {
+			return Poll::Ready(None);
+		}
+
+		let (head, len) = (self.head, self.len);
+		for offset in 0..len {
+			let slot = self.as_mut().slot((head + offset) % N);
+			if !slot.is_done() {
+				// Ready results are picked up below, in order.
+				let _ = slot.poll(cx);
+			}
+		}
+
+		match self.as_mut().slot(head).take_output() {
+			Some(output) => {
+				let this = unsafe {
+					//SAFETY: Not pinned fields.
+					self.get_unchecked_mut()
+				};
+				this.head = (head + 1) % N;
+				this.len -= 1;
+				Poll::Ready(Some(output))
+			}
+			None => Poll::Pending,
+		}
+	}
+
+	/// Returns a [`Future`] that resolves to the output of the oldest entry,
+	/// or to [`None`] if the queue is empty.
+	#[allow(clippy::should_implement_trait)] // Like `StreamExt::next`, but without depending on it.
+	#[must_use]
+	pub fn next(self: Pin<&mut Self>) -> Next<'_, F, N> {
+		Next(self)
+	}
+}
+
+impl<F: Future, const N: usize> Default for FutureQueue<F, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Yields [`None`] whenever the queue is empty, but can be resumed after pushing more [`Future`]s.
+#[cfg(feature = "futures-core")]
+impl<F: Future, const N: usize> Stream for FutureQueue<F, N> {
+	type Item = F::Output;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		FutureQueue::poll_next(self, cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, const N: usize> FusedStream for FutureQueue<F, N> {
+	fn is_terminated(&self) -> bool {
+		self.is_empty()
+	}
+}
+
+/// Shows only the occupancy, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F: Future, const N: usize> core::fmt::Debug for FutureQueue<F, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("FutureQueue")
+			.field("len", &self.len)
+			.field("capacity", &N)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that resolves to the next output of a [`FutureQueue`]. See [`FutureQueue::next`].
+#[derive(Debug)]
+pub struct Next<'a, F: Future, const N: usize>(Pin<&'a mut FutureQueue<F, N>>);
+
+impl<F: Future, const N: usize> Future for Next<'_, F, N> {
+	type Output = Option<F::Output>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.0.as_mut().poll_next(cx)
+	}
+}
@@ -0,0 +1,259 @@
+//! A [`PinnedSlab`] whose handles detect when their value was removed, even if the slot was reused since.
+
+use crate::pinned_slab::{self, PinnedSlab};
+use alloc::vec::Vec;
+use core::{iter::FusedIterator, pin::Pin};
+
+/// Refers to a value in a [`GenerationalArena`].
+///
+/// Once that value is removed, the handle is stale and won't find another value in its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle {
+	index: usize,
+	generation: u64,
+}
+
+impl Handle {
+	/// The slot this refers to, which is reused after removal.
+	#[must_use]
+	pub fn index(self) -> usize {
+		self.index
+	}
+
+	/// How often the slot's value was removed before this handle was created.
+	#[must_use]
+	pub fn generation(self) -> u64 {
+		self.generation
+	}
+}
+
+/// A collection of pinned values, each addressed by a [`Handle`] that can't accidentally refer to a later value.
+///
+/// Like in a [`PinnedSlab`], values never move while they are in the arena,
+/// so they can be accessed as [`Pin<&mut T>`] without pinning the arena itself.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::generational_arena::GenerationalArena;
+///
+/// let mut tasks = GenerationalArena::new();
+/// let first = tasks.insert("first");
+/// assert!(tasks.remove_pinned(first));
+///
+/// let second = tasks.insert("second");
+/// assert_eq!(first.index(), second.index());
+/// assert!(tasks.get(first).is_none());
+/// assert_eq!(tasks.get(second).as_deref(), Some(&"second"));
+/// ```
+///
+/// > The generations are 64 bits wide, so a slot won't wrap around before the program is done.
+pub struct GenerationalArena<T> {
+	slab: PinnedSlab<T>,
+	/// The current generation of each slot that was ever used.
+	generations: Vec<u64>,
+}
+
+impl<T> GenerationalArena<T> {
+	/// Creates a new empty [`GenerationalArena`], without allocating.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			slab: PinnedSlab::new(),
+			generations: Vec::new(),
+		}
+	}
+
+	/// The number of values.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.slab.len()
+	}
+
+	/// Whether there are no values.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.slab.is_empty()
+	}
+
+	/// Whether `handle` refers to a value.
+	#[must_use]
+	pub fn contains(&self, handle: Handle) -> bool {
+		self.is_current(handle) && self.slab.contains(handle.index)
+	}
+
+	/// Stores `value` and returns its [`Handle`].
+	pub fn insert(&mut self, value: T) -> Handle {
+		let index = self.slab.insert(value);
+		if index == self.generations.len() {
+			self.generations.push(0);
+		}
+		Handle {
+			index,
+			generation: self.generations[index],
+		}
+	}
+
+	/// Gives shared access to the value `handle` refers to.
+	#[must_use]
+	pub fn get(&self, handle: Handle) -> Option<Pin<&T>> {
+		self.is_current(handle)
+			.then(|| self.slab.get(handle.index))
+			.flatten()
+	}
+
+	/// Gives pinning exclusive access to the value `handle` refers to.
+	#[must_use]
+	pub fn get_pinned(&mut self, handle: Handle) -> Option<Pin<&mut T>> {
+		if self.is_current(handle) {
+			self.slab.get_pinned(handle.index)
+		} else {
+			None
+		}
+	}
+
+	/// Gives exclusive access to the value `handle` refers to.
+	#[must_use]
+	pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T>
+	where
+		T: Unpin,
+	{
+		self.get_pinned(handle).map(Pin::get_mut)
+	}
+
+	/// Moves the value `handle` refers to out of the arena.
+	pub fn remove(&mut self, handle: Handle) -> Option<T>
+	where
+		T: Unpin,
+	{
+		let value = if self.is_current(handle) {
+			self.slab.remove(handle.index)
+		} else {
+			None
+		};
+		if value.is_some() {
+			self.generations[handle.index] += 1;
+		}
+		value
+	}
+
+	/// Drops the value `handle` refers to in place.
+	///
+	/// Returns whether there was one.
+	pub fn remove_pinned(&mut self, handle: Handle) -> bool {
+		let removed = self.is_current(handle) && self.slab.remove_pinned(handle.index);
+		if removed {
+			self.generations[handle.index] += 1;
+		}
+		removed
+	}
+
+	/// Drops all values in place, making all [`Handle`]s stale.
+	pub fn clear(&mut self) {
+		for (index, _) in &self.slab {
+			self.generations[index] += 1;
+		}
+		self.slab.clear();
+	}
+
+	/// Iterates over the [`Handle`]s and values in index order.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter {
+			values: self.slab.iter(),
+			generations: &self.generations,
+		}
+	}
+
+	/// Iterates over the [`Handle`]s and values in index order, pinning exclusively.
+	#[must_use]
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		IterMut {
+			values: self.slab.iter_mut(),
+			generations: &self.generations,
+		}
+	}
+
+	fn is_current(&self, handle: Handle) -> bool {
+		self.generations.get(handle.index) == Some(&handle.generation)
+	}
+}
+
+impl<T> Default for GenerationalArena<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for GenerationalArena<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_map()
+			.entries(self.iter().map(|(handle, value)| (handle, value.get_ref())))
+			.finish()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a GenerationalArena<T> {
+	type Item = (Handle, Pin<&'a T>);
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut GenerationalArena<T> {
+	type Item = (Handle, Pin<&'a mut T>);
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// A sharing pinning iterator over a [`GenerationalArena`]. See [`GenerationalArena::iter`].
+#[derive(Debug)]
+pub struct Iter<'a, T> {
+	values: pinned_slab::Iter<'a, T>,
+	generations: &'a [u64],
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+	type Item = (Handle, Pin<&'a T>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (index, value) = self.values.next()?;
+		let generation = self.generations[index];
+		Some((Handle { index, generation }, value))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.values.size_hint()
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An exclusive pinning iterator over a [`GenerationalArena`]. See [`GenerationalArena::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+	values: pinned_slab::IterMut<'a, T>,
+	generations: &'a [u64],
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+	type Item = (Handle, Pin<&'a mut T>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (index, value) = self.values.next()?;
+		let generation = self.generations[index];
+		Some((Handle { index, generation }, value))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.values.size_hint()
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
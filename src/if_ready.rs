@@ -1,46 +1,193 @@
-//! A [`Future`] adaptor that is always ready, but may return [`None`].
+//! [`Future`] adaptors that are always ready, but may not have an output.
 
+use crate::waker::noop_context;
 use core::{
 	future::Future,
 	pin::Pin,
 	task::{Context, Poll},
 };
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+#[cfg(feature = "stream")]
+use futures_core::Stream;
 
 /// Returns a [`Future`] that is ready immediately,
 /// returning [`None`] iff `future` is not ready immediately when polled.
 pub fn if_ready<F>(future: F) -> IfReady<F> {
-	IfReady(Some(future))
+	IfReady {
+		future: Some(future),
+		lenient: false,
+	}
+}
+
+/// Polls `future` once, without an asynchronous context, and returns its output iff it was ready.
+///
+/// This works anywhere, including synchronous interrupt handlers,
+/// but `future` won't be able to wake anything and is dropped either way.
+///
+/// > Use [`if_ready`] inside `async` code instead, so that the real [`Context`] is passed on.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::if_ready::now_or_never;
+///
+/// assert_eq!(now_or_never(async { 1 }), Some(1));
+/// assert_eq!(now_or_never(core::future::pending::<()>()), None);
+/// ```
+pub fn now_or_never<F: Future>(future: F) -> Option<F::Output> {
+	let mut future = future;
+	let future = unsafe {
+		//SAFETY: Shadowed, so it can't be moved anymore.
+		Pin::new_unchecked(&mut future)
+	};
+	match future.poll(&mut noop_context()) {
+		Poll::Ready(output) => Some(output),
+		Poll::Pending => None,
+	}
 }
 
 /// A [`Future`] that is always ready, but may return [`None`].
+///
+/// By default, polling it again after completion panics.
+/// See [`IfReady::lenient`] for a mode that returns [`Poll::Pending`] instead.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct IfReady<F>(Option<F>);
+pub struct IfReady<F> {
+	/// [`None`] once completed.
+	future: Option<F>,
+	lenient: bool,
+}
+
+impl<F> IfReady<F> {
+	/// Makes this [`IfReady`] return [`Poll::Pending`] when polled after completion, instead of panicking.
+	///
+	/// This is useful in `select!`-style loops that may poll all branches each time,
+	/// in combination with [`FusedFuture`](`futures_core::FusedFuture`) or [`IfReady::is_terminated`].
+	#[must_use]
+	pub fn lenient(mut self) -> Self {
+		self.lenient = true;
+		self
+	}
+
+	/// Whether this [`IfReady`] has completed, in which case the inner [`Future`] was dropped.
+	#[must_use]
+	pub fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+}
 
 impl<F> Unpin for IfReady<F> {}
 
 impl<F: Future> Future for IfReady<F> {
 	type Output = Option<F::Output>;
 
-	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.future.is_none() && self.lenient {
+			return Poll::Pending;
+		}
+
 		unsafe {
-			let output = match Pin::map_unchecked_mut(self.as_mut(), |this| &mut this.0)
+			let output = match Pin::map_unchecked_mut(self.as_mut(), |this| &mut this.future)
 				.as_pin_mut()
 
 				// We could alternatively just return [`None`],
 				// but that would make the API more error-prone to consume.
 				.expect("`IfReady` erroneously polled twice")
 
-				// Alternatively, we could pass a fake context here that just doesn't schedule anything.
-				// We'd still have to drop the inner future before returning regardless, though.
-				.poll(cx)
+				// The inner future is dropped right after, so it gets a detached context.
+				// Otherwise, it might register a wake-up that would be spurious.
+				.poll(&mut noop_context())
 			{
 				Poll::Pending => None,
 				Poll::Ready(output) => Some(output),
 			};
 
 			// We *have* to drop the inner [`Future`] now, since we can't guarantee it won't stay in place.
-			Pin::get_unchecked_mut(self).0 = None;
+			Pin::get_unchecked_mut(self).future = None;
 			Poll::Ready(output)
 		}
 	}
 }
+
+/// The inner [`Future`] is dropped when this one completes, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for IfReady<F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+}
+
+/// Returns a [`Future`] that is ready immediately,
+/// handing `future` back as error iff it is not ready immediately when polled.
+///
+/// [`!Unpin`](`Unpin`) [`Future`]s can be used by pinning them first, for example through [`Box::pin`](`alloc::boxed::Box::pin`).
+pub fn try_ready<F: Future + Unpin>(future: F) -> TryReady<F> {
+	TryReady(Some(future))
+}
+
+/// A [`Future`] that is always ready, but may hand back its inner [`Future`] instead of an output.
+///
+/// > Unlike [`IfReady`], this can't drop its inner [`Future`] just because it was pinned,
+/// > so handing it back requires [`Unpin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TryReady<F>(Option<F>);
+
+impl<F: Future + Unpin> Future for TryReady<F> {
+	type Output = Result<F::Output, F>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut future = self.0.take().expect("`TryReady` erroneously polled twice");
+		Poll::Ready(match Pin::new(&mut future).poll(cx) {
+			Poll::Ready(output) => Ok(output),
+			Poll::Pending => Err(future),
+		})
+	}
+}
+
+/// The inner [`Future`] is moved out when this one completes, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<F: Future + Unpin> FusedFuture for TryReady<F> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_none()
+	}
+}
+
+/// Returns a [`Future`] that is ready immediately, with the next item of `stream` iff one is ready immediately.
+///
+/// The output is [`Some(Some(item))`](`Some`) for an item, [`Some(None)`](`Some`) if `stream` has ended,
+/// and [`None`] if it isn't ready. `stream` is only borrowed, so it can be polled again later.
+///
+/// > Unlike [`if_ready`], this passes on the real [`Context`], since `stream` stays around to be woken.
+#[cfg(feature = "stream")]
+#[must_use]
+pub fn next_if_ready<S: Stream + ?Sized>(stream: Pin<&mut S>) -> NextIfReady<'_, S> {
+	NextIfReady(Some(stream))
+}
+
+/// A [`Future`] that is always ready, with the next item of a [`Stream`] if there is one right away.
+#[cfg(feature = "stream")]
+#[derive(Debug)]
+pub struct NextIfReady<'a, S: ?Sized>(Option<Pin<&'a mut S>>);
+
+#[cfg(feature = "stream")]
+impl<S: Stream + ?Sized> Future for NextIfReady<'_, S> {
+	type Output = Option<Option<S::Item>>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let stream = self
+			.0
+			.take()
+			.expect("`NextIfReady` erroneously polled twice");
+		Poll::Ready(match stream.poll_next(cx) {
+			Poll::Ready(item) => Some(item),
+			Poll::Pending => None,
+		})
+	}
+}
+
+/// The borrow is released when this completes, so that's a free completion flag.
+#[cfg(feature = "stream")]
+impl<S: Stream + ?Sized> FusedFuture for NextIfReady<'_, S> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_none()
+	}
+}
@@ -0,0 +1,151 @@
+//! Element-by-element in-place initialisation of (pinned) [arrays](https://doc.rust-lang.org/stable/core/primitive.array.html).
+
+use crate::pinned_pin::PinnedPin;
+use core::{
+	mem::{self, MaybeUninit},
+	pin::Pin,
+	ptr,
+};
+
+/// Initialises the array in `storage` in place, one element at a time and in order,
+/// by calling `init` with each index and a pinning reference to the respective uninitialised element.
+///
+/// If `init` panics, the elements initialised up to that point are dropped in place (in order).
+///
+/// > The [`MaybeUninit`] elements are handed out as pinned because they are going to be pinned as soon as they're initialised.
+/// > It's fine to reference the element from elsewhere in the array from there on, as long as that is cleaned up when dropping.
+///
+/// # Safety
+///
+/// `init` must fully initialise the element it's called with whenever it returns normally.
+///
+/// The returned array is pinned, so `storage`'s memory must not be reused or invalidated
+/// before the array was dropped in place (for example using [`ptr::drop_in_place`]), or ever, if it is never dropped.
+/// Leaking the array is fine.
+pub unsafe fn init_array_pinned<T, const N: usize>(
+	storage: &mut MaybeUninit<PinnedPin<[T; N]>>,
+	mut init: impl FnMut(usize, Pin<&mut MaybeUninit<T>>),
+) -> Pin<&mut PinnedPin<[T; N]>> {
+	// `PinnedPin` is transparent, so its storage is that of the array.
+	let array =
+		&mut *(storage as *mut MaybeUninit<PinnedPin<[T; N]>>).cast::<MaybeUninit<[T; N]>>();
+	init_array(array, |index, element| {
+		//SAFETY: The element is pinned from here on per this function's contract.
+		init(index, Pin::new_unchecked(element))
+	});
+	Pin::new_unchecked(&mut *storage.as_mut_ptr())
+}
+
+/// Like [`init_array_pinned`], but without pinning, for arrays that are moved out of `storage` afterwards.
+///
+/// If `init` panics, the elements initialised up to that point are dropped in place (in order).
+///
+/// # Safety
+///
+/// `init` must fully initialise the element it's called with whenever it returns normally.
+pub unsafe fn init_array<T, const N: usize>(
+	storage: &mut MaybeUninit<[T; N]>,
+	mut init: impl FnMut(usize, &mut MaybeUninit<T>),
+) -> &mut [T; N] {
+	/// Drops the initialised prefix if `init` panics.
+	struct Guard<T> {
+		first: *mut T,
+		initialized: usize,
+	}
+
+	impl<T> Drop for Guard<T> {
+		fn drop(&mut self) {
+			unsafe {
+				//SAFETY: Exactly the first `self.initialized` elements are initialised,
+				// and they are never used again after this.
+				ptr::slice_from_raw_parts_mut(self.first, self.initialized).drop_in_place()
+			}
+		}
+	}
+
+	let first = storage.as_mut_ptr().cast::<T>();
+	let mut guard = Guard {
+		first,
+		initialized: 0,
+	};
+
+	while guard.initialized < N {
+		//SAFETY: In bounds.
+		init(
+			guard.initialized,
+			&mut *first.add(guard.initialized).cast::<MaybeUninit<T>>(),
+		);
+		guard.initialized += 1;
+	}
+
+	// Everything is initialised, so the array as a whole takes over the drop responsibility.
+	mem::forget(guard);
+	&mut *storage.as_mut_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+	extern crate std;
+
+	use super::init_array_pinned;
+	use crate::pinned_pin::PinnedPin;
+	use core::{
+		mem::MaybeUninit,
+		pin::Pin,
+		ptr,
+		sync::atomic::{AtomicUsize, Ordering},
+	};
+	use std::panic::{catch_unwind, AssertUnwindSafe};
+
+	/// Counts its drops.
+	struct Counted<'a>(&'a AtomicUsize);
+
+	impl Drop for Counted<'_> {
+		fn drop(&mut self) {
+			self.0.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	fn load(drops: &[AtomicUsize; 4]) -> [usize; 4] {
+		let mut drops = drops.iter();
+		[(); 4].map(|()| drops.next().unwrap().load(Ordering::Relaxed))
+	}
+
+	#[test]
+	fn panic_drops_only_the_initialised_prefix() {
+		let drops: [AtomicUsize; 4] = Default::default();
+		let mut storage = MaybeUninit::<PinnedPin<[Counted<'_>; 4]>>::uninit();
+
+		let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+			init_array_pinned(&mut storage, |index, element| {
+				assert!(index < 2, "Initialisation failed.");
+				Pin::get_unchecked_mut(element).write(Counted(&drops[index]));
+			});
+		}));
+		assert!(result.is_err());
+		assert_eq!(load(&drops), [1, 1, 0, 0]);
+	}
+
+	#[test]
+	fn initialises_every_element_in_order() {
+		let drops: [AtomicUsize; 4] = Default::default();
+		let mut storage = MaybeUninit::<PinnedPin<[Counted<'_>; 4]>>::uninit();
+
+		let mut next = 0;
+		let array = unsafe {
+			init_array_pinned(&mut storage, |index, element| {
+				assert_eq!(index, next);
+				next += 1;
+				Pin::get_unchecked_mut(element).write(Counted(&drops[index]));
+			})
+		};
+		assert_eq!(next, 4);
+		assert_eq!(load(&drops), [0; 4]);
+
+		unsafe {
+			//SAFETY: Dropped in place, and `storage` isn't used afterwards.
+			ptr::drop_in_place(Pin::get_unchecked_mut(array))
+		}
+		assert_eq!(load(&drops), [1; 4]);
+	}
+}
@@ -0,0 +1,207 @@
+//! A [`Future`] wrapper that records how it's polled.
+
+use crate::timer::Clock;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use crate::{
+	atomic_waker::AtomicWaker,
+	poll_stats::PollObserver,
+	waker::{waker_from_arc, ArcWake},
+};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+use core::{
+	future::Future,
+	ops::Sub,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Wraps `future` in an [`Instrumented`] that counts polls, but doesn't keep time.
+pub fn instrumented<F: Future>(future: F) -> Instrumented<F> {
+	Instrumented::new(future, ())
+}
+
+/// Wraps `future` in an [`Instrumented`] that counts polls and reads `clock` to time them.
+pub fn instrumented_with<F: Future, C: Clock>(future: F, clock: C) -> Instrumented<F, C> {
+	Instrumented::new(future, clock)
+}
+
+/// A transparent [`Future`] wrapper that records how often `future` was polled, and when.
+///
+/// Wrap each constituent of a [`JoinFuture`](`crate::join_future::JoinFuture`) in one of these
+/// to find out which of them held it up.
+///
+/// The statistics can be read through the accessors while the [`Instrumented`] is pinned,
+/// and are kept after completion.
+///
+/// > With the default `()` [`Clock`], the instants are all `()` and take up no space.
+#[pin_project]
+pub struct Instrumented<F, C: Clock = ()> {
+	#[pin]
+	future: F,
+	clock: C,
+	polls: usize,
+	first_polled_at: Option<C::Instant>,
+	last_polled_at: Option<C::Instant>,
+	ready_at: Option<C::Instant>,
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	observed: Option<Arc<ObservedWake>>,
+}
+
+/// Forwards wake-ups to the most recently registered [`Waker`](`core::task::Waker`), after recording them.
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+struct ObservedWake {
+	observer: &'static dyn PollObserver,
+	waker: AtomicWaker,
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl ArcWake for ObservedWake {
+	fn wake_by_ref(arc_self: &Arc<Self>) {
+		arc_self.observer.record_wake();
+		arc_self.waker.wake();
+	}
+}
+
+impl<F, C: Clock> Instrumented<F, C> {
+	/// Creates a new instance of [`Instrumented`] that wraps `future` and reads `clock` when it's polled.
+	#[must_use]
+	pub fn new(future: F, clock: C) -> Self {
+		Self {
+			future,
+			clock,
+			polls: 0,
+			first_polled_at: None,
+			last_polled_at: None,
+			ready_at: None,
+			#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+			observed: None,
+		}
+	}
+
+	/// Records each wake of the inner [`Future`] into `observer`, by polling it with a forwarding [`Waker`](`core::task::Waker`).
+	///
+	/// Wrap a combinator that is [observed](`crate::join_future::JoinFuture::observed_by`) by the same [`PollStats`](`crate::poll_stats::PollStats`)
+	/// in this to also fill in its polls-per-wake histogram.
+	///
+	/// > This allocates once, here.
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	#[must_use]
+	pub fn observed_by(mut self, observer: &'static dyn PollObserver) -> Self {
+		self.observed = Some(Arc::new(ObservedWake {
+			observer,
+			waker: AtomicWaker::new(),
+		}));
+		self
+	}
+
+	/// Gives pinning exclusive access to the inner [`Future`].
+	#[must_use]
+	pub fn future_pin_mut(self: Pin<&mut Self>) -> Pin<&mut F> {
+		self.project().future
+	}
+
+	/// The [`Clock`] this [`Instrumented`] reads.
+	#[must_use]
+	pub fn clock(&self) -> &C {
+		&self.clock
+	}
+
+	/// How often the inner [`Future`] was polled, in total.
+	#[must_use]
+	pub fn polls(&self) -> usize {
+		self.polls
+	}
+
+	/// How often polling the inner [`Future`] returned [`Poll::Pending`].
+	#[must_use]
+	pub fn pending_polls(&self) -> usize {
+		self.polls - usize::from(self.is_ready())
+	}
+
+	/// Whether the inner [`Future`] has completed.
+	#[must_use]
+	pub fn is_ready(&self) -> bool {
+		self.ready_at.is_some()
+	}
+
+	/// When the inner [`Future`] was first polled, if it was.
+	#[must_use]
+	pub fn first_polled_at(&self) -> Option<C::Instant> {
+		self.first_polled_at
+	}
+
+	/// When the inner [`Future`] was most recently polled, if it was.
+	#[must_use]
+	pub fn last_polled_at(&self) -> Option<C::Instant> {
+		self.last_polled_at
+	}
+
+	/// When the inner [`Future`] completed, if it has.
+	#[must_use]
+	pub fn ready_at(&self) -> Option<C::Instant> {
+		self.ready_at
+	}
+
+	/// How long the inner [`Future`] took to complete, from its first poll, if it has.
+	#[must_use]
+	pub fn time_to_ready(&self) -> Option<<C::Instant as Sub>::Output>
+	where
+		C::Instant: Sub,
+	{
+		Some(self.ready_at? - self.first_polled_at?)
+	}
+}
+
+impl<F: Future, C: Clock> Future for Instrumented<F, C> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		let now = this.clock.now();
+		this.first_polled_at.get_or_insert(now);
+		*this.last_polled_at = Some(now);
+		*this.polls += 1;
+
+		#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+		let poll = if let Some(observed) = this.observed {
+			// Registering first means no wake-up can slip through between the two steps.
+			observed.waker.register(cx.waker());
+			let waker = waker_from_arc(observed.clone());
+			this.future.poll(&mut Context::from_waker(&waker))
+		} else {
+			this.future.poll(cx)
+		};
+		#[cfg(not(all(feature = "alloc", target_has_atomic = "ptr")))]
+		let poll = this.future.poll(cx);
+		if poll.is_ready() && this.ready_at.is_none() {
+			*this.ready_at = Some(this.clock.now());
+		}
+		poll
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: FusedFuture, C: Clock> FusedFuture for Instrumented<F, C> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_terminated()
+	}
+}
+
+/// Shows only the statistics, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F, C: Clock> core::fmt::Debug for Instrumented<F, C>
+where
+	C::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Instrumented")
+			.field("polls", &self.polls)
+			.field("first_polled_at", &self.first_polled_at)
+			.field("last_polled_at", &self.last_polled_at)
+			.field("ready_at", &self.ready_at)
+			.finish_non_exhaustive()
+	}
+}
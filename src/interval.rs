@@ -0,0 +1,165 @@
+//! A [`Stream`](`futures_core::Stream`)-like ticker on top of [`Timer`].
+
+use crate::{
+	sleep::{self, Delay},
+	timer::Timer,
+};
+#[cfg(feature = "futures-core")]
+use core::pin::Pin;
+use core::{
+	ops::Add,
+	task::{Context, Poll},
+	time::Duration,
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+
+/// What an [`Interval`] does when it's polled late, after one or more ticks were due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MissedTicks {
+	/// Yields all missed ticks right away, one per poll, to catch up with the original schedule.
+	#[default]
+	Burst,
+	/// Yields one tick right away and continues with the next tick of the original schedule that is still in the future.
+	Skip,
+	/// Yields one tick right away and restarts the schedule from now.
+	Delay,
+}
+
+/// Creates an [`Interval`] that ticks every `period` on the driver registered with [`set_driver`](`crate::sleep::set_driver`).
+///
+/// # Panics
+///
+/// Iff no driver was registered.
+#[must_use]
+pub fn interval(period: Duration) -> Interval {
+	Interval::new(sleep::expect_driver(), period)
+}
+
+/// Creates an [`Interval`] that ticks every `period` on `timer`.
+pub fn interval_on<T: Timer, D: Copy>(timer: T, period: D) -> Interval<T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	Interval::new(timer, period)
+}
+
+/// Yields the scheduled instant of each tick, every `period`, indefinitely.
+///
+/// The first tick is due right away.
+/// By default, ticks missed due to late polling are yielded in a [burst](`MissedTicks::Burst`),
+/// see [`.with_missed_ticks(…)`](`Interval::with_missed_ticks`) for alternatives.
+///
+/// > This doesn't need to be pinned. Periodic firmware tasks can keep one in a plain `loop`.
+pub struct Interval<T: Timer = &'static dyn Delay, D = Duration> {
+	timer: T,
+	period: D,
+	next: T::Instant,
+	missed_ticks: MissedTicks,
+}
+
+impl<T: Timer, D: Copy> Interval<T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	/// Creates a new instance of [`Interval`] that ticks every `period` on `timer`, starting now.
+	#[must_use]
+	pub fn new(timer: T, period: D) -> Self {
+		Self {
+			next: timer.now(),
+			timer,
+			period,
+			missed_ticks: MissedTicks::default(),
+		}
+	}
+
+	/// Sets what to do about missed ticks.
+	#[must_use]
+	pub fn with_missed_ticks(mut self, missed_ticks: MissedTicks) -> Self {
+		self.missed_ticks = missed_ticks;
+		self
+	}
+
+	/// The time between ticks.
+	#[must_use]
+	pub fn period(&self) -> D {
+		self.period
+	}
+
+	/// When the next tick is due.
+	#[must_use]
+	pub fn next_tick(&self) -> T::Instant {
+		self.next
+	}
+
+	/// Restarts the schedule, with the next tick due one `period` from now.
+	pub fn reset(&mut self) {
+		self.next = self.timer.now() + self.period;
+	}
+
+	/// Yields the scheduled instant of the next tick once it's due.
+	pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<T::Instant> {
+		let now = self.timer.now();
+		let tick = self.next;
+		if now < tick {
+			self.timer.wake_at(tick, cx.waker());
+			return Poll::Pending;
+		}
+
+		self.next = match self.missed_ticks {
+			MissedTicks::Burst => tick + self.period,
+			MissedTicks::Skip => {
+				let mut next = tick + self.period;
+				while next <= now {
+					next = next + self.period;
+				}
+				next
+			}
+			MissedTicks::Delay => now + self.period,
+		};
+		Poll::Ready(tick)
+	}
+}
+
+impl<T: Timer, D> Unpin for Interval<T, D> {}
+
+/// Never ends.
+#[cfg(feature = "futures-core")]
+impl<T: Timer, D: Copy> Stream for Interval<T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	type Item = T::Instant;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.get_mut().poll_tick(cx).map(Some)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(usize::MAX, None)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T: Timer, D: Copy> FusedStream for Interval<T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+/// Shows only the schedule, since `T` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T: Timer, D: core::fmt::Debug> core::fmt::Debug for Interval<T, D>
+where
+	T::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Interval")
+			.field("period", &self.period)
+			.field("next", &self.next)
+			.field("missed_ticks", &self.missed_ticks)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,19 @@
+//! Intrusive collections, which link caller-provided pinned nodes in place instead of allocating.
+//!
+//! Each collection borrows its nodes for as long as they are linked,
+//! so the borrow checker ensures they are neither moved nor dropped in the meantime.
+//! Removing a node hands that borrow back.
+//!
+//! [`SinglyLinkedList`] borrows its nodes exclusively, and so can hand out their values mutably.
+//! The others borrow them shared and must be pinned themselves, in exchange for unlinking any node by reference.
+//!
+//! > The nodes don't have to be `!Unpin` themselves. Taking them as [`Pin<&mut _>`](`core::pin::Pin`) means
+//! > the values inside can be, so that pinned [`Future`](`core::future::Future`)s can be queued up without a heap.
+
+pub mod avl_tree;
+pub mod doubly_linked_list;
+pub mod singly_linked_list;
+
+pub use avl_tree::AvlTree;
+pub use doubly_linked_list::DoublyLinkedList;
+pub use singly_linked_list::SinglyLinkedList;
@@ -0,0 +1,649 @@
+//! An intrusive AVL tree ordered by a key embedded in each [`Node`], with O(log n) insertion and removal.
+
+use core::{
+	borrow::Borrow,
+	cell::Cell,
+	cmp::Ordering,
+	iter::FusedIterator,
+	marker::{PhantomData, PhantomPinned},
+	ops::Deref,
+	pin::Pin,
+	ptr::{self, NonNull},
+};
+
+type Link<K, T> = Option<NonNull<Node<K, T>>>;
+
+/// A key and value with embedded links, which can be inserted into an [`AvlTree`].
+///
+/// The tree only borrows it shared, so `&Node` doubles as handle for [`AvlTree::remove`].
+pub struct Node<K, T> {
+	parent: Cell<Link<K, T>>,
+	left: Cell<Link<K, T>>,
+	right: Cell<Link<K, T>>,
+	height: Cell<u8>,
+	/// The address of the [`AvlTree`] this is linked into, or null.
+	///
+	/// > As with the [`DoublyLinkedList`](`super::DoublyLinkedList`), this is only ever compared.
+	tree: Cell<*const ()>,
+	key: K,
+	/// Pinned, structurally.
+	value: T,
+}
+
+impl<K, T> Node<K, T> {
+	/// Creates a new unlinked [`Node`] holding `key` and `value`.
+	#[must_use]
+	pub const fn new(key: K, value: T) -> Self {
+		Self {
+			parent: Cell::new(None),
+			left: Cell::new(None),
+			right: Cell::new(None),
+			height: Cell::new(0),
+			tree: Cell::new(ptr::null()),
+			key,
+			value,
+		}
+	}
+
+	/// Whether this [`Node`] is linked into an [`AvlTree`].
+	#[must_use]
+	pub fn is_linked(&self) -> bool {
+		!self.tree.get().is_null()
+	}
+
+	/// The key this [`Node`] is ordered by.
+	#[must_use]
+	pub fn key(&self) -> &K {
+		&self.key
+	}
+
+	/// Pinning shared access to the value.
+	#[must_use]
+	pub fn get(self: Pin<&Self>) -> Pin<&T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked(|this| &this.value)
+		}
+	}
+
+	/// Pinning exclusive access to the value.
+	#[must_use]
+	pub fn get_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked_mut(|this| &mut this.value)
+		}
+	}
+
+	/// Unwraps the key and value.
+	pub fn into_inner(self) -> (K, T) {
+		(self.key, self.value)
+	}
+}
+
+impl<K, T> Deref for Node<K, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<K: core::fmt::Debug, T: core::fmt::Debug> core::fmt::Debug for Node<K, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Node")
+			.field("key", &self.key)
+			.field("value", &self.value)
+			.field("linked", &self.is_linked())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A self-balancing binary search tree of [`Node`]s that it borrows for `'a`, ordered by their keys.
+///
+/// Equal keys are kept in insertion order, so this works as a stable priority queue,
+/// for example of timer deadlines.
+///
+/// It must be pinned to be modified, since its [`Node`]s remember which tree they are in.
+/// That way, any [`Node`] can be [removed](`AvlTree::remove`) without searching for it first.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::pin::Pin;
+/// use unpin_choices_dsa::intrusive::avl_tree::{AvlTree, Node};
+///
+/// let (a, b, c) = (Node::new(30, 'a'), Node::new(10, 'b'), Node::new(20, 'c'));
+/// let mut tree = Box::pin(AvlTree::new());
+/// for node in [&a, &b, &c] {
+///     tree.as_mut().insert(Pin::new(node));
+/// }
+///
+/// assert_eq!(tree.get(&20).map(|node| **node), Some('c'));
+/// assert!(tree.as_mut().remove(Pin::new(&b)));
+/// assert_eq!(tree.as_mut().pop_first().map(|node| *node.key()), Some(20));
+/// assert!(tree.iter().map(|node| **node).eq(['a']));
+/// ```
+pub struct AvlTree<'a, K, T> {
+	root: Link<K, T>,
+	len: usize,
+	_pinned: PhantomPinned,
+	_nodes: PhantomData<Pin<&'a Node<K, T>>>,
+}
+
+impl<'a, K, T> AvlTree<'a, K, T> {
+	/// Creates a new empty [`AvlTree`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			root: None,
+			len: 0,
+			_pinned: PhantomPinned,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// The number of linked [`Node`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether no [`Node`]s are linked.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.root.is_none()
+	}
+
+	/// Whether `node` is linked into this tree.
+	#[must_use]
+	pub fn contains(&self, node: &Node<K, T>) -> bool {
+		node.tree.get() == self.id()
+	}
+
+	/// The [`Node`] with the least key, and of those the one inserted first.
+	#[must_use]
+	pub fn first(&self) -> Option<Pin<&'a Node<K, T>>> {
+		self.root
+			.map(|root| unsafe { Self::pinned(leftmost(root)) })
+	}
+
+	/// The [`Node`] with the greatest key, and of those the one inserted last.
+	#[must_use]
+	pub fn last(&self) -> Option<Pin<&'a Node<K, T>>> {
+		self.root
+			.map(|root| unsafe { Self::pinned(rightmost(root)) })
+	}
+
+	/// The first [`Node`] with a key equal to `key`, in O(log n) time.
+	#[must_use]
+	pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<Pin<&'a Node<K, T>>>
+	where
+		K: Borrow<Q>,
+	{
+		let mut found = None;
+		let mut current = self.root;
+		while let Some(node) = current {
+			let node = unsafe { Self::pinned(node) };
+			current = match key.cmp(node.key.borrow()) {
+				Ordering::Less => node.left.get(),
+				Ordering::Greater => node.right.get(),
+				Ordering::Equal => {
+					found = Some(node);
+					node.left.get()
+				}
+			};
+		}
+		found
+	}
+
+	/// Links `node` by its key, after any with an equal key, in O(log n) time.
+	///
+	/// # Panics
+	///
+	/// Iff `node` is already linked.
+	pub fn insert(self: Pin<&mut Self>, node: Pin<&'a Node<K, T>>)
+	where
+		K: Ord,
+	{
+		assert!(!node.is_linked(), "`Node` is already linked.");
+		let this = unsafe { self.get_unchecked_mut() };
+
+		let mut parent = None;
+		let mut current = this.root;
+		let mut is_left = false;
+		while let Some(next) = current {
+			let next_ref = unsafe { next.as_ref() };
+			parent = Some(next);
+			is_left = node.key < next_ref.key;
+			current = if is_left {
+				next_ref.left.get()
+			} else {
+				next_ref.right.get()
+			};
+		}
+
+		node.tree.set(this.id());
+		node.parent.set(parent);
+		node.left.set(None);
+		node.right.set(None);
+		node.height.set(1);
+		let node = NonNull::from(node.get_ref());
+		match parent {
+			None => this.root = Some(node),
+			Some(parent) if is_left => unsafe { parent.as_ref() }.left.set(Some(node)),
+			Some(parent) => unsafe { parent.as_ref() }.right.set(Some(node)),
+		}
+		this.len += 1;
+		unsafe { this.rebalance_from(parent) }
+	}
+
+	/// Unlinks `node` in O(log n) time, if it's linked into this tree.
+	///
+	/// Returns whether it was.
+	#[must_use]
+	pub fn remove(self: Pin<&mut Self>, node: Pin<&Node<K, T>>) -> bool {
+		let this = unsafe { self.get_unchecked_mut() };
+		let linked = this.contains(&node);
+		if linked {
+			unsafe { this.unlink(NonNull::from(node.get_ref())) };
+		}
+		linked
+	}
+
+	/// Unlinks the [first](`AvlTree::first`) [`Node`] and returns it.
+	#[must_use]
+	pub fn pop_first(self: Pin<&mut Self>) -> Option<Pin<&'a Node<K, T>>> {
+		let this = unsafe { self.get_unchecked_mut() };
+		this.root.map(|root| unsafe { this.unlink(leftmost(root)) })
+	}
+
+	/// Unlinks the [last](`AvlTree::last`) [`Node`] and returns it.
+	#[must_use]
+	pub fn pop_last(self: Pin<&mut Self>) -> Option<Pin<&'a Node<K, T>>> {
+		let this = unsafe { self.get_unchecked_mut() };
+		this.root
+			.map(|root| unsafe { this.unlink(rightmost(root)) })
+	}
+
+	/// Unlinks all [`Node`]s.
+	pub fn clear(self: Pin<&mut Self>) {
+		unsafe { self.get_unchecked_mut() }.clear_links()
+	}
+
+	/// Iterates over the [`Node`]s in order.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, K, T> {
+		Iter {
+			front: self.root.map(|root| unsafe { leftmost(root) }),
+			back: self.root.map(|root| unsafe { rightmost(root) }),
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// Identifies this tree to its [`Node`]s. Only stable while pinned.
+	fn id(&self) -> *const () {
+		(self as *const Self).cast()
+	}
+
+	/// # Safety
+	///
+	/// `node` must be linked into a live [`AvlTree<'a, K, T>`].
+	unsafe fn pinned(node: NonNull<Node<K, T>>) -> Pin<&'a Node<K, T>> {
+		//SAFETY: Linked `Node`s were pinned and are borrowed for `'a`.
+		Pin::new_unchecked(&*node.as_ptr())
+	}
+
+	/// Points `parent`'s link to `old` at `new` instead, or the root if `parent` is [`None`].
+	///
+	/// # Safety
+	///
+	/// `old` must be a child of `parent` in this tree.
+	unsafe fn replace_child(
+		&mut self,
+		parent: Link<K, T>,
+		old: NonNull<Node<K, T>>,
+		new: Link<K, T>,
+	) {
+		match parent {
+			None => self.root = new,
+			Some(parent) => {
+				let parent = parent.as_ref();
+				if parent.left.get() == Some(old) {
+					parent.left.set(new);
+				} else {
+					parent.right.set(new);
+				}
+			}
+		}
+	}
+
+	/// # Safety
+	///
+	/// `node` must be linked into this tree.
+	unsafe fn unlink(&mut self, node: NonNull<Node<K, T>>) -> Pin<&'a Node<K, T>> {
+		let node = Self::pinned(node);
+		let node_ptr = NonNull::from(node.get_ref());
+		let parent = node.parent.get();
+		let rebalance_from = match (node.left.get(), node.right.get()) {
+			(None, child) | (child, None) => {
+				self.replace_child(parent, node_ptr, child);
+				if let Some(child) = child {
+					child.as_ref().parent.set(parent);
+				}
+				parent
+			}
+			(Some(left), Some(right)) => {
+				// Replace `node` with its in-order successor.
+				let successor = leftmost(right);
+				let successor_ref = successor.as_ref();
+				let rebalance_from = if successor == right {
+					successor
+				} else {
+					let successor_parent = successor_ref.parent.get().expect("unreachable");
+					let successor_right = successor_ref.right.get();
+					successor_parent.as_ref().left.set(successor_right);
+					if let Some(successor_right) = successor_right {
+						successor_right.as_ref().parent.set(Some(successor_parent));
+					}
+					successor_ref.right.set(Some(right));
+					right.as_ref().parent.set(Some(successor));
+					successor_parent
+				};
+				successor_ref.left.set(Some(left));
+				left.as_ref().parent.set(Some(successor));
+				successor_ref.parent.set(parent);
+				successor_ref.height.set(node.height.get());
+				self.replace_child(parent, node_ptr, Some(successor));
+				Some(rebalance_from)
+			}
+		};
+
+		node.parent.set(None);
+		node.left.set(None);
+		node.right.set(None);
+		node.height.set(0);
+		node.tree.set(ptr::null());
+		self.len -= 1;
+		self.rebalance_from(rebalance_from);
+		node
+	}
+
+	/// Restores heights and balance from `node` up to the root.
+	///
+	/// # Safety
+	///
+	/// `node` must be [`None`] or linked into this tree.
+	unsafe fn rebalance_from(&mut self, mut node: Link<K, T>) {
+		while let Some(current) = node {
+			update_height(current);
+			let current = match balance(current) {
+				2.. => {
+					let left = current.as_ref().left.get().expect("unreachable");
+					if balance(left) < 0 {
+						self.rotate_left(left);
+					}
+					self.rotate_right(current)
+				}
+				i16::MIN..=-2 => {
+					let right = current.as_ref().right.get().expect("unreachable");
+					if balance(right) > 0 {
+						self.rotate_right(right);
+					}
+					self.rotate_left(current)
+				}
+				_ => current,
+			};
+			node = current.as_ref().parent.get();
+		}
+	}
+
+	/// Returns the new subtree root.
+	///
+	/// # Safety
+	///
+	/// `node` must be linked into this tree and have a right child.
+	unsafe fn rotate_left(&mut self, node: NonNull<Node<K, T>>) -> NonNull<Node<K, T>> {
+		let node_ref = node.as_ref();
+		let pivot = node_ref.right.get().expect("unreachable");
+		let pivot_ref = pivot.as_ref();
+		let inner = pivot_ref.left.get();
+		node_ref.right.set(inner);
+		if let Some(inner) = inner {
+			inner.as_ref().parent.set(Some(node));
+		}
+		let parent = node_ref.parent.get();
+		pivot_ref.parent.set(parent);
+		self.replace_child(parent, node, Some(pivot));
+		pivot_ref.left.set(Some(node));
+		node_ref.parent.set(Some(pivot));
+		update_height(node);
+		update_height(pivot);
+		pivot
+	}
+
+	/// Returns the new subtree root.
+	///
+	/// # Safety
+	///
+	/// `node` must be linked into this tree and have a left child.
+	unsafe fn rotate_right(&mut self, node: NonNull<Node<K, T>>) -> NonNull<Node<K, T>> {
+		let node_ref = node.as_ref();
+		let pivot = node_ref.left.get().expect("unreachable");
+		let pivot_ref = pivot.as_ref();
+		let inner = pivot_ref.right.get();
+		node_ref.left.set(inner);
+		if let Some(inner) = inner {
+			inner.as_ref().parent.set(Some(node));
+		}
+		let parent = node_ref.parent.get();
+		pivot_ref.parent.set(parent);
+		self.replace_child(parent, node, Some(pivot));
+		pivot_ref.right.set(Some(node));
+		node_ref.parent.set(Some(pivot));
+		update_height(node);
+		update_height(pivot);
+		pivot
+	}
+
+	/// Unlinks all [`Node`]s, leaves first, without recursion.
+	fn clear_links(&mut self) {
+		let mut current = self.root.take();
+		self.len = 0;
+		while let Some(node) = current {
+			let node = unsafe { Self::pinned(node) };
+			current = if let Some(left) = node.left.take() {
+				Some(left)
+			} else if let Some(right) = node.right.take() {
+				Some(right)
+			} else {
+				node.height.set(0);
+				node.tree.set(ptr::null());
+				node.parent.take()
+			};
+		}
+	}
+}
+
+/// # Safety
+///
+/// `node` and its descendants must be linked.
+unsafe fn leftmost<K, T>(mut node: NonNull<Node<K, T>>) -> NonNull<Node<K, T>> {
+	while let Some(left) = node.as_ref().left.get() {
+		node = left;
+	}
+	node
+}
+
+/// # Safety
+///
+/// `node` and its descendants must be linked.
+unsafe fn rightmost<K, T>(mut node: NonNull<Node<K, T>>) -> NonNull<Node<K, T>> {
+	while let Some(right) = node.as_ref().right.get() {
+		node = right;
+	}
+	node
+}
+
+/// # Safety
+///
+/// `node` must be linked.
+unsafe fn height<K, T>(node: Link<K, T>) -> u8 {
+	node.map_or(0, |node| node.as_ref().height.get())
+}
+
+/// # Safety
+///
+/// `node` must be linked.
+unsafe fn update_height<K, T>(node: NonNull<Node<K, T>>) {
+	let node = node.as_ref();
+	node.height
+		.set(1 + height(node.left.get()).max(height(node.right.get())));
+}
+
+/// Left height minus right height.
+///
+/// # Safety
+///
+/// `node` must be linked.
+unsafe fn balance<K, T>(node: NonNull<Node<K, T>>) -> i16 {
+	let node = node.as_ref();
+	i16::from(height(node.left.get())) - i16::from(height(node.right.get()))
+}
+
+/// # Safety
+///
+/// `node` must be linked.
+unsafe fn successor<K, T>(node: NonNull<Node<K, T>>) -> Link<K, T> {
+	if let Some(right) = node.as_ref().right.get() {
+		return Some(leftmost(right));
+	}
+	let mut child = node;
+	let mut parent = node.as_ref().parent.get();
+	while let Some(ancestor) = parent {
+		if ancestor.as_ref().right.get() != Some(child) {
+			break;
+		}
+		child = ancestor;
+		parent = ancestor.as_ref().parent.get();
+	}
+	parent
+}
+
+/// # Safety
+///
+/// `node` must be linked.
+unsafe fn predecessor<K, T>(node: NonNull<Node<K, T>>) -> Link<K, T> {
+	if let Some(left) = node.as_ref().left.get() {
+		return Some(rightmost(left));
+	}
+	let mut child = node;
+	let mut parent = node.as_ref().parent.get();
+	while let Some(ancestor) = parent {
+		if ancestor.as_ref().left.get() != Some(child) {
+			break;
+		}
+		child = ancestor;
+		parent = ancestor.as_ref().parent.get();
+	}
+	parent
+}
+
+impl<K, T> Default for AvlTree<'_, K, T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Unlinks all [`Node`]s, so that they can be linked elsewhere afterwards.
+impl<K, T> Drop for AvlTree<'_, K, T> {
+	fn drop(&mut self) {
+		self.clear_links()
+	}
+}
+
+impl<K: core::fmt::Debug, T: core::fmt::Debug> core::fmt::Debug for AvlTree<'_, K, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_map()
+			.entries(self.iter().map(|node| {
+				let node = node.get_ref();
+				(&node.key, &node.value)
+			}))
+			.finish()
+	}
+}
+
+impl<'l, K, T> IntoIterator for &'l AvlTree<'_, K, T> {
+	type Item = Pin<&'l Node<K, T>>;
+	type IntoIter = Iter<'l, K, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// An in-order iterator over the [`Node`]s of an [`AvlTree`]. See [`AvlTree::iter`].
+pub struct Iter<'l, K, T> {
+	front: Link<K, T>,
+	back: Link<K, T>,
+	len: usize,
+	_nodes: PhantomData<&'l Node<K, T>>,
+}
+
+impl<'l, K, T> Iterator for Iter<'l, K, T> {
+	type Item = Pin<&'l Node<K, T>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.len == 0 {
+			return None;
+		}
+		let node = self.front?;
+		self.front = unsafe { successor(node) };
+		self.len -= 1;
+		Some(unsafe {
+			//SAFETY: Linked `Node`s are pinned and borrowed by the tree, which is borrowed for `'l`.
+			Pin::new_unchecked(&*node.as_ptr())
+		})
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<K, T> DoubleEndedIterator for Iter<'_, K, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		if self.len == 0 {
+			return None;
+		}
+		let node = self.back?;
+		self.back = unsafe { predecessor(node) };
+		self.len -= 1;
+		Some(unsafe {
+			//SAFETY: As above.
+			Pin::new_unchecked(&*node.as_ptr())
+		})
+	}
+}
+
+impl<K, T> Clone for Iter<'_, K, T> {
+	fn clone(&self) -> Self {
+		Self {
+			front: self.front,
+			back: self.back,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+}
+
+impl<K, T> ExactSizeIterator for Iter<'_, K, T> {}
+impl<K, T> FusedIterator for Iter<'_, K, T> {}
+
+impl<K, T> core::fmt::Debug for Iter<'_, K, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Iter")
+			.field("len", &self.len)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,538 @@
+//! An intrusive doubly-linked list with O(1) unlinking of any [`Node`] and a [`CursorMut`] for splicing.
+
+use core::{
+	cell::Cell,
+	iter::FusedIterator,
+	marker::{PhantomData, PhantomPinned},
+	ops::Deref,
+	pin::Pin,
+	ptr::{self, NonNull},
+};
+
+/// A value with embedded links, which can be linked into a [`DoublyLinkedList`].
+///
+/// The list only borrows it shared, so `&Node` doubles as handle for [`DoublyLinkedList::unlink`].
+pub struct Node<T> {
+	prev: Cell<Option<NonNull<Node<T>>>>,
+	next: Cell<Option<NonNull<Node<T>>>>,
+	/// The address of the [`DoublyLinkedList`] this is linked into, or null.
+	///
+	/// > This is only ever compared, never dereferenced, so a stale one can't cause access to freed memory.
+	list: Cell<*const ()>,
+	/// Pinned, structurally.
+	value: T,
+}
+
+impl<T> Node<T> {
+	/// Creates a new unlinked [`Node`] holding `value`.
+	#[must_use]
+	pub const fn new(value: T) -> Self {
+		Self {
+			prev: Cell::new(None),
+			next: Cell::new(None),
+			list: Cell::new(ptr::null()),
+			value,
+		}
+	}
+
+	/// Whether this [`Node`] is linked into a [`DoublyLinkedList`].
+	#[must_use]
+	pub fn is_linked(&self) -> bool {
+		!self.list.get().is_null()
+	}
+
+	/// Pinning shared access to the value.
+	#[must_use]
+	pub fn get(self: Pin<&Self>) -> Pin<&T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked(|this| &this.value)
+		}
+	}
+
+	/// Pinning exclusive access to the value.
+	#[must_use]
+	pub fn get_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked_mut(|this| &mut this.value)
+		}
+	}
+
+	/// Unwraps the value.
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+}
+
+impl<T> Deref for Node<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Node<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Node")
+			.field("value", &self.value)
+			.field("linked", &self.is_linked())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A doubly-linked list of [`Node`]s that it borrows for `'a`.
+///
+/// It must be pinned to be modified, since its [`Node`]s remember which list they are in.
+/// That way, any [`Node`] can be [unlinked](`DoublyLinkedList::unlink`) in O(1) time.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::pin::Pin;
+/// use unpin_choices_dsa::intrusive::doubly_linked_list::{DoublyLinkedList, Node};
+///
+/// let (a, b, c) = (Node::new(1), Node::new(2), Node::new(3));
+/// let mut list = Box::pin(DoublyLinkedList::new());
+/// for node in [&a, &b, &c] {
+///     list.as_mut().push_back(Pin::new(node));
+/// }
+///
+/// assert!(list.as_mut().unlink(Pin::new(&b)));
+/// assert!(!b.is_linked());
+///
+/// let mut cursor = list.as_mut().cursor_front_mut();
+/// cursor.insert_after(Pin::new(&b));
+/// assert!(list.iter().map(|node| **node).eq([1, 2, 3]));
+/// ```
+pub struct DoublyLinkedList<'a, T> {
+	head: Option<NonNull<Node<T>>>,
+	tail: Option<NonNull<Node<T>>>,
+	len: usize,
+	_pinned: PhantomPinned,
+	_nodes: PhantomData<Pin<&'a Node<T>>>,
+}
+
+impl<'a, T> DoublyLinkedList<'a, T> {
+	/// Creates a new empty [`DoublyLinkedList`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			head: None,
+			tail: None,
+			len: 0,
+			_pinned: PhantomPinned,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// The number of linked [`Node`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether no [`Node`]s are linked.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.head.is_none()
+	}
+
+	/// Whether `node` is linked into this list.
+	#[must_use]
+	pub fn contains(&self, node: &Node<T>) -> bool {
+		node.list.get() == self.id()
+	}
+
+	/// The front [`Node`].
+	#[must_use]
+	pub fn front(&self) -> Option<Pin<&'a Node<T>>> {
+		self.head.map(|head| unsafe { Self::pinned(head) })
+	}
+
+	/// The back [`Node`].
+	#[must_use]
+	pub fn back(&self) -> Option<Pin<&'a Node<T>>> {
+		self.tail.map(|tail| unsafe { Self::pinned(tail) })
+	}
+
+	/// Links `node` at the front.
+	///
+	/// # Panics
+	///
+	/// Iff `node` is already linked.
+	pub fn push_front(self: Pin<&mut Self>, node: Pin<&'a Node<T>>) {
+		let this = unsafe { self.get_unchecked_mut() };
+		unsafe { this.link_between(None, this.head, node) }
+	}
+
+	/// Links `node` at the back.
+	///
+	/// # Panics
+	///
+	/// Iff `node` is already linked.
+	pub fn push_back(self: Pin<&mut Self>, node: Pin<&'a Node<T>>) {
+		let this = unsafe { self.get_unchecked_mut() };
+		unsafe { this.link_between(this.tail, None, node) }
+	}
+
+	/// Unlinks the front [`Node`] and returns it.
+	#[must_use]
+	pub fn pop_front(self: Pin<&mut Self>) -> Option<Pin<&'a Node<T>>> {
+		let this = unsafe { self.get_unchecked_mut() };
+		this.head.map(|head| unsafe { this.unlink_linked(head) })
+	}
+
+	/// Unlinks the back [`Node`] and returns it.
+	#[must_use]
+	pub fn pop_back(self: Pin<&mut Self>) -> Option<Pin<&'a Node<T>>> {
+		let this = unsafe { self.get_unchecked_mut() };
+		this.tail.map(|tail| unsafe { this.unlink_linked(tail) })
+	}
+
+	/// Unlinks `node` in O(1) time, if it's linked into this list.
+	///
+	/// Returns whether it was.
+	#[must_use]
+	pub fn unlink(self: Pin<&mut Self>, node: Pin<&Node<T>>) -> bool {
+		let this = unsafe { self.get_unchecked_mut() };
+		let linked = this.contains(&node);
+		if linked {
+			unsafe { this.unlink_linked(NonNull::from(node.get_ref())) };
+		}
+		linked
+	}
+
+	/// Unlinks all [`Node`]s.
+	pub fn clear(self: Pin<&mut Self>) {
+		unsafe { self.get_unchecked_mut() }.clear_links()
+	}
+
+	/// Iterates over the [`Node`]s from front to back.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter {
+			front: self.head,
+			back: self.tail,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// Creates a [`CursorMut`] at the front [`Node`], or at the "ghost" position if the list is empty.
+	#[must_use]
+	pub fn cursor_front_mut(self: Pin<&mut Self>) -> CursorMut<'_, 'a, T> {
+		let current = self.head;
+		CursorMut {
+			list: unsafe { self.get_unchecked_mut() },
+			current,
+		}
+	}
+
+	/// Creates a [`CursorMut`] at the back [`Node`], or at the "ghost" position if the list is empty.
+	#[must_use]
+	pub fn cursor_back_mut(self: Pin<&mut Self>) -> CursorMut<'_, 'a, T> {
+		let current = self.tail;
+		CursorMut {
+			list: unsafe { self.get_unchecked_mut() },
+			current,
+		}
+	}
+
+	/// Creates a [`CursorMut`] at `node`, if it's linked into this list.
+	#[must_use]
+	pub fn cursor_at_mut(
+		self: Pin<&mut Self>,
+		node: Pin<&Node<T>>,
+	) -> Option<CursorMut<'_, 'a, T>> {
+		self.contains(&node).then(|| CursorMut {
+			list: unsafe { self.get_unchecked_mut() },
+			current: Some(NonNull::from(node.get_ref())),
+		})
+	}
+
+	/// Identifies this list to its [`Node`]s. Only stable while pinned.
+	fn id(&self) -> *const () {
+		(self as *const Self).cast()
+	}
+
+	/// # Safety
+	///
+	/// `node` must be linked into a live [`DoublyLinkedList<'a, T>`].
+	unsafe fn pinned(node: NonNull<Node<T>>) -> Pin<&'a Node<T>> {
+		//SAFETY: Linked `Node`s were pinned and are borrowed for `'a`.
+		Pin::new_unchecked(&*node.as_ptr())
+	}
+
+	/// Links `node` between the adjacent `prev` and `next`.
+	///
+	/// # Safety
+	///
+	/// This list must be pinned, and `prev` and `next` must be adjacent in it, where [`None`] means its end.
+	unsafe fn link_between(
+		&mut self,
+		prev: Option<NonNull<Node<T>>>,
+		next: Option<NonNull<Node<T>>>,
+		node: Pin<&'a Node<T>>,
+	) {
+		assert!(!node.is_linked(), "`Node` is already linked.");
+		node.list.set(self.id());
+		node.prev.set(prev);
+		node.next.set(next);
+		let node = NonNull::from(node.get_ref());
+		match prev {
+			Some(prev) => prev.as_ref().next.set(Some(node)),
+			None => self.head = Some(node),
+		}
+		match next {
+			Some(next) => next.as_ref().prev.set(Some(node)),
+			None => self.tail = Some(node),
+		}
+		self.len += 1;
+	}
+
+	/// # Safety
+	///
+	/// `node` must be linked into this list.
+	unsafe fn unlink_linked(&mut self, node: NonNull<Node<T>>) -> Pin<&'a Node<T>> {
+		let node = Self::pinned(node);
+		let (prev, next) = (node.prev.take(), node.next.take());
+		match prev {
+			Some(prev) => prev.as_ref().next.set(next),
+			None => self.head = next,
+		}
+		match next {
+			Some(next) => next.as_ref().prev.set(prev),
+			None => self.tail = prev,
+		}
+		node.list.set(ptr::null());
+		self.len -= 1;
+		node
+	}
+
+	fn clear_links(&mut self) {
+		let mut current = self.head.take();
+		self.tail = None;
+		self.len = 0;
+		while let Some(node) = current {
+			let node = unsafe { Self::pinned(node) };
+			current = node.next.take();
+			node.prev.set(None);
+			node.list.set(ptr::null());
+		}
+	}
+}
+
+impl<T> Default for DoublyLinkedList<'_, T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Unlinks all [`Node`]s, so that they can be linked elsewhere afterwards.
+impl<T> Drop for DoublyLinkedList<'_, T> {
+	fn drop(&mut self) {
+		self.clear_links()
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for DoublyLinkedList<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_list()
+			.entries(self.iter().map(|node| &**node.get_ref()))
+			.finish()
+	}
+}
+
+impl<'l, T> IntoIterator for &'l DoublyLinkedList<'_, T> {
+	type Item = Pin<&'l Node<T>>;
+	type IntoIter = Iter<'l, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// An iterator over the [`Node`]s of a [`DoublyLinkedList`]. See [`DoublyLinkedList::iter`].
+pub struct Iter<'l, T> {
+	front: Option<NonNull<Node<T>>>,
+	back: Option<NonNull<Node<T>>>,
+	len: usize,
+	_nodes: PhantomData<&'l Node<T>>,
+}
+
+impl<'l, T> Iterator for Iter<'l, T> {
+	type Item = Pin<&'l Node<T>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.len == 0 {
+			return None;
+		}
+		let node = unsafe {
+			//SAFETY: Linked `Node`s are pinned and borrowed by the list, which is borrowed for `'l`.
+			Pin::new_unchecked(&*self.front?.as_ptr())
+		};
+		self.front = node.next.get();
+		self.len -= 1;
+		Some(node)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		if self.len == 0 {
+			return None;
+		}
+		let node = unsafe {
+			//SAFETY: As above.
+			Pin::new_unchecked(&*self.back?.as_ptr())
+		};
+		self.back = node.prev.get();
+		self.len -= 1;
+		Some(node)
+	}
+}
+
+impl<T> Clone for Iter<'_, T> {
+	fn clone(&self) -> Self {
+		Self {
+			front: self.front,
+			back: self.back,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> core::fmt::Debug for Iter<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Iter")
+			.field("len", &self.len)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A position in a [`DoublyLinkedList`] that can modify it in place.
+///
+/// Like [`LinkedList`](https://doc.rust-lang.org/stable/std/collections/struct.LinkedList.html)'s unstable cursors,
+/// it can also point at a "ghost" position between the back and the front.
+pub struct CursorMut<'l, 'a, T> {
+	/// Pinned, but never moved out of.
+	list: &'l mut DoublyLinkedList<'a, T>,
+	/// [`None`] at the ghost position.
+	current: Option<NonNull<Node<T>>>,
+}
+
+impl<'a, T> CursorMut<'_, 'a, T> {
+	/// The [`Node`] at the cursor, or [`None`] at the ghost position.
+	#[must_use]
+	pub fn current(&self) -> Option<Pin<&'a Node<T>>> {
+		self.current
+			.map(|current| unsafe { DoublyLinkedList::pinned(current) })
+	}
+
+	/// The [`Node`] after the cursor, which is the front one at the ghost position.
+	#[must_use]
+	pub fn peek_next(&self) -> Option<Pin<&'a Node<T>>> {
+		self.next()
+			.map(|next| unsafe { DoublyLinkedList::pinned(next) })
+	}
+
+	/// The [`Node`] before the cursor, which is the back one at the ghost position.
+	#[must_use]
+	pub fn peek_prev(&self) -> Option<Pin<&'a Node<T>>> {
+		self.prev()
+			.map(|prev| unsafe { DoublyLinkedList::pinned(prev) })
+	}
+
+	/// Moves the cursor to the next [`Node`], wrapping through the ghost position.
+	pub fn move_next(&mut self) {
+		self.current = self.next();
+	}
+
+	/// Moves the cursor to the previous [`Node`], wrapping through the ghost position.
+	pub fn move_prev(&mut self) {
+		self.current = self.prev();
+	}
+
+	/// Links `node` after the cursor, which is at the front at the ghost position.
+	///
+	/// # Panics
+	///
+	/// Iff `node` is already linked.
+	pub fn insert_after(&mut self, node: Pin<&'a Node<T>>) {
+		let next = self.next();
+		unsafe { self.list.link_between(self.current, next, node) }
+	}
+
+	/// Links `node` before the cursor, which is at the back at the ghost position.
+	///
+	/// # Panics
+	///
+	/// Iff `node` is already linked.
+	pub fn insert_before(&mut self, node: Pin<&'a Node<T>>) {
+		let prev = self.prev();
+		unsafe { self.list.link_between(prev, self.current, node) }
+	}
+
+	/// Unlinks the [`Node`] at the cursor and moves the cursor to the next one.
+	///
+	/// Returns [`None`] at the ghost position.
+	pub fn remove_current(&mut self) -> Option<Pin<&'a Node<T>>> {
+		let current = self.current?;
+		self.current = self.next();
+		Some(unsafe { self.list.unlink_linked(current) })
+	}
+
+	/// Moves all [`Node`]s of `other` after the cursor, in order.
+	///
+	/// This takes O(n) time in the length of `other`, as each [`Node`] is told its new list.
+	pub fn splice_after(&mut self, other: Pin<&mut DoublyLinkedList<'a, T>>) {
+		let other = unsafe { other.get_unchecked_mut() };
+		while let Some(back) = other.tail {
+			let node = unsafe { other.unlink_linked(back) };
+			self.insert_after(node);
+		}
+	}
+
+	/// Moves all [`Node`]s of `other` before the cursor, in order.
+	///
+	/// This takes O(n) time in the length of `other`, as each [`Node`] is told its new list.
+	pub fn splice_before(&mut self, other: Pin<&mut DoublyLinkedList<'a, T>>) {
+		let other = unsafe { other.get_unchecked_mut() };
+		while let Some(front) = other.head {
+			let node = unsafe { other.unlink_linked(front) };
+			self.insert_before(node);
+		}
+	}
+
+	fn next(&self) -> Option<NonNull<Node<T>>> {
+		match self.current {
+			Some(current) => unsafe { current.as_ref() }.next.get(),
+			None => self.list.head,
+		}
+	}
+
+	fn prev(&self) -> Option<NonNull<Node<T>>> {
+		match self.current {
+			Some(current) => unsafe { current.as_ref() }.prev.get(),
+			None => self.list.tail,
+		}
+	}
+}
+
+impl<T> core::fmt::Debug for CursorMut<'_, '_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("CursorMut")
+			.field("ghost", &self.current.is_none())
+			.field("len", &self.list.len)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,427 @@
+//! An intrusive singly-linked list with O(1) access to both ends.
+
+use core::{iter::FusedIterator, marker::PhantomData, ops::Deref, pin::Pin, ptr::NonNull};
+
+/// A value with an embedded link, which can be pushed onto a [`SinglyLinkedList`].
+pub struct Node<T> {
+	/// Only meaningful while linked.
+	next: Option<NonNull<Node<T>>>,
+	/// Pinned, structurally.
+	value: T,
+}
+
+impl<T> Node<T> {
+	/// Creates a new unlinked [`Node`] holding `value`.
+	#[must_use]
+	pub const fn new(value: T) -> Self {
+		Self { next: None, value }
+	}
+
+	/// Pinning shared access to the value.
+	#[must_use]
+	pub fn get(self: Pin<&Self>) -> Pin<&T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked(|this| &this.value)
+		}
+	}
+
+	/// Pinning exclusive access to the value.
+	#[must_use]
+	pub fn get_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked_mut(|this| &mut this.value)
+		}
+	}
+
+	/// Unwraps the value.
+	pub fn into_inner(self) -> T {
+		self.value
+	}
+}
+
+impl<T> Deref for Node<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.value
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Node<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("Node").field(&self.value).finish()
+	}
+}
+
+/// A singly-linked list of [`Node`]s that it exclusively borrows for `'a`.
+///
+/// Linked [`Node`]s are only accessible through their values, since swapping two [`Node`]s would swap their links.
+/// Unlinking one hands back its borrow.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::pin::Pin;
+/// use unpin_choices_dsa::intrusive::singly_linked_list::{Node, SinglyLinkedList};
+///
+/// let (mut a, mut b, mut c) = (Node::new(1), Node::new(2), Node::new(3));
+/// let mut list = SinglyLinkedList::new();
+/// list.push_back(Pin::new(&mut b));
+/// list.push_front(Pin::new(&mut a));
+/// list.push_back(Pin::new(&mut c));
+///
+/// assert!(list.iter().map(|value| *value).eq([1, 2, 3]));
+/// assert_eq!(list.remove_first(|value| *value == 2).map(|node| **node), Some(2));
+/// assert_eq!(list.pop_front().map(|node| **node), Some(1));
+/// assert_eq!(list.len(), 1);
+/// ```
+pub struct SinglyLinkedList<'a, T> {
+	head: Option<NonNull<Node<T>>>,
+	tail: Option<NonNull<Node<T>>>,
+	len: usize,
+	_nodes: PhantomData<Pin<&'a mut Node<T>>>,
+}
+
+/// # Safety notes
+///
+/// This is semantically a collection of `Pin<&'a mut Node<T>>`.
+unsafe impl<T: Send> Send for SinglyLinkedList<'_, T> {}
+unsafe impl<T: Sync> Sync for SinglyLinkedList<'_, T> {}
+
+impl<'a, T> SinglyLinkedList<'a, T> {
+	/// Creates a new empty [`SinglyLinkedList`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			head: None,
+			tail: None,
+			len: 0,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// The number of linked [`Node`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether no [`Node`]s are linked.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.head.is_none()
+	}
+
+	/// Links `node` at the front.
+	pub fn push_front(&mut self, node: Pin<&'a mut Node<T>>) {
+		let node = unsafe {
+			//SAFETY: Only the link is written to, and the `Node` is never moved.
+			Pin::into_inner_unchecked(node)
+		};
+		node.next = self.head;
+		let node = NonNull::from(node);
+		if self.tail.is_none() {
+			self.tail = Some(node);
+		}
+		self.head = Some(node);
+		self.len += 1;
+	}
+
+	/// Links `node` at the back.
+	pub fn push_back(&mut self, node: Pin<&'a mut Node<T>>) {
+		let node = unsafe {
+			//SAFETY: Only the link is written to, and the `Node` is never moved.
+			Pin::into_inner_unchecked(node)
+		};
+		node.next = None;
+		let node = NonNull::from(node);
+		match self.tail {
+			Some(mut tail) => unsafe {
+				//SAFETY: Linked `Node`s are exclusively borrowed by this list.
+				tail.as_mut().next = Some(node)
+			},
+			None => self.head = Some(node),
+		}
+		self.tail = Some(node);
+		self.len += 1;
+	}
+
+	/// Unlinks the front [`Node`] and hands it back.
+	pub fn pop_front(&mut self) -> Option<Pin<&'a mut Node<T>>> {
+		let mut head = self.head?;
+		let node = unsafe {
+			//SAFETY: Linked `Node`s are exclusively borrowed by this list for `'a`.
+			head.as_mut()
+		};
+		self.head = node.next.take();
+		if self.head.is_none() {
+			self.tail = None;
+		}
+		self.len -= 1;
+		Some(unsafe {
+			//SAFETY: It was pinned when linked.
+			Pin::new_unchecked(node)
+		})
+	}
+
+	/// Unlinks the first [`Node`] whose value matches `predicate` and hands it back.
+	///
+	/// This takes O(n) time, as the predecessor has to be found.
+	pub fn remove_first(
+		&mut self,
+		mut predicate: impl FnMut(&T) -> bool,
+	) -> Option<Pin<&'a mut Node<T>>> {
+		let mut previous: Option<NonNull<Node<T>>> = None;
+		let mut current = self.head;
+		while let Some(mut node) = current {
+			let node = unsafe {
+				//SAFETY: Linked `Node`s are exclusively borrowed by this list for `'a`.
+				node.as_mut()
+			};
+			if predicate(&node.value) {
+				let next = node.next.take();
+				match previous {
+					Some(mut previous) => unsafe {
+						//SAFETY: As above.
+						previous.as_mut().next = next
+					},
+					None => self.head = next,
+				}
+				if next.is_none() {
+					self.tail = previous;
+				}
+				self.len -= 1;
+				return Some(unsafe {
+					//SAFETY: It was pinned when linked.
+					Pin::new_unchecked(node)
+				});
+			}
+			previous = current;
+			current = node.next;
+		}
+		None
+	}
+
+	/// Unlinks all [`Node`]s.
+	pub fn clear(&mut self) {
+		while self.pop_front().is_some() {}
+	}
+
+	/// Pinning shared access to the value of the front [`Node`].
+	#[must_use]
+	pub fn front(&self) -> Option<Pin<&T>> {
+		self.head.map(|head| unsafe {
+			//SAFETY: Linked `Node`s are pinned and borrowed by this list.
+			Pin::new_unchecked(&head.as_ref().value)
+		})
+	}
+
+	/// Pinning exclusive access to the value of the front [`Node`].
+	#[must_use]
+	pub fn front_mut(&mut self) -> Option<Pin<&mut T>> {
+		self.head.map(|mut head| unsafe {
+			//SAFETY: Linked `Node`s are pinned and exclusively borrowed by this list.
+			Pin::new_unchecked(&mut head.as_mut().value)
+		})
+	}
+
+	/// Pinning shared access to the value of the back [`Node`].
+	#[must_use]
+	pub fn back(&self) -> Option<Pin<&T>> {
+		self.tail.map(|tail| unsafe {
+			//SAFETY: Linked `Node`s are pinned and borrowed by this list.
+			Pin::new_unchecked(&tail.as_ref().value)
+		})
+	}
+
+	/// Pinning exclusive access to the value of the back [`Node`].
+	#[must_use]
+	pub fn back_mut(&mut self) -> Option<Pin<&mut T>> {
+		self.tail.map(|mut tail| unsafe {
+			//SAFETY: Linked `Node`s are pinned and exclusively borrowed by this list.
+			Pin::new_unchecked(&mut tail.as_mut().value)
+		})
+	}
+
+	/// Iterates over the values from front to back, with pinning shared access.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter {
+			next: self.head,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+
+	/// Iterates over the values from front to back, with pinning exclusive access.
+	#[must_use]
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		IterMut {
+			next: self.head,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+}
+
+impl<T> Default for SinglyLinkedList<'_, T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for SinglyLinkedList<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_list()
+			.entries(self.iter().map(Pin::get_ref))
+			.finish()
+	}
+}
+
+impl<'l, T> IntoIterator for &'l SinglyLinkedList<'_, T> {
+	type Item = Pin<&'l T>;
+	type IntoIter = Iter<'l, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'l, T> IntoIterator for &'l mut SinglyLinkedList<'_, T> {
+	type Item = Pin<&'l mut T>;
+	type IntoIter = IterMut<'l, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// Unlinks the [`Node`]s one by one, handing back their borrows.
+impl<'a, T> IntoIterator for SinglyLinkedList<'a, T> {
+	type Item = Pin<&'a mut Node<T>>;
+	type IntoIter = IntoIter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoIter(self)
+	}
+}
+
+/// A sharing pinning iterator over a [`SinglyLinkedList`]. See [`SinglyLinkedList::iter`].
+pub struct Iter<'l, T> {
+	next: Option<NonNull<Node<T>>>,
+	len: usize,
+	_nodes: PhantomData<&'l Node<T>>,
+}
+
+/// # Safety notes
+///
+/// This is semantically an iterator over `&'l Node<T>`.
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<'l, T> Iterator for Iter<'l, T> {
+	type Item = Pin<&'l T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let node = unsafe {
+			//SAFETY: Linked `Node`s are pinned and borrowed by the list, which is borrowed for `'l`.
+			self.next?.as_ref()
+		};
+		self.next = node.next;
+		self.len -= 1;
+		Some(unsafe { Pin::new_unchecked(&node.value) })
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<T> Clone for Iter<'_, T> {
+	fn clone(&self) -> Self {
+		Self {
+			next: self.next,
+			len: self.len,
+			_nodes: PhantomData,
+		}
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> core::fmt::Debug for Iter<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Iter")
+			.field("len", &self.len)
+			.finish_non_exhaustive()
+	}
+}
+
+/// An exclusive pinning iterator over a [`SinglyLinkedList`]. See [`SinglyLinkedList::iter_mut`].
+pub struct IterMut<'l, T> {
+	next: Option<NonNull<Node<T>>>,
+	len: usize,
+	_nodes: PhantomData<&'l mut Node<T>>,
+}
+
+/// # Safety notes
+///
+/// This is semantically an iterator over `&'l mut Node<T>`.
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'l, T> Iterator for IterMut<'l, T> {
+	type Item = Pin<&'l mut T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let node = unsafe {
+			//SAFETY: Linked `Node`s are pinned and exclusively borrowed by the list, which is exclusively borrowed for `'l`.
+			// Each is handed out only once.
+			self.next?.as_mut()
+		};
+		self.next = node.next;
+		self.len -= 1;
+		Some(unsafe { Pin::new_unchecked(&mut node.value) })
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<T> core::fmt::Debug for IterMut<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("IterMut")
+			.field("len", &self.len)
+			.finish_non_exhaustive()
+	}
+}
+
+/// An iterator that unlinks the [`Node`]s of a [`SinglyLinkedList`]. See its [`IntoIterator`] implementation.
+pub struct IntoIter<'a, T>(SinglyLinkedList<'a, T>);
+
+impl<'a, T> Iterator for IntoIter<'a, T> {
+	type Item = Pin<&'a mut Node<T>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.0.pop_front()
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.0.len, Some(self.0.len))
+	}
+}
+
+impl<T> ExactSizeIterator for IntoIter<'_, T> {}
+impl<T> FusedIterator for IntoIter<'_, T> {}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for IntoIter<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("IntoIter").field(&self.0).finish()
+	}
+}
@@ -0,0 +1,98 @@
+//! A [`Future`] that joins exactly two [`Future`]s, in entirely safe code.
+
+use crate::maybe_done::MaybeDone;
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Creates a [`Future`] that completes with the outputs of both `a` and `b` once both have completed.
+///
+/// This is equivalent to [`join((a, b))`](`crate::join_future::join`), but doesn't need to name a [`Futures`](`crate::join_future::Futures`) implementation:
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{join2::join2, join_future::join};
+/// # async fn example() {
+///
+/// assert_eq!(
+///     join2(async { 1 }, async { "2" }).await,
+///     join((async { 1 }, async { "2" })).await,
+/// );
+/// # }
+/// ```
+pub fn join2<A: Future, B: Future>(a: A, b: B) -> Join2<A, B> {
+	Join2::new(a, b)
+}
+
+/// A [`Future`] that completes with the outputs of both `a` and `b` once both have completed.
+///
+/// Each inner [`Future`] is polled once when the [`Join2`] is polled, until completed.
+///
+/// > This is the reference implementation for [`JoinFuture`](`crate::join_future::JoinFuture`)'s behaviour,
+/// > written with [`MaybeDone`] instead of shared [`MaybeUninit`](`core::mem::MaybeUninit`) storage.
+/// > It's a little larger as a result, but there's no `unsafe` to get wrong.
+#[pin_project]
+pub struct Join2<A: Future, B: Future> {
+	#[pin]
+	a: MaybeDone<A>,
+	#[pin]
+	b: MaybeDone<B>,
+}
+
+impl<A: Future, B: Future> Join2<A, B> {
+	/// Creates a new instance of [`Join2`] from the given `a` and `b`.
+	#[must_use]
+	pub fn new(a: A, b: B) -> Self {
+		Self {
+			a: MaybeDone::new(a),
+			b: MaybeDone::new(b),
+		}
+	}
+}
+
+impl<A: Future, B: Future> Future for Join2<A, B> {
+	type Output = (A::Output, B::Output);
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		assert!(!this.a.is_gone(), "`Join2` was previously completed.");
+
+		// Both are polled even if the first is pending.
+		let a = this.a.as_mut().poll(cx);
+		let b = this.b.as_mut().poll(cx);
+		if a.is_pending() || b.is_pending() {
+			return Poll::Pending;
+		}
+
+		Poll::Ready((
+			this.a.take_output().expect("unreachable"),
+			this.b.take_output().expect("unreachable"),
+		))
+	}
+}
+
+/// The outputs are taken on completion, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<A: Future, B: Future> FusedFuture for Join2<A, B> {
+	fn is_terminated(&self) -> bool {
+		self.a.is_gone()
+	}
+}
+
+impl<A: Future, B: Future> core::fmt::Debug for Join2<A, B>
+where
+	MaybeDone<A>: core::fmt::Debug,
+	MaybeDone<B>: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Join2")
+			.field("a", &self.a)
+			.field("b", &self.b)
+			.finish()
+	}
+}
@@ -0,0 +1,131 @@
+//! A [`Future`] that interlaces a dynamic number of [`Future`]s. Not threading!
+
+use crate::{
+	completion::{Bitmap, Completion},
+	pinned_pin::PinnedPin,
+};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{
+	future::Future,
+	mem::MaybeUninit,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use tap::Pipe;
+
+/// Returns a [`Future`] that completes when all [`Future`]s in `futures` complete.
+///
+/// The output is a [`Vec`] of the outputs of those [`Future`]s, in the same order.
+///
+/// Each inner [`Future`] is polled once when the [`JoinAll`] is polled, until completed.
+pub fn join_all<F: Future>(futures: impl Into<Box<[F]>>) -> JoinAll<F> {
+	JoinAll::new(futures.into())
+}
+
+/// A [`Future`] that completes as soon as all [`Future`]s in `futures` have completed.
+///
+/// Each inner [`Future`] is polled once when the [`JoinAll`] is polled, until completed.
+///
+/// This is the dynamically-sized counterpart to [`crate::join_future::JoinFuture`].
+/// All of its state is boxed, so it is [`Unpin`] regardless of `F`.
+pub struct JoinAll<F: Future> {
+	completion: Box<[u64]>,
+	futures: Pin<Box<PinnedPin<[F]>>>,
+	/// [`None`] once the outputs were handed out.
+	outputs: Option<Box<[MaybeUninit<F::Output>]>>,
+}
+
+impl<F: Future> JoinAll<F> {
+	/// Creates a new instance of [`JoinAll`] from the given `futures`.
+	#[must_use]
+	pub fn new(futures: Box<[F]>) -> Self {
+		PinnedPin::wrap_boxed(futures)
+			.pipe(Pin::from)
+			.pipe(Self::new_pinned)
+	}
+
+	/// Creates a new instance of [`JoinAll`] from the given already-pinned `futures`.
+	#[must_use]
+	pub fn new_pinned(futures: Pin<Box<PinnedPin<[F]>>>) -> Self {
+		let len = futures.len();
+		Self {
+			completion: vec![0; (len + 63) / 64].into_boxed_slice(),
+			futures,
+			outputs: Some((0..len).map(|_| MaybeUninit::uninit()).collect()),
+		}
+	}
+}
+
+impl<F: Future> Future for JoinAll<F> {
+	type Output = Vec<F::Output>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = &mut *self;
+		let outputs = this
+			.outputs
+			.as_mut()
+			.expect("`JoinAll` was previously completed.");
+
+		let mut incomplete = false;
+		for (index, (future, output)) in this
+			.futures
+			.as_mut()
+			.into_iter()
+			.zip(outputs.iter_mut())
+			.enumerate()
+		{
+			if !this.completion.get(index) {
+				match future.poll(cx) {
+					Poll::Pending => incomplete = true,
+					Poll::Ready(value) => {
+						*output = MaybeUninit::new(value);
+						this.completion.set(index, true);
+					}
+				}
+			}
+		}
+
+		if incomplete {
+			return Poll::Pending;
+		}
+
+		let outputs = this.outputs.take().expect("unreachable");
+		// Release the completed futures right away, as they may hold onto resources.
+		this.futures = Box::pin(PinnedPin([]));
+		this.completion = Box::default();
+		Poll::Ready(
+			unsafe {
+				//SAFETY: Every slot was initialised above, since none is incomplete,
+				// and `MaybeUninit<T>` has the same layout as `T`. The slice length is kept by the pointer cast.
+				Box::from_raw(Box::into_raw(outputs) as *mut [F::Output])
+			}
+			.into_vec(),
+		)
+	}
+}
+
+impl<F: Future> Drop for JoinAll<F> {
+	fn drop(&mut self) {
+		if let Some(outputs) = self.outputs.as_mut() {
+			for (_, output) in outputs
+				.iter_mut()
+				.enumerate()
+				.filter(|(index, _)| self.completion.get(*index))
+			{
+				unsafe {
+					//SAFETY: Completed outputs are initialised, and are dropped only once here.
+					output.as_mut_ptr().drop_in_place()
+				}
+			}
+		}
+	}
+}
+
+/// Only the completion state is shown, since neither `F` nor its output are necessarily [`Debug`](`core::fmt::Debug`).
+impl<F: Future> core::fmt::Debug for JoinAll<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("JoinAll")
+			.field("completion", &Bitmap(&self.completion, self.futures.len()))
+			.finish_non_exhaustive()
+	}
+}
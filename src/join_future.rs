@@ -1,29 +1,46 @@
 //! A [`Future`] that can interlace [`Future`]s. Not threading!
 
-use bitvec::prelude::*;
+#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+use crate::poll_stats::PollObserver;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use crate::waker_dispatch::WakerDispatch;
+use crate::{
+	completion::{Bitmap, Completion, CompletionFor, SmallestCompletion},
+	either::Either2,
+	init_array_pinned::init_array,
+	map::Map,
+};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 use core::{
-	convert::identity,
-	future::Future,
-	mem::MaybeUninit,
+	future::{Future, IntoFuture},
+	mem::{ManuallyDrop, MaybeUninit},
+	num::NonZeroUsize,
 	pin::Pin,
+	ptr,
 	task::{Context, Poll},
 };
+#[cfg(feature = "futures-core")]
 use futures_core::FusedFuture;
-use pin_project::pin_project;
+use pin_project::{pin_project, pinned_drop};
 use project_uninit::partial_init;
 
 /// Returns a [`Future`] that completes when all [`Future`]s in `futures` complete.
 ///
+/// `futures` may also contain [`IntoFuture`]s, which are converted up front. See [`IntoFutures`].
+///
 /// The output is a collection of the outputs of those [`Future`]s.
 ///
 /// Each inner [`Future`] is polled once when the [`JoinFuture`] is polled, until completed.
-pub fn join<Fs: Futures>(futures: Fs) -> JoinFuture<Fs> {
-	JoinFuture::new(futures)
+pub fn join<Fs: IntoFutures>(futures: Fs) -> JoinFuture<Fs::Futures> {
+	JoinFuture::new(futures.into_futures())
 }
 
 /// A [`Future`] that completes as soon as all [`Future`]s in `futures` have completed.
 ///
 /// Each inner [`Future`] is polled once when the [`JoinFuture`] is polled, until completed.
+/// (See [`JoinFuture::new_dispatching`] for a way to only poll those that were woken,
+/// and [`JoinFuture::with_budget`] for a way to bound the work done per poll.)
 ///
 /// > It's pretty neat that we can do this also without a macro,
 /// > since that *may* lead to lower compile times due to less total emitted code.
@@ -31,10 +48,18 @@ pub fn join<Fs: Futures>(futures: Fs) -> JoinFuture<Fs> {
 /// > It's not as versatile as a macro if we don't control storage for the composed futures, though.
 ///
 /// Compare and contrast [`crate::any_future::AnyFuture`].
-#[pin_project]
-#[derive(Debug)]
+#[pin_project(PinnedDrop)]
 pub struct JoinFuture<Fs: Futures> {
 	completion: Fs::Completion,
+	/// Set once the outputs were handed out.
+	done: bool,
+	budget: Option<NonZeroUsize>,
+	/// Where to resume polling, if there is a budget.
+	cursor: usize,
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	dispatch: Option<WakerDispatch>,
+	#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+	observer: Option<&'static dyn PollObserver>,
 	//TODO: Use `PinnedPin`.
 	#[pin]
 	futures: Fs,
@@ -45,10 +70,209 @@ impl<Fs: Futures> JoinFuture<Fs> {
 	/// Creates a new instance of [`JoinFuture`] from the given `futures`.
 	#[must_use]
 	pub fn new(futures: Fs) -> Self {
+		let mut outputs = MaybeUninit::uninit();
+		futures.init_outputs(&mut outputs);
 		Self {
 			completion: Fs::initial_completion(),
+			done: false,
+			budget: None,
+			cursor: 0,
+			#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+			dispatch: None,
+			#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+			observer: None,
 			futures,
-			outputs: MaybeUninit::uninit(),
+			outputs,
+		}
+	}
+
+	/// Creates a new instance of [`JoinFuture`] from the given `futures`,
+	/// which polls each inner [`Future`] only after it was woken (and once initially).
+	///
+	/// This costs a few allocations up front, but avoids redundant polls of large joins.
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	#[must_use]
+	pub fn new_dispatching(futures: Fs) -> Self {
+		let mut this = Self::new(futures);
+		this.dispatch = Some(WakerDispatch::new(this.len()));
+		this
+	}
+
+	/// Limits how many incomplete constituent [`Future`]s are polled each time this [`JoinFuture`] is polled.
+	///
+	/// When the budget runs out, the [`JoinFuture`] remembers where it stopped, wakes itself and returns [`Poll::Pending`].
+	/// The next poll resumes from the following constituent [`Future`],
+	/// so all of them get their turn and the work per poll is bounded independently of their number.
+	///
+	/// A budget of `1` means exactly one constituent [`Future`] is polled each time.
+	///
+	/// > This trades throughput for latency, which is mainly useful for soft real-time uses.
+	/// > The rest of the executor's tasks get a chance to run in between.
+	#[must_use]
+	pub fn with_budget(mut self, budget: NonZeroUsize) -> Self {
+		self.budget = Some(budget);
+		self
+	}
+
+	/// Records each poll of this [`JoinFuture`] into `observer`, along with how many constituent [`Future`]s it polled.
+	///
+	/// See [`PollStats`](`crate::poll_stats::PollStats`).
+	#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+	#[must_use]
+	pub fn observed_by(mut self, observer: &'static dyn PollObserver) -> Self {
+		self.observer = Some(observer);
+		self
+	}
+
+	/// The number of constituent [`Future`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.futures.len()
+	}
+
+	/// Whether there are no constituent [`Future`]s.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.futures.is_empty()
+	}
+
+	/// The number of constituent [`Future`]s that have completed so far.
+	///
+	/// This is useful to report progress of a large join that is polled manually.
+	#[must_use]
+	pub fn completed_count(&self) -> usize {
+		self.completion.count_ones()
+	}
+
+	/// Shared access to the constituent [`Future`]s, for example to inspect them while debugging.
+	#[must_use]
+	pub fn futures(&self) -> &Fs {
+		&self.futures
+	}
+
+	/// Whether the constituent [`Future`] at `index` has completed.
+	///
+	/// # Panics
+	///
+	/// Iff `index >= self.len()`.
+	#[must_use]
+	pub fn is_complete(&self, index: usize) -> bool {
+		assert!(
+			index < self.len(),
+			"Index {} out of bounds for a `JoinFuture` of length {}.",
+			index,
+			self.len()
+		);
+		self.completion.get(index)
+	}
+
+	/// Cancels this join, returning the constituent [`Future`]s along with the outputs of those that already completed.
+	///
+	/// The [`Future`]s that haven't completed yet can then be driven elsewhere.
+	/// Those that have completed must not be polled again, in general.
+	///
+	/// If this [`JoinFuture`] has already completed, its outputs were handed out, so all partial outputs are missing.
+	///
+	/// > This consumes the [`JoinFuture`] by value, which means it isn't pinned (anymore).
+	/// > A pinned boxed instance can still be moved out of its box with `*Pin::into_inner(boxed)` if `Fs: Unpin`.
+	#[must_use]
+	pub fn into_futures(self) -> (Fs, Fs::PartialOutputs) {
+		let mut this = ManuallyDrop::new(self);
+		let this = &mut *this;
+		let partial_outputs = unsafe {
+			if this.done {
+				//SAFETY: No flags are set.
+				Fs::take_outputs(&mut this.outputs, &Fs::initial_completion())
+			} else {
+				//SAFETY: The completion flags match the initialised outputs, which aren't used again.
+				Fs::take_outputs(&mut this.outputs, &this.completion)
+			}
+		};
+		unsafe {
+			//SAFETY: `this` is never used again and not dropped, so each field is moved or dropped exactly once here.
+			// `budget`, `cursor`, `done` and `observer` are trivial.
+			let futures = ptr::read(&this.futures);
+			ptr::drop_in_place(&mut this.completion);
+			#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+			ptr::drop_in_place(&mut this.dispatch);
+			(futures, partial_outputs)
+		}
+	}
+
+	/// Re-arms this [`JoinFuture`] in place, so that it can be polled (to completion) again.
+	///
+	/// `reinit` receives the pinned constituent [`Future`]s and must reset or replace them in place,
+	/// for example through [`Pin::set`]. Afterwards, all completion flags are cleared
+	/// and the [`JoinFuture`] starts over with polling all of them.
+	///
+	/// This doesn't allocate, and keeps any budget, waker dispatch and observer.
+	///
+	/// If the [`JoinFuture`] hadn't completed yet, outputs of already-completed constituent [`Future`]s are dropped.
+	///
+	/// # Panics
+	///
+	/// Iff `reinit` changes the number of constituent [`Future`]s.
+	pub fn reset(self: Pin<&mut Self>, reinit: impl FnOnce(Pin<&mut Fs>)) {
+		let mut this = self.project();
+		if !*this.done {
+			drop(unsafe {
+				//SAFETY: The completion flags match the initialised outputs, which aren't used again.
+				Fs::take_outputs(this.outputs, this.completion)
+			});
+		}
+		this.completion.clear();
+		*this.done = false;
+
+		let len = this.futures.len();
+		reinit(this.futures.as_mut());
+		assert_eq!(
+			this.futures.len(),
+			len,
+			"`JoinFuture::reset`'s `reinit` must not change the number of constituent `Future`s."
+		);
+		this.futures.init_outputs(this.outputs);
+
+		*this.cursor = 0;
+		#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+		if let Some(dispatch) = this.dispatch {
+			dispatch.mark_all_dirty()
+		}
+	}
+
+	/// Transforms this [`JoinFuture`]'s outputs with `f`, without a hand-written wrapper [`Future`].
+	#[must_use]
+	pub fn map<F: FnOnce(Fs::Outputs) -> T, T>(self, f: F) -> Map<Self, F> {
+		Map::new(self, f)
+	}
+}
+
+/// Shows the completion bitmap, with constituent [`Future`]s in index order from left to right.
+///
+/// Neither the constituent [`Future`]s nor their outputs are shown, since they aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<Fs: Futures> core::fmt::Debug for JoinFuture<Fs> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let mut debug_struct = f.debug_struct("JoinFuture");
+		debug_struct
+			.field("completion", &Bitmap(&self.completion, self.len()))
+			.field("done", &self.done)
+			.field("budget", &self.budget)
+			.field("cursor", &self.cursor);
+		#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+		debug_struct.field("dispatching", &self.dispatch.is_some());
+		debug_struct.finish_non_exhaustive()
+	}
+}
+
+/// Drops the outputs of constituent [`Future`]s that completed, unless they were handed out already.
+#[pinned_drop]
+impl<Fs: Futures> PinnedDrop for JoinFuture<Fs> {
+	fn drop(self: Pin<&mut Self>) {
+		let this = self.project();
+		if !*this.done {
+			drop(unsafe {
+				//SAFETY: The completion flags match the initialised outputs, which aren't used again.
+				Fs::take_outputs(this.outputs, this.completion)
+			});
 		}
 	}
 }
@@ -58,126 +282,929 @@ impl<Fs: Futures> Future for JoinFuture<Fs> {
 
 	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
 		let this = self.project();
+		assert!(!*this.done, "`JoinFuture` was previously completed.");
 
-		match Fs::poll(this.completion, this.futures, this.outputs, cx) {
-			Poll::Pending => Poll::Pending,
-			Poll::Ready(()) => Poll::Ready(unsafe {
-				//SAFETY: Validity of this operation is directly required by [`JoinFutureFutures`]'s implementation contract.
-				this.outputs.as_ptr().read()
-			}),
+		let len = this.futures.len();
+		let completion = &mut *this.completion;
+		let mut futures = this.futures;
+		let outputs = this.outputs;
+
+		let initial_budget = this.budget.map_or(usize::MAX, NonZeroUsize::get);
+		let mut budget = initial_budget;
+		let start = *this.cursor;
+		let mut next = start;
+
+		// Returns `false` iff the budget ran out before `index` could be polled.
+		let mut poll_at = |index: usize, cx: &mut Context<'_>| {
+			if completion.get(index) {
+				return true;
+			}
+			if budget == 0 {
+				return false;
+			}
+			budget -= 1;
+			next = index + 1;
+			if Fs::poll_at(futures.as_mut(), index, outputs, cx).is_ready() {
+				completion.set(index, true)
+			}
+			true
+		};
+
+		#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+		let exhausted = if let Some(dispatch) = this.dispatch {
+			// Registering first means no wake-up can slip through between the two steps.
+			dispatch.register(cx.waker());
+			dispatch.drain_dirty_from(start, |index| {
+				index >= len || poll_at(index, &mut Context::from_waker(dispatch.waker(index)))
+			})
+		} else {
+			!(start..len).chain(0..start).all(|index| poll_at(index, cx))
+		};
+		#[cfg(not(all(feature = "alloc", target_has_atomic = "ptr")))]
+		let exhausted = !(start..len).chain(0..start).all(|index| poll_at(index, cx));
+
+		if this.budget.is_some() {
+			*this.cursor = if next < len { next } else { 0 };
+		}
+
+		#[cfg(any(target_has_atomic = "ptr", feature = "critical-section"))]
+		if let Some(observer) = this.observer {
+			observer.record_poll(initial_budget - budget);
+		}
+
+		if this.completion.all(len) {
+			*this.done = true;
+			Poll::Ready(unsafe {
+				//SAFETY: Validity of this operation is directly required by [`Futures`]'s implementation contract.
+				outputs.as_ptr().read()
+			})
+		} else {
+			if exhausted {
+				cx.waker().wake_by_ref();
+			}
+			Poll::Pending
 		}
 	}
 }
 
 /// # Safety
 ///
-/// When [`JoinFutureFutures::poll`] returns [`Poll::Ready`],
+/// Once [`Futures::poll_at`] returned [`Poll::Ready`] for each index in `0..futures.len()`,
 /// then it must be valid to read `outputs` as initialised value once directly afterwards.
+///
+/// [`Futures::take_outputs`] must read exactly the outputs of indices flagged in `completion`,
+/// and only those. It may rely on [`Futures::init_outputs`] having been called on `outputs`.
+///
+/// [`JoinFuture`] polls each index only until it returns [`Poll::Ready`].
 pub unsafe trait Futures: Sized {
 	/// The combined output type.
 	type Outputs;
+	/// The combined output type if only some of the constituent [`Future`]s completed,
+	/// usually with each output wrapped in an [`Option`].
+	type PartialOutputs;
 	/// A way to track [`Future`] completion.
-	type Completion;
+	///
+	/// Use [`CompletionFor<N>`]'s [`SmallestCompletion`] implementation to pick a fitting type.
+	type Completion: Completion;
 
 	/// The initial `Self::Completion` value.
 	fn initial_completion() -> Self::Completion;
 
-	/// Like [`Future::poll`].
-	fn poll(
-		completion: &mut Self::Completion,
+	/// The number of constituent [`Future`]s.
+	fn len(&self) -> usize;
+
+	/// Whether there are no constituent [`Future`]s.
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Prepares `outputs` before any constituent [`Future`] is polled, for example by writing an enum discriminant.
+	///
+	/// This is called again whenever the constituent [`Future`]s start over, like after [`JoinFuture::reset`]'s `reinit`.
+	/// The default implementation does nothing.
+	fn init_outputs(&self, outputs: &mut MaybeUninit<Self::Outputs>) {
+		let _ = outputs;
+	}
+
+	/// Like [`Future::poll`], but for only the constituent [`Future`] at `index`.
+	///
+	/// When that [`Future`] completes, its output must be written into the matching part of `outputs`.
+	///
+	/// # Panics
+	///
+	/// Iff `index >= futures.len()`.
+	fn poll_at(
 		futures: Pin<&mut Self>,
+		index: usize,
 		outputs: &mut MaybeUninit<Self::Outputs>,
 		cx: &mut Context<'_>,
 	) -> Poll<()>;
+
+	/// Moves the outputs flagged in `completion` out of `outputs`.
+	///
+	/// # Safety
+	///
+	/// The outputs at the flagged indices must be initialised,
+	/// and are considered uninitialised afterwards.
+	unsafe fn take_outputs(
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		completion: &Self::Completion,
+	) -> Self::PartialOutputs;
 }
 
-// A good implementation of Futures over arrays still requires better const-generics as of Rust 1.57.
-// (Specifically: Using such a constant in the definition of an associated type.)
+/// Types that can be converted into [`Futures`] up front, like collections of [`IntoFuture`]s.
+///
+/// This is what [`join`] accepts, so that for example builder types can be joined directly.
+///
+/// Compare and contrast [`crate::any_future::IntoFutures`].
+pub trait IntoFutures {
+	/// The [`Futures`] this converts into.
+	type Futures: Futures;
+
+	/// Converts `self` into [`Futures`], converting each [`IntoFuture`] in turn.
+	fn into_futures(self) -> Self::Futures;
+}
 
-unsafe impl Futures for () {
-	type Outputs = ();
-	type Completion = BitArr!(for 0);
+impl<F: IntoFuture, const N: usize> IntoFutures for [F; N]
+where
+	[F::IntoFuture; N]: Futures,
+{
+	type Futures = [F::IntoFuture; N];
 
-	fn initial_completion() -> Self::Completion {
-		BitArray::zeroed()
+	fn into_futures(self) -> Self::Futures {
+		self.map(IntoFuture::into_future)
 	}
+}
 
-	fn poll(
-		_completion: &mut Self::Completion,
-		_futures: Pin<&mut Self>,
-		_results: &mut MaybeUninit<Self::Outputs>,
-		_cx: &mut Context<'_>,
-	) -> Poll<()> {
-		Poll::Ready(())
+impl IntoFutures for () {
+	type Futures = ();
+
+	fn into_futures(self) -> Self::Futures {}
+}
+
+impl<F: IntoFuture> IntoFutures for Option<F> {
+	type Futures = Option<F::IntoFuture>;
+
+	fn into_futures(self) -> Self::Futures {
+		self.map(IntoFuture::into_future)
 	}
 }
 
-unsafe impl<F1> Futures for (F1,)
+impl<A, B> IntoFutures for Either2<A, B>
 where
-	F1: Future,
+	A: IntoFutures,
+	B: IntoFutures,
+	B::Futures: Futures<Completion = <A::Futures as Futures>::Completion>,
 {
-	type Outputs = (F1::Output,);
-	type Completion = BitArr!(for 1);
+	type Futures = Either2<A::Futures, B::Futures>;
+
+	fn into_futures(self) -> Self::Futures {
+		match self {
+			Either2::A(a) => Either2::A(a.into_futures()),
+			Either2::B(b) => Either2::B(b.into_futures()),
+		}
+	}
+}
+
+/// Pinned pointers are already [`Futures`], so this is the identity.
+impl<P> IntoFutures for Pin<P>
+where
+	Pin<P>: Futures,
+{
+	type Futures = Self;
+
+	fn into_futures(self) -> Self::Futures {
+		self
+	}
+}
+
+/// Arrays of up to 256 [`Future`]s are supported, see [`SmallestCompletion`].
+///
+/// Use [`crate::join_all`] for anything larger.
+unsafe impl<F: Future, const N: usize> Futures for [F; N]
+where
+	CompletionFor<N>: SmallestCompletion,
+{
+	type Outputs = [F::Output; N];
+	type PartialOutputs = [Option<F::Output>; N];
+	type Completion = <CompletionFor<N> as SmallestCompletion>::Completion;
 
 	fn initial_completion() -> Self::Completion {
-		BitArray::zeroed()
+		Self::Completion::default()
 	}
 
-	fn poll(
-		completion: &mut Self::Completion,
+	fn len(&self) -> usize {
+		N
+	}
+
+	fn poll_at(
 		futures: Pin<&mut Self>,
+		index: usize,
 		outputs: &mut MaybeUninit<Self::Outputs>,
 		cx: &mut Context<'_>,
 	) -> Poll<()> {
-		let mut stepped = false;
-		let mut incomplete = false;
+		match unsafe {
+			//SAFETY: Plain structural pin projection. This panics iff out of bounds.
+			futures.map_unchecked_mut(|futures| &mut futures[index])
+		}
+		.poll(cx)
+		{
+			Poll::Pending => Poll::Pending,
+			Poll::Ready(output) => {
+				unsafe {
+					//SAFETY: In bounds, as checked above.
+					outputs
+						.as_mut_ptr()
+						.cast::<F::Output>()
+						.add(index)
+						.write(output)
+				}
+				Poll::Ready(())
+			}
+		}
+	}
+
+	unsafe fn take_outputs(
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		completion: &Self::Completion,
+	) -> Self::PartialOutputs {
+		let outputs = outputs.as_ptr().cast::<F::Output>();
+		let mut partial_outputs = MaybeUninit::uninit();
+		init_array(&mut partial_outputs, |index, partial_output| {
+			//SAFETY: Exactly the flagged outputs are initialised, and each is read once.
+			// Nothing in here panics for `index < N`, so no output can be read again by a retry.
+			partial_output.write(completion.get(index).then(|| outputs.add(index).read()));
+		});
+		//SAFETY: Fully initialised by `init_array`.
+		partial_outputs.assume_init()
+	}
+}
+
+unsafe impl Futures for () {
+	type Outputs = ();
+	type PartialOutputs = ();
+	type Completion = <CompletionFor<0> as SmallestCompletion>::Completion;
+
+	fn initial_completion() -> Self::Completion {
+		Self::Completion::default()
+	}
+
+	fn len(&self) -> usize {
+		0
+	}
+
+	fn poll_at(
+		_futures: Pin<&mut Self>,
+		index: usize,
+		_outputs: &mut MaybeUninit<Self::Outputs>,
+		_cx: &mut Context<'_>,
+	) -> Poll<()> {
+		panic!("Index {} out of bounds for `()`.", index)
+	}
+
+	unsafe fn take_outputs(
+		_outputs: &mut MaybeUninit<Self::Outputs>,
+		_completion: &Self::Completion,
+	) -> Self::PartialOutputs {
+	}
+}
 
+macro_rules! tuple_futures {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$len:literal($($F:ident.$index:tt),*$(,)?)
+	),*$(,)?} => {$(
+		unsafe impl<$($F),*> Futures for ($($F,)*)
+		where
+			$($F: Future,)*
 		{
-			let mut completion = completion.get_mut(0).unwrap();
-			if !*completion {
-				stepped = true;
-				match unsafe { futures.map_unchecked_mut(|futures| &mut futures.0) }.poll(cx) {
-					Poll::Pending => incomplete = true,
-					Poll::Ready(output) => {
-						partial_init!(outputs => 0 = output);
-						*completion = true
-					}
+			type Outputs = ($($F::Output,)*);
+			type PartialOutputs = ($(Option<$F::Output>,)*);
+			type Completion = <CompletionFor<$len> as SmallestCompletion>::Completion;
+
+			fn initial_completion() -> Self::Completion {
+				Self::Completion::default()
+			}
+
+			fn len(&self) -> usize {
+				$len
+			}
+
+			fn poll_at(
+				futures: Pin<&mut Self>,
+				index: usize,
+				outputs: &mut MaybeUninit<Self::Outputs>,
+				cx: &mut Context<'_>,
+			) -> Poll<()> {
+				match index {
+					$($index => match unsafe {
+						//SAFETY: Plain structural pin projection.
+						futures.map_unchecked_mut(|futures| &mut futures.$index)
+					}.poll(cx) {
+						Poll::Pending => Poll::Pending,
+						Poll::Ready(output) => {
+							partial_init!(outputs => $index = output);
+							Poll::Ready(())
+						}
+					},)*
+					_ => panic!("Index {} out of bounds for a {}-tuple.", index, $len),
 				}
 			}
+
+			unsafe fn take_outputs(
+				outputs: &mut MaybeUninit<Self::Outputs>,
+				completion: &Self::Completion,
+			) -> Self::PartialOutputs {
+				let outputs = outputs.as_ptr();
+				($(
+					completion.get($index).then(|| ptr::addr_of!((*outputs).$index).read()),
+				)*)
+			}
 		}
 
-		if incomplete {
-			Poll::Pending
+		impl<$($F: IntoFuture),*> IntoFutures for ($($F,)*) {
+			type Futures = ($($F::IntoFuture,)*);
+
+			fn into_futures(self) -> Self::Futures {
+				($(self.$index.into_future(),)*)
+			}
+		}
+	)*};
+}
+
+tuple_futures! {
+	1(F0.0),
+	2(F0.0, F1.1),
+	3(F0.0, F1.1, F2.2),
+	4(F0.0, F1.1, F2.2, F3.3),
+	5(F0.0, F1.1, F2.2, F3.3, F4.4),
+	6(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5),
+	7(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6),
+	8(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6, F7.7),
+	9(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6, F7.7, F8.8),
+	10(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6, F7.7, F8.8, F9.9),
+	11(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6, F7.7, F8.8, F9.9, F10.10),
+	12(F0.0, F1.1, F2.2, F3.3, F4.4, F5.5, F6.6, F7.7, F8.8, F9.9, F10.10, F11.11),
+}
+
+macro_rules! pinning_pointer_futures {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$(#[$attr:meta])*
+		impl<$($l:lifetime,)? $Fs:ident> for $P:ty
+	),*$(,)?} => {$(
+		$(#[$attr])*
+		unsafe impl<$($l,)? $Fs: Futures> Futures for $P {
+			type Outputs = $Fs::Outputs;
+			type PartialOutputs = $Fs::PartialOutputs;
+			type Completion = $Fs::Completion;
+
+			fn initial_completion() -> Self::Completion {
+				$Fs::initial_completion()
+			}
+
+			fn len(&self) -> usize {
+				(**self).len()
+			}
+
+			fn init_outputs(&self, outputs: &mut MaybeUninit<Self::Outputs>) {
+				(**self).init_outputs(outputs)
+			}
+
+			fn poll_at(
+				futures: Pin<&mut Self>,
+				index: usize,
+				outputs: &mut MaybeUninit<Self::Outputs>,
+				cx: &mut Context<'_>,
+			) -> Poll<()> {
+				// The pinning pointer is `Unpin`, so this doesn't need any `unsafe`.
+				$Fs::poll_at(Pin::into_inner(futures).as_mut(), index, outputs, cx)
+			}
+
+			unsafe fn take_outputs(
+				outputs: &mut MaybeUninit<Self::Outputs>,
+				completion: &Self::Completion,
+			) -> Self::PartialOutputs {
+				$Fs::take_outputs(outputs, completion)
+			}
+		}
+	)*};
+}
+
+pinning_pointer_futures! {
+	/// Pinning references to [`Futures`] are [`Futures`] themselves.
+	///
+	/// This makes it possible to join [`Future`]s in long-lived (for example `static`) storage, like `Pin<&mut [F; N]>`,
+	/// without moving them into the [`JoinFuture`]. [`JoinFuture::reset`] can then re-arm them in place.
+	///
+	/// # Safety notes
+	///
+	/// Only pointers that always dereference to the same place are supported.
+	/// [`JoinFuture`] relies on the length and any variant of its [`Futures`] staying the same between [`Futures::init_outputs`] calls,
+	/// which a custom [`DerefMut`](`core::ops::DerefMut`) implementation could otherwise break by switching targets:
+	///
+	/// ```compile_fail
+	/// # #![allow(deprecated)]
+	/// use core::{
+	///     future::{ready, Ready},
+	///     ops::{Deref, DerefMut},
+	///     pin::Pin,
+	/// };
+	/// use unpin_choices_dsa::join_future::JoinFuture;
+	///
+	/// /// Dereferences to a different [`Option`] each time it's dereferenced mutably.
+	/// struct Swapping(Option<Ready<()>>, Option<Ready<()>>, bool);
+	///
+	/// impl Deref for Swapping {
+	///     type Target = Option<Ready<()>>;
+	///     fn deref(&self) -> &Self::Target {
+	///         if self.2 { &self.1 } else { &self.0 }
+	///     }
+	/// }
+	///
+	/// impl DerefMut for Swapping {
+	///     fn deref_mut(&mut self) -> &mut Self::Target {
+	///         self.2 = !self.2;
+	///         if self.2 { &mut self.1 } else { &mut self.0 }
+	///     }
+	/// }
+	///
+	/// // `init_outputs` would see `None` here, but `poll_at` `Some`.
+	/// let _ = JoinFuture::new(Pin::new(Swapping(None, Some(ready(())), false)));
+	/// ```
+	impl<'a, Fs> for Pin<&'a mut Fs>,
+	/// Boxed [`Futures`] are [`Futures`] themselves, which is useful to join large numbers of [`Future`]s without moving them.
+	///
+	/// See the implementation for `Pin<&mut Fs>`.
+	#[cfg(feature = "alloc")]
+	impl<Fs> for Pin<Box<Fs>>,
+}
+
+/// An optional [`Future`], which counts as no constituent [`Future`]s at all if absent.
+///
+/// The output is [`None`] in that case, right away.
+///
+/// # Safety notes
+///
+/// The length depends on the variant, and [`Futures::init_outputs`] writes the output only for [`None`].
+/// That's sound because [`JoinFuture`] has exclusive access to the pinned [`Option`],
+/// which can change only through [`JoinFuture::reset`]'s `reinit`, and that
+/// calls [`Futures::init_outputs`] again and panics if the length (and so the variant) changed.
+/// (Pinning pointers to an [`Option`] are [`Futures`] only if they can't switch targets, see above.)
+unsafe impl<F: Future> Futures for Option<F> {
+	type Outputs = Option<F::Output>;
+	type PartialOutputs = Option<F::Output>;
+	type Completion = <CompletionFor<1> as SmallestCompletion>::Completion;
+
+	fn initial_completion() -> Self::Completion {
+		Self::Completion::default()
+	}
+
+	fn len(&self) -> usize {
+		usize::from(self.is_some())
+	}
+
+	fn init_outputs(&self, outputs: &mut MaybeUninit<Self::Outputs>) {
+		if self.is_none() {
+			*outputs = MaybeUninit::new(None);
+		}
+	}
+
+	fn poll_at(
+		futures: Pin<&mut Self>,
+		index: usize,
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		cx: &mut Context<'_>,
+	) -> Poll<()> {
+		match (index, futures.as_pin_mut()) {
+			(0, Some(future)) => future.poll(cx).map(|output| {
+				*outputs = MaybeUninit::new(Some(output));
+			}),
+			_ => panic!("Index {} out of bounds for an optional `Future`.", index),
+		}
+	}
+
+	unsafe fn take_outputs(
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		completion: &Self::Completion,
+	) -> Self::PartialOutputs {
+		if completion.get(0) {
+			outputs.as_ptr().read()
 		} else {
-			assert!(stepped, "`JoinFuture` was previously completed.");
-			Poll::Ready(())
+			None
 		}
 	}
 }
 
-impl<Fs: FusedFutures> FusedFuture for JoinFuture<Fs>
+/// Joins the constituent [`Future`]s of whichever side is active.
+///
+/// Both sides must use the same [`Completion`] type, which is the case for arrays of similar length.
+///
+/// # Panics
+///
+/// [`Futures::poll_at`] panics iff the active side differs from the one [`Futures::init_outputs`] saw,
+/// since the outputs are laid out for that one.
+unsafe impl<A, B> Futures for Either2<A, B>
 where
-	for<'a> &'a Fs::Completion: IntoIterator<Item = bool>,
+	A: Futures,
+	B: Futures<Completion = A::Completion>,
 {
-	fn is_terminated(&self) -> bool {
-		if (&self.completion).into_iter().all(identity) {
-			return true;
+	type Outputs = Either2<A::Outputs, B::Outputs>;
+	type PartialOutputs = Either2<A::PartialOutputs, B::PartialOutputs>;
+	type Completion = A::Completion;
+
+	fn initial_completion() -> Self::Completion {
+		A::initial_completion()
+	}
+
+	fn len(&self) -> usize {
+		match self {
+			Either2::A(a) => a.len(),
+			Either2::B(b) => b.len(),
+		}
+	}
+
+	fn init_outputs(&self, outputs: &mut MaybeUninit<Self::Outputs>) {
+		let repr = outputs
+			.as_mut_ptr()
+			.cast::<Either2Repr<A::Outputs, B::Outputs>>();
+		unsafe {
+			//SAFETY: `Either2` is `#[repr(C)]`, so this is its layout. The payload isn't touched.
+			ptr::addr_of_mut!((*repr).tag).write(match self {
+				Either2::A(_) => Either2Tag::A,
+				Either2::B(_) => Either2Tag::B,
+			})
+		}
+		match self {
+			Either2::A(a) => a.init_outputs(unsafe { payload(outputs) }),
+			Either2::B(b) => b.init_outputs(unsafe { payload(outputs) }),
+		}
+	}
+
+	fn poll_at(
+		futures: Pin<&mut Self>,
+		index: usize,
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		cx: &mut Context<'_>,
+	) -> Poll<()> {
+		let repr = outputs
+			.as_ptr()
+			.cast::<Either2Repr<A::Outputs, B::Outputs>>();
+		unsafe {
+			//SAFETY: The discriminant was written by `init_outputs`.
+			let tag = ptr::addr_of!((*repr).tag).read();
+			//SAFETY: Plain structural pin projection.
+			// The payload is only accessed as the variant the discriminant in `outputs` names.
+			match (Pin::get_unchecked_mut(futures), tag) {
+				(Either2::A(a), Either2Tag::A) => {
+					A::poll_at(Pin::new_unchecked(a), index, payload(outputs), cx)
+				}
+				(Either2::B(b), Either2Tag::B) => {
+					B::poll_at(Pin::new_unchecked(b), index, payload(outputs), cx)
+				}
+				_ => panic!("The active `Either2` variant changed after `Futures::init_outputs`."),
+			}
 		}
+	}
 
-		self.futures.all_terminated()
+	unsafe fn take_outputs(
+		outputs: &mut MaybeUninit<Self::Outputs>,
+		completion: &Self::Completion,
+	) -> Self::PartialOutputs {
+		let repr = outputs
+			.as_ptr()
+			.cast::<Either2Repr<A::Outputs, B::Outputs>>();
+		//SAFETY: The discriminant was written by `init_outputs`.
+		match ptr::addr_of!((*repr).tag).read() {
+			Either2Tag::A => Either2::A(A::take_outputs(payload(outputs), completion)),
+			Either2Tag::B => Either2::B(B::take_outputs(payload(outputs), completion)),
+		}
 	}
 }
 
-/// Exposes an `all_terminated` method if [`FusedFuture`] can be implemented through a [`Futures`].
-pub trait FusedFutures: Futures
-where
-	for<'a> &'a Self::Completion: IntoIterator<Item = bool>,
-{
-	/// Returns whether all constituent [`Future`]s have terminated.
-	///
-	/// Note that this is **not** directly analogous to [`FusedFuture::is_terminated`]:
-	/// A result of `false` does **not** imply that this instance can be polled through [`Futures`].
-	fn all_terminated(&self) -> bool;
+/// The layout of [`Either2`], which is guaranteed through its `#[repr(C)]`.
+#[repr(C)]
+struct Either2Repr<A, B> {
+	tag: Either2Tag,
+	payload: Either2Payload<A, B>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+enum Either2Tag {
+	A,
+	B,
+}
+
+#[repr(C)]
+union Either2Payload<A, B> {
+	a: ManuallyDrop<A>,
+	b: ManuallyDrop<B>,
+}
+
+/// Either variant's payload in `outputs`, which is at the same offset for both.
+///
+/// # Safety
+///
+/// `T` must be the payload type of the variant whose discriminant is (or will be) written.
+unsafe fn payload<A, B, T>(outputs: &mut MaybeUninit<Either2<A, B>>) -> &mut MaybeUninit<T> {
+	let repr = outputs.as_mut_ptr().cast::<Either2Repr<A, B>>();
+	&mut *ptr::addr_of_mut!((*repr).payload).cast::<MaybeUninit<T>>()
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for JoinFuture<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
 }
 
-//TODO: `FusedFutures` implementations.
+#[cfg(test)]
+mod tests {
+	use super::JoinFuture;
+	use crate::{
+		test_futures::{
+			drop_tracker, pending_forever, poll_counter, ready_after, DropTracker, PendingForever,
+			PollCounter,
+		},
+		waker::{counting_waker, noop_context},
+	};
+	use core::{
+		future::{ready, Future, Ready},
+		num::NonZeroUsize,
+		pin::Pin,
+		sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+		task::{Context, Poll},
+	};
+
+	/// Five never-completing constituents, each counting its polls.
+	fn counted_pending(polls: &[AtomicUsize; 5]) -> [PollCounter<'_, PendingForever<()>>; 5] {
+		let mut polls = polls.iter();
+		[(); 5].map(|()| poll_counter(pending_forever(), polls.next().unwrap()))
+	}
+
+	fn load(polls: &[AtomicUsize; 5]) -> [usize; 5] {
+		let mut polls = polls.iter();
+		[(); 5].map(|()| polls.next().unwrap().load(Ordering::Relaxed))
+	}
+
+	/// An output that counts its drops.
+	#[derive(Debug)]
+	struct Counted<'a>(&'a AtomicUsize);
+
+	impl Drop for Counted<'_> {
+		fn drop(&mut self) {
+			self.0.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	type HalfReady<'a> = (
+		DropTracker<'a, Ready<Counted<'a>>>,
+		DropTracker<'a, PendingForever<Counted<'a>>>,
+	);
+
+	/// A join of which the first constituent completes on the first poll, and the second never.
+	fn half_ready<'a>(drops: &'a AtomicUsize, dropped: &'a [AtomicBool; 2]) -> HalfReady<'a> {
+		(
+			drop_tracker(ready(Counted(drops)), &dropped[0]),
+			drop_tracker(pending_forever(), &dropped[1]),
+		)
+	}
+
+	fn flags(dropped: &[AtomicBool; 2]) -> [bool; 2] {
+		[
+			dropped[0].load(Ordering::Relaxed),
+			dropped[1].load(Ordering::Relaxed),
+		]
+	}
+
+	#[test]
+	fn option_none_is_ready_right_away() {
+		let mut join = JoinFuture::new(None::<core::future::Ready<u8>>);
+		assert!(join.is_empty());
+		assert_eq!(
+			Pin::new(&mut join).poll(&mut noop_context()),
+			Poll::Ready(None)
+		);
+	}
+
+	#[test]
+	fn option_some_completes_with_some() {
+		let mut join = JoinFuture::new(Some(ready_after(2, 7_u8)));
+		assert_eq!(join.len(), 1);
+		let mut cx = noop_context();
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Ready(Some(7)));
+	}
+
+	#[test]
+	fn polls_every_constituent_each_time_by_default() {
+		let polls = [AtomicUsize::new(0), AtomicUsize::new(0)];
+		let mut join = JoinFuture::new((
+			poll_counter(pending_forever::<()>(), &polls[0]),
+			poll_counter(ready_after(2, ()), &polls[1]),
+		));
+		let mut cx = noop_context();
+		for _ in 0..3 {
+			assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		}
+		assert_eq!(
+			[
+				polls[0].load(Ordering::Relaxed),
+				polls[1].load(Ordering::Relaxed)
+			],
+			[3, 3]
+		);
+	}
+
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	#[test]
+	fn dispatching_skips_unwoken_constituents() {
+		static WAKES: AtomicUsize = AtomicUsize::new(0);
+
+		let polls = [AtomicUsize::new(0), AtomicUsize::new(0)];
+		let load = || {
+			[
+				polls[0].load(Ordering::Relaxed),
+				polls[1].load(Ordering::Relaxed),
+			]
+		};
+		// Only the second one wakes itself, each time it's pending.
+		let mut join = JoinFuture::new_dispatching((
+			poll_counter(pending_forever::<()>(), &polls[0]),
+			poll_counter(ready_after(2, ()), &polls[1]),
+		));
+		let waker = counting_waker(&WAKES);
+		let mut cx = Context::from_waker(&waker);
+
+		// Initially, each one is polled once.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(), [1, 1]);
+		assert_eq!(WAKES.load(Ordering::Acquire), 1);
+
+		// Afterwards, only the woken one is.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(), [1, 2]);
+		assert_eq!(WAKES.load(Ordering::Acquire), 2);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(), [1, 3]);
+
+		// Completed, so not woken again.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(), [1, 3]);
+		assert_eq!(WAKES.load(Ordering::Acquire), 2);
+	}
+
+	#[test]
+	fn budget_resumes_round_robin() {
+		static WAKES: AtomicUsize = AtomicUsize::new(0);
+
+		let polls = Default::default();
+		let mut join =
+			JoinFuture::new(counted_pending(&polls)).with_budget(NonZeroUsize::new(2).unwrap());
+		let waker = counting_waker(&WAKES);
+		let mut cx = Context::from_waker(&waker);
+
+		// Each constituent gets its turn within ceil(5 / 2) = 3 polls.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 0, 0, 0]);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 1, 1, 0]);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [2, 1, 1, 1, 1]);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [2, 2, 2, 1, 1]);
+
+		// Each cut-off wakes the task, so that it's polled again.
+		assert_eq!(WAKES.load(Ordering::Acquire), 4);
+	}
+
+	#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+	#[test]
+	fn budget_keeps_cut_off_constituents_dirty() {
+		static WAKES: AtomicUsize = AtomicUsize::new(0);
+
+		let polls = Default::default();
+		let mut join = JoinFuture::new_dispatching(counted_pending(&polls))
+			.with_budget(NonZeroUsize::new(2).unwrap());
+		let waker = counting_waker(&WAKES);
+		let mut cx = Context::from_waker(&waker);
+
+		// All start out woken, and those that didn't fit into the budget stay that way.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 0, 0, 0]);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 1, 1, 0]);
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 1, 1, 1]);
+		assert_eq!(WAKES.load(Ordering::Acquire), 2);
+
+		// None were woken since.
+		assert_eq!(Pin::new(&mut join).poll(&mut cx), Poll::Pending);
+		assert_eq!(load(&polls), [1, 1, 1, 1, 1]);
+		assert_eq!(WAKES.load(Ordering::Acquire), 2);
+	}
+
+	#[test]
+	fn reset_drops_partial_outputs_once() {
+		let drops = AtomicUsize::new(0);
+		let (old, new) = <([AtomicBool; 2], [AtomicBool; 2])>::default();
+		let mut join = JoinFuture::new(half_ready(&drops, &old));
+		let mut cx = noop_context();
+
+		assert!(Pin::new(&mut join).poll(&mut cx).is_pending());
+		assert_eq!(join.completed_count(), 1);
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+
+		Pin::new(&mut join).reset(|mut futures| futures.set(half_ready(&drops, &new)));
+		assert_eq!(join.completed_count(), 0);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+		assert_eq!(flags(&old), [true, true]);
+		assert_eq!(flags(&new), [false, false]);
+
+		// The replacement completes and its output is dropped along with the join, once.
+		assert!(Pin::new(&mut join).poll(&mut cx).is_pending());
+		assert_eq!(join.completed_count(), 1);
+		drop(join);
+		assert_eq!(drops.load(Ordering::Relaxed), 2);
+		assert_eq!(flags(&new), [true, true]);
+	}
+
+	#[test]
+	fn reset_after_completion_leaves_outputs_alone() {
+		let drops = AtomicUsize::new(0);
+		let dropped = AtomicBool::new(false);
+		let mut join = JoinFuture::new([drop_tracker(ready(Counted(&drops)), &dropped)]);
+		let mut cx = noop_context();
+
+		let outputs = match Pin::new(&mut join).poll(&mut cx) {
+			Poll::Ready(outputs) => outputs,
+			Poll::Pending => unreachable!(),
+		};
+		// The outputs were handed out, so they aren't the join's to drop anymore.
+		Pin::new(&mut join)
+			.reset(|mut futures| futures.set([drop_tracker(ready(Counted(&drops)), &dropped)]));
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+		drop(outputs);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+
+		// This drops only the value still inside the unpolled replacement.
+		drop(join);
+		assert_eq!(drops.load(Ordering::Relaxed), 2);
+		assert!(dropped.load(Ordering::Relaxed));
+	}
+
+	#[test]
+	fn drop_drops_partial_outputs_once() {
+		let drops = AtomicUsize::new(0);
+		let dropped = Default::default();
+		let mut join = JoinFuture::new(half_ready(&drops, &dropped));
+
+		assert!(Pin::new(&mut join).poll(&mut noop_context()).is_pending());
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+		drop(join);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+		assert_eq!(flags(&dropped), [true, true]);
+	}
+
+	#[test]
+	fn into_futures_hands_out_partial_outputs() {
+		let drops = AtomicUsize::new(0);
+		let dropped = Default::default();
+		let mut join = JoinFuture::new(half_ready(&drops, &dropped));
+
+		assert!(Pin::new(&mut join).poll(&mut noop_context()).is_pending());
+		let (futures, (first, second)) = join.into_futures();
+		assert!(first.is_some());
+		assert!(second.is_none());
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+		assert_eq!(flags(&dropped), [false, false]);
+
+		drop(first);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+		drop(futures);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+		assert_eq!(flags(&dropped), [true, true]);
+	}
+
+	#[test]
+	fn into_futures_after_completion_hands_out_nothing() {
+		let drops = AtomicUsize::new(0);
+		let dropped = AtomicBool::new(false);
+		let mut join = JoinFuture::new([drop_tracker(ready(Counted(&drops)), &dropped)]);
+
+		let outputs = match Pin::new(&mut join).poll(&mut noop_context()) {
+			Poll::Ready(outputs) => outputs,
+			Poll::Pending => unreachable!(),
+		};
+		let (futures, [partial]) = join.into_futures();
+		assert!(partial.is_none());
+		drop(futures);
+		assert_eq!(drops.load(Ordering::Relaxed), 0);
+		assert!(dropped.load(Ordering::Relaxed));
+
+		drop(outputs);
+		assert_eq!(drops.load(Ordering::Relaxed), 1);
+	}
+}
@@ -0,0 +1,161 @@
+//! A [`JoinFuture`](`crate::join_future::JoinFuture`) variant that writes its outputs into caller-provided storage.
+
+use crate::{
+	completion::{Bitmap, Completion},
+	join_future::Futures,
+};
+use core::{
+	future::Future,
+	mem::MaybeUninit,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::{pin_project, pinned_drop};
+
+/// Returns a [`Future`] that completes when all [`Future`]s in `futures` complete,
+/// writing their outputs directly into `outputs` and then resolving to a pinning reference to them.
+///
+/// See [`JoinInto::new`] for a safe variant if the outputs are [`Unpin`].
+///
+/// # Safety
+///
+/// The outputs are pinned once the [`JoinInto`] completes, so from then on `outputs`' memory must not be reused
+/// or invalidated before they were dropped in place (for example using [`ptr::drop_in_place`](`core::ptr::drop_in_place`)),
+/// or ever, if they are never dropped. Leaking them is fine.
+///
+/// (If the [`JoinInto`] is dropped before completing, it drops any outputs written so far by itself.)
+pub unsafe fn join_into<Fs: Futures>(
+	futures: Fs,
+	mut outputs: Pin<&mut MaybeUninit<Fs::Outputs>>,
+) -> JoinInto<'_, Fs> {
+	futures.init_outputs(outputs.as_mut().get_unchecked_mut());
+	JoinInto {
+		completion: Fs::initial_completion(),
+		futures,
+		outputs: Some(outputs),
+	}
+}
+
+/// A [`Future`] that completes as soon as all [`Future`]s in `futures` have completed,
+/// with outputs written into borrowed storage instead of an internal buffer.
+///
+/// This avoids a copy of the combined outputs when they end up in a larger pinned structure anyway.
+///
+/// > This is a leaner sibling of [`JoinFuture`](`crate::join_future::JoinFuture`), without waker dispatch or a budget.
+#[pin_project(PinnedDrop)]
+pub struct JoinInto<'a, Fs: Futures> {
+	completion: Fs::Completion,
+	#[pin]
+	futures: Fs,
+	/// [`None`] once the outputs were handed out.
+	outputs: Option<Pin<&'a mut MaybeUninit<Fs::Outputs>>>,
+}
+
+impl<'a, Fs: Futures> JoinInto<'a, Fs> {
+	/// Creates a new instance of [`JoinInto`] that writes into `outputs`.
+	///
+	/// Use [`join_into`] instead if the outputs aren't [`Unpin`].
+	#[must_use]
+	pub fn new(futures: Fs, outputs: &'a mut MaybeUninit<Fs::Outputs>) -> Self
+	where
+		Fs::Outputs: Unpin,
+	{
+		unsafe {
+			//SAFETY: Pinning is trivial for `Unpin` outputs, so not dropping them is only a leak.
+			join_into(futures, Pin::new(outputs))
+		}
+	}
+
+	/// The number of constituent [`Future`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.futures.len()
+	}
+
+	/// Whether there are no constituent [`Future`]s.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.futures.is_empty()
+	}
+
+	/// The number of constituent [`Future`]s that have completed so far.
+	#[must_use]
+	pub fn completed_count(&self) -> usize {
+		self.completion.count_ones()
+	}
+}
+
+/// Shows the completion bitmap, like [`JoinFuture`](`crate::join_future::JoinFuture`)'s [`Debug`](`core::fmt::Debug`) implementation.
+impl<Fs: Futures> core::fmt::Debug for JoinInto<'_, Fs> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("JoinInto")
+			.field("completion", &Bitmap(&self.completion, self.len()))
+			.field("done", &self.outputs.is_none())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Drops the outputs written so far in place, unless they were handed out already.
+#[pinned_drop]
+impl<Fs: Futures> PinnedDrop for JoinInto<'_, Fs> {
+	fn drop(self: Pin<&mut Self>) {
+		let this = self.project();
+		if let Some(outputs) = this.outputs {
+			drop(unsafe {
+				//SAFETY: The completion flags match the initialised outputs, which aren't used again.
+				// Moving them out to drop them is fine, since they haven't been pinned yet.
+				Fs::take_outputs(outputs.as_mut().get_unchecked_mut(), this.completion)
+			});
+		}
+	}
+}
+
+impl<'a, Fs: Futures> Future for JoinInto<'a, Fs> {
+	type Output = Pin<&'a mut Fs::Outputs>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		let outputs = this
+			.outputs
+			.as_mut()
+			.expect("`JoinInto` was previously completed.");
+
+		let len = this.futures.len();
+		for index in 0..len {
+			if !this.completion.get(index)
+				&& Fs::poll_at(
+					this.futures.as_mut(),
+					index,
+					unsafe {
+						//SAFETY: Only written to in place.
+						outputs.as_mut().get_unchecked_mut()
+					},
+					cx,
+				)
+				.is_ready()
+			{
+				this.completion.set(index, true)
+			}
+		}
+
+		if this.completion.all(len) {
+			let outputs = this.outputs.take().expect("unreachable");
+			Poll::Ready(unsafe {
+				//SAFETY: Validity of this operation is directly required by [`Futures`]'s implementation contract.
+				// The pinning is upheld by `join_into`'s contract.
+				outputs.map_unchecked_mut(|outputs| &mut *outputs.as_mut_ptr())
+			})
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for JoinInto<'_, Fs> {
+	fn is_terminated(&self) -> bool {
+		self.outputs.is_none()
+	}
+}
@@ -0,0 +1,221 @@
+//! Labelled constituent [`Future`]s and builders for combinators over them, for more legible [`Debug`] output.
+
+use crate::{
+	any_future::{AnyFuture, Futures as AnyFutures, Heterogeneous},
+	join_future::{Futures as JoinFutures, JoinFuture},
+};
+use core::{
+	fmt::{self, Debug, Formatter},
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// A [`Future`] with a `&'static str` label, which also tracks whether it has completed.
+///
+/// Its [`Debug`] output shows only the label and that state, like `Labelled("uart-rx", Pending)`,
+/// so `F` doesn't have to be [`Debug`] itself.
+///
+/// Once completed, it returns [`Poll::Pending`] without polling `F` again.
+#[pin_project]
+pub struct Labelled<F> {
+	label: &'static str,
+	ready: bool,
+	#[pin]
+	future: F,
+}
+
+impl<F> Labelled<F> {
+	/// Labels `future` with `label`.
+	#[must_use]
+	pub fn new(label: &'static str, future: F) -> Self {
+		Self {
+			label,
+			ready: false,
+			future,
+		}
+	}
+
+	/// This [`Future`]'s label.
+	#[must_use]
+	pub fn label(&self) -> &'static str {
+		self.label
+	}
+
+	/// Whether the labelled [`Future`] has completed.
+	#[must_use]
+	pub fn is_ready(&self) -> bool {
+		self.ready
+	}
+}
+
+impl<F: Future> Future for Labelled<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		if *this.ready {
+			return Poll::Pending;
+		}
+		let output = this.future.poll(cx);
+		*this.ready = output.is_ready();
+		output
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for Labelled<F> {
+	fn is_terminated(&self) -> bool {
+		self.ready
+	}
+}
+
+impl<F> Debug for Labelled<F> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		#[derive(Debug)]
+		enum State {
+			Pending,
+			Ready,
+		}
+
+		f.debug_tuple("Labelled")
+			.field(&self.label)
+			.field(if self.ready {
+				&State::Ready
+			} else {
+				&State::Pending
+			})
+			.finish()
+	}
+}
+
+/// Collects up to twelve [`Labelled`] [`Future`]s with possibly different output types, to race them.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// # async fn example(a: impl core::future::Future<Output = u8>, b: impl core::future::Future<Output = ()>) {
+/// use unpin_choices_dsa::labelled::AnyBuilder;
+///
+/// let race = AnyBuilder::new().child("a", a).child("b", b).build();
+/// // `{:?}` now shows e.g. `Labelled("a", Pending)` for each child.
+/// let output = race.await;
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AnyBuilder<Fs>(Fs);
+
+/// Collects up to twelve [`Labelled`] [`Future`]s with possibly different output types, to join them.
+///
+/// See [`AnyBuilder`] for an example, which works the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct JoinBuilder<Fs>(Fs);
+
+impl AnyBuilder<()> {
+	/// Creates a new empty [`AnyBuilder`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self(())
+	}
+}
+
+impl JoinBuilder<()> {
+	/// Creates a new empty [`JoinBuilder`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self(())
+	}
+}
+
+impl<Fs> AnyBuilder<Fs> {
+	/// Creates the [`AnyFuture`] over the collected [`Labelled`] [`Future`]s.
+	///
+	/// Its [`Debug`] output shows the label and state of each of them.
+	/// The output is an [`Either2`](`crate::either::Either2`), [`Either3`](`crate::either::Either3`), …
+	/// like for [`any_heterogeneous`](`crate::any_future::any_heterogeneous`).
+	#[must_use]
+	pub fn build(self) -> AnyFuture<Heterogeneous<Fs>>
+	where
+		Heterogeneous<Fs>: AnyFutures,
+	{
+		AnyFuture::new(Heterogeneous(self.0))
+	}
+}
+
+impl<Fs: JoinFutures> JoinBuilder<Fs> {
+	/// Creates the [`JoinFuture`] over the collected [`Labelled`] [`Future`]s, wrapped in a [`LabelledJoin`].
+	#[must_use]
+	pub fn build(self) -> LabelledJoin<Fs> {
+		LabelledJoin(JoinFuture::new(self.0))
+	}
+}
+
+macro_rules! builder_children {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		($($F:ident.$index:tt),*) -> $Next:ident
+	),*$(,)?} => {$(
+		impl<$($F),*> AnyBuilder<($(Labelled<$F>,)*)> {
+			/// Adds `future` with `label`.
+			#[must_use]
+			pub fn child<$Next>(self, label: &'static str, future: $Next) -> AnyBuilder<($(Labelled<$F>,)* Labelled<$Next>,)> {
+				AnyBuilder(($((self.0).$index,)* Labelled::new(label, future),))
+			}
+		}
+
+		impl<$($F),*> JoinBuilder<($(Labelled<$F>,)*)> {
+			/// Adds `future` with `label`.
+			#[must_use]
+			pub fn child<$Next>(self, label: &'static str, future: $Next) -> JoinBuilder<($(Labelled<$F>,)* Labelled<$Next>,)> {
+				JoinBuilder(($((self.0).$index,)* Labelled::new(label, future),))
+			}
+		}
+	)*};
+}
+
+builder_children! {
+	() -> A,
+	(A.0) -> B,
+	(A.0, B.1) -> C,
+	(A.0, B.1, C.2) -> D,
+	(A.0, B.1, C.2, D.3) -> E,
+	(A.0, B.1, C.2, D.3, E.4) -> F,
+	(A.0, B.1, C.2, D.3, E.4, F.5) -> G,
+	(A.0, B.1, C.2, D.3, E.4, F.5, G.6) -> H,
+	(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7) -> I,
+	(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8) -> J,
+	(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9) -> K,
+	(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7, I.8, J.9, K.10) -> L,
+}
+
+/// A [`JoinFuture`] whose [`Debug`] output also shows its constituent [`Future`]s. See [`JoinBuilder`].
+///
+/// > [`JoinFuture`] can't do this by itself, since its [`Debug`] implementation doesn't require `Fs: Debug`.
+#[pin_project]
+pub struct LabelledJoin<Fs: JoinFutures>(#[pin] pub JoinFuture<Fs>);
+
+impl<Fs: JoinFutures> Future for LabelledJoin<Fs> {
+	type Output = Fs::Outputs;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.project().0.poll(cx)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: JoinFutures> FusedFuture for LabelledJoin<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_terminated()
+	}
+}
+
+impl<Fs: JoinFutures + Debug> Debug for LabelledJoin<Fs> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LabelledJoin")
+			.field("join", &self.0)
+			.field("children", self.0.futures())
+			.finish()
+	}
+}
@@ -0,0 +1,40 @@
+//! A [`Future`] that runs a closure when it's first polled.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Creates a [`Lazy`] that completes with the result of `f` on its first poll.
+///
+/// Side effects in `f` are deferred until then, which is when a combinator first drives it,
+/// rather than when the [`Future`] is created.
+pub fn lazy<F: FnOnce(&mut Context<'_>) -> T, T>(f: F) -> Lazy<F> {
+	Lazy(Some(f))
+}
+
+/// A [`Future`] that completes with the result of its closure on its first poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Lazy<F>(Option<F>);
+
+impl<F> Unpin for Lazy<F> {}
+
+impl<F: FnOnce(&mut Context<'_>) -> T, T> Future for Lazy<F> {
+	type Output = T;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let f = self.0.take().expect("`Lazy` was previously completed.");
+		Poll::Ready(f(cx))
+	}
+}
+
+/// The closure is consumed on completion, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<F: FnOnce(&mut Context<'_>) -> T, T> FusedFuture for Lazy<F> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_none()
+	}
+}
@@ -0,0 +1,103 @@
+//! A value that is initialised asynchronously on first use, for example in a `static`.
+
+use crate::once_cell::{GetOrInit, GetOrInitPinned, OnceCell};
+use core::{
+	future::{Future, IntoFuture},
+	pin::Pin,
+};
+
+/// A [`OnceCell`] that knows its own initialiser, so it can be used as `static`.
+///
+/// The first [`.get()`](`Lazy::get`) that is awaited runs the [`Future`] returned by `F`,
+/// and any others wait for it to finish. `&Lazy` can also be awaited directly.
+///
+/// `F` is called again only if a running initialiser is dropped before it finishes.
+///
+/// Compare [`crate::lazy::Lazy`], which is a [`Future`] that defers a closure rather than a shared value.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::future::{ready, Ready};
+/// use unpin_choices_dsa::{block_on::block_on, lazy_cell::Lazy};
+///
+/// static ANSWER: Lazy<u32, fn() -> Ready<u32>> = Lazy::new(|| ready(42));
+///
+/// assert_eq!(ANSWER.try_get(), None);
+/// assert_eq!(block_on(ANSWER.get()), &42);
+/// assert_eq!(block_on(async { *(&ANSWER).await }), 42);
+/// ```
+///
+/// > In a `static`, the initialiser's [`Future`] type must be nameable.
+/// > That's usually the case for hand-written peripheral drivers, and with `"alloc"` a boxed `dyn` [`Future`] works too.
+pub struct Lazy<T, F> {
+	cell: OnceCell<T>,
+	init: F,
+}
+
+impl<T, F> Lazy<T, F> {
+	/// Creates a new uninitialised [`Lazy`] that will be initialised by awaiting `init()`.
+	#[must_use]
+	pub const fn new(init: F) -> Self {
+		Self {
+			cell: OnceCell::new(),
+			init,
+		}
+	}
+
+	/// The value, if initialised already.
+	#[must_use]
+	pub fn try_get(&self) -> Option<&T> {
+		self.cell.get()
+	}
+
+	/// Unwraps the value, if initialised.
+	pub fn into_inner(self) -> Option<T> {
+		self.cell.into_inner()
+	}
+}
+
+impl<T, F, Fut> Lazy<T, F>
+where
+	F: Fn() -> Fut,
+	Fut: Future<Output = T>,
+{
+	/// Returns a [`Future`] that completes with a reference to the value, initialising it if necessary.
+	pub fn get(&self) -> GetOrInit<'_, T, &F, Fut> {
+		self.cell.get_or_init(&self.init)
+	}
+
+	/// Like [`.get()`](`Lazy::get`), but completes with [`Pin<&T>`].
+	///
+	/// > A `static` can be pinned with [`Pin::static_ref`].
+	#[must_use]
+	pub fn get_pinned(self: Pin<&Self>) -> GetOrInitPinned<'_, T, &F, Fut> {
+		let this = self.get_ref();
+		unsafe {
+			//SAFETY: `cell` is structurally pinned.
+			Pin::new_unchecked(&this.cell)
+		}
+		.get_or_init_pinned(&this.init)
+	}
+}
+
+impl<'a, T, F, Fut> IntoFuture for &'a Lazy<T, F>
+where
+	F: Fn() -> Fut,
+	Fut: Future<Output = T>,
+{
+	type Output = &'a T;
+	type IntoFuture = GetOrInit<'a, T, &'a F, Fut>;
+
+	fn into_future(self) -> Self::IntoFuture {
+		self.get()
+	}
+}
+
+/// Shows only the value, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T: core::fmt::Debug, F> core::fmt::Debug for Lazy<T, F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Lazy")
+			.field("value", &self.cell.get())
+			.finish_non_exhaustive()
+	}
+}
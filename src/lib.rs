@@ -10,6 +10,37 @@
 //! certain subjective comments are still indented using markdown quote blocks:
 //!
 //! > I'm excited to see how pinning will interact with future Rust language features.
+//!
+//! # Features
+//!
+//! The pinning wrappers ([`anti_pinned`], [`pinned_pin`] and their helpers, like [`pinned_pin_anti_pinned`]) and the [`Waker`](`core::task::Waker`) utilities ([`waker`], [`atomic_waker`] and [`waker_set`]) don't need any features.
+//! Everything else is split into the following subsystems, all of which are enabled by default:
+//!
+//! - `"alloc"`: Items that need a global allocator, like boxed constructors and dynamically-sized combinators.
+//! - `"combinators"`: [`Future`](`core::future::Future`) combinators, like [`any_future`] and [`join_future`].
+//! - `"containers"`: Pinning collections and handles into them.
+//! - `"executor"`: Minimal executors, like [`block_on`].
+//! - `"futures-core"`: [`FusedFuture`](`futures_core::FusedFuture`) implementations and the [`fused`] adaptor.
+//! - `"macros"`: Macro front-ends to the combinators.
+//! - `"stream"`: [`Stream`](`futures_core::Stream`) adaptors, like [`split_stream`]. Implies `"futures-core"`.
+//! - `"sync"`: Synchronisation primitives, like [`stop_token`].
+//!
+//! Additionally, `"std"` enables items that need the standard library, like [`thread_block_on`] and [`thread_timer`]. It implies `"alloc"`, but isn't enabled by default.
+//! Neither is `"critical-section"`, which enables the interrupt-safe [`wake_queue`] through the [`critical-section`](https://docs.rs/critical-section/1) crate
+//! (and [`poll_stats`] on targets without atomic compare-and-swap, see below).
+//!
+//! Items that are available only with a combination of features are gated on all of them.
+//!
+//! Items that need pointer-sized atomic compare-and-swap are also gated on `target_has_atomic = "ptr"`,
+//! so they are missing on targets like `thumbv6m-none-eabi`:
+//! `atomic_waker`, the `Arc`-based conversions and [`Waker`](`core::task::Waker`)s, `counting_waker`, `pinned_elem`,
+//! the whole `"sync"` subsystem, the global timer with everything that sleeps (like `deadline` and `retry`),
+//! the executors, `test_futures`, and `pinned_future_set` with the combinators built on it
+//! as well as the other combinators' `new_dispatching` constructors.
+//! `poll_stats` falls back to the `"critical-section"` feature there, if enabled.
+//!
+//! > This way, you can compile only what you need if you disable default features,
+//! > which matters most on embedded targets.
 
 #![deprecated = "This crate is permanently unstable and not intended for production use."]
 #![doc(html_root_url = "https://docs.rs/unpin-choices-dsa/0.0.1")]
@@ -21,14 +52,173 @@
 #[doc = include_str!("../README.md")]
 mod readme {}
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+#[cfg(all(
+	feature = "alloc",
+	feature = "combinators",
+	feature = "sync",
+	target_has_atomic = "ptr"
+))]
+pub mod abortable;
 pub mod anti_pinned;
+#[cfg(feature = "combinators")]
 pub mod any_future;
+#[cfg(target_has_atomic = "ptr")]
+pub mod atomic_waker;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod barrier;
+#[cfg(feature = "executor")]
+pub mod block_on;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod broadcast;
+#[cfg(feature = "combinators")]
+pub mod completion;
+#[cfg(feature = "combinators")]
+pub mod completions;
+#[cfg(all(feature = "combinators", target_has_atomic = "ptr"))]
+pub mod deadline;
+#[cfg(all(feature = "alloc", feature = "combinators", target_has_atomic = "ptr"))]
+pub mod deadline_future_set;
+#[cfg(all(feature = "combinators", feature = "stream", target_has_atomic = "ptr"))]
+pub mod debounce;
+#[cfg(all(feature = "alloc", feature = "combinators"))]
+pub mod dyn_future;
+#[cfg(feature = "combinators")]
+pub mod either;
+#[cfg(feature = "combinators")]
+pub mod either_future;
+#[cfg(feature = "combinators")]
+pub mod fuse;
+#[cfg(feature = "futures-core")]
+pub mod fused;
+#[cfg(feature = "combinators")]
+pub mod future_queue;
+#[cfg(all(feature = "alloc", feature = "containers"))]
+pub mod generational_arena;
+#[cfg(feature = "combinators")]
 pub mod if_ready;
+pub mod init_array_pinned;
+#[cfg(feature = "combinators")]
+pub mod instrumented;
+#[cfg(all(feature = "combinators", target_has_atomic = "ptr"))]
+pub mod interval;
+#[cfg(feature = "containers")]
+pub mod intrusive;
+#[cfg(feature = "combinators")]
+pub mod join2;
+#[cfg(all(feature = "alloc", feature = "combinators"))]
+pub mod join_all;
+#[cfg(feature = "combinators")]
 pub mod join_future;
+#[cfg(feature = "combinators")]
+pub mod join_into;
+#[cfg(feature = "combinators")]
+pub mod labelled;
+#[cfg(feature = "combinators")]
+pub mod lazy;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod lazy_cell;
+#[cfg(all(feature = "alloc", feature = "combinators"))]
+pub mod local_shared;
+#[cfg(feature = "macros")]
+mod macros;
+#[cfg(feature = "combinators")]
+pub mod map;
+#[cfg(feature = "combinators")]
+pub mod maybe_done;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod mpsc;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod mutex;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod notify;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod once_cell;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod oneshot;
+#[cfg(all(feature = "alloc", feature = "containers"))]
+pub mod pinned_arena;
+#[cfg(all(feature = "alloc", feature = "containers"))]
+pub mod pinned_deque;
+#[cfg(all(feature = "alloc", feature = "containers", target_has_atomic = "ptr"))]
+pub mod pinned_elem;
+#[cfg(all(feature = "alloc", feature = "combinators", target_has_atomic = "ptr"))]
+pub mod pinned_future_set;
 pub mod pinned_pin;
+pub mod pinned_pin_anti_pinned;
 pub mod pinned_pin_pins_items;
+#[cfg(all(feature = "alloc", feature = "containers"))]
+pub mod pinned_slab;
+#[cfg(all(feature = "alloc", feature = "containers"))]
+pub mod pinned_vec;
+#[cfg(feature = "combinators")]
+pub mod poll_fn_pinned;
+#[cfg(all(
+	feature = "combinators",
+	any(target_has_atomic = "ptr", feature = "critical-section")
+))]
+pub mod poll_stats;
+#[cfg(all(feature = "alloc", feature = "combinators"))]
+pub mod race_remaining;
+#[cfg(feature = "combinators")]
 pub mod ready_or_never;
+#[cfg(all(feature = "combinators", target_has_atomic = "ptr"))]
+pub mod retry;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod rw_lock;
+#[cfg(all(feature = "alloc", feature = "combinators", target_has_atomic = "ptr"))]
+pub mod scope;
+#[cfg(feature = "combinators")]
+pub mod select_ok;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod semaphore;
+#[cfg(all(feature = "alloc", feature = "sync", target_has_atomic = "ptr"))]
+pub mod shared;
+#[cfg(all(feature = "combinators", target_has_atomic = "ptr"))]
+pub mod sleep;
+#[cfg(all(feature = "alloc", feature = "stream"))]
+pub mod split_stream;
+#[cfg(all(feature = "executor", target_has_atomic = "ptr"))]
+pub mod static_executor;
+#[cfg(feature = "combinators")]
+pub mod static_future_set;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod stop_token;
+#[cfg(all(feature = "executor", target_has_atomic = "ptr"))]
+pub mod superloop;
+#[cfg(all(feature = "alloc", feature = "combinators"))]
+pub mod test_clock;
+#[cfg(all(feature = "combinators", target_has_atomic = "ptr"))]
+pub mod test_futures;
+#[cfg(all(feature = "std", feature = "executor"))]
+pub mod thread_block_on;
+#[cfg(all(feature = "std", feature = "combinators"))]
+pub mod thread_timer;
+#[cfg(all(feature = "combinators", feature = "stream", target_has_atomic = "ptr"))]
+pub mod throttle;
+#[cfg(feature = "combinators")]
+pub mod timeout;
+#[cfg(feature = "combinators")]
+pub mod timer;
+#[cfg(feature = "critical-section")]
+pub mod wake_queue;
+pub mod waker;
+pub mod waker_set;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+pub mod watch;
+#[cfg(feature = "combinators")]
+pub mod yield_now;
 
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+mod spin_lock;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+mod state_ref;
 mod unchecked_tap;
+#[cfg(all(feature = "sync", target_has_atomic = "ptr"))]
+mod waiter_list;
+#[cfg(all(feature = "alloc", feature = "combinators", target_has_atomic = "ptr"))]
+mod waker_dispatch;
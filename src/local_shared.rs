@@ -0,0 +1,191 @@
+//! A [`Future`] that can be cloned and awaited by several tasks on the same thread, each getting a clone of the output.
+
+use crate::waker_set::WakerSet;
+use alloc::rc::Rc;
+use core::{
+	cell::RefCell,
+	future::Future,
+	mem,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Wraps `future` into a [`LocalShared`], which can be cloned to await it from several places.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::cell::Cell;
+/// use unpin_choices_dsa::{block_on::block_on, join2::join2, local_shared::local_shared};
+///
+/// let runs = Cell::new(0);
+/// let shared = local_shared(async {
+///     runs.set(runs.get() + 1);
+///     String::from("done")
+/// });
+///
+/// let (a, b) = block_on(join2(shared.clone(), shared));
+/// assert_eq!((a.as_str(), b.as_str()), ("done", "done"));
+/// assert_eq!(runs.get(), 1);
+/// ```
+pub fn local_shared<F: Future>(future: F) -> LocalShared<F>
+where
+	F::Output: Clone,
+{
+	LocalShared::new(future)
+}
+
+struct Inner<F: Future> {
+	/// Pinned, structurally.
+	state: RefCell<State<F>>,
+	/// The tasks of clones that are waiting for the output.
+	wakers: RefCell<WakerSet>,
+}
+
+enum State<F: Future> {
+	Pending(F),
+	Done(F::Output),
+}
+
+/// A [`Future`] that shares the output of an inner [`Future`] between all its clones.
+///
+/// Whichever clone is polled drives the inner [`Future`], with its own task's [`Waker`](`core::task::Waker`).
+/// Once it completes, all other waiting clones are woken to pick up a clone of the output.
+/// If a waiting clone is dropped, the others are woken too, so that one of them takes over.
+///
+/// This is [`Rc`]-based and so not [`Send`]. See [`Shared`](`crate::shared::Shared`) for a thread-safe version.
+///
+/// # Panics
+///
+/// Polling a clone from within the inner [`Future`] panics, since that can't make progress.
+pub struct LocalShared<F: Future> {
+	inner: Rc<Inner<F>>,
+	/// Whether this clone's task may be waiting in `inner.wakers`.
+	waiting: bool,
+}
+
+impl<F: Future> LocalShared<F>
+where
+	F::Output: Clone,
+{
+	/// Creates a new instance of [`LocalShared`] around `future`.
+	#[must_use]
+	pub fn new(future: F) -> Self {
+		Self {
+			inner: Rc::new(Inner {
+				state: RefCell::new(State::Pending(future)),
+				wakers: RefCell::new(WakerSet::new()),
+			}),
+			waiting: false,
+		}
+	}
+
+	/// A clone of the output, if the inner [`Future`] has completed.
+	///
+	/// # Panics
+	///
+	/// Iff called from within the inner [`Future`].
+	#[must_use]
+	pub fn peek(&self) -> Option<F::Output> {
+		match &*self.inner.state.borrow() {
+			State::Pending(_) => None,
+			State::Done(output) => Some(output.clone()),
+		}
+	}
+
+	/// The number of clones of this [`LocalShared`], including itself.
+	#[must_use]
+	pub fn clone_count(&self) -> usize {
+		Rc::strong_count(&self.inner)
+	}
+}
+
+impl<F: Future> Clone for LocalShared<F> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: Rc::clone(&self.inner),
+			waiting: false,
+		}
+	}
+}
+
+impl<F: Future> Unpin for LocalShared<F> {}
+
+impl<F: Future> Future for LocalShared<F>
+where
+	F::Output: Clone,
+{
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		let mut state = this
+			.inner
+			.state
+			.try_borrow_mut()
+			.expect("`LocalShared` was polled from within its inner `Future`.");
+
+		let output = match &mut *state {
+			State::Done(output) => return Poll::Ready(output.clone()),
+			State::Pending(future) => {
+				let future = unsafe {
+					//SAFETY: Structurally pinned, as `inner` is never moved out of the `Rc`.
+					Pin::new_unchecked(future)
+				};
+				match future.poll(cx) {
+					Poll::Ready(output) => output,
+					Poll::Pending => {
+						drop(state);
+						this.inner.wakers.borrow_mut().insert(cx.waker());
+						this.waiting = true;
+						return Poll::Pending;
+					}
+				}
+			}
+		};
+
+		// Drops the inner `Future` in place.
+		*state = State::Done(output.clone());
+		drop(state);
+		this.waiting = false;
+		// Taken first, so they're woken without holding the borrow.
+		let mut wakers = mem::take(&mut *this.inner.wakers.borrow_mut());
+		wakers.wake_all();
+		Poll::Ready(output)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for LocalShared<F>
+where
+	F::Output: Clone,
+{
+	/// Never, since each clone can be polled again to get another clone of the output.
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+impl<F: Future> Drop for LocalShared<F> {
+	fn drop(&mut self) {
+		if self.waiting {
+			// This clone's task may be the one the inner `Future` would wake, so another has to take over.
+			let mut wakers = mem::take(&mut *self.inner.wakers.borrow_mut());
+			wakers.wake_all();
+		}
+	}
+}
+
+/// Shows only the progress, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F: Future> core::fmt::Debug for LocalShared<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("LocalShared")
+			.field(
+				"done",
+				&matches!(self.inner.state.try_borrow().as_deref(), Ok(State::Done(_))),
+			)
+			.field("clone_count", &Rc::strong_count(&self.inner))
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,138 @@
+//! Macro front-ends to the combinators.
+//!
+//! > These are exported at the crate root, as is usual for `macro_rules!` macros.
+
+/// Races two to twelve [`Future`](`core::future::Future`)s with possibly different output types,
+/// mapping each output to a common type through its own closure.
+///
+/// This evaluates to a [`Future`](`core::future::Future`) that completes with the result of the first closure that runs.
+/// It doesn't allocate and doesn't need the `futures` crate.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// # async fn example(a: impl core::future::Future<Output = u8>, b: impl core::future::Future<Output = &'static str>) {
+/// use unpin_choices_dsa::any;
+///
+/// let message: String = any! {
+///     a => |number| format!("number: {}", number),
+///     b => |text| format!("text: {}", text),
+/// }
+/// .await;
+/// # }
+/// ```
+///
+/// > This is sugar for [`any_heterogeneous`](`crate::any_future::any_heterogeneous`) over [`Map`](`crate::map::Map`)ped [`Future`](`core::future::Future`)s,
+/// > followed by [`.into_inner()`](`crate::either::Either2::into_inner`) on the combined output.
+#[cfg(feature = "combinators")]
+#[macro_export]
+macro_rules! any {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$future:expr => $map:expr
+	),*$(,)?} => {
+		$crate::map::map(
+			$crate::any_future::any_heterogeneous(($($crate::map::map($future, $map),)*)),
+			|either| either.into_inner(),
+		)
+	};
+}
+
+/// Joins up to twelve [`Future`](`core::future::Future`)s with possibly different output types.
+///
+/// This evaluates to a [`JoinFuture`](`crate::join_future::JoinFuture`) that completes with a tuple of their outputs, in order.
+/// Awaiting it pins it (and with it the constituent [`Future`](`core::future::Future`)s) in place in the surrounding `async` block's state,
+/// so nothing is allocated.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// # async fn example(a: impl core::future::Future<Output = u8>, b: impl core::future::Future<Output = &'static str>) {
+/// use unpin_choices_dsa::join;
+///
+/// let (number, text) = join!(a, b).await;
+/// # }
+/// ```
+///
+/// > This is sugar for [`join`](`crate::join_future::join`) over a tuple,
+/// > but it reads more like the familiar macros from the `futures` crate.
+#[cfg(feature = "combinators")]
+#[macro_export]
+macro_rules! join {
+	($($future:expr),*$(,)?) => {
+		$crate::join_future::join(($($future,)*))
+	};
+}
+
+/// Waits for the first of several [`Future`](`core::future::Future`)s to complete and then runs the matching branch,
+/// optionally running a `default` branch instead if none of them is ready right away.
+///
+/// This can only be used inside `async` code, since it awaits the race in place.
+/// Branch bodies are plain expressions there, so they may `return`, `break` or use `?` as usual.
+/// The [`Future`](`core::future::Future`)s that didn't complete are dropped before the branch runs.
+///
+/// Each branch is written as `pattern = future => body`, where the pattern must be irrefutable.
+/// The branches must be separated by commas, even if their bodies are blocks,
+/// and the `default => body` branch, if any, must come last.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// # async fn example(a: impl core::future::Future<Output = u8>, b: impl core::future::Future<Output = Option<u8>>) -> Option<u8> {
+/// use unpin_choices_dsa::select;
+///
+/// let number = select! {
+///     number = a => number,
+///     other = b => other?,
+///     default => 0,
+/// };
+/// # Some(number)
+/// # }
+/// ```
+///
+/// > This nests [`any_heterogeneous`](`crate::any_future::any_heterogeneous`) pairwise, so that each branch corresponds to one
+/// > [`Either2`](`crate::either::Either2`) variant, and wraps the race in [`if_ready`](`crate::if_ready::if_ready`) for the `default` branch.
+/// > Counting the branches instead wouldn't be possible without a lot more macro machinery.
+#[cfg(feature = "combinators")]
+#[macro_export]
+macro_rules! select {
+	// Collects the branches into bracketed groups, one at a time.
+	(@parse [$($branches:tt)*] default => $default:expr $(,)?) => {
+		$crate::select!(@default [$($branches)*] $default)
+	};
+	(@parse [$($branches:tt)*] $pat:pat = $future:expr => $body:expr $(, $($rest:tt)*)?) => {
+		$crate::select!(@parse [$($branches)* [$pat, $future, $body]] $($($rest)*)?)
+	};
+	(@parse [$($branches:tt)+]) => {
+		$crate::select!(@match ($crate::select!(@race $($branches)+).await) $($branches)+)
+	};
+
+	(@default [$($branches:tt)+] $default:expr) => {
+		match $crate::if_ready::if_ready($crate::select!(@race $($branches)+)).await {
+			::core::option::Option::Some(output) => $crate::select!(@match (output) $($branches)+),
+			::core::option::Option::None => $default,
+		}
+	};
+
+	// Builds the race of the branch futures.
+	(@race [$pat:pat, $future:expr, $body:expr]) => {
+		$future
+	};
+	(@race [$pat:pat, $future:expr, $body:expr] $($rest:tt)+) => {
+		$crate::any_future::any_heterogeneous(($future, $crate::select!(@race $($rest)+)))
+	};
+
+	// Takes the race's output apart again.
+	(@match ($output:expr) [$pat:pat, $future:expr, $body:expr]) => {
+		match $output {
+			$pat => $body,
+		}
+	};
+	(@match ($output:expr) [$pat:pat, $future:expr, $body:expr] $($rest:tt)+) => {
+		match $output {
+			$crate::either::Either2::A($pat) => $body,
+			$crate::either::Either2::B(rest) => $crate::select!(@match (rest) $($rest)+),
+		}
+	};
+
+	($($input:tt)*) => {
+		$crate::select!(@parse [] $($input)*)
+	};
+}
@@ -0,0 +1,65 @@
+//! A [`Future`] adaptor that transforms the output of another.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Returns a [`Future`] that completes with the output of `future` passed through `f`.
+pub fn map<Fut: Future, F: FnOnce(Fut::Output) -> T, T>(future: Fut, f: F) -> Map<Fut, F> {
+	Map::new(future, f)
+}
+
+/// A [`Future`] that completes with the output of `future` passed through `f`.
+///
+/// [`AnyFuture::map`](`crate::any_future::AnyFuture::map`) and [`JoinFuture::map`](`crate::join_future::JoinFuture::map`) return this too.
+///
+/// > Taking the closure through a function with an `FnOnce` bound lets Rust infer its parameter type,
+/// > which is what makes this usable from macros like [`any!`](`crate::any!`).
+#[derive(Debug)]
+#[pin_project]
+pub struct Map<Fut, F> {
+	#[pin]
+	future: Fut,
+	/// [`None`] once completed.
+	f: Option<F>,
+}
+
+impl<Fut, F> Map<Fut, F> {
+	/// Creates a new instance of [`Map`] that transforms the output of `future` with `f`.
+	#[must_use]
+	pub fn new(future: Fut, f: F) -> Self {
+		Self { future, f: Some(f) }
+	}
+
+	/// Gives pinning exclusive access to the inner [`Future`].
+	#[must_use]
+	pub fn future_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Fut> {
+		self.project().future
+	}
+}
+
+impl<Fut: Future, F: FnOnce(Fut::Output) -> T, T> Future for Map<Fut, F> {
+	type Output = T;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		assert!(this.f.is_some(), "`Map` was previously completed.");
+		this.future.poll(cx).map(|output| {
+			let f = this.f.take().expect("unreachable");
+			f(output)
+		})
+	}
+}
+
+/// The closure is consumed on completion, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<Fut: Future, F: FnOnce(Fut::Output) -> T, T> FusedFuture for Map<Fut, F> {
+	fn is_terminated(&self) -> bool {
+		self.f.is_none()
+	}
+}
@@ -0,0 +1,104 @@
+//! A [`Future`] wrapper that holds on to its inner [`Future`]'s output after completion.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Wraps `future` in a [`MaybeDone`].
+pub fn maybe_done<F: Future>(future: F) -> MaybeDone<F> {
+	MaybeDone::new(future)
+}
+
+/// A [`Future`] that drives `F` to completion and then stores its output until it's taken.
+///
+/// Polling it completes with `()` once the output is available, and keeps doing so until the output is taken.
+/// This makes it a safe building block for join-style combinators:
+/// Poll each [`MaybeDone`] until all of them are ready, then [`.take_output()`](`MaybeDone::take_output`) from each.
+///
+/// > [`JoinFuture`](`crate::join_future::JoinFuture`) writes outputs into shared [`MaybeUninit`](`core::mem::MaybeUninit`) storage instead,
+/// > which avoids the per-future discriminant but needs `unsafe` to get right.
+#[pin_project(project = MaybeDoneProj, project_replace = MaybeDoneProjReplace)]
+#[derive(Debug)]
+pub enum MaybeDone<F: Future> {
+	/// The inner [`Future`] is still running.
+	Future(#[pin] F),
+	/// The inner [`Future`] completed with this output.
+	Done(F::Output),
+	/// The output was taken.
+	Gone,
+}
+
+impl<F: Future> MaybeDone<F> {
+	/// Creates a new instance of [`MaybeDone`] that drives `future`.
+	#[must_use]
+	pub fn new(future: F) -> Self {
+		Self::Future(future)
+	}
+
+	/// Whether the output is available.
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		matches!(self, Self::Done(_))
+	}
+
+	/// Whether the output was taken.
+	#[must_use]
+	pub fn is_gone(&self) -> bool {
+		matches!(self, Self::Gone)
+	}
+
+	/// Gives exclusive access to the output, if it's available.
+	#[must_use]
+	pub fn output_mut(self: Pin<&mut Self>) -> Option<&mut F::Output> {
+		match self.project() {
+			MaybeDoneProj::Done(output) => Some(output),
+			MaybeDoneProj::Future(_) | MaybeDoneProj::Gone => None,
+		}
+	}
+
+	/// Takes the output, if it's available.
+	///
+	/// [`None`] is returned if the inner [`Future`] hasn't completed yet, in which case it's left untouched,
+	/// or if the output was taken already.
+	#[must_use]
+	pub fn take_output(mut self: Pin<&mut Self>) -> Option<F::Output> {
+		if !self.is_done() {
+			return None;
+		}
+		match self.as_mut().project_replace(Self::Gone) {
+			MaybeDoneProjReplace::Done(output) => Some(output),
+			MaybeDoneProjReplace::Future(_) | MaybeDoneProjReplace::Gone => unreachable!(),
+		}
+	}
+}
+
+impl<F: Future> Future for MaybeDone<F> {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let output = match self.as_mut().project() {
+			MaybeDoneProj::Future(future) => match future.poll(cx) {
+				Poll::Ready(output) => output,
+				Poll::Pending => return Poll::Pending,
+			},
+			MaybeDoneProj::Done(_) => return Poll::Ready(()),
+			MaybeDoneProj::Gone => panic!("`MaybeDone`'s output was previously taken."),
+		};
+		// Drops the inner `Future` in place.
+		self.set(Self::Done(output));
+		Poll::Ready(())
+	}
+}
+
+/// Terminated once the output was taken, as polling panics then.
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for MaybeDone<F> {
+	fn is_terminated(&self) -> bool {
+		self.is_gone()
+	}
+}
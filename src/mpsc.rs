@@ -0,0 +1,507 @@
+//! A bounded multi-producer, single-consumer channel over a fixed-capacity ring buffer.
+//!
+//! Like with [`oneshot`](`crate::oneshot`), the channel state is either borrowed from a [`Channel`] the caller owns,
+//! which needs no allocation, or shared through an [`Arc`](`alloc::sync::Arc`) with [`channel`] (with the `"alloc"` feature).
+//! Waiting senders are tracked in intrusive lists inside their pinned [`Send`] [`Future`]s, so waiting doesn't allocate either.
+
+use crate::{
+	spin_lock::SpinLock,
+	state_ref::StateRef,
+	waiter_list::{Waiter, WaiterList},
+};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::{
+	fmt::{self, Display, Formatter},
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+#[cfg(feature = "stream")]
+use futures_core::{FusedStream, Stream};
+
+/// The error returned by [`Sender::try_send`], with the value that wasn't sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrySendError<T> {
+	/// The buffer is full.
+	Full(T),
+	/// The [`Receiver`] was dropped.
+	Closed(T),
+}
+
+impl<T> TrySendError<T> {
+	/// Takes back the value that wasn't sent.
+	pub fn into_inner(self) -> T {
+		match self {
+			TrySendError::Full(value) | TrySendError::Closed(value) => value,
+		}
+	}
+}
+
+impl<T> Display for TrySendError<T> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			TrySendError::Full(_) => "the channel is full",
+			TrySendError::Closed(_) => "the `Receiver` was dropped",
+		})
+	}
+}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TryRecvError {
+	/// The buffer is empty.
+	Empty,
+	/// The buffer is empty and all [`Sender`]s were dropped.
+	Closed,
+}
+
+impl Display for TryRecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			TryRecvError::Empty => "the channel is empty",
+			TryRecvError::Closed => "the channel is empty and all `Sender`s were dropped",
+		})
+	}
+}
+
+/// The state of a channel with room for `N` values, which [`Sender`]s and the [`Receiver`] borrow.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, join2::join2, mpsc::Channel};
+///
+/// let mut channel = Channel::<_, 2>::new();
+/// let (sender, mut receiver) = channel.split();
+///
+/// let produce = async move {
+///     for i in 0..5 {
+///         // Waits whenever the buffer is full.
+///         sender.send(i).await.unwrap();
+///     }
+/// };
+/// let consume = async {
+///     let mut sum = 0;
+///     while let Some(i) = receiver.recv().await {
+///         sum += i;
+///     }
+///     sum
+/// };
+/// assert_eq!(block_on(join2(produce, consume)).1, 10);
+/// ```
+pub struct Channel<T, const N: usize> {
+	state: SpinLock<State<T, N>>,
+}
+
+struct State<T, const N: usize> {
+	/// Entries `head..head + len` (wrapping) are [`Some`], the rest [`None`].
+	buffer: [Option<T>; N],
+	head: usize,
+	len: usize,
+	senders: usize,
+	receiver_alive: bool,
+	receiver: Option<Waker>,
+	send_waiters: WaiterList,
+}
+
+impl<T, const N: usize> State<T, N> {
+	fn push(&mut self, value: T) {
+		self.buffer[(self.head + self.len) % N] = Some(value);
+		self.len += 1;
+	}
+
+	fn pop(&mut self) -> Option<T> {
+		if self.len == 0 {
+			return None;
+		}
+		let value = self.buffer[self.head].take();
+		self.head = (self.head + 1) % N;
+		self.len -= 1;
+		value
+	}
+}
+
+impl<T, const N: usize> Channel<T, N> {
+	const NONE: Option<T> = None;
+
+	/// Creates a new empty [`Channel`].
+	///
+	/// > A capacity of `0` is allowed, but then nothing can be sent.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			state: SpinLock::new(State {
+				buffer: [Self::NONE; N],
+				head: 0,
+				len: 0,
+				senders: 0,
+				receiver_alive: true,
+				receiver: None,
+				send_waiters: WaiterList::new(),
+			}),
+		}
+	}
+
+	/// Resets this [`Channel`], dropping any buffered values, and splits it into a [`Sender`] and the [`Receiver`].
+	///
+	/// Clone the [`Sender`] for more producers.
+	pub fn split(&mut self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+		*self = Self::new();
+		self.state.lock().senders = 1;
+		let this = &*self;
+		(
+			Sender {
+				channel: StateRef::Borrowed(this),
+			},
+			Receiver {
+				channel: StateRef::Borrowed(this),
+			},
+		)
+	}
+}
+
+impl<T, const N: usize> Default for Channel<T, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only the number of buffered values, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Channel<T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Channel")
+			.field("len", &self.state.lock().len)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a heap-allocated channel with room for `N` values, whose halves can be moved anywhere independently.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn channel<T, const N: usize>() -> (Sender<'static, T, N>, Receiver<'static, T, N>) {
+	let channel = Arc::new(Channel::new());
+	channel.state.lock().senders = 1;
+	(
+		Sender {
+			channel: StateRef::Shared(Arc::clone(&channel)),
+		},
+		Receiver {
+			channel: StateRef::Shared(channel),
+		},
+	)
+}
+
+/// A sending half of a channel. Clone it for more producers.
+pub struct Sender<'a, T, const N: usize> {
+	channel: StateRef<'a, Channel<T, N>>,
+}
+
+impl<'a, T, const N: usize> Sender<'a, T, N> {
+	/// Sends `value` if there's room in the buffer right away, and wakes the [`Receiver`].
+	///
+	/// # Errors
+	///
+	/// Iff the buffer is full or the [`Receiver`] was dropped, in which case `value` is handed back.
+	pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+		let mut state = self.channel.state.lock();
+		if !state.receiver_alive {
+			Err(TrySendError::Closed(value))
+		} else if state.len == N {
+			Err(TrySendError::Full(value))
+		} else {
+			state.push(value);
+			let receiver = state.receiver.take();
+			drop(state);
+			if let Some(receiver) = receiver {
+				receiver.wake()
+			}
+			Ok(())
+		}
+	}
+
+	/// Returns a [`Future`] that sends `value` once there's room in the buffer.
+	///
+	/// It completes with `value` as error iff the [`Receiver`] is dropped first.
+	///
+	/// Waiting senders are served in order, but [`.try_send(…)`](`Sender::try_send`) may take freed space first.
+	#[must_use]
+	pub fn send(&self, value: T) -> Send<'_, 'a, T, N> {
+		Send {
+			sender: self,
+			value: Some(value),
+			waiter: Waiter::new(),
+			registered: false,
+		}
+	}
+
+	/// Whether the [`Receiver`] was dropped, so that sending would fail.
+	#[must_use]
+	pub fn is_closed(&self) -> bool {
+		!self.channel.state.lock().receiver_alive
+	}
+}
+
+impl<T, const N: usize> Clone for Sender<'_, T, N> {
+	fn clone(&self) -> Self {
+		self.channel.state.lock().senders += 1;
+		Self {
+			channel: self.channel.clone(),
+		}
+	}
+}
+
+impl<T, const N: usize> Drop for Sender<'_, T, N> {
+	fn drop(&mut self) {
+		let mut state = self.channel.state.lock();
+		state.senders -= 1;
+		let receiver = if state.senders == 0 {
+			state.receiver.take()
+		} else {
+			None
+		};
+		drop(state);
+		if let Some(receiver) = receiver {
+			receiver.wake()
+		}
+	}
+}
+
+/// Shows only whether it's closed, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Sender<'_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Sender")
+			.field("closed", &self.is_closed())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that sends a value once there's room in the buffer. See [`Sender::send`].
+pub struct Send<'s, 'a, T, const N: usize> {
+	sender: &'s Sender<'a, T, N>,
+	/// [`None`] once completed.
+	value: Option<T>,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked, to skip locking on drop otherwise.
+	registered: bool,
+}
+
+impl<T, const N: usize> Future for Send<'_, '_, T, N> {
+	type Output = Result<(), T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		let value = this.value.take().expect("`Send` was previously completed.");
+
+		let mut state = this.sender.channel.state.lock();
+		if state.receiver_alive && state.len == N {
+			unsafe {
+				//SAFETY: `waiter` is only used with this list, and removed on drop.
+				state.send_waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			this.value = Some(value);
+			return Poll::Pending;
+		}
+
+		if this.registered {
+			unsafe {
+				//SAFETY: As above.
+				state.send_waiters.remove(waiter);
+			}
+			this.registered = false;
+		}
+		if !state.receiver_alive {
+			return Poll::Ready(Err(value));
+		}
+
+		state.push(value);
+		// There may be room for the next waiting sender too.
+		let next = if state.len < N {
+			state.send_waiters.notify_one()
+		} else {
+			None
+		};
+		let receiver = state.receiver.take();
+		drop(state);
+		for waker in next.into_iter().chain(receiver) {
+			waker.wake()
+		}
+		Poll::Ready(Ok(()))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T, const N: usize> FusedFuture for Send<'_, '_, T, N> {
+	fn is_terminated(&self) -> bool {
+		self.value.is_none()
+	}
+}
+
+impl<T, const N: usize> Drop for Send<'_, '_, T, N> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let mut state = self.sender.channel.state.lock();
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				state.send_waiters.remove(waiter)
+			};
+			// Passes on the space this `Send` was woken for.
+			let next = if notified {
+				state.send_waiters.notify_one()
+			} else {
+				None
+			};
+			drop(state);
+			if let Some(next) = next {
+				next.wake()
+			}
+		}
+	}
+}
+
+/// Shows only the progress, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Send<'_, '_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Send")
+			.field("done", &self.value.is_none())
+			.finish_non_exhaustive()
+	}
+}
+
+/// The receiving half of a channel.
+///
+/// With the `"stream"` feature, it's also a [`Stream`] of the received values.
+pub struct Receiver<'a, T, const N: usize> {
+	channel: StateRef<'a, Channel<T, N>>,
+}
+
+impl<'a, T, const N: usize> Receiver<'a, T, N> {
+	/// Takes the next value without waiting, if there is one.
+	///
+	/// # Errors
+	///
+	/// [`TryRecvError::Empty`] if the buffer is empty, or [`TryRecvError::Closed`] if it will stay empty.
+	pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+		let mut state = self.channel.state.lock();
+		match state.pop() {
+			Some(value) => {
+				let next = state.send_waiters.notify_one();
+				drop(state);
+				if let Some(next) = next {
+					next.wake()
+				}
+				Ok(value)
+			}
+			None if state.senders == 0 => Err(TryRecvError::Closed),
+			None => Err(TryRecvError::Empty),
+		}
+	}
+
+	/// Takes the next value, or registers to be woken once there is one.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) iff the buffer is empty and all [`Sender`]s were dropped.
+	pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+		match self.try_recv() {
+			Ok(value) => Poll::Ready(Some(value)),
+			Err(TryRecvError::Closed) => Poll::Ready(None),
+			Err(TryRecvError::Empty) => {
+				let mut state = self.channel.state.lock();
+				// Checked again under the lock, in case a value arrived in between.
+				if state.len > 0 || state.senders == 0 {
+					drop(state);
+					return self.poll_recv(cx);
+				}
+				match &state.receiver {
+					Some(receiver) if receiver.will_wake(cx.waker()) => (),
+					_ => state.receiver = Some(cx.waker().clone()),
+				}
+				Poll::Pending
+			}
+		}
+	}
+
+	/// Returns a [`Future`] that completes with the next value,
+	/// or with [`None`] once the buffer is empty and all [`Sender`]s were dropped.
+	#[must_use]
+	pub fn recv(&mut self) -> Recv<'_, 'a, T, N> {
+		Recv(self)
+	}
+}
+
+impl<T, const N: usize> Drop for Receiver<'_, T, N> {
+	fn drop(&mut self) {
+		self.channel.state.lock().receiver_alive = false;
+
+		// Dropped one by one outside the lock, since that may run arbitrary code.
+		loop {
+			let value = self.channel.state.lock().pop();
+			if value.is_none() {
+				break;
+			}
+		}
+
+		// All waiting senders fail now.
+		while let Some(waker) = self.channel.state.lock().send_waiters.notify_one() {
+			waker.wake()
+		}
+	}
+}
+
+/// Ends once the buffer is empty and all [`Sender`]s were dropped.
+#[cfg(feature = "stream")]
+impl<T, const N: usize> Stream for Receiver<'_, T, N> {
+	type Item = T;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.get_mut().poll_recv(cx)
+	}
+}
+
+/// Can't tell whether it ended without locking, so this is always `false`.
+#[cfg(feature = "stream")]
+impl<T, const N: usize> FusedStream for Receiver<'_, T, N> {
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+/// Shows only the number of buffered values, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Receiver<'_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Receiver")
+			.field("len", &self.channel.state.lock().len)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes with the next value received. See [`Receiver::recv`].
+pub struct Recv<'r, 'a, T, const N: usize>(&'r mut Receiver<'a, T, N>);
+
+impl<T, const N: usize> Future for Recv<'_, '_, T, N> {
+	type Output = Option<T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.get_mut().0.poll_recv(cx)
+	}
+}
+
+/// Shows only the number of buffered values, to not require `T: Debug`.
+impl<T, const N: usize> core::fmt::Debug for Recv<'_, '_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("Recv").field(&self.0).finish()
+	}
+}
@@ -0,0 +1,373 @@
+//! An asynchronous mutex that queues waiting [`Future`]s intrusively, and can hand out [`Pin<&mut T>`].
+
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	cell::UnsafeCell,
+	future::Future,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// An asynchronous mutual exclusion lock around a `T`.
+///
+/// Waiting [`Lock`] [`Future`]s queue up in FIFO order without allocating,
+/// and the lock is handed directly to the next one when released, so none of them can starve.
+///
+/// If the [`Mutex`] is pinned, then `T` is too, and [`.lock_pinned()`](`Mutex::lock_pinned`) grants [`Pin<&mut T>`].
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, mutex::Mutex};
+///
+/// let mutex = Mutex::new(1);
+/// block_on(async {
+///     let mut guard = mutex.lock().await;
+///     *guard += 1;
+///     assert!(mutex.try_lock().is_none());
+/// });
+/// assert_eq!(mutex.into_inner(), 2);
+/// ```
+///
+/// > Unlike [`std::sync::Mutex`](https://doc.rust-lang.org/stable/std/sync/struct.Mutex.html), this isn't poisoned by panics.
+pub struct Mutex<T: ?Sized> {
+	state: SpinLock<State>,
+	/// Pinned, structurally.
+	value: UnsafeCell<T>,
+}
+
+struct State {
+	locked: bool,
+	waiters: WaiterList,
+}
+
+/// # Safety notes
+///
+/// Access to `value` is synchronised through `state`, so it's only ever accessed by one thread at a time.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+	/// Creates a new unlocked [`Mutex`] around `value`.
+	#[must_use]
+	pub const fn new(value: T) -> Self {
+		Self {
+			state: SpinLock::new(State {
+				locked: false,
+				waiters: WaiterList::new(),
+			}),
+			value: UnsafeCell::new(value),
+		}
+	}
+
+	/// Unwraps the value.
+	pub fn into_inner(self) -> T {
+		self.value.into_inner()
+	}
+}
+
+impl<T: ?Sized> Mutex<T> {
+	/// Returns a [`Future`] that completes with a [`MutexGuard`] once the lock is acquired.
+	#[must_use]
+	pub fn lock(&self) -> Lock<'_, T> {
+		Lock {
+			mutex: self,
+			waiter: Waiter::new(),
+			registered: false,
+			done: false,
+		}
+	}
+
+	/// Acquires the lock only if it's free and nobody is waiting for it.
+	#[must_use]
+	pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+		let mut state = self.state.lock();
+		(!state.locked).then(|| {
+			state.locked = true;
+			MutexGuard::new(self)
+		})
+	}
+
+	/// Like [`.lock()`](`Mutex::lock`), but the guard grants [`Pin<&mut T>`].
+	#[must_use]
+	pub fn lock_pinned(self: Pin<&Self>) -> LockPinned<'_, T> {
+		LockPinned {
+			lock: self.get_ref().lock(),
+		}
+	}
+
+	/// Like [`.try_lock()`](`Mutex::try_lock`), but the guard grants [`Pin<&mut T>`].
+	#[must_use]
+	pub fn try_lock_pinned(self: Pin<&Self>) -> Option<PinnedMutexGuard<'_, T>> {
+		self.get_ref()
+			.try_lock()
+			.map(|guard| PinnedMutexGuard { guard })
+	}
+
+	/// Accesses the value through an exclusive borrow, which needs no locking.
+	pub fn get_mut(&mut self) -> &mut T {
+		self.value.get_mut()
+	}
+
+	/// Accesses the pinned value through an exclusive borrow, which needs no locking.
+	#[must_use]
+	pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked_mut(|this| this.value.get_mut())
+		}
+	}
+
+	/// Hands the lock to the next waiting [`Lock`], or releases it if there is none.
+	fn unlock(&self) {
+		let mut state = self.state.lock();
+		let next = state.waiters.notify_one();
+		if next.is_none() {
+			state.locked = false;
+		}
+		drop(state);
+		if let Some(next) = next {
+			next.wake()
+		}
+	}
+}
+
+impl<T: Default> Default for Mutex<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+/// Shows only whether it's locked, to not require `T: Debug` and to not wait for the lock.
+impl<T: ?Sized> core::fmt::Debug for Mutex<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Mutex")
+			.field("locked", &self.state.lock().locked)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes with a [`MutexGuard`] once the lock is acquired. See [`Mutex::lock`].
+pub struct Lock<'a, T: ?Sized> {
+	mutex: &'a Mutex<T>,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked or handed the lock, to skip locking on drop otherwise.
+	registered: bool,
+	done: bool,
+}
+
+impl<'a, T: ?Sized> Future for Lock<'a, T> {
+	type Output = MutexGuard<'a, T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		assert!(!this.done, "`Lock` was previously completed.");
+
+		let mut state = this.mutex.state.lock();
+		let acquired = if this.registered {
+			unsafe {
+				//SAFETY: `waiter` is only used with this list, and removed on drop.
+				state.waiters.is_notified(waiter)
+			}
+		} else if state.locked {
+			false
+		} else {
+			state.locked = true;
+			true
+		};
+
+		if acquired {
+			if this.registered {
+				unsafe {
+					//SAFETY: As above.
+					state.waiters.remove(waiter);
+				}
+				this.registered = false;
+			}
+			this.done = true;
+			Poll::Ready(MutexGuard::new(this.mutex))
+		} else {
+			unsafe {
+				//SAFETY: As above. This keeps the place in the queue if already linked.
+				state.waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T: ?Sized> FusedFuture for Lock<'_, T> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+impl<T: ?Sized> Drop for Lock<'_, T> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				self.mutex.state.lock().waiters.remove(waiter)
+			};
+			// The lock was handed to this `Lock`, so it's passed on.
+			if notified {
+				self.mutex.unlock()
+			}
+		}
+	}
+}
+
+/// Shows only the progress, to not require `T: Debug`.
+impl<T: ?Sized> core::fmt::Debug for Lock<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Lock")
+			.field("waiting", &self.registered)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes with a [`PinnedMutexGuard`] once the lock is acquired. See [`Mutex::lock_pinned`].
+pub struct LockPinned<'a, T: ?Sized> {
+	/// Pinned, structurally.
+	lock: Lock<'a, T>,
+}
+
+impl<'a, T: ?Sized> Future for LockPinned<'a, T> {
+	type Output = PinnedMutexGuard<'a, T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let lock = unsafe {
+			//SAFETY: Structurally pinned.
+			self.map_unchecked_mut(|this| &mut this.lock)
+		};
+		lock.poll(cx).map(|guard| PinnedMutexGuard { guard })
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T: ?Sized> FusedFuture for LockPinned<'_, T> {
+	fn is_terminated(&self) -> bool {
+		self.lock.done
+	}
+}
+
+/// Shows only the progress, to not require `T: Debug`.
+impl<T: ?Sized> core::fmt::Debug for LockPinned<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("LockPinned")
+			.field("waiting", &self.lock.registered)
+			.field("done", &self.lock.done)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Exclusive access to the value in a [`Mutex`], until dropped.
+///
+/// It dereferences mutably only if `T: Unpin`, since the [`Mutex`] may be pinned.
+/// Use [`Mutex::lock_pinned`] to mutate a `T` that isn't.
+pub struct MutexGuard<'a, T: ?Sized> {
+	mutex: &'a Mutex<T>,
+	/// Makes this [`Send`] and [`Sync`] only as far as a `&mut T` would be.
+	_value: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: ?Sized> MutexGuard<'a, T> {
+	fn new(mutex: &'a Mutex<T>) -> Self {
+		Self {
+			mutex,
+			_value: PhantomData,
+		}
+	}
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe {
+			//SAFETY: The lock is held.
+			&*self.mutex.value.get()
+		}
+	}
+}
+
+impl<T: ?Sized + Unpin> DerefMut for MutexGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		unsafe {
+			//SAFETY: The lock is held exclusively, and `T` isn't pinned.
+			&mut *self.mutex.value.get()
+		}
+	}
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+	fn drop(&mut self) {
+		self.mutex.unlock()
+	}
+}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for MutexGuard<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
+
+/// Exclusive access to the pinned value in a [`Mutex`], until dropped.
+///
+/// It dereferences mutably only if `T: Unpin`. Otherwise, use [`.as_mut()`](`PinnedMutexGuard::as_mut`).
+pub struct PinnedMutexGuard<'a, T: ?Sized> {
+	guard: MutexGuard<'a, T>,
+}
+
+impl<T: ?Sized> PinnedMutexGuard<'_, T> {
+	/// Grants pinned exclusive access to the value.
+	#[must_use]
+	pub fn as_mut(&mut self) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: The lock is held exclusively,
+			// and this guard can only be created through a pinned `Mutex`, in which `value` is structurally pinned.
+			Pin::new_unchecked(&mut *self.guard.mutex.value.get())
+		}
+	}
+}
+
+impl<T: ?Sized> Deref for PinnedMutexGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.guard
+	}
+}
+
+impl<T: ?Sized + Unpin> DerefMut for PinnedMutexGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.guard
+	}
+}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for PinnedMutexGuard<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
@@ -0,0 +1,234 @@
+//! A signal that wakes one or all waiting tasks, without carrying data.
+
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	future::Future,
+	mem,
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Wakes waiting [`Notified`] [`Future`]s, one at a time or all at once.
+///
+/// [`.notify_one()`](`Notify::notify_one`) without any waiters stores a single permit,
+/// which the next [`Notified`] consumes to complete right away.
+/// That way, a notification sent just before a task starts waiting isn't lost.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, notify::Notify};
+///
+/// let notify = Notify::new();
+///
+/// notify.notify_one();
+/// notify.notify_one(); // There's only ever one permit.
+/// block_on(notify.notified());
+///
+/// let notified = notify.notified();
+/// notify.notify_all();
+/// block_on(notified);
+/// ```
+///
+/// > Waiters are queued intrusively, so this needs no allocation.
+pub struct Notify {
+	state: SpinLock<State>,
+}
+
+struct State {
+	permit: bool,
+	/// Incremented by each [`Notify::notify_all`]. [`Notified`] instances store it as their [`Waiter`]'s demand.
+	generation: usize,
+	waiters: WaiterList,
+}
+
+impl State {
+	/// Notifies the front waiter that wasn't notified by [`Notify::notify_all`] already, or else stores the permit.
+	fn notify_one(&mut self) -> Option<Waker> {
+		let generation = self.generation;
+		let waker = self.waiters.notify_first(|demand| demand == generation);
+		if waker.is_none() {
+			self.permit = true;
+		}
+		waker
+	}
+}
+
+impl Notify {
+	/// Creates a new [`Notify`] without a stored permit.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			state: SpinLock::new(State {
+				permit: false,
+				generation: 0,
+				waiters: WaiterList::new(),
+			}),
+		}
+	}
+
+	/// Returns a [`Future`] that completes once notified.
+	///
+	/// It's notified by any [`.notify_all()`](`Notify::notify_all`) from now on, even before it's polled,
+	/// but only receives [`.notify_one()`](`Notify::notify_one`) notifications or consumes a permit once polled.
+	#[must_use]
+	pub fn notified(&self) -> Notified<'_> {
+		let generation = self.state.lock().generation;
+		Notified {
+			notify: self,
+			generation,
+			waiter: Waiter::with_demand(generation),
+			registered: false,
+			done: false,
+		}
+	}
+
+	/// Wakes the longest-waiting [`Notified`], or stores a permit if none are waiting.
+	pub fn notify_one(&self) {
+		let waker = self.state.lock().notify_one();
+		if let Some(waker) = waker {
+			waker.wake()
+		}
+	}
+
+	/// Wakes all current [`Notified`] instances, without storing a permit.
+	pub fn notify_all(&self) {
+		let mut state = self.state.lock();
+		state.generation = state.generation.wrapping_add(1);
+		drop(state);
+
+		// Each is woken outside the lock. Those created in the meantime have the new generation and stay queued.
+		loop {
+			let mut state = self.state.lock();
+			let generation = state.generation;
+			let waker = state.waiters.notify_first(|demand| demand != generation);
+			drop(state);
+			match waker {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+}
+
+impl Default for Notify {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl core::fmt::Debug for Notify {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let state = self.state.lock();
+		let (permit, waiting) = (state.permit, state.waiters.len());
+		drop(state);
+		f.debug_struct("Notify")
+			.field("permit", &permit)
+			.field("waiting", &waiting)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes once notified. See [`Notify::notified`].
+pub struct Notified<'a> {
+	notify: &'a Notify,
+	/// The [`Notify::notify_all`] generation this was created in.
+	generation: usize,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked or was notified, to skip locking on drop otherwise.
+	registered: bool,
+	done: bool,
+}
+
+impl Future for Notified<'_> {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		assert!(!this.done, "`Notified` was previously completed.");
+
+		let mut state = this.notify.state.lock();
+		let notified = state.generation != this.generation
+			|| if this.registered {
+				unsafe {
+					//SAFETY: `waiter` is only used with this list, and removed on drop.
+					state.waiters.is_notified(waiter)
+				}
+			} else {
+				mem::take(&mut state.permit)
+			};
+
+		if notified {
+			if this.registered {
+				unsafe {
+					//SAFETY: As above.
+					state.waiters.remove(waiter);
+				}
+				this.registered = false;
+			}
+			this.done = true;
+			Poll::Ready(())
+		} else {
+			unsafe {
+				//SAFETY: As above. This keeps the place in the queue if already linked.
+				state.waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl FusedFuture for Notified<'_> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+impl Drop for Notified<'_> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let mut state = self.notify.state.lock();
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				state.waiters.remove(waiter)
+			};
+			// Passes on a `notify_one` notification, but not one from `notify_all`.
+			let next = if notified && state.generation == self.generation {
+				state.notify_one()
+			} else {
+				None
+			};
+			drop(state);
+			if let Some(next) = next {
+				next.wake()
+			}
+		}
+	}
+}
+
+impl core::fmt::Debug for Notified<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Notified")
+			.field("waiting", &self.registered)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
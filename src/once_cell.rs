@@ -0,0 +1,402 @@
+//! A cell that is initialised at most once, asynchronously, and keeps its value pinned in place.
+
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	cell::UnsafeCell,
+	future::Future,
+	mem::MaybeUninit,
+	pin::Pin,
+	sync::atomic::{AtomicBool, Ordering},
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// A cell that is set at most once, either directly or by the first of any number of concurrent asynchronous initialisers.
+///
+/// While one [`GetOrInit`] runs its initialiser, the others wait for it instead of running theirs.
+/// If it's dropped before finishing, the next waiting one takes over with its own initialiser.
+///
+/// Once set, the value is never moved again until the [`OnceCell`] is dropped or taken apart through an exclusive reference,
+/// so a pinned [`OnceCell`] hands out [`Pin<&T>`] through [`.get_pin()`](`OnceCell::get_pin`)
+/// and [`.get_or_init_pinned(…)`](`OnceCell::get_or_init_pinned`).
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, join_future::join, once_cell::OnceCell};
+///
+/// let cell = OnceCell::new();
+/// let (a, b) = block_on(join((
+///     cell.get_or_init(|| async { 1 }),
+///     cell.get_or_init(|| async { unreachable!() }),
+/// )));
+/// assert_eq!((a, b), (&1, &1));
+/// assert_eq!(cell.set(2), Err(2));
+/// ```
+pub struct OnceCell<T> {
+	/// Set (while `state` is locked) once `value` is initialised.
+	ready: AtomicBool,
+	state: SpinLock<State>,
+	/// Pinned, structurally.
+	value: UnsafeCell<MaybeUninit<T>>,
+}
+
+struct State {
+	/// Whether a [`GetOrInit`] is running its initialiser.
+	initializing: bool,
+	waiters: WaiterList,
+}
+
+/// # Safety notes
+///
+/// `value` is written only once, before `ready` is released, and shared afterwards.
+unsafe impl<T: Send> Send for OnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+	/// Creates a new empty [`OnceCell`].
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			ready: AtomicBool::new(false),
+			state: SpinLock::new(State {
+				initializing: false,
+				waiters: WaiterList::new(),
+			}),
+			value: UnsafeCell::new(MaybeUninit::uninit()),
+		}
+	}
+
+	/// The value, if set.
+	#[must_use]
+	pub fn get(&self) -> Option<&T> {
+		self.ready.load(Ordering::Acquire).then(|| unsafe {
+			//SAFETY: Initialised before `ready` was released, and never written again.
+			(*self.value.get()).assume_init_ref()
+		})
+	}
+
+	/// The pinned value, if set.
+	#[must_use]
+	pub fn get_pin(self: Pin<&Self>) -> Option<Pin<&T>> {
+		self.get_ref().get().map(|value| unsafe {
+			//SAFETY: `value` is structurally pinned.
+			Pin::new_unchecked(value)
+		})
+	}
+
+	/// Exclusive access to the value, if set.
+	pub fn get_mut(&mut self) -> Option<&mut T> {
+		(*self.ready.get_mut()).then(|| unsafe {
+			//SAFETY: Initialised, and the borrow is exclusive.
+			self.value.get_mut().assume_init_mut()
+		})
+	}
+
+	/// Sets the value, unless it's set or being initialised already.
+	///
+	/// # Errors
+	///
+	/// Iff the value is set or being initialised already, in which case `value` is returned.
+	pub fn set(&self, value: T) -> Result<(), T> {
+		let state = self.state.lock();
+		if state.initializing || self.ready.load(Ordering::Relaxed) {
+			return Err(value);
+		}
+		unsafe {
+			//SAFETY: Nobody else writes or reads `value` while it's not ready and not being initialised.
+			(*self.value.get()).write(value);
+		}
+		self.ready.store(true, Ordering::Release);
+		drop(state);
+		Ok(())
+	}
+
+	/// Returns a [`Future`] that completes with a reference to the value,
+	/// setting it by calling `init` and awaiting the result first unless some other initialiser gets to it first.
+	///
+	/// See [`OnceCell`] for how concurrent initialisers interact.
+	pub fn get_or_init<F, Fut>(&self, init: F) -> GetOrInit<'_, T, F, Fut>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = T>,
+	{
+		GetOrInit {
+			cell: self,
+			init: Some(init),
+			future: None,
+			waiter: Waiter::new(),
+			registered: false,
+			done: false,
+		}
+	}
+
+	/// Like [`.get_or_init(…)`](`OnceCell::get_or_init`), but completes with [`Pin<&T>`].
+	pub fn get_or_init_pinned<F, Fut>(self: Pin<&Self>, init: F) -> GetOrInitPinned<'_, T, F, Fut>
+	where
+		F: FnOnce() -> Fut,
+		Fut: Future<Output = T>,
+	{
+		GetOrInitPinned {
+			get_or_init: self.get_ref().get_or_init(init),
+		}
+	}
+
+	/// Takes the value out, leaving this [`OnceCell`] empty.
+	pub fn take(&mut self) -> Option<T> {
+		let ready = self.ready.get_mut();
+		(*ready).then(|| {
+			*ready = false;
+			unsafe {
+				//SAFETY: Initialised, and now marked as not.
+				self.value.get_mut().assume_init_read()
+			}
+		})
+	}
+
+	/// Unwraps the value, if set.
+	pub fn into_inner(mut self) -> Option<T> {
+		self.take()
+	}
+
+	/// Publishes `value` as set by the running initialiser, and wakes all waiters.
+	fn complete(&self, value: T) {
+		let state = self.state.lock();
+		unsafe {
+			//SAFETY: Only the running initialiser writes `value`, and nobody reads it before it's ready.
+			(*self.value.get()).write(value);
+		}
+		self.ready.store(true, Ordering::Release);
+		drop(state);
+
+		// Nobody registers once the value is ready.
+		loop {
+			let waker = self.state.lock().waiters.notify_one();
+			match waker {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+
+	/// Lets the next waiter take over after an initialiser was cancelled.
+	fn abandon(&self) {
+		let mut state = self.state.lock();
+		state.initializing = false;
+		let next = state.waiters.notify_one();
+		drop(state);
+		if let Some(next) = next {
+			next.wake()
+		}
+	}
+}
+
+impl<T> Default for OnceCell<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Drop for OnceCell<T> {
+	fn drop(&mut self) {
+		if *self.ready.get_mut() {
+			unsafe {
+				//SAFETY: Initialised, and dropped in place as it may be pinned.
+				self.value.get_mut().assume_init_drop()
+			}
+		}
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for OnceCell<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_tuple("OnceCell").field(&self.get()).finish()
+	}
+}
+
+/// A [`Future`] that completes with a reference to the value of a [`OnceCell`], initialising it if necessary.
+/// See [`OnceCell::get_or_init`].
+pub struct GetOrInit<'a, T, F, Fut> {
+	cell: &'a OnceCell<T>,
+	/// Taken when this starts initialising.
+	init: Option<F>,
+	/// [`Some`] while this is initialising. Pinned, structurally.
+	future: Option<Fut>,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked or was notified, to skip locking on drop otherwise.
+	registered: bool,
+	done: bool,
+}
+
+impl<'a, T, F, Fut> Future for GetOrInit<'a, T, F, Fut>
+where
+	F: FnOnce() -> Fut,
+	Fut: Future<Output = T>,
+{
+	type Output = &'a T;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: `future` and `waiter` are structurally pinned and not moved. `future` is only dropped in place.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		assert!(!this.done, "`GetOrInit` was previously completed.");
+
+		if this.future.is_none() {
+			if let Some(value) = this.cell.get() {
+				this.done = true;
+				return Poll::Ready(value);
+			}
+
+			let mut state = this.cell.state.lock();
+			let ready = this.cell.ready.load(Ordering::Relaxed);
+			if ready || !state.initializing {
+				if this.registered {
+					unsafe {
+						//SAFETY: `waiter` is only used with this list, and removed on drop.
+						state.waiters.remove(waiter);
+					}
+					this.registered = false;
+				}
+				if ready {
+					drop(state);
+					this.done = true;
+					return Poll::Ready(this.cell.get().expect("unreachable"));
+				}
+				state.initializing = true;
+				drop(state);
+				let init = this.init.take().expect("unreachable");
+				this.future = Some(init());
+			} else {
+				unsafe {
+					//SAFETY: As above. This keeps the place in the queue if already linked.
+					state.waiters.register(waiter, cx.waker());
+				}
+				this.registered = true;
+				return Poll::Pending;
+			}
+		}
+
+		let future = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(this.future.as_mut().expect("unreachable"))
+		};
+		match future.poll(cx) {
+			Poll::Pending => Poll::Pending,
+			Poll::Ready(value) => {
+				this.future = None;
+				this.cell.complete(value);
+				this.done = true;
+				Poll::Ready(this.cell.get().expect("unreachable"))
+			}
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T, F, Fut> FusedFuture for GetOrInit<'_, T, F, Fut>
+where
+	F: FnOnce() -> Fut,
+	Fut: Future<Output = T>,
+{
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+impl<T, F, Fut> Drop for GetOrInit<'_, T, F, Fut> {
+	fn drop(&mut self) {
+		if self.future.is_some() {
+			// Cancelled while initialising. The initialiser is dropped first, in place.
+			self.future = None;
+			self.cell.abandon();
+		} else if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let mut state = self.cell.state.lock();
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				state.waiters.remove(waiter)
+			};
+			// Passes on the chance to take over initialising.
+			let next = if notified && !state.initializing {
+				state.waiters.notify_one()
+			} else {
+				None
+			};
+			drop(state);
+			if let Some(next) = next {
+				next.wake()
+			}
+		}
+	}
+}
+
+/// Shows only the progress, since `F` and `Fut` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T, F, Fut> core::fmt::Debug for GetOrInit<'_, T, F, Fut> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("GetOrInit")
+			.field("initializing", &self.future.is_some())
+			.field("waiting", &self.registered)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes with a pinned reference to the value of a [`OnceCell`], initialising it if necessary.
+/// See [`OnceCell::get_or_init_pinned`].
+pub struct GetOrInitPinned<'a, T, F, Fut> {
+	/// Pinned, structurally.
+	get_or_init: GetOrInit<'a, T, F, Fut>,
+}
+
+impl<'a, T, F, Fut> Future for GetOrInitPinned<'a, T, F, Fut>
+where
+	F: FnOnce() -> Fut,
+	Fut: Future<Output = T>,
+{
+	type Output = Pin<&'a T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let get_or_init = unsafe {
+			//SAFETY: Structurally pinned.
+			self.map_unchecked_mut(|this| &mut this.get_or_init)
+		};
+		get_or_init.poll(cx).map(|value| unsafe {
+			//SAFETY: This can only be created through a pinned `OnceCell`, in which `value` is structurally pinned.
+			Pin::new_unchecked(value)
+		})
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T, F, Fut> FusedFuture for GetOrInitPinned<'_, T, F, Fut>
+where
+	F: FnOnce() -> Fut,
+	Fut: Future<Output = T>,
+{
+	fn is_terminated(&self) -> bool {
+		self.get_or_init.done
+	}
+}
+
+/// Shows only the progress, since `F` and `Fut` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T, F, Fut> core::fmt::Debug for GetOrInitPinned<'_, T, F, Fut> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("GetOrInitPinned")
+			.field("initializing", &self.get_or_init.future.is_some())
+			.field("waiting", &self.get_or_init.registered)
+			.field("done", &self.get_or_init.done)
+			.finish_non_exhaustive()
+	}
+}
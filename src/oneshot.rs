@@ -0,0 +1,294 @@
+//! A channel for sending a single value from one task to another.
+//!
+//! The channel state is either borrowed from a [`Oneshot`] the caller owns, which needs no allocation,
+//! or shared through an [`Arc`](`alloc::sync::Arc`) with [`channel`] (with the `"alloc"` feature).
+
+use crate::{atomic_waker::AtomicWaker, state_ref::StateRef};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::{
+	cell::UnsafeCell,
+	fmt::{self, Display, Formatter},
+	future::Future,
+	pin::Pin,
+	sync::atomic::{AtomicU8, Ordering},
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// The error returned by [`Receiver`] if the [`Sender`] was dropped without sending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RecvError;
+
+impl Display for RecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("the `Sender` was dropped without sending")
+	}
+}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TryRecvError {
+	/// No value was sent yet.
+	Empty,
+	/// The [`Sender`] was dropped without sending.
+	Closed,
+}
+
+impl Display for TryRecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			TryRecvError::Empty => "no value was sent yet",
+			TryRecvError::Closed => "the `Sender` was dropped without sending",
+		})
+	}
+}
+
+const SENT: u8 = 0b001;
+const SENDER_DROPPED: u8 = 0b010;
+const RECEIVER_DROPPED: u8 = 0b100;
+
+/// The state of a oneshot channel, which [`Sender`] and [`Receiver`] borrow.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, oneshot::Oneshot};
+///
+/// let mut oneshot = Oneshot::new();
+/// let (sender, receiver) = oneshot.split();
+///
+/// sender.send("response").unwrap();
+/// assert_eq!(block_on(receiver), Ok("response"));
+/// ```
+pub struct Oneshot<T> {
+	/// Whichever side sets the second of [`SENT`] and [`RECEIVER_DROPPED`] is responsible for `value`.
+	state: AtomicU8,
+	/// Written once by the [`Sender`] before setting [`SENT`].
+	value: UnsafeCell<Option<T>>,
+	receiver: AtomicWaker,
+}
+
+/// # Safety notes
+///
+/// Access to `value` is synchronised through `state`.
+unsafe impl<T: Send> Sync for Oneshot<T> {}
+
+impl<T> Oneshot<T> {
+	/// Creates a new instance of [`Oneshot`] without a value.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			state: AtomicU8::new(0),
+			value: UnsafeCell::new(None),
+			receiver: AtomicWaker::new(),
+		}
+	}
+
+	/// Resets this [`Oneshot`] and splits it into its two borrowing halves.
+	pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
+		*self = Self::new();
+		let this = &*self;
+		(
+			Sender {
+				oneshot: StateRef::Borrowed(this),
+			},
+			Receiver {
+				oneshot: StateRef::Borrowed(this),
+				done: false,
+			},
+		)
+	}
+}
+
+impl<T> Default for Oneshot<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only the state, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Oneshot<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Oneshot")
+			.field("state", &self.state.load(Ordering::Acquire))
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a heap-allocated oneshot channel, whose halves can be moved anywhere independently.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, oneshot};
+///
+/// let (sender, receiver) = oneshot::channel();
+/// std::thread::spawn(move || sender.send(1 + 1).unwrap());
+/// assert_eq!(block_on(receiver), Ok(2));
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn channel<T>() -> (Sender<'static, T>, Receiver<'static, T>) {
+	let oneshot = Arc::new(Oneshot::new());
+	(
+		Sender {
+			oneshot: StateRef::Shared(Arc::clone(&oneshot)),
+		},
+		Receiver {
+			oneshot: StateRef::Shared(oneshot),
+			done: false,
+		},
+	)
+}
+
+/// The sending half of a oneshot channel.
+pub struct Sender<'a, T> {
+	oneshot: StateRef<'a, Oneshot<T>>,
+}
+
+impl<T> Sender<'_, T> {
+	/// Sends `value` and wakes the [`Receiver`].
+	///
+	/// # Errors
+	///
+	/// Iff the [`Receiver`] was dropped, in which case `value` is handed back.
+	pub fn send(self, value: T) -> Result<(), T> {
+		let oneshot = &*self.oneshot;
+		if oneshot.state.load(Ordering::Acquire) & RECEIVER_DROPPED != 0 {
+			return Err(value);
+		}
+
+		unsafe {
+			//SAFETY: Only the `Sender` writes, and only before setting `SENT`.
+			*oneshot.value.get() = Some(value);
+		}
+		let state = oneshot.state.fetch_or(SENT, Ordering::AcqRel);
+		if state & RECEIVER_DROPPED != 0 {
+			// The `Receiver` is gone and won't look at `value`.
+			return match unsafe {
+				//SAFETY: Set the second flag, so responsible for `value`.
+				(*oneshot.value.get()).take()
+			} {
+				Some(value) => Err(value),
+				None => unreachable!(),
+			};
+		}
+
+		oneshot.receiver.wake();
+		// Dropping `self` now sets `SENDER_DROPPED` too, which `SENT` takes precedence over.
+		Ok(())
+	}
+
+	/// Whether the [`Receiver`] was dropped, so that sending would fail.
+	#[must_use]
+	pub fn is_closed(&self) -> bool {
+		self.oneshot.state.load(Ordering::Acquire) & RECEIVER_DROPPED != 0
+	}
+}
+
+impl<T> Drop for Sender<'_, T> {
+	fn drop(&mut self) {
+		self.oneshot
+			.state
+			.fetch_or(SENDER_DROPPED, Ordering::AcqRel);
+		self.oneshot.receiver.wake();
+	}
+}
+
+/// Shows only whether it's closed, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Sender<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Sender")
+			.field("closed", &self.is_closed())
+			.finish_non_exhaustive()
+	}
+}
+
+/// The receiving half of a oneshot channel, which is a [`Future`] that completes with the sent value.
+pub struct Receiver<'a, T> {
+	oneshot: StateRef<'a, Oneshot<T>>,
+	/// Whether the value was taken or the [`Sender`] found dropped.
+	done: bool,
+}
+
+impl<T> Receiver<'_, T> {
+	/// Takes the value without waiting, if it was sent.
+	///
+	/// # Errors
+	///
+	/// [`TryRecvError::Empty`] if there's no value yet, or [`TryRecvError::Closed`] if there won't be one
+	/// (which includes it having been received before).
+	pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+		if self.done {
+			return Err(TryRecvError::Closed);
+		}
+
+		let state = self.oneshot.state.load(Ordering::Acquire);
+		if state & SENT != 0 {
+			self.done = true;
+			match unsafe {
+				//SAFETY: Written before `SENT` was released, and the `Sender` is done with it.
+				(*self.oneshot.value.get()).take()
+			} {
+				Some(value) => Ok(value),
+				None => unreachable!(),
+			}
+		} else if state & SENDER_DROPPED != 0 {
+			self.done = true;
+			Err(TryRecvError::Closed)
+		} else {
+			Err(TryRecvError::Empty)
+		}
+	}
+}
+
+impl<T> Unpin for Receiver<'_, T> {}
+
+impl<T> Future for Receiver<'_, T> {
+	type Output = Result<T, RecvError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		assert!(!this.done, "`Receiver` was previously completed.");
+
+		// Registered first, so that a send after the check below isn't missed.
+		this.oneshot.receiver.register(cx.waker());
+		match this.try_recv() {
+			Ok(value) => Poll::Ready(Ok(value)),
+			Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError)),
+			Err(TryRecvError::Empty) => Poll::Pending,
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> FusedFuture for Receiver<'_, T> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+impl<T> Drop for Receiver<'_, T> {
+	fn drop(&mut self) {
+		let state = self
+			.oneshot
+			.state
+			.fetch_or(RECEIVER_DROPPED, Ordering::AcqRel);
+		if state & SENT != 0 {
+			// Dropped here rather than with the `Oneshot`, which may live much longer.
+			drop(unsafe {
+				//SAFETY: Set the second flag, so responsible for `value`.
+				(*self.oneshot.value.get()).take()
+			});
+		}
+	}
+}
+
+/// Shows only the progress, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Receiver<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Receiver")
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,142 @@
+//! An arena that hands out pinned references to its values, which are all dropped together.
+
+use alloc::vec::Vec;
+use core::{cell::RefCell, mem, pin::Pin};
+
+/// The capacity of the first chunk, if none was given. Each further chunk is twice as large as the previous one.
+const DEFAULT_CAPACITY: usize = 8;
+
+/// An allocator for values of type `T` that are pinned for as long as the arena is borrowed.
+///
+/// Values can't be removed individually. They are dropped in place, together, when the arena is dropped.
+///
+/// Since [`.alloc(…)`](`PinnedArena::alloc`) only needs a shared reference,
+/// values can point at each other with the arena's lifetime, even cyclically:
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::{cell::Cell, marker::PhantomPinned};
+/// use unpin_choices_dsa::pinned_arena::PinnedArena;
+///
+/// struct Node<'a> {
+///     value: u32,
+///     next: Cell<Option<&'a Node<'a>>>,
+///     _pinned: PhantomPinned,
+/// }
+///
+/// let arena = PinnedArena::new();
+/// let new_node = |value| {
+///     arena
+///         .alloc(Node { value, next: Cell::new(None), _pinned: PhantomPinned })
+///         .into_ref()
+///         .get_ref()
+/// };
+/// let (a, b) = (new_node(1), new_node(2));
+/// a.next.set(Some(b));
+/// b.next.set(Some(a));
+///
+/// assert_eq!(a.next.get().unwrap().next.get().unwrap().value, 1);
+/// assert_eq!(arena.len(), 2);
+/// ```
+///
+/// > There's no [`Drop`] implementation on this type itself, so that the standard library's
+/// > [`Vec`] drop check exemption applies and values may reference each other like that.
+pub struct PinnedArena<T> {
+	chunks: RefCell<Chunks<T>>,
+}
+
+struct Chunks<T> {
+	/// Never pushed to beyond its capacity, so that it isn't reallocated.
+	current: Vec<T>,
+	full: Vec<Vec<T>>,
+}
+
+impl<T> PinnedArena<T> {
+	/// Creates a new empty [`PinnedArena`], without allocating.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			chunks: RefCell::new(Chunks {
+				current: Vec::new(),
+				full: Vec::new(),
+			}),
+		}
+	}
+
+	/// Creates a new empty [`PinnedArena`] with room for `capacity` values in its first chunk.
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self {
+			chunks: RefCell::new(Chunks {
+				current: Vec::with_capacity(capacity),
+				full: Vec::new(),
+			}),
+		}
+	}
+
+	/// Moves `value` into the arena and pins it there.
+	#[allow(clippy::mut_from_ref)] // Each value is handed out only once.
+	pub fn alloc(&self, value: T) -> Pin<&mut T> {
+		let mut chunks = self.chunks.borrow_mut();
+		let current = &mut chunks.current;
+		if current.len() == current.capacity() {
+			let capacity = current.capacity().saturating_mul(2).max(DEFAULT_CAPACITY);
+			let full = mem::replace(current, Vec::with_capacity(capacity));
+			if !full.is_empty() {
+				chunks.full.push(full);
+			}
+		}
+
+		let current = &mut chunks.current;
+		current.push(value);
+		unsafe {
+			//SAFETY:
+			// Each value is handed out only once, and its chunk is never reallocated or shrunk.
+			// Moving the `Vec` itself doesn't move its heap buffer,
+			// and the values are dropped in place only once the arena isn't borrowed anymore.
+			Pin::new_unchecked(&mut *current.as_mut_ptr().add(current.len() - 1))
+		}
+	}
+
+	/// The number of values in the arena.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		let chunks = self.chunks.borrow();
+		chunks.current.len() + chunks.full.iter().map(Vec::len).sum::<usize>()
+	}
+
+	/// Whether the arena holds no values.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Iterates over all values in allocation order, pinning exclusively.
+	pub fn iter_mut(&mut self) -> impl Iterator<Item = Pin<&mut T>> {
+		let chunks = self.chunks.get_mut();
+		chunks
+			.full
+			.iter_mut()
+			.flat_map(|chunk| chunk.iter_mut())
+			.chain(chunks.current.iter_mut())
+			.map(|value| unsafe {
+				//SAFETY: The values are pinned, as above.
+				Pin::new_unchecked(value)
+			})
+	}
+}
+
+impl<T> Default for PinnedArena<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only the number of values, since they may be exclusively borrowed.
+impl<T> core::fmt::Debug for PinnedArena<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("PinnedArena")
+			.field("len", &self.len())
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,474 @@
+//! A double-ended queue of pinned values, stored in fixed-size blocks so that pushing at either end never moves them.
+
+use alloc::{
+	boxed::Box,
+	collections::{vec_deque, VecDeque},
+};
+use core::{
+	iter::FusedIterator,
+	mem::{self, MaybeUninit},
+	pin::Pin,
+	ptr, slice,
+};
+
+/// The number of values per block.
+const BLOCK_LEN: usize = 32;
+
+type Block<T> = Box<[MaybeUninit<T>]>;
+
+/// A double-ended queue that allocates in blocks of fixed size instead of reallocating,
+/// so that its values can be accessed as [`Pin<&mut T>`] without pinning the queue itself.
+///
+/// Values can be moved out at either end only if they are [`Unpin`],
+/// but can always be [dropped in place](`PinnedDeque::drop_front`) there.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, pinned_deque::PinnedDeque};
+///
+/// async fn task(value: u32) -> u32 {
+///     value
+/// }
+///
+/// let mut tasks = PinnedDeque::new();
+/// tasks.push_back(task(2));
+/// tasks.push_front(task(1));
+/// tasks.push_back(task(3));
+///
+/// let mut outputs = vec![];
+/// while let Some(task) = tasks.front_mut() {
+///     outputs.push(block_on(task));
+///     tasks.drop_front();
+/// }
+/// assert_eq!(outputs, [1, 2, 3]);
+/// ```
+pub struct PinnedDeque<T> {
+	/// Each exactly [`BLOCK_LEN`] long.
+	/// Only `len` slots starting at `head` (counted across blocks) are initialised.
+	blocks: VecDeque<Block<T>>,
+	/// Less than [`BLOCK_LEN`], except briefly if a value panics while being dropped at the front.
+	head: usize,
+	len: usize,
+}
+
+impl<T> PinnedDeque<T> {
+	/// Creates a new empty [`PinnedDeque`], without allocating.
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			blocks: VecDeque::new(),
+			head: 0,
+			len: 0,
+		}
+	}
+
+	/// The number of values.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether there are no values.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Prepends `value` and returns it pinned.
+	pub fn push_front(&mut self, value: T) -> Pin<&mut T> {
+		if self.head == 0 {
+			self.blocks.push_front(new_block());
+			self.head = BLOCK_LEN;
+		}
+		self.head -= 1;
+		self.len += 1;
+		let value = self.slot(0).write(value);
+		unsafe {
+			//SAFETY: Values are only moved out if they are `Unpin`, and otherwise dropped in place.
+			Pin::new_unchecked(value)
+		}
+	}
+
+	/// Appends `value` and returns it pinned.
+	pub fn push_back(&mut self, value: T) -> Pin<&mut T> {
+		if self.head + self.len == self.blocks.len() * BLOCK_LEN {
+			self.blocks.push_back(new_block());
+		}
+		let index = self.len;
+		self.len += 1;
+		let value = self.slot(index).write(value);
+		unsafe {
+			//SAFETY: As above.
+			Pin::new_unchecked(value)
+		}
+	}
+
+	/// Moves the front value out of the queue.
+	pub fn pop_front(&mut self) -> Option<T>
+	where
+		T: Unpin,
+	{
+		(self.len > 0).then(|| {
+			let value = unsafe {
+				//SAFETY: Initialised, and excluded from the queue right below.
+				self.slot(0).assume_init_read()
+			};
+			self.head += 1;
+			self.len -= 1;
+			self.trim();
+			value
+		})
+	}
+
+	/// Moves the back value out of the queue.
+	pub fn pop_back(&mut self) -> Option<T>
+	where
+		T: Unpin,
+	{
+		(self.len > 0).then(|| {
+			self.len -= 1;
+			let index = self.len;
+			let value = unsafe {
+				//SAFETY: Initialised, and now excluded from the queue.
+				self.slot(index).assume_init_read()
+			};
+			self.trim();
+			value
+		})
+	}
+
+	/// Drops the front value in place.
+	///
+	/// Returns whether there was one.
+	pub fn drop_front(&mut self) -> bool {
+		let any = self.len > 0;
+		if any {
+			let value = self.slot(0).as_mut_ptr();
+			self.head += 1;
+			self.len -= 1;
+			unsafe {
+				//SAFETY: Initialised, and now excluded from the queue.
+				ptr::drop_in_place(value)
+			}
+			self.trim();
+		}
+		any
+	}
+
+	/// Drops the back value in place.
+	///
+	/// Returns whether there was one.
+	pub fn drop_back(&mut self) -> bool {
+		let any = self.len > 0;
+		if any {
+			self.len -= 1;
+			let index = self.len;
+			let value = self.slot(index).as_mut_ptr();
+			unsafe {
+				//SAFETY: As above.
+				ptr::drop_in_place(value)
+			}
+			self.trim();
+		}
+		any
+	}
+
+	/// Drops all values in place, back to front.
+	///
+	/// If one of them panics, the rest are dropped anyway.
+	pub fn clear(&mut self) {
+		/// Continues on unwind.
+		struct Rest<'a, T>(&'a mut PinnedDeque<T>);
+		impl<T> Drop for Rest<'_, T> {
+			fn drop(&mut self) {
+				self.0.clear()
+			}
+		}
+
+		while !self.is_empty() {
+			let rest = Rest(self);
+			rest.0.drop_back();
+			mem::forget(rest);
+		}
+	}
+
+	/// Retrieves a shared reference to a pinned value,
+	/// or [`None`] exactly whenever `index >= len`.
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<Pin<&T>> {
+		(index < self.len).then(|| {
+			let index = self.head + index;
+			unsafe {
+				//SAFETY: Initialised and pinned.
+				Pin::new_unchecked(
+					self.blocks[index / BLOCK_LEN][index % BLOCK_LEN].assume_init_ref(),
+				)
+			}
+		})
+	}
+
+	/// Retrieves an exclusive reference to a pinned value,
+	/// or [`None`] exactly whenever `index >= len`.
+	#[must_use]
+	pub fn get_mut(&mut self, index: usize) -> Option<Pin<&mut T>> {
+		(index < self.len).then(|| unsafe {
+			//SAFETY: As above.
+			Pin::new_unchecked(self.slot(index).assume_init_mut())
+		})
+	}
+
+	/// The front value.
+	#[must_use]
+	pub fn front(&self) -> Option<Pin<&T>> {
+		self.get(0)
+	}
+
+	/// The front value, pinned exclusively.
+	#[must_use]
+	pub fn front_mut(&mut self) -> Option<Pin<&mut T>> {
+		self.get_mut(0)
+	}
+
+	/// The back value.
+	#[must_use]
+	pub fn back(&self) -> Option<Pin<&T>> {
+		self.get(self.len.checked_sub(1)?)
+	}
+
+	/// The back value, pinned exclusively.
+	#[must_use]
+	pub fn back_mut(&mut self) -> Option<Pin<&mut T>> {
+		self.get_mut(self.len.checked_sub(1)?)
+	}
+
+	/// A sharing pinning iterator over the values, front to back.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		let (head, end, used) = self.bounds();
+		let mut blocks = self.blocks.range(..used);
+		let front = blocks
+			.next()
+			.map_or(&[][..], |block| &block[head..end.min(BLOCK_LEN)]);
+		let back = blocks
+			.next_back()
+			.map_or(&[][..], |block| &block[..end - (used - 1) * BLOCK_LEN]);
+		Iter {
+			blocks,
+			front: front.iter(),
+			back: back.iter(),
+			remaining: self.len,
+		}
+	}
+
+	/// An exclusive pinning iterator over the values, front to back.
+	#[must_use]
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		let (head, end, used) = self.bounds();
+		let mut blocks = self.blocks.range_mut(..used);
+		let front = blocks
+			.next()
+			.map_or(&mut [][..], |block| &mut block[head..end.min(BLOCK_LEN)]);
+		let back = blocks.next_back().map_or(&mut [][..], |block| {
+			&mut block[..end - (used - 1) * BLOCK_LEN]
+		});
+		IterMut {
+			blocks,
+			front: front.iter_mut(),
+			back: back.iter_mut(),
+			remaining: self.len,
+		}
+	}
+
+	fn slot(&mut self, index: usize) -> &mut MaybeUninit<T> {
+		let index = self.head + index;
+		&mut self.blocks[index / BLOCK_LEN][index % BLOCK_LEN]
+	}
+
+	/// The head, the end counted from the first block, and the number of blocks in use.
+	fn bounds(&self) -> (usize, usize, usize) {
+		let end = self.head + self.len;
+		(self.head, end, (end + BLOCK_LEN - 1) / BLOCK_LEN)
+	}
+
+	/// Frees blocks without values.
+	fn trim(&mut self) {
+		while self.head >= BLOCK_LEN {
+			self.blocks.pop_front();
+			self.head -= BLOCK_LEN;
+		}
+		let (_, _, used) = self.bounds();
+		self.blocks.truncate(used.max(1).min(self.blocks.len()));
+	}
+}
+
+fn new_block<T>() -> Block<T> {
+	(0..BLOCK_LEN).map(|_| MaybeUninit::uninit()).collect()
+}
+
+impl<T> Drop for PinnedDeque<T> {
+	fn drop(&mut self) {
+		self.clear()
+	}
+}
+
+impl<T> Default for PinnedDeque<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Extend<T> for PinnedDeque<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.push_back(value);
+		}
+	}
+}
+
+impl<T> FromIterator<T> for PinnedDeque<T> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut deque = Self::new();
+		deque.extend(iter);
+		deque
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for PinnedDeque<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_list()
+			.entries(self.iter().map(Pin::get_ref))
+			.finish()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a PinnedDeque<T> {
+	type Item = Pin<&'a T>;
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut PinnedDeque<T> {
+	type Item = Pin<&'a mut T>;
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// A sharing pinning iterator over a [`PinnedDeque`]. See [`PinnedDeque::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+	/// These fields must not be public, as they don't guarantee pinning by themselves.
+	blocks: vec_deque::Iter<'a, Block<T>>,
+	front: slice::Iter<'a, MaybeUninit<T>>,
+	back: slice::Iter<'a, MaybeUninit<T>>,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+	type Item = Pin<&'a T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.front.next() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: Initialised and pinned.
+					Pin::new_unchecked(value.assume_init_ref())
+				});
+			}
+			self.front = match self.blocks.next() {
+				Some(block) => block.iter(),
+				None => mem::replace(&mut self.back, [].iter()),
+			};
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.back.next_back() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: As above.
+					Pin::new_unchecked(value.assume_init_ref())
+				});
+			}
+			self.back = match self.blocks.next_back() {
+				Some(block) => block.iter(),
+				None => mem::replace(&mut self.front, [].iter()),
+			};
+		}
+		None
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An exclusive pinning iterator over a [`PinnedDeque`]. See [`PinnedDeque::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+	/// These fields must not be public, as they don't guarantee pinning by themselves.
+	blocks: vec_deque::IterMut<'a, Block<T>>,
+	front: slice::IterMut<'a, MaybeUninit<T>>,
+	back: slice::IterMut<'a, MaybeUninit<T>>,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+	type Item = Pin<&'a mut T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.front.next() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: Initialised and pinned.
+					Pin::new_unchecked(value.assume_init_mut())
+				});
+			}
+			self.front = match self.blocks.next() {
+				Some(block) => block.iter_mut(),
+				None => mem::replace(&mut self.back, [].iter_mut()),
+			};
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.back.next_back() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: As above.
+					Pin::new_unchecked(value.assume_init_mut())
+				});
+			}
+			self.back = match self.blocks.next_back() {
+				Some(block) => block.iter_mut(),
+				None => mem::replace(&mut self.front, [].iter_mut()),
+			};
+		}
+		None
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
@@ -0,0 +1,167 @@
+//! Owned handles to individual items of a shared pinned [array](https://doc.rust-lang.org/stable/core/primitive.array.html)
+//! or [slice](https://doc.rust-lang.org/stable/core/primitive.slice.html).
+//!
+//! Shared ownership through [`Arc`] or [`Rc`] keeps the whole collection alive,
+//! so the individual items can be handed out without any lifetime attached to them.
+//!
+//! > This is essentially a pin projection through a reference-counting pointer,
+//! > which works out because the pointer never gives out exclusive access to its target.
+
+use crate::pinned_pin::PinnedPin;
+use alloc::{rc::Rc, sync::Arc};
+use core::{borrow::Borrow, marker::PhantomData, ops::Deref, pin::Pin};
+
+/// An owned handle to a single pinned item of a shared pinned collection.
+///
+/// `P` is the pointer that keeps the collection alive, by default an [`Arc`].
+///
+/// Dereferences to the item. Use [`.as_pinned()`](`PinnedElem::as_pinned`) to access it as pinned.
+pub struct PinnedElem<Item, P = Arc<PinnedPin<[Item]>>> {
+	/// `P` may be any [`Deref`] implementation, which could return a shorter slice later on,
+	/// so [`.as_pinned()`](`PinnedElem::as_pinned`) checks the index again on each access.
+	items: Pin<P>,
+	index: usize,
+	_item: PhantomData<Item>,
+}
+
+impl<Item, P> PinnedElem<Item, P>
+where
+	P: Deref<Target = PinnedPin<[Item]>>,
+{
+	/// Creates a new [`PinnedElem`] for the item at `index` in `items`.
+	///
+	/// For arrays, see also [`PinnedPin::into_elem_arced`] and [`PinnedPin::into_elem_rced`].
+	///
+	/// # Errors
+	///
+	/// Iff `index` is out of bounds, `items` is returned unchanged.
+	pub fn new(items: Pin<P>, index: usize) -> Result<Self, Pin<P>> {
+		if index < items.len() {
+			Ok(Self {
+				items,
+				index,
+				_item: PhantomData,
+			})
+		} else {
+			Err(items)
+		}
+	}
+
+	/// Gives pinning shared access to the item.
+	///
+	/// # Panics
+	///
+	/// Iff `P`'s [`Deref`] implementation now returns a collection too short for [`.index()`](`PinnedElem::index`).
+	/// This can't happen with [`Arc`] or [`Rc`].
+	#[must_use]
+	pub fn as_pinned(&self) -> Pin<&Item> {
+		self.items
+			.as_ref()
+			.get(self.index)
+			.expect("`PinnedElem`'s collection shrank.")
+	}
+
+	/// The index of the item in its collection.
+	#[must_use]
+	pub fn index(&self) -> usize {
+		self.index
+	}
+
+	/// Gives pinning shared access to the whole collection.
+	#[must_use]
+	pub fn items(&self) -> Pin<&PinnedPin<[Item]>> {
+		self.items.as_ref()
+	}
+
+	/// Releases the handle, returning the collection pointer.
+	#[must_use]
+	pub fn into_items(self) -> Pin<P> {
+		self.items
+	}
+}
+
+impl<Item, P> Deref for PinnedElem<Item, P>
+where
+	P: Deref<Target = PinnedPin<[Item]>>,
+{
+	type Target = Item;
+
+	fn deref(&self) -> &Self::Target {
+		// A shared reference to the item is fine even without the `Pin<_>`.
+		Pin::get_ref(self.as_pinned())
+	}
+}
+
+impl<Item, P> Borrow<Item> for PinnedElem<Item, P>
+where
+	P: Deref<Target = PinnedPin<[Item]>>,
+{
+	fn borrow(&self) -> &Item {
+		self
+	}
+}
+
+/// Cloning only clones the pointer, so this doesn't require `Item: Clone`.
+impl<Item, P> Clone for PinnedElem<Item, P>
+where
+	P: Deref<Target = PinnedPin<[Item]>> + Clone,
+{
+	fn clone(&self) -> Self {
+		Self {
+			items: self.items.clone(),
+			index: self.index,
+			_item: PhantomData,
+		}
+	}
+}
+
+/// Only the targeted item is shown, since the rest of the collection isn't accessible through this handle.
+impl<Item, P> core::fmt::Debug for PinnedElem<Item, P>
+where
+	P: Deref<Target = PinnedPin<[Item]>>,
+	Item: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("PinnedElem")
+			.field("index", &self.index)
+			.field("item", &**self)
+			.finish_non_exhaustive()
+	}
+}
+
+macro_rules! elem_conversions {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$rc:ident($into_elem:ident$(,)?)
+	),*$(,)?} => {$(
+		impl<Item, const N: usize> PinnedPin<[Item; N]> {
+			/// Creates an owned handle to the pinned item at `index`,
+			/// which keeps the whole array alive.
+			///
+			/// # Errors
+			///
+			/// Iff `index >= N`, `this` is returned unchanged.
+			pub fn $into_elem(
+				this: Pin<$rc<Self>>,
+				index: usize,
+			) -> Result<PinnedElem<Item, $rc<PinnedPin<[Item]>>>, Pin<$rc<Self>>> {
+				if index < N {
+					// The unsizing coercion also works through `Pin<_>` and `PinnedPin<_>`.
+					let items: Pin<$rc<PinnedPin<[Item]>>> = this;
+					Ok(PinnedElem {
+						items,
+						index,
+						_item: PhantomData,
+					})
+				} else {
+					Err(this)
+				}
+			}
+		}
+	)*};
+}
+
+elem_conversions! {
+	Arc(into_elem_arced),
+	Rc(into_elem_rced),
+}
@@ -0,0 +1,208 @@
+//! A dynamic set of pinned [`Future`]s that yields their outputs as they complete.
+
+use crate::waker_dispatch::{WakerDispatch, BITS};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+
+/// A growable set of [`Future`]s that are driven together, yielding each output along with its key as soon as it's available.
+///
+/// Each [`Future`] is boxed individually, so it stays in place even as the set grows, and may be [`!Unpin`](`Unpin`).
+/// Only entries that were woken since their last poll are polled again.
+///
+/// Keys are reused only after the entry's output was yielded or it was removed.
+///
+/// > This is the unordered counterpart to [`JoinAll`](`crate::join_all::JoinAll`),
+/// > similar to `FuturesUnordered` from the `futures` crate, but without an intrusive run queue.
+/// > The per-slot dirty flags it uses instead are cheap enough at the sizes this is meant for.
+pub struct PinnedFutureSet<F: Future> {
+	slots: Vec<Option<Pin<Box<F>>>>,
+	/// Indices of vacant slots.
+	vacant: Vec<usize>,
+	/// One per [`BITS`] slots.
+	dispatches: Vec<WakerDispatch>,
+}
+
+impl<F: Future> PinnedFutureSet<F> {
+	/// Creates a new empty [`PinnedFutureSet`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			slots: Vec::new(),
+			vacant: Vec::new(),
+			dispatches: Vec::new(),
+		}
+	}
+
+	/// The number of entries that haven't been yielded or removed yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.slots.len() - self.vacant.len()
+	}
+
+	/// Whether there are no entries left.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Whether there is an entry at `key`.
+	#[must_use]
+	pub fn contains(&self, key: usize) -> bool {
+		matches!(self.slots.get(key), Some(Some(_)))
+	}
+
+	/// Adds `future` and returns its key.
+	pub fn push(&mut self, future: F) -> usize {
+		let future = Some(Box::pin(future));
+		if let Some(index) = self.vacant.pop() {
+			self.slots[index] = future;
+			self.dispatches[index / BITS].mark_dirty(index % BITS);
+			index
+		} else {
+			let index = self.slots.len();
+			if index % BITS == 0 {
+				// Children of a new dispatch start out dirty.
+				self.dispatches.push(WakerDispatch::new(BITS));
+			}
+			self.slots.push(future);
+			index
+		}
+	}
+
+	/// Drops the entry at `key` early, without yielding its output.
+	///
+	/// Returns whether there was such an entry.
+	pub fn remove(&mut self, key: usize) -> bool {
+		match self.slots.get_mut(key) {
+			Some(slot @ Some(_)) => {
+				*slot = None;
+				self.vacant.push(key);
+				true
+			}
+			_ => false,
+		}
+	}
+
+	/// Gives pinning exclusive access to the entry at `key`, if there is one.
+	#[must_use]
+	pub fn get_pin_mut(&mut self, key: usize) -> Option<Pin<&mut F>> {
+		self.slots
+			.get_mut(key)
+			.and_then(|slot| slot.as_mut().map(Pin::as_mut))
+	}
+
+	/// Yields the key and output of the next entry that completed.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) iff the set is empty.
+	pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<(usize, F::Output)>> {
+		if self.is_empty() {
+			return Poll::Ready(None);
+		}
+
+		let slots = &mut self.slots;
+		let mut completed = None;
+		for (chunk, dispatch) in self.dispatches.iter().enumerate() {
+			dispatch.register(cx.waker());
+			dispatch.drain_dirty_from(0, |offset| {
+				let index = chunk * BITS + offset;
+				if let Some(future) = slots.get_mut(index).and_then(Option::as_mut) {
+					if let Poll::Ready(output) = future
+						.as_mut()
+						.poll(&mut Context::from_waker(dispatch.waker(offset)))
+					{
+						completed = Some((index, output));
+						// Stops draining. The now-vacant slot stays dirty, which is harmless.
+						return false;
+					}
+				}
+				true
+			});
+
+			if let Some((index, output)) = completed {
+				self.slots[index] = None;
+				self.vacant.push(index);
+				return Poll::Ready(Some((index, output)));
+			}
+		}
+		Poll::Pending
+	}
+
+	/// Returns a [`Future`] that resolves to the key and output of the next entry that completed,
+	/// or to [`None`] if the set is empty.
+	#[allow(clippy::should_implement_trait)] // Like `StreamExt::next`, but without depending on it.
+	pub fn next(&mut self) -> Next<'_, F> {
+		Next(self)
+	}
+}
+
+impl<F: Future> Default for PinnedFutureSet<F> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<F: Future> Extend<F> for PinnedFutureSet<F> {
+	fn extend<I: IntoIterator<Item = F>>(&mut self, iter: I) {
+		for future in iter {
+			self.push(future);
+		}
+	}
+}
+
+impl<F: Future> FromIterator<F> for PinnedFutureSet<F> {
+	fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+		let mut this = Self::new();
+		this.extend(iter);
+		this
+	}
+}
+
+impl<F: Future> Unpin for PinnedFutureSet<F> {}
+
+/// Yields [`None`] whenever the set is empty, but can be resumed after pushing more entries.
+#[cfg(feature = "futures-core")]
+impl<F: Future> Stream for PinnedFutureSet<F> {
+	type Item = (usize, F::Output);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		PinnedFutureSet::poll_next(self.get_mut(), cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len(), Some(self.len()))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedStream for PinnedFutureSet<F> {
+	fn is_terminated(&self) -> bool {
+		self.is_empty()
+	}
+}
+
+impl<F: Future> core::fmt::Debug for PinnedFutureSet<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("PinnedFutureSet")
+			.field("len", &self.len())
+			.field("capacity", &self.slots.len())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that resolves to the next output of a [`PinnedFutureSet`]. See [`PinnedFutureSet::next`].
+#[derive(Debug)]
+pub struct Next<'a, F: Future>(&'a mut PinnedFutureSet<F>);
+
+impl<F: Future> Future for Next<'_, F> {
+	type Output = Option<(usize, F::Output)>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.0.poll_next(cx)
+	}
+}
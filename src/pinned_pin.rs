@@ -1,6 +1,9 @@
 //! A minimal content-pinning wrapper.
 
-use alloc::{boxed::Box, rc::Rc, sync::Arc};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, rc::Rc};
 use core::{
 	borrow::{Borrow, BorrowMut},
 	mem,
@@ -83,18 +86,21 @@ impl<T> From<T> for PinnedPin<T> {
 }
 
 /// Implementing this for [`Box`] is specifically allowed.
+#[cfg(feature = "alloc")]
 impl<T: ?Sized> From<Box<T>> for Box<PinnedPin<T>> {
 	fn from(boxed: Box<T>) -> Self {
 		PinnedPin::wrap_boxed(boxed)
 	}
 }
 
+#[cfg(feature = "alloc")]
 impl<T> From<Pin<Box<T>>> for Pin<Box<PinnedPin<T>>> {
 	fn from(boxed: Pin<Box<T>>) -> Self {
 		PinnedPin::wrap_pinned_boxed(boxed)
 	}
 }
 
+#[cfg(feature = "alloc")]
 macro_rules! boxed_conversions {
 	// There's a bit of syntax noise here. In short:
 	// `{$(…),*$(,)?} => {$(…)}` cleanly makes a macro variadic over a comma-separated pattern.
@@ -136,9 +142,14 @@ macro_rules! boxed_conversions {
 	)*};
 }
 
+#[cfg(feature = "alloc")]
 boxed_conversions! {
 	Box(wrap_boxed, unwrap_boxed, wrap_pinned_boxed, unwrap_pinned_boxed),
 	Rc(wrap_rced, unwrap_rced, wrap_pinned_rced, unwrap_pinned_rced),
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+boxed_conversions! {
 	Arc(wrap_arced, unwrap_arced, wrap_pinned_arced, unwrap_pinned_arced),
 }
 
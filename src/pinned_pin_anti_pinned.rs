@@ -0,0 +1,216 @@
+//! Conversions between [`PinnedPin`] and [`AntiPinned`].
+//!
+//! Unpinned, both wrappers are interchangeable with `T` and therefore with each other,
+//! so the by-value and plain reference conversions are always available.
+//!
+//! Pinned, going from either wrapper to the other is only sound in general if `T: Unpin`:
+//!
+//! - From [`PinnedPin<T>`] to [`AntiPinned<T>`], since the latter would give out `&mut T`.
+//! - From [`AntiPinned<T>`] to [`PinnedPin<T>`], since the `T` may have been moved before
+//!   and may be moved again after, through other handles to the same [`AntiPinned<T>`].
+//!
+//! Each of these conversions also has an `unsafe` `…_unchecked` variant without that bound.
+//!
+//! > The exception is a pinned [`Box`](`alloc::boxed::Box`) of [`AntiPinned<T>`], which owns the `T` exclusively.
+//! > It can be unwrapped and then pinned again for free.
+
+use crate::{anti_pinned::AntiPinned, pinned_pin::PinnedPin};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, rc::Rc};
+#[cfg(feature = "alloc")]
+use core::mem;
+use core::pin::Pin;
+
+impl<T> From<PinnedPin<T>> for AntiPinned<T> {
+	fn from(value: PinnedPin<T>) -> Self {
+		value.into_anti_pinned()
+	}
+}
+
+impl<T> From<AntiPinned<T>> for PinnedPin<T> {
+	fn from(value: AntiPinned<T>) -> Self {
+		value.into_pinned_pin()
+	}
+}
+
+impl<T> PinnedPin<T> {
+	/// Rewraps the value in [`AntiPinned<_>`].
+	#[must_use]
+	pub fn into_anti_pinned(self) -> AntiPinned<T> {
+		AntiPinned(self.0)
+	}
+}
+
+impl<T> AntiPinned<T> {
+	/// Rewraps the value in [`PinnedPin<_>`].
+	#[must_use]
+	pub fn into_pinned_pin(self) -> PinnedPin<T> {
+		PinnedPin(self.0)
+	}
+}
+
+impl<T: ?Sized> PinnedPin<T> {
+	/// Reinterprets this [`PinnedPin<T>`] as [`AntiPinned<T>`].
+	#[must_use]
+	pub fn as_anti_pinned(&self) -> &AntiPinned<T> {
+		AntiPinned::from_ref(&self.0)
+	}
+
+	/// Reinterprets this [`PinnedPin<T>`] as [`AntiPinned<T>`], exclusively.
+	#[must_use]
+	pub fn as_anti_pinned_mut(&mut self) -> &mut AntiPinned<T> {
+		AntiPinned::from_mut(&mut self.0)
+	}
+
+	/// Reinterprets this pinned [`PinnedPin<T>`] as pinned [`AntiPinned<T>`], exclusively.
+	#[must_use]
+	pub fn as_anti_pinned_pin_mut(self: Pin<&mut Self>) -> Pin<&mut AntiPinned<T>>
+	where
+		T: Unpin,
+	{
+		unsafe {
+			//SAFETY: `T: Unpin`.
+			self.as_anti_pinned_pin_mut_unchecked()
+		}
+	}
+
+	/// Reinterprets this pinned [`PinnedPin<T>`] as pinned [`AntiPinned<T>`], exclusively,
+	/// even if `T` isn't [`Unpin`].
+	///
+	/// # Safety
+	///
+	/// The `T` must not be moved or invalidated without being dropped through the returned reference,
+	/// as [`AntiPinned<T>`] gives out `&mut T` freely.
+	#[must_use]
+	pub unsafe fn as_anti_pinned_pin_mut_unchecked(
+		self: Pin<&mut Self>,
+	) -> Pin<&mut AntiPinned<T>> {
+		Pin::new(Pin::into_inner_unchecked(self).as_anti_pinned_mut())
+	}
+}
+
+impl<T: ?Sized> AntiPinned<T> {
+	/// Reinterprets this [`AntiPinned<T>`] as [`PinnedPin<T>`].
+	#[must_use]
+	pub fn as_pinned_pin(&self) -> &PinnedPin<T> {
+		PinnedPin::from_ref(&self.0)
+	}
+
+	/// Reinterprets this [`AntiPinned<T>`] as [`PinnedPin<T>`], exclusively.
+	#[must_use]
+	pub fn as_pinned_pin_mut(&mut self) -> &mut PinnedPin<T> {
+		PinnedPin::from_mut(&mut self.0)
+	}
+
+	/// Reinterprets this pinned [`AntiPinned<T>`] as pinned [`PinnedPin<T>`], exclusively.
+	#[must_use]
+	pub fn as_pinned_pin_pin_mut(self: Pin<&mut Self>) -> Pin<&mut PinnedPin<T>>
+	where
+		T: Unpin,
+	{
+		unsafe {
+			//SAFETY: `T: Unpin`.
+			self.as_pinned_pin_pin_mut_unchecked()
+		}
+	}
+
+	/// Reinterprets this pinned [`AntiPinned<T>`] as pinned [`PinnedPin<T>`], exclusively,
+	/// even if `T` isn't [`Unpin`].
+	///
+	/// # Safety
+	///
+	/// From now on and until it is dropped, the `T` must not be moved or invalidated without being dropped,
+	/// also not through this [`AntiPinned<T>`] once the returned reference expires.
+	#[must_use]
+	pub unsafe fn as_pinned_pin_pin_mut_unchecked(self: Pin<&mut Self>) -> Pin<&mut PinnedPin<T>> {
+		Pin::new_unchecked(Pin::into_inner(self).as_pinned_pin_mut())
+	}
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! pinned_conversions {
+	// See `crate::pinned_pin` for the macro syntax.
+	{$(
+		$box:ident($to_anti_pinned:ident, $to_anti_pinned_unchecked:ident$(, $to_pinned_pin:ident, $to_pinned_pin_unchecked:ident)?$(,)?)
+	),*$(,)?} => {$(
+		impl<T: ?Sized> PinnedPin<T> {
+			/// Rewraps a pinned value in [`AntiPinned<_>`], in place.
+			#[must_use]
+			pub fn $to_anti_pinned(this: Pin<$box<Self>>) -> Pin<$box<AntiPinned<T>>>
+			where
+				T: Unpin,
+			{
+				unsafe {
+					//SAFETY: `T: Unpin`.
+					Self::$to_anti_pinned_unchecked(this)
+				}
+			}
+
+			/// Rewraps a pinned value in [`AntiPinned<_>`], in place, even if `T` isn't [`Unpin`].
+			///
+			/// # Safety
+			///
+			/// The `T` must not be moved or invalidated without being dropped through the returned pointer or its clones,
+			/// as [`AntiPinned<T>`] gives out `&mut T` freely.
+			#[must_use]
+			pub unsafe fn $to_anti_pinned_unchecked(this: Pin<$box<Self>>) -> Pin<$box<AntiPinned<T>>> {
+				//SAFETY: See <`PinnedPin`#safety-notes> and <`AntiPinned`#safety-notes>. Pinning is upheld by the caller.
+				mem::transmute(this)
+			}
+		}
+
+		// This part is optional.
+		$(impl<T: ?Sized> AntiPinned<T> {
+			/// Rewraps a pinned value in [`PinnedPin<_>`], in place.
+			#[must_use]
+			pub fn $to_pinned_pin(this: Pin<$box<Self>>) -> Pin<$box<PinnedPin<T>>>
+			where
+				T: Unpin,
+			{
+				unsafe {
+					//SAFETY: `T: Unpin`.
+					Self::$to_pinned_pin_unchecked(this)
+				}
+			}
+
+			/// Rewraps a pinned value in [`PinnedPin<_>`], in place, even if `T` isn't [`Unpin`].
+			///
+			/// # Safety
+			///
+			/// The `T` must not have been moved through other clones of this pointer,
+			/// and from now on must not be moved or invalidated without being dropped through any remaining ones.
+			#[must_use]
+			pub unsafe fn $to_pinned_pin_unchecked(this: Pin<$box<Self>>) -> Pin<$box<PinnedPin<T>>> {
+				//SAFETY: See <`PinnedPin`#safety-notes> and <`AntiPinned`#safety-notes>. Pinning is upheld by the caller.
+				mem::transmute(this)
+			}
+		})?
+	)*};
+}
+
+#[cfg(feature = "alloc")]
+pinned_conversions! {
+	Box(into_anti_pinned_pinned_boxed, into_anti_pinned_pinned_boxed_unchecked),
+	Rc(into_anti_pinned_pinned_rced, into_anti_pinned_pinned_rced_unchecked, into_pinned_pin_pinned_rced, into_pinned_pin_pinned_rced_unchecked),
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pinned_conversions! {
+	Arc(into_anti_pinned_pinned_arced, into_anti_pinned_pinned_arced_unchecked, into_pinned_pin_pinned_arced, into_pinned_pin_pinned_arced_unchecked),
+}
+
+/// Behind the exclusively-owning [`Box<T>`] alone, we don't need `where T: Unpin` to pin the instance again.
+///
+/// Any move of the `T` must have happened through this very [`Box`] before, which is fine.
+#[cfg(feature = "alloc")]
+impl<T: ?Sized> AntiPinned<T> {
+	/// Rewraps a pinned boxed value in [`PinnedPin<_>`], in place.
+	#[must_use]
+	pub fn into_pinned_pin_pinned_boxed(this: Pin<Box<Self>>) -> Pin<Box<PinnedPin<T>>> {
+		Pin::from(PinnedPin::wrap_boxed(Self::unwrap_boxed(Pin::into_inner(
+			this,
+		))))
+	}
+}
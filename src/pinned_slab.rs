@@ -0,0 +1,358 @@
+//! A slab of pinned values addressed by stable keys, which grows without moving them.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{iter::FusedIterator, mem, pin::Pin, slice};
+
+/// The length of the first chunk. Each further chunk is as long as all previous ones together.
+const FIRST_CHUNK_LEN_LOG2: u32 = 4;
+const FIRST_CHUNK_LEN: usize = 1 << FIRST_CHUNK_LEN_LOG2;
+
+enum Slot<T> {
+	Occupied(T),
+	Vacant { next_free: Option<usize> },
+}
+
+/// A collection of pinned values, each addressed by a `usize` key that stays valid until it is removed.
+///
+/// Values are stored in chunks that are never reallocated, so inserting never moves existing ones
+/// and they can be accessed as [`Pin<&mut T>`] without pinning the slab itself.
+///
+/// Removal either moves the value out, which requires `T: Unpin`, or drops it in place.
+/// Keys of removed values are reused.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, pinned_slab::PinnedSlab};
+///
+/// async fn task(value: u32) -> u32 {
+///     value
+/// }
+///
+/// let mut slab = PinnedSlab::new();
+/// let a = slab.insert(task(1));
+/// let b = slab.insert(task(2));
+///
+/// assert_eq!(block_on(slab.get_pinned(b).unwrap()), 2);
+/// assert!(slab.remove_pinned(a));
+/// assert_eq!(slab.insert(task(3)), a);
+/// ```
+///
+/// > Chunk lengths double, which keeps the number of allocations logarithmic in the capacity,
+/// > but means memory is only returned to the allocator once the whole slab is dropped.
+pub struct PinnedSlab<T> {
+	/// Never reallocated individually, only the list of them is.
+	chunks: Vec<Box<[Slot<T>]>>,
+	len: usize,
+	/// The number of slots that were ever occupied.
+	used: usize,
+	free: Option<usize>,
+}
+
+impl<T> PinnedSlab<T> {
+	/// Creates a new empty [`PinnedSlab`], without allocating.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			chunks: Vec::new(),
+			len: 0,
+			used: 0,
+			free: None,
+		}
+	}
+
+	/// The number of values.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether there are no values.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The number of values that fit without allocating another chunk.
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		(FIRST_CHUNK_LEN << self.chunks.len()) - FIRST_CHUNK_LEN
+	}
+
+	/// Whether `key` refers to a value.
+	#[must_use]
+	pub fn contains(&self, key: usize) -> bool {
+		matches!(self.slot(key), Some(Slot::Occupied(_)))
+	}
+
+	/// Stores `value` and returns its key.
+	pub fn insert(&mut self, value: T) -> usize {
+		let key = if let Some(key) = self.free {
+			key
+		} else {
+			if self.used == self.capacity() {
+				let len = FIRST_CHUNK_LEN << self.chunks.len();
+				self.chunks
+					.push((0..len).map(|_| Slot::Vacant { next_free: None }).collect());
+			}
+			self.used += 1;
+			self.used - 1
+		};
+
+		let slot = self.used_slot(key);
+		let next_free = match *slot {
+			Slot::Vacant { next_free } => next_free,
+			Slot::Occupied(_) => unreachable!(),
+		};
+		*slot = Slot::Occupied(value);
+		self.free = next_free;
+		self.len += 1;
+		key
+	}
+
+	/// Gives shared access to the value at `key`.
+	#[must_use]
+	pub fn get(&self, key: usize) -> Option<Pin<&T>> {
+		match self.slot(key)? {
+			Slot::Occupied(value) => Some(unsafe {
+				//SAFETY: Values are only moved out if they are `Unpin`.
+				Pin::new_unchecked(value)
+			}),
+			Slot::Vacant { .. } => None,
+		}
+	}
+
+	/// Gives pinning exclusive access to the value at `key`.
+	#[must_use]
+	pub fn get_pinned(&mut self, key: usize) -> Option<Pin<&mut T>> {
+		match self.slot_mut(key)? {
+			Slot::Occupied(value) => Some(unsafe {
+				//SAFETY: As above.
+				Pin::new_unchecked(value)
+			}),
+			Slot::Vacant { .. } => None,
+		}
+	}
+
+	/// Gives exclusive access to the value at `key`.
+	#[must_use]
+	pub fn get_mut(&mut self, key: usize) -> Option<&mut T>
+	where
+		T: Unpin,
+	{
+		self.get_pinned(key).map(Pin::get_mut)
+	}
+
+	/// Moves the value at `key` out of the slab.
+	pub fn remove(&mut self, key: usize) -> Option<T>
+	where
+		T: Unpin,
+	{
+		self.contains(key).then(|| {
+			let vacant = self.vacate(key);
+			match mem::replace(self.used_slot(key), vacant) {
+				Slot::Occupied(value) => value,
+				Slot::Vacant { .. } => unreachable!(),
+			}
+		})
+	}
+
+	/// Drops the value at `key` in place.
+	///
+	/// Returns whether there was one.
+	pub fn remove_pinned(&mut self, key: usize) -> bool {
+		let occupied = self.contains(key);
+		if occupied {
+			let vacant = self.vacate(key);
+			*self.used_slot(key) = vacant;
+		}
+		occupied
+	}
+
+	/// Drops all values in place.
+	///
+	/// The chunks are kept for reuse.
+	pub fn clear(&mut self) {
+		self.len = 0;
+		self.used = 0;
+		self.free = None;
+		for slot in self.chunks.iter_mut().flat_map(|chunk| chunk.iter_mut()) {
+			*slot = Slot::Vacant { next_free: None };
+		}
+	}
+
+	/// Iterates over the keys and values in key order.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter {
+			chunks: self.chunks.iter(),
+			slots: [].iter(),
+			key: 0,
+			remaining: self.len,
+		}
+	}
+
+	/// Iterates over the keys and values in key order, pinning exclusively.
+	#[must_use]
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		IterMut {
+			chunks: self.chunks.iter_mut(),
+			slots: [].iter_mut(),
+			key: 0,
+			remaining: self.len,
+		}
+	}
+
+	/// Updates the bookkeeping for `key` being removed and returns its new slot.
+	fn vacate(&mut self, key: usize) -> Slot<T> {
+		self.len -= 1;
+		Slot::Vacant {
+			next_free: self.free.replace(key),
+		}
+	}
+
+	/// # Panics
+	///
+	/// Iff `key` is at or above `used`.
+	fn used_slot(&mut self, key: usize) -> &mut Slot<T> {
+		self.slot_mut(key).expect("unreachable")
+	}
+
+	fn slot(&self, key: usize) -> Option<&Slot<T>> {
+		let (chunk, index) = locate(key)?;
+		self.chunks.get(chunk)?.get(index)
+	}
+
+	fn slot_mut(&mut self, key: usize) -> Option<&mut Slot<T>> {
+		let (chunk, index) = locate(key)?;
+		self.chunks.get_mut(chunk)?.get_mut(index)
+	}
+}
+
+/// The chunk and index within it of `key`.
+fn locate(key: usize) -> Option<(usize, usize)> {
+	let offset = key.checked_add(FIRST_CHUNK_LEN)?;
+	let log2 = usize::BITS - 1 - offset.leading_zeros();
+	Some(((log2 - FIRST_CHUNK_LEN_LOG2) as usize, offset - (1 << log2)))
+}
+
+impl<T> Default for PinnedSlab<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for PinnedSlab<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_map()
+			.entries(self.iter().map(|(key, value)| (key, value.get_ref())))
+			.finish()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a PinnedSlab<T> {
+	type Item = (usize, Pin<&'a T>);
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut PinnedSlab<T> {
+	type Item = (usize, Pin<&'a mut T>);
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// A sharing pinning iterator over a [`PinnedSlab`]. See [`PinnedSlab::iter`].
+pub struct Iter<'a, T> {
+	chunks: slice::Iter<'a, Box<[Slot<T>]>>,
+	slots: slice::Iter<'a, Slot<T>>,
+	key: usize,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+	type Item = (usize, Pin<&'a T>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(slot) = self.slots.next() {
+				self.key += 1;
+				if let Slot::Occupied(value) = slot {
+					self.remaining -= 1;
+					return Some((self.key - 1, unsafe {
+						//SAFETY: Values are only moved out if they are `Unpin`.
+						Pin::new_unchecked(value)
+					}));
+				}
+			} else {
+				self.slots = self.chunks.next()?.iter();
+			}
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+impl<T> core::fmt::Debug for Iter<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Iter")
+			.field("remaining", &self.remaining)
+			.finish_non_exhaustive()
+	}
+}
+
+/// An exclusive pinning iterator over a [`PinnedSlab`]. See [`PinnedSlab::iter_mut`].
+pub struct IterMut<'a, T> {
+	chunks: slice::IterMut<'a, Box<[Slot<T>]>>,
+	slots: slice::IterMut<'a, Slot<T>>,
+	key: usize,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+	type Item = (usize, Pin<&'a mut T>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(slot) = self.slots.next() {
+				self.key += 1;
+				if let Slot::Occupied(value) = slot {
+					self.remaining -= 1;
+					return Some((self.key - 1, unsafe {
+						//SAFETY: As above.
+						Pin::new_unchecked(value)
+					}));
+				}
+			} else {
+				self.slots = self.chunks.next()?.iter_mut();
+			}
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+impl<T> core::fmt::Debug for IterMut<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("IterMut")
+			.field("remaining", &self.remaining)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,375 @@
+//! A growable sequence of pinned values, stored in fixed-size blocks so that pushing never moves them.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	iter::FusedIterator,
+	mem::{self, MaybeUninit},
+	pin::Pin,
+	ptr, slice,
+};
+
+/// The number of values per block.
+const BLOCK_LEN: usize = 32;
+
+type Block<T> = Box<[MaybeUninit<T>]>;
+
+/// A vector that allocates in blocks of fixed size instead of reallocating,
+/// so that its values can be accessed as [`Pin<&mut T>`] without pinning the vector itself.
+///
+/// The accessors and iterators mirror the ones on [`PinnedPin<[T]>`](`crate::pinned_pin::PinnedPin`),
+/// see [`pinned_pin_pins_items`](`crate::pinned_pin_pins_items`).
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, pinned_vec::PinnedVec};
+///
+/// async fn task(value: u32) -> u32 {
+///     value
+/// }
+///
+/// let mut tasks: PinnedVec<_> = (0..100).map(task).collect();
+/// let first = tasks.get(0).map(|task| &*task as *const _);
+/// tasks.push(task(100));
+/// assert_eq!(tasks.get(0).map(|task| &*task as *const _), first);
+///
+/// let sum: u32 = tasks.iter_mut().map(block_on).sum();
+/// assert_eq!(sum, 5050);
+/// ```
+///
+/// > Unlike [`PinnedSlab`](`crate::pinned_slab::PinnedSlab`), this is dense and only shrinks at the end,
+/// > so indexing is a division and the iterators don't have to skip over vacant slots.
+pub struct PinnedVec<T> {
+	/// Each exactly [`BLOCK_LEN`] long. Only the first `len` slots are initialised.
+	blocks: Vec<Block<T>>,
+	len: usize,
+}
+
+impl<T> PinnedVec<T> {
+	/// Creates a new empty [`PinnedVec`], without allocating.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			blocks: Vec::new(),
+			len: 0,
+		}
+	}
+
+	/// The number of values.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether there are no values.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// The number of values that fit without allocating another block.
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		self.blocks.len() * BLOCK_LEN
+	}
+
+	/// Appends `value` and returns it pinned.
+	pub fn push(&mut self, value: T) -> Pin<&mut T> {
+		if self.len == self.capacity() {
+			self.blocks
+				.push((0..BLOCK_LEN).map(|_| MaybeUninit::uninit()).collect());
+		}
+		let index = self.len;
+		self.len += 1;
+		let value = self.slot(index).write(value);
+		unsafe {
+			//SAFETY: Values are only moved out if they are `Unpin`, and otherwise dropped in place.
+			Pin::new_unchecked(value)
+		}
+	}
+
+	/// Moves the last value out of the vector.
+	pub fn pop(&mut self) -> Option<T>
+	where
+		T: Unpin,
+	{
+		(self.len > 0).then(|| {
+			self.len -= 1;
+			let index = self.len;
+			unsafe {
+				//SAFETY: Initialised, and now excluded from `len`.
+				self.slot(index).assume_init_read()
+			}
+		})
+	}
+
+	/// Drops the values at `len` and after in place, back to front.
+	///
+	/// If one of them panics, the rest are dropped anyway.
+	pub fn truncate(&mut self, len: usize) {
+		/// Continues on unwind.
+		struct Rest<'a, T>(&'a mut PinnedVec<T>, usize);
+		impl<T> Drop for Rest<'_, T> {
+			fn drop(&mut self) {
+				self.0.truncate(self.1)
+			}
+		}
+
+		while self.len > len {
+			self.len -= 1;
+			let index = self.len;
+			let value = self.slot(index).as_mut_ptr();
+			let rest = Rest(self, len);
+			unsafe {
+				//SAFETY: Initialised, and now excluded from `len`.
+				ptr::drop_in_place(value)
+			}
+			mem::forget(rest);
+		}
+	}
+
+	/// Drops all values in place.
+	///
+	/// The blocks are kept for reuse.
+	pub fn clear(&mut self) {
+		self.truncate(0)
+	}
+
+	/// Retrieves a shared reference to a pinned value,
+	/// or [`None`] exactly whenever `index >= len`.
+	#[must_use]
+	pub fn get(&self, index: usize) -> Option<Pin<&T>> {
+		(index < self.len).then(|| unsafe {
+			//SAFETY: Initialised and pinned.
+			Pin::new_unchecked(self.blocks[index / BLOCK_LEN][index % BLOCK_LEN].assume_init_ref())
+		})
+	}
+
+	/// Retrieves an exclusive reference to a pinned value,
+	/// or [`None`] exactly whenever `index >= len`.
+	#[must_use]
+	pub fn get_mut(&mut self, index: usize) -> Option<Pin<&mut T>> {
+		(index < self.len).then(|| unsafe {
+			//SAFETY: As above.
+			Pin::new_unchecked(self.slot(index).assume_init_mut())
+		})
+	}
+
+	/// A sharing pinning iterator over the values.
+	#[must_use]
+	pub fn iter(&self) -> Iter<'_, T> {
+		let (blocks, back) = self.split_blocks();
+		Iter {
+			blocks: blocks.iter(),
+			front: [].iter(),
+			back: back.iter(),
+			remaining: self.len,
+		}
+	}
+
+	/// An exclusive pinning iterator over the values.
+	#[must_use]
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		let remaining = self.len;
+		let (blocks, back) = self.split_blocks_mut();
+		IterMut {
+			blocks: blocks.iter_mut(),
+			front: [].iter_mut(),
+			back: back.iter_mut(),
+			remaining,
+		}
+	}
+
+	fn slot(&mut self, index: usize) -> &mut MaybeUninit<T> {
+		&mut self.blocks[index / BLOCK_LEN][index % BLOCK_LEN]
+	}
+
+	/// The full blocks and the initialised part of the last one.
+	fn split_blocks(&self) -> (&[Block<T>], &[MaybeUninit<T>]) {
+		let blocks = &self.blocks[..(self.len + BLOCK_LEN - 1) / BLOCK_LEN];
+		match blocks.split_last() {
+			Some((last, full)) if self.len % BLOCK_LEN != 0 => {
+				(full, &last[..self.len % BLOCK_LEN])
+			}
+			_ => (blocks, &[]),
+		}
+	}
+
+	/// The full blocks and the initialised part of the last one.
+	fn split_blocks_mut(&mut self) -> (&mut [Block<T>], &mut [MaybeUninit<T>]) {
+		let len = self.len;
+		let blocks = &mut self.blocks[..(len + BLOCK_LEN - 1) / BLOCK_LEN];
+		if len % BLOCK_LEN == 0 {
+			return (blocks, &mut []);
+		}
+		let (last, full) = blocks.split_last_mut().expect("unreachable");
+		(full, &mut last[..len % BLOCK_LEN])
+	}
+}
+
+impl<T> Drop for PinnedVec<T> {
+	fn drop(&mut self) {
+		self.clear()
+	}
+}
+
+impl<T> Default for PinnedVec<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Extend<T> for PinnedVec<T> {
+	fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+		for value in iter {
+			self.push(value);
+		}
+	}
+}
+
+impl<T> FromIterator<T> for PinnedVec<T> {
+	fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+		let mut vec = Self::new();
+		vec.extend(iter);
+		vec
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for PinnedVec<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_list()
+			.entries(self.iter().map(Pin::get_ref))
+			.finish()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a PinnedVec<T> {
+	type Item = Pin<&'a T>;
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+impl<'a, T> IntoIterator for &'a mut PinnedVec<T> {
+	type Item = Pin<&'a mut T>;
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter_mut()
+	}
+}
+
+/// A sharing pinning iterator over a [`PinnedVec`]. See [`PinnedVec::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a, T> {
+	/// These fields must not be public, as they don't guarantee pinning by themselves.
+	blocks: slice::Iter<'a, Block<T>>,
+	front: slice::Iter<'a, MaybeUninit<T>>,
+	back: slice::Iter<'a, MaybeUninit<T>>,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+	type Item = Pin<&'a T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.front.next() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: Initialised and pinned.
+					Pin::new_unchecked(value.assume_init_ref())
+				});
+			}
+			self.front = match self.blocks.next() {
+				Some(block) => block.iter(),
+				None => mem::replace(&mut self.back, [].iter()),
+			};
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.back.next_back() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: As above.
+					Pin::new_unchecked(value.assume_init_ref())
+				});
+			}
+			self.back = match self.blocks.next_back() {
+				Some(block) => block.iter(),
+				None => mem::replace(&mut self.front, [].iter()),
+			};
+		}
+		None
+	}
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+
+/// An exclusive pinning iterator over a [`PinnedVec`]. See [`PinnedVec::iter_mut`].
+#[derive(Debug)]
+pub struct IterMut<'a, T> {
+	/// These fields must not be public, as they don't guarantee pinning by themselves.
+	blocks: slice::IterMut<'a, Block<T>>,
+	front: slice::IterMut<'a, MaybeUninit<T>>,
+	back: slice::IterMut<'a, MaybeUninit<T>>,
+	remaining: usize,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+	type Item = Pin<&'a mut T>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.front.next() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: Initialised and pinned.
+					Pin::new_unchecked(value.assume_init_mut())
+				});
+			}
+			self.front = match self.blocks.next() {
+				Some(block) => block.iter_mut(),
+				None => mem::replace(&mut self.back, [].iter_mut()),
+			};
+		}
+		None
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.remaining, Some(self.remaining))
+	}
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		while self.remaining > 0 {
+			if let Some(value) = self.back.next_back() {
+				self.remaining -= 1;
+				return Some(unsafe {
+					//SAFETY: As above.
+					Pin::new_unchecked(value.assume_init_mut())
+				});
+			}
+			self.back = match self.blocks.next_back() {
+				Some(block) => block.iter_mut(),
+				None => mem::replace(&mut self.front, [].iter_mut()),
+			};
+		}
+		None
+	}
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
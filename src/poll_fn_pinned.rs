@@ -0,0 +1,69 @@
+//! A [`Future`] made from a closure over pinned state.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use pin_project::pin_project;
+
+/// Creates a [`PollFnPinned`] that polls by calling `f` with pinning access to `state`.
+///
+/// This is an ad-hoc [`Future`] over [`!Unpin`](`Unpin`) state, like another [`Future`] or a [`PinnedPin`](`crate::pinned_pin::PinnedPin`),
+/// without writing out a struct and [`Future`] implementation:
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::{future::Future, task::Poll};
+/// use unpin_choices_dsa::poll_fn_pinned::poll_fn_pinned;
+///
+/// # async fn example(inner: impl Future<Output = u8>) {
+/// // Counts how often `inner` is polled.
+/// let mut polls = 0;
+/// let output = poll_fn_pinned(inner, |inner, cx| {
+///     polls += 1;
+///     inner.poll(cx)
+/// })
+/// .await;
+/// # }
+/// ```
+pub fn poll_fn_pinned<S, F: FnMut(Pin<&mut S>, &mut Context<'_>) -> Poll<T>, T>(
+	state: S,
+	f: F,
+) -> PollFnPinned<S, F> {
+	PollFnPinned { state, f }
+}
+
+/// A [`Future`] that polls by calling `f` with pinning access to `state`.
+///
+/// > `f` itself isn't pinned, as it's an [`FnMut`] that is only called through `&mut`.
+#[pin_project]
+#[derive(Debug)]
+pub struct PollFnPinned<S, F> {
+	#[pin]
+	state: S,
+	f: F,
+}
+
+impl<S, F> PollFnPinned<S, F> {
+	/// Gives pinning exclusive access to the state.
+	#[must_use]
+	pub fn state_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+		self.project().state
+	}
+
+	/// Unwraps the state.
+	#[must_use]
+	pub fn into_state(self) -> S {
+		self.state
+	}
+}
+
+impl<S, F: FnMut(Pin<&mut S>, &mut Context<'_>) -> Poll<T>, T> Future for PollFnPinned<S, F> {
+	type Output = T;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		(this.f)(this.state, cx)
+	}
+}
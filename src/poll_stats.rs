@@ -0,0 +1,257 @@
+//! Fixed-bucket poll metrics that need neither allocation nor a lock.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Collects histograms of polls per wake and of constituent [`Future`](`core::future::Future`)s polled per poll.
+///
+/// All recording methods take `&self` and are lock-free, so a `static` [`PollStats`] can be fed from
+/// both a combinator's poll and its [`Waker`](`core::task::Waker`)s, on any thread.
+///
+/// The combinators do that through [`PollObserver`]:
+///
+/// - [`JoinFuture::observed_by`](`crate::join_future::JoinFuture::observed_by`) and
+///   [`AnyFuture::observed_by`](`crate::any_future::AnyFuture::observed_by`) record their polls,
+/// - [`Instrumented::observed_by`](`crate::instrumented::Instrumented::observed_by`) records the wakes of whatever it wraps.
+///
+/// Values are sorted into `N` power-of-two buckets, see [`Histogram`].
+///
+/// > Each recording is a few relaxed atomic operations. That's cheap enough to keep around in firmware,
+/// > but a [`.snapshot()`](`PollStats::snapshot`) taken while recording isn't necessarily consistent across counters.
+/// >
+/// > Targets without atomic read-modify-write operations (like `thumbv6m-none-eabi`) need the `"critical-section"` feature,
+/// > in which case each recording briefly enters a critical section instead.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::{future::{ready, Future}, pin::Pin, task::Poll};
+/// use unpin_choices_dsa::{join_future::JoinFuture, poll_stats::PollStats, waker::noop_context};
+///
+/// static STATS: PollStats<4> = PollStats::new();
+///
+/// let mut join = JoinFuture::new([ready(1), ready(2)]).observed_by(&STATS);
+/// assert_eq!(Pin::new(&mut join).poll(&mut noop_context()), Poll::Ready([1, 2]));
+///
+/// let stats = STATS.snapshot();
+/// assert_eq!(stats.polls, 1);
+/// assert_eq!(stats.polled_per_poll.buckets, [0, 0, 1, 0]);
+/// ```
+#[derive(Debug)]
+pub struct PollStats<const N: usize> {
+	polls: AtomicUsize,
+	wakes: AtomicUsize,
+	polls_since_wake: AtomicUsize,
+	polls_per_wake: [AtomicUsize; N],
+	polled_per_poll: [AtomicUsize; N],
+}
+
+/// A point-in-time copy of a [`PollStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PollStatsSnapshot<const N: usize> {
+	/// The total number of recorded polls.
+	pub polls: usize,
+	/// The total number of recorded wakes.
+	pub wakes: usize,
+	/// How often the combinator was polled between consecutive wakes.
+	pub polls_per_wake: Histogram<N>,
+	/// How many constituent [`Future`](`core::future::Future`)s were polled each time.
+	pub polled_per_poll: Histogram<N>,
+}
+
+/// A histogram with `N` power-of-two buckets.
+///
+/// Bucket `0` counts the value `0`, bucket `i` counts values in `2^(i-1)..2^i`,
+/// and the last bucket also counts all larger values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Histogram<const N: usize> {
+	/// The number of values recorded into each bucket.
+	pub buckets: [usize; N],
+}
+
+#[allow(clippy::declare_interior_mutable_const)] // Only used to initialise arrays.
+const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+impl<const N: usize> PollStats<N> {
+	/// Creates a new empty [`PollStats`].
+	///
+	/// # Panics
+	///
+	/// Iff `N` is `0`.
+	#[must_use]
+	pub const fn new() -> Self {
+		assert!(N > 0, "`PollStats` needs at least one bucket.");
+		Self {
+			polls: AtomicUsize::new(0),
+			wakes: AtomicUsize::new(0),
+			polls_since_wake: AtomicUsize::new(0),
+			polls_per_wake: [ZERO; N],
+			polled_per_poll: [ZERO; N],
+		}
+	}
+
+	/// Records one poll of the observed combinator, during which `polled` constituent [`Future`](`core::future::Future`)s were polled.
+	pub fn record_poll(&self, polled: usize) {
+		increment(&self.polls);
+		increment(&self.polls_since_wake);
+		increment(&self.polled_per_poll[Histogram::<N>::bucket(polled)]);
+	}
+
+	/// Records a wake of the observed combinator, closing the current polls-per-wake count.
+	///
+	/// Polls before the first wake count towards it, too.
+	pub fn record_wake(&self) {
+		increment(&self.wakes);
+		let polls = take(&self.polls_since_wake);
+		increment(&self.polls_per_wake[Histogram::<N>::bucket(polls)]);
+	}
+
+	/// Copies the current counters.
+	#[must_use]
+	pub fn snapshot(&self) -> PollStatsSnapshot<N> {
+		PollStatsSnapshot {
+			polls: self.polls.load(Ordering::Relaxed),
+			wakes: self.wakes.load(Ordering::Relaxed),
+			polls_per_wake: Histogram::load(&self.polls_per_wake),
+			polled_per_poll: Histogram::load(&self.polled_per_poll),
+		}
+	}
+
+	/// Resets all counters to zero.
+	pub fn reset(&self) {
+		for counter in [&self.polls, &self.wakes, &self.polls_since_wake]
+			.into_iter()
+			.chain(&self.polls_per_wake)
+			.chain(&self.polled_per_poll)
+		{
+			counter.store(0, Ordering::Relaxed)
+		}
+	}
+}
+
+impl<const N: usize> Default for PollStats<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(target_has_atomic = "ptr")]
+fn increment(counter: &AtomicUsize) {
+	counter.fetch_add(1, Ordering::Relaxed);
+}
+
+#[cfg(target_has_atomic = "ptr")]
+fn take(counter: &AtomicUsize) -> usize {
+	counter.swap(0, Ordering::Relaxed)
+}
+
+#[cfg(not(target_has_atomic = "ptr"))]
+fn increment(counter: &AtomicUsize) {
+	critical_section::with(|_| {
+		counter.store(
+			counter.load(Ordering::Relaxed).wrapping_add(1),
+			Ordering::Relaxed,
+		);
+	});
+}
+
+#[cfg(not(target_has_atomic = "ptr"))]
+fn take(counter: &AtomicUsize) -> usize {
+	critical_section::with(|_| {
+		let value = counter.load(Ordering::Relaxed);
+		counter.store(0, Ordering::Relaxed);
+		value
+	})
+}
+
+/// The hooks through which combinators feed a [`PollStats`] without naming its bucket count.
+///
+/// This is implemented by [`PollStats`], but can also forward the events elsewhere.
+pub trait PollObserver: Sync + core::fmt::Debug {
+	/// See [`PollStats::record_poll`].
+	fn record_poll(&self, polled: usize);
+
+	/// See [`PollStats::record_wake`].
+	fn record_wake(&self);
+}
+
+impl<const N: usize> PollObserver for PollStats<N> {
+	fn record_poll(&self, polled: usize) {
+		self.record_poll(polled);
+	}
+
+	fn record_wake(&self) {
+		self.record_wake();
+	}
+}
+
+impl<const N: usize> Histogram<N> {
+	/// The index of the bucket `value` is counted in.
+	#[must_use]
+	pub fn bucket(value: usize) -> usize {
+		let bucket = (usize::BITS - value.leading_zeros()) as usize;
+		bucket.min(N - 1)
+	}
+
+	/// The smallest value counted in the bucket at `index`.
+	///
+	/// # Panics
+	///
+	/// Iff `index >= N`.
+	#[must_use]
+	pub fn bucket_start(index: usize) -> usize {
+		assert!(
+			index < N,
+			"Bucket index {index} out of bounds for {N} buckets."
+		);
+		match index {
+			0 => 0,
+			// Buckets past the size of `usize` stay empty.
+			index if index > usize::BITS as usize => usize::MAX,
+			index => 1 << (index - 1),
+		}
+	}
+
+	/// The total number of recorded values.
+	#[must_use]
+	pub fn total(&self) -> usize {
+		self.buckets.iter().sum()
+	}
+
+	fn load(counters: &[AtomicUsize; N]) -> Self {
+		let mut buckets = [0; N];
+		for (bucket, counter) in buckets.iter_mut().zip(counters) {
+			*bucket = counter.load(Ordering::Relaxed);
+		}
+		Self { buckets }
+	}
+}
+
+#[cfg(all(test, feature = "alloc", target_has_atomic = "ptr"))]
+mod tests {
+	use super::PollStats;
+	use crate::{
+		instrumented::instrumented, join_future::JoinFuture, waker::noop_context,
+		yield_now::yield_now,
+	};
+	use core::{future::Future, pin::Pin, task::Poll};
+
+	#[test]
+	fn observed_join_records_polls_and_wakes() {
+		static STATS: PollStats<4> = PollStats::new();
+
+		let mut join =
+			instrumented(JoinFuture::new([yield_now(), yield_now()]).observed_by(&STATS))
+				.observed_by(&STATS);
+		assert!(Pin::new(&mut join).poll(&mut noop_context()).is_pending());
+		assert_eq!(
+			Pin::new(&mut join).poll(&mut noop_context()),
+			Poll::Ready([(), ()])
+		);
+
+		let stats = STATS.snapshot();
+		assert_eq!(stats.polls, 2);
+		// Each `yield_now` wakes once.
+		assert_eq!(stats.wakes, 2);
+		assert_eq!(stats.polled_per_poll.buckets, [0, 0, 2, 0]);
+		assert_eq!(stats.polls_per_wake.total(), 2);
+	}
+}
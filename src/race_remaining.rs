@@ -0,0 +1,82 @@
+//! A [`Future`] that races [`Unpin`] [`Future`]s and then hands back those that didn't complete.
+
+use alloc::vec::Vec;
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Creates a [`Future`] that completes when any [`Future`] in `futures` completes.
+///
+/// The output is the output of that [`Future`] along with all others, in their original order,
+/// so that they can be raced again or driven further in another way.
+///
+/// [`!Unpin`](`Unpin`) [`Future`]s can be used by pinning them first, for example as [`DynFuture`](`crate::dyn_future::DynFuture`)s.
+pub fn race_remaining<F: Future + Unpin>(futures: impl Into<Vec<F>>) -> RaceRemaining<F> {
+	RaceRemaining::new(futures.into())
+}
+
+/// A [`Future`] that completes when any [`Future`] in `futures` completes,
+/// and then outputs the remaining ones alongside that [`Future`]'s output.
+///
+/// Like [`AnyFuture`](`crate::any_future::AnyFuture`), it starts each poll after the [`Future`] the previous poll started at.
+///
+/// > Handing back the losers requires moving them, hence the [`Unpin`] bound.
+/// > Boxed [`Future`]s only move their pointer, which is cheap.
+#[derive(Debug)]
+pub struct RaceRemaining<F> {
+	/// Where the next poll starts.
+	cursor: usize,
+	/// [`None`] once the remaining [`Future`]s were handed out.
+	futures: Option<Vec<F>>,
+}
+
+impl<F: Future + Unpin> RaceRemaining<F> {
+	/// Creates a new instance of [`RaceRemaining`] from the given `futures`.
+	#[must_use]
+	pub fn new(futures: Vec<F>) -> Self {
+		Self {
+			cursor: 0,
+			futures: Some(futures),
+		}
+	}
+}
+
+impl<F> Unpin for RaceRemaining<F> {}
+
+impl<F: Future + Unpin> Future for RaceRemaining<F> {
+	type Output = (F::Output, Vec<F>);
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = &mut *self;
+		let futures = this
+			.futures
+			.as_mut()
+			.expect("`RaceRemaining` was previously completed.");
+
+		let len = futures.len();
+		let start = if this.cursor >= len { 0 } else { this.cursor };
+		this.cursor = start + 1;
+
+		for index in (start..len).chain(0..start) {
+			if let Poll::Ready(output) = Pin::new(&mut futures[index]).poll(cx) {
+				let mut futures = this.futures.take().expect("unreachable");
+				// Keeps the remaining ones in order. (The completed [`Future`] is dropped here.)
+				futures.remove(index);
+				return Poll::Ready((output, futures));
+			}
+		}
+		Poll::Pending
+	}
+}
+
+/// The remaining [`Future`]s are moved out on completion, so that's a free completion flag.
+#[cfg(feature = "futures-core")]
+impl<F: Future + Unpin> FusedFuture for RaceRemaining<F> {
+	fn is_terminated(&self) -> bool {
+		self.futures.is_none()
+	}
+}
@@ -1,10 +1,15 @@
-//! A [`Future`] adaptor that is either immediately ready or never completes.
+//! [`Future`] adaptors that are either immediately ready or never complete (unless cancelled).
 
+use crate::waker::noop_context;
 use core::{
+	fmt::{self, Display, Formatter},
 	future::Future,
 	pin::Pin,
 	task::{Context, Poll},
 };
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
 
 /// Returns a [`Future`] that is either ready immediately or,
 /// iff `future` is not ready when polled, will never complete at all.
@@ -12,25 +17,127 @@ pub fn ready_or_never<F>(future: F) -> ReadyOrNever<F> {
 	ReadyOrNever(Some(future))
 }
 
-/// A [`Future`] that is always ready, but may return [`None`].
+/// A [`Future`] that is either ready on its first poll or never completes.
+///
+/// Polling it again after that returns [`Poll::Pending`] without doing anything.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ReadyOrNever<F>(Option<F>);
 
+impl<F> ReadyOrNever<F> {
+	/// Whether the inner [`Future`] was polled and dropped already.
+	///
+	/// If so, this [`ReadyOrNever`] either completed already or never will, so there's no point in polling it again.
+	#[must_use]
+	pub fn is_consumed(&self) -> bool {
+		self.0.is_none()
+	}
+}
+
 impl<F> Unpin for ReadyOrNever<F> {}
 
 impl<F: Future> Future for ReadyOrNever<F> {
 	type Output = F::Output;
 
-	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+	fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
 		unsafe {
 			let output = Pin::map_unchecked_mut(self.as_mut(), |this| &mut this.0)
 				.as_pin_mut()
-				// Alternatively, we could pass a fake context here that just doesn't schedule anything.
-				// We'd still have to drop the inner future before returning regardless, though.
-				.map_or(Poll::Pending, |inner| inner.poll(cx));
+				// The inner future is dropped right after, so it gets a detached context.
+				// Otherwise, it might register a wake-up that would be spurious.
+				.map_or(Poll::Pending, |inner| {
+					inner.poll(&mut noop_context())
+				});
 			// We *have* to drop the inner [`Future`] now, since we can't guarantee it won't stay in place.
 			Pin::get_unchecked_mut(self).0 = None;
 			output
 		}
 	}
 }
+
+/// The inner [`Future`] is dropped after the first poll, after which this one either completed or never will.
+/// Either way, there's no point in polling it again.
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for ReadyOrNever<F> {
+	fn is_terminated(&self) -> bool {
+		self.0.is_none()
+	}
+}
+
+/// Returns a [`Future`] that is either ready immediately or,
+/// iff `future` is not ready when polled, completes with [`Cancelled`] once `cancel` completes.
+///
+/// `cancel` can be any [`Future`], like a [`Sleep`](`crate::sleep::Sleep`) or a channel receiver.
+pub fn ready_or_cancel<F: Future, C: Future>(future: F, cancel: C) -> ReadyOrCancel<F, C> {
+	ReadyOrCancel {
+		future: Some(future),
+		cancel,
+		done: false,
+	}
+}
+
+/// The error returned when an operation was cancelled before it completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("operation was cancelled")
+	}
+}
+
+/// A [`Future`] that is either ready on its first poll or completes with [`Cancelled`] once `cancel` completes.
+///
+/// > Compared to [`ReadyOrNever`], this lets the surrounding task end (and release its resources)
+/// > instead of staying pending forever.
+#[pin_project]
+#[derive(Debug)]
+pub struct ReadyOrCancel<F, C> {
+	/// [`None`] after the first poll.
+	#[pin]
+	future: Option<F>,
+	#[pin]
+	cancel: C,
+	done: bool,
+}
+
+impl<F, C> ReadyOrCancel<F, C> {
+	/// Whether the inner [`Future`] was polled and dropped already.
+	#[must_use]
+	pub fn is_consumed(&self) -> bool {
+		self.future.is_none()
+	}
+}
+
+impl<F: Future, C: Future> Future for ReadyOrCancel<F, C> {
+	type Output = Result<F::Output, Cancelled>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		assert!(!*this.done, "`ReadyOrCancel` was previously completed.");
+
+		if let Some(future) = this.future.as_mut().as_pin_mut() {
+			// The inner future is dropped right after, so it gets a detached context.
+			let output = future.poll(&mut noop_context());
+			this.future.set(None);
+			if let Poll::Ready(output) = output {
+				*this.done = true;
+				return Poll::Ready(Ok(output));
+			}
+		}
+
+		let cancelled = this.cancel.poll(cx).is_ready();
+		*this.done = cancelled;
+		if cancelled {
+			Poll::Ready(Err(Cancelled))
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, C: Future> FusedFuture for ReadyOrCancel<F, C> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
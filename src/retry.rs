@@ -0,0 +1,226 @@
+//! A [`Future`] that restarts a fallible operation according to a [`RetryPolicy`].
+
+use crate::{
+	sleep::{self, Delay},
+	timer::Timer,
+};
+use core::{
+	future::Future,
+	ops::Add,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Decides whether and after which delay to retry a failed attempt.
+///
+/// This is implemented for closures of the form `|attempt, error| -> Option<delay>`.
+pub trait RetryPolicy<E> {
+	/// The type of delays, which is added to a [`Timer`]'s instants.
+	type Delay;
+
+	/// Called after each failed attempt, where `attempt` counts from `1`.
+	///
+	/// Returns how long to wait before the next attempt, or [`None`] to give up with `error`.
+	fn on_error(&mut self, attempt: usize, error: &E) -> Option<Self::Delay>;
+}
+
+impl<E, D, P: FnMut(usize, &E) -> Option<D>> RetryPolicy<E> for P {
+	type Delay = D;
+
+	fn on_error(&mut self, attempt: usize, error: &E) -> Option<D> {
+		self(attempt, error)
+	}
+}
+
+/// Retries up to `max_retries` times, waiting `delay` each time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedDelay<D> {
+	/// How long to wait before each retry.
+	pub delay: D,
+	/// How often to retry at most.
+	pub max_retries: usize,
+}
+
+impl<E, D: Copy> RetryPolicy<E> for FixedDelay<D> {
+	type Delay = D;
+
+	fn on_error(&mut self, attempt: usize, _: &E) -> Option<D> {
+		(attempt <= self.max_retries).then_some(self.delay)
+	}
+}
+
+/// Retries up to `max_retries` times, doubling the delay each time, starting at `initial` and capped at `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExponentialBackoff {
+	/// How long to wait before the first retry.
+	pub initial: Duration,
+	/// The longest delay between retries.
+	pub max: Duration,
+	/// How often to retry at most.
+	pub max_retries: usize,
+}
+
+impl<E> RetryPolicy<E> for ExponentialBackoff {
+	type Delay = Duration;
+
+	fn on_error(&mut self, attempt: usize, _: &E) -> Option<Duration> {
+		(attempt <= self.max_retries).then(|| {
+			u32::try_from(attempt - 1)
+				.ok()
+				.and_then(|shift| 1_u32.checked_shl(shift))
+				.and_then(|factor| self.initial.checked_mul(factor))
+				.map_or(self.max, |delay| delay.min(self.max))
+		})
+	}
+}
+
+/// Creates a [`Retry`] that calls `make_future` for each attempt and waits on the driver registered with [`set_driver`](`crate::sleep::set_driver`) in between.
+///
+/// # Panics
+///
+/// Iff no driver was registered.
+pub fn retry<M, Fut, T, E, P>(make_future: M, policy: P) -> Retry<M, Fut, P>
+where
+	M: FnMut() -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	P: RetryPolicy<E, Delay = Duration>,
+{
+	Retry::new(sleep::expect_driver(), make_future, policy)
+}
+
+/// Creates a [`Retry`] that calls `make_future` for each attempt and waits on `timer` in between.
+pub fn retry_on<Ti, M, Fut, T, E, P>(timer: Ti, make_future: M, policy: P) -> Retry<M, Fut, P, Ti>
+where
+	Ti: Timer,
+	M: FnMut() -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	P: RetryPolicy<E>,
+	Ti::Instant: Add<P::Delay, Output = Ti::Instant>,
+{
+	Retry::new(timer, make_future, policy)
+}
+
+/// A [`Future`] that runs attempts made by `make_future` until one succeeds or `policy` gives up.
+///
+/// Each attempt is dropped in place as soon as it fails, before the delay.
+///
+/// > Only the deadline is stored while waiting, so the [`Timer`] doesn't have to be [`Clone`].
+#[pin_project]
+pub struct Retry<M, Fut, P, Ti: Timer = &'static dyn Delay> {
+	make_future: M,
+	policy: P,
+	timer: Ti,
+	attempt: usize,
+	#[pin]
+	state: State<Fut, Ti::Instant>,
+}
+
+#[pin_project(project = StateProj)]
+enum State<Fut, I> {
+	Running(#[pin] Fut),
+	Waiting(I),
+	Done,
+}
+
+impl<M, Fut, P, Ti: Timer> Retry<M, Fut, P, Ti>
+where
+	M: FnMut() -> Fut,
+{
+	/// Creates a new instance of [`Retry`], starting the first attempt right away.
+	#[must_use]
+	pub fn new(timer: Ti, mut make_future: M, policy: P) -> Self {
+		Self {
+			state: State::Running(make_future()),
+			make_future,
+			policy,
+			timer,
+			attempt: 1,
+		}
+	}
+
+	/// The number of the current attempt, counting from `1`.
+	#[must_use]
+	pub fn attempt(&self) -> usize {
+		self.attempt
+	}
+
+	/// The [`RetryPolicy`] in use.
+	#[must_use]
+	pub fn policy(&self) -> &P {
+		&self.policy
+	}
+}
+
+impl<M, Fut, T, E, P, Ti> Future for Retry<M, Fut, P, Ti>
+where
+	M: FnMut() -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	P: RetryPolicy<E>,
+	Ti: Timer,
+	Ti::Instant: Add<P::Delay, Output = Ti::Instant>,
+{
+	type Output = Result<T, E>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		loop {
+			match this.state.as_mut().project() {
+				StateProj::Running(future) => match future.poll(cx) {
+					Poll::Pending => return Poll::Pending,
+					Poll::Ready(Ok(value)) => {
+						this.state.set(State::Done);
+						return Poll::Ready(Ok(value));
+					}
+					Poll::Ready(Err(error)) => match this.policy.on_error(*this.attempt, &error) {
+						None => {
+							this.state.set(State::Done);
+							return Poll::Ready(Err(error));
+						}
+						Some(delay) => {
+							let deadline = this.timer.now() + delay;
+							this.state.set(State::Waiting(deadline));
+						}
+					},
+				},
+				StateProj::Waiting(&mut deadline) => {
+					if this.timer.now() < deadline {
+						this.timer.wake_at(deadline, cx.waker());
+						return Poll::Pending;
+					}
+					*this.attempt += 1;
+					this.state.set(State::Running((this.make_future)()));
+				}
+				StateProj::Done => panic!("`Retry` was previously completed."),
+			}
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<M, Fut, T, E, P, Ti> FusedFuture for Retry<M, Fut, P, Ti>
+where
+	M: FnMut() -> Fut,
+	Fut: Future<Output = Result<T, E>>,
+	P: RetryPolicy<E>,
+	Ti: Timer,
+	Ti::Instant: Add<P::Delay, Output = Ti::Instant>,
+{
+	fn is_terminated(&self) -> bool {
+		matches!(self.state, State::Done)
+	}
+}
+
+/// Shows only the progress, since most of the parts aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<M, Fut, P, Ti: Timer> core::fmt::Debug for Retry<M, Fut, P, Ti> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Retry")
+			.field("attempt", &self.attempt)
+			.field("waiting", &matches!(self.state, State::Waiting(_)))
+			.field("done", &matches!(self.state, State::Done))
+			.finish_non_exhaustive()
+	}
+}
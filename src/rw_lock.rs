@@ -0,0 +1,544 @@
+//! An asynchronous readers-writer lock that queues waiting [`Future`]s intrusively, with a selectable [`Fairness`].
+
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	cell::UnsafeCell,
+	future::Future,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// In which order an [`RwLock`] grants waiting readers and writers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Fairness {
+	/// Strictly in arrival order, where consecutive readers are granted together.
+	///
+	/// No reader acquires the lock while anyone is waiting.
+	#[default]
+	Fifo,
+	/// Waiting writers go first, and readers are granted all together once none are left.
+	///
+	/// No reader acquires the lock while a writer is waiting, but writers can starve readers.
+	WriterPreferred,
+}
+
+/// The demand of a waiting reader.
+const READ: usize = 1;
+/// The demand of a waiting writer.
+const WRITE: usize = usize::MAX;
+
+/// An asynchronous readers-writer lock around a `T`, which allows either many readers or one writer at a time.
+///
+/// Waiting [`Future`]s queue up without allocating, and the lock is handed directly to the next ones when released,
+/// in the order given by its [`Fairness`].
+///
+/// If the [`RwLock`] is pinned, then `T` is too, and [`.write_pinned()`](`RwLock::write_pinned`) grants [`Pin<&mut T>`].
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, rw_lock::RwLock};
+///
+/// let rw_lock = RwLock::new(1);
+/// block_on(async {
+///     let (a, b) = (rw_lock.read().await, rw_lock.read().await);
+///     assert_eq!(*a + *b, 2);
+///     assert!(rw_lock.try_write().is_none());
+///     drop((a, b));
+///
+///     *rw_lock.write().await += 1;
+/// });
+/// assert_eq!(rw_lock.into_inner(), 2);
+/// ```
+pub struct RwLock<T: ?Sized> {
+	state: SpinLock<State>,
+	fairness: Fairness,
+	/// Pinned, structurally.
+	value: UnsafeCell<T>,
+}
+
+struct State {
+	readers: usize,
+	writer: bool,
+	/// Writers that are linked into `waiters`, for [`Fairness::WriterPreferred`].
+	waiting_writers: usize,
+	waiters: WaiterList,
+}
+
+/// # Safety notes
+///
+/// Access to `value` is synchronised through `state`, so it's either shared or exclusive to one thread.
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+	/// Creates a new unlocked [`RwLock`] around `value`, with [`Fairness::Fifo`].
+	#[must_use]
+	pub const fn new(value: T) -> Self {
+		Self::with_fairness(value, Fairness::Fifo)
+	}
+
+	/// Creates a new unlocked [`RwLock`] around `value`.
+	#[must_use]
+	pub const fn with_fairness(value: T, fairness: Fairness) -> Self {
+		Self {
+			state: SpinLock::new(State {
+				readers: 0,
+				writer: false,
+				waiting_writers: 0,
+				waiters: WaiterList::new(),
+			}),
+			fairness,
+			value: UnsafeCell::new(value),
+		}
+	}
+
+	/// Unwraps the value.
+	pub fn into_inner(self) -> T {
+		self.value.into_inner()
+	}
+}
+
+impl<T: ?Sized> RwLock<T> {
+	/// The [`Fairness`] this [`RwLock`] was created with.
+	#[must_use]
+	pub fn fairness(&self) -> Fairness {
+		self.fairness
+	}
+
+	/// Returns a [`Future`] that completes with an [`RwLockReadGuard`] once shared access is granted.
+	#[must_use]
+	pub fn read(&self) -> Read<'_, T> {
+		Read {
+			acquire: Acquire::new(self, READ),
+		}
+	}
+
+	/// Returns a [`Future`] that completes with an [`RwLockWriteGuard`] once exclusive access is granted.
+	#[must_use]
+	pub fn write(&self) -> Write<'_, T> {
+		Write {
+			acquire: Acquire::new(self, WRITE),
+		}
+	}
+
+	/// Like [`.write()`](`RwLock::write`), but the guard grants [`Pin<&mut T>`].
+	#[must_use]
+	pub fn write_pinned(self: Pin<&Self>) -> WritePinned<'_, T> {
+		WritePinned {
+			acquire: Acquire::new(self.get_ref(), WRITE),
+		}
+	}
+
+	/// Acquires shared access only if that's possible without waiting.
+	#[must_use]
+	pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+		self.try_acquire(&mut self.state.lock(), READ)
+			.then(|| RwLockReadGuard::new(self))
+	}
+
+	/// Acquires exclusive access only if that's possible without waiting.
+	#[must_use]
+	pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+		self.try_acquire(&mut self.state.lock(), WRITE)
+			.then(|| RwLockWriteGuard::new(self))
+	}
+
+	/// Like [`.try_write()`](`RwLock::try_write`), but the guard grants [`Pin<&mut T>`].
+	#[must_use]
+	pub fn try_write_pinned(self: Pin<&Self>) -> Option<PinnedRwLockWriteGuard<'_, T>> {
+		self.get_ref()
+			.try_write()
+			.map(|guard| PinnedRwLockWriteGuard { guard })
+	}
+
+	/// Accesses the value through an exclusive borrow, which needs no locking.
+	pub fn get_mut(&mut self) -> &mut T {
+		self.value.get_mut()
+	}
+
+	/// Accesses the pinned value through an exclusive borrow, which needs no locking.
+	#[must_use]
+	pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: `value` is structurally pinned.
+			self.map_unchecked_mut(|this| this.value.get_mut())
+		}
+	}
+
+	/// Acquires the lock for `demand` if that doesn't skip ahead of waiters that should go first.
+	fn try_acquire(&self, state: &mut State, demand: usize) -> bool {
+		let acquired = if demand == WRITE {
+			!state.writer && state.readers == 0 && state.waiters.len() == 0
+		} else {
+			!state.writer
+				&& match self.fairness {
+					Fairness::Fifo => state.waiters.len() == 0,
+					Fairness::WriterPreferred => state.waiting_writers == 0,
+				}
+		};
+		if acquired {
+			if demand == WRITE {
+				state.writer = true;
+			} else {
+				state.readers += 1;
+			}
+		}
+		acquired
+	}
+
+	/// Hands the lock to the next waiter if possible, and returns its [`Waker`].
+	fn grant_next(&self, state: &mut State) -> Option<Waker> {
+		if state.writer {
+			return None;
+		}
+		let writer_next = match self.fairness {
+			Fairness::Fifo => state.waiters.front_demand()? == WRITE,
+			Fairness::WriterPreferred => state.waiting_writers > 0,
+		};
+		if writer_next {
+			if state.readers > 0 {
+				return None;
+			}
+			let waker = state.waiters.notify_first(|demand| demand == WRITE)?;
+			state.writer = true;
+			state.waiting_writers -= 1;
+			Some(waker)
+		} else {
+			let waker = state.waiters.notify_first(|demand| demand == READ)?;
+			state.readers += 1;
+			Some(waker)
+		}
+	}
+
+	/// Hands the lock on as far as possible, waking each new holder outside the lock.
+	fn wake_granted(&self) {
+		loop {
+			let next = self.grant_next(&mut self.state.lock());
+			match next {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+
+	fn release(&self, demand: usize) {
+		let mut state = self.state.lock();
+		if demand == WRITE {
+			state.writer = false;
+		} else {
+			state.readers -= 1;
+		}
+		drop(state);
+		self.wake_granted()
+	}
+}
+
+impl<T: Default> Default for RwLock<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+/// Shows only the lock state, to not require `T: Debug` and to not wait for the lock.
+impl<T: ?Sized> core::fmt::Debug for RwLock<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let state = self.state.lock();
+		let (readers, writer) = (state.readers, state.writer);
+		drop(state);
+		f.debug_struct("RwLock")
+			.field("fairness", &self.fairness)
+			.field("readers", &readers)
+			.field("writer", &writer)
+			.finish_non_exhaustive()
+	}
+}
+
+/// The waiting logic shared by [`Read`], [`Write`] and [`WritePinned`].
+struct Acquire<'a, T: ?Sized> {
+	rw_lock: &'a RwLock<T>,
+	/// Either [`READ`] or [`WRITE`].
+	demand: usize,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked or was granted the lock, to skip locking on drop otherwise.
+	registered: bool,
+	done: bool,
+}
+
+impl<'a, T: ?Sized> Acquire<'a, T> {
+	fn new(rw_lock: &'a RwLock<T>, demand: usize) -> Self {
+		Self {
+			rw_lock,
+			demand,
+			waiter: Waiter::with_demand(demand),
+			registered: false,
+			done: false,
+		}
+	}
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'a RwLock<T>> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		assert!(!this.done, "The lock was previously acquired.");
+
+		let mut state = this.rw_lock.state.lock();
+		let acquired = if this.registered {
+			unsafe {
+				//SAFETY: `waiter` is only used with this list, and removed on drop.
+				state.waiters.is_notified(waiter)
+			}
+		} else {
+			this.rw_lock.try_acquire(&mut state, this.demand)
+		};
+
+		if acquired {
+			if this.registered {
+				unsafe {
+					//SAFETY: As above.
+					state.waiters.remove(waiter);
+				}
+				this.registered = false;
+			}
+			this.done = true;
+			Poll::Ready(this.rw_lock)
+		} else {
+			if !this.registered && this.demand == WRITE {
+				state.waiting_writers += 1;
+			}
+			unsafe {
+				//SAFETY: As above. This keeps the place in the queue if already linked.
+				state.waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			Poll::Pending
+		}
+	}
+}
+
+impl<T: ?Sized> Drop for Acquire<'_, T> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let mut state = self.rw_lock.state.lock();
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				state.waiters.remove(waiter)
+			};
+			if notified {
+				// The lock was granted to this `Acquire`, so it's passed on.
+				drop(state);
+				self.rw_lock.release(self.demand);
+			} else {
+				if self.demand == WRITE {
+					state.waiting_writers -= 1;
+				}
+				drop(state);
+				// Waiters behind this one may be able to go now.
+				self.rw_lock.wake_granted();
+			}
+		}
+	}
+}
+
+macro_rules! acquire_future {
+	($(#[$meta:meta])* $name:ident => $guard:ident) => {
+		$(#[$meta])*
+		pub struct $name<'a, T: ?Sized> {
+			/// Pinned, structurally.
+			acquire: Acquire<'a, T>,
+		}
+
+		impl<'a, T: ?Sized> Future for $name<'a, T> {
+			type Output = $guard<'a, T>;
+
+			fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+				let acquire = unsafe {
+					//SAFETY: Structurally pinned.
+					self.map_unchecked_mut(|this| &mut this.acquire)
+				};
+				acquire.poll(cx).map($guard::new)
+			}
+		}
+
+		#[cfg(feature = "futures-core")]
+		impl<T: ?Sized> FusedFuture for $name<'_, T> {
+			fn is_terminated(&self) -> bool {
+				self.acquire.done
+			}
+		}
+
+		/// Shows only the progress, to not require `T: Debug`.
+		impl<T: ?Sized> core::fmt::Debug for $name<'_, T> {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				f.debug_struct(stringify!($name))
+					.field("waiting", &self.acquire.registered)
+					.field("done", &self.acquire.done)
+					.finish_non_exhaustive()
+			}
+		}
+	};
+}
+
+acquire_future!(
+	/// A [`Future`] that completes with an [`RwLockReadGuard`] once shared access is granted. See [`RwLock::read`].
+	Read => RwLockReadGuard
+);
+acquire_future!(
+	/// A [`Future`] that completes with an [`RwLockWriteGuard`] once exclusive access is granted. See [`RwLock::write`].
+	Write => RwLockWriteGuard
+);
+acquire_future!(
+	/// A [`Future`] that completes with a [`PinnedRwLockWriteGuard`] once exclusive access is granted. See [`RwLock::write_pinned`].
+	WritePinned => PinnedRwLockWriteGuard
+);
+
+/// Shared access to the value in an [`RwLock`], until dropped.
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+	rw_lock: &'a RwLock<T>,
+	/// Makes this [`Send`] and [`Sync`] only as far as a `&T` would be.
+	_value: PhantomData<&'a T>,
+}
+
+impl<'a, T: ?Sized> RwLockReadGuard<'a, T> {
+	fn new(rw_lock: &'a RwLock<T>) -> Self {
+		Self {
+			rw_lock,
+			_value: PhantomData,
+		}
+	}
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe {
+			//SAFETY: Shared access is held.
+			&*self.rw_lock.value.get()
+		}
+	}
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+	fn drop(&mut self) {
+		self.rw_lock.release(READ)
+	}
+}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for RwLockReadGuard<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
+
+/// Exclusive access to the value in an [`RwLock`], until dropped.
+///
+/// It dereferences mutably only if `T: Unpin`, since the [`RwLock`] may be pinned.
+/// Use [`RwLock::write_pinned`] to mutate a `T` that isn't.
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+	rw_lock: &'a RwLock<T>,
+	/// Makes this [`Send`] and [`Sync`] only as far as a `&mut T` would be.
+	_value: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: ?Sized> RwLockWriteGuard<'a, T> {
+	fn new(rw_lock: &'a RwLock<T>) -> Self {
+		Self {
+			rw_lock,
+			_value: PhantomData,
+		}
+	}
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe {
+			//SAFETY: Exclusive access is held.
+			&*self.rw_lock.value.get()
+		}
+	}
+}
+
+impl<T: ?Sized + Unpin> DerefMut for RwLockWriteGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		unsafe {
+			//SAFETY: Exclusive access is held, and `T` isn't pinned.
+			&mut *self.rw_lock.value.get()
+		}
+	}
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+	fn drop(&mut self) {
+		self.rw_lock.release(WRITE)
+	}
+}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for RwLockWriteGuard<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
+
+/// Exclusive access to the pinned value in an [`RwLock`], until dropped.
+///
+/// It dereferences mutably only if `T: Unpin`. Otherwise, use [`.as_mut()`](`PinnedRwLockWriteGuard::as_mut`).
+pub struct PinnedRwLockWriteGuard<'a, T: ?Sized> {
+	guard: RwLockWriteGuard<'a, T>,
+}
+
+impl<'a, T: ?Sized> PinnedRwLockWriteGuard<'a, T> {
+	fn new(rw_lock: &'a RwLock<T>) -> Self {
+		Self {
+			guard: RwLockWriteGuard::new(rw_lock),
+		}
+	}
+
+	/// Grants pinned exclusive access to the value.
+	#[must_use]
+	pub fn as_mut(&mut self) -> Pin<&mut T> {
+		unsafe {
+			//SAFETY: Exclusive access is held,
+			// and this guard can only be created through a pinned `RwLock`, in which `value` is structurally pinned.
+			Pin::new_unchecked(&mut *self.guard.rw_lock.value.get())
+		}
+	}
+}
+
+impl<T: ?Sized> Deref for PinnedRwLockWriteGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.guard
+	}
+}
+
+impl<T: ?Sized + Unpin> DerefMut for PinnedRwLockWriteGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.guard
+	}
+}
+
+impl<T: ?Sized + core::fmt::Debug> core::fmt::Debug for PinnedRwLockWriteGuard<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
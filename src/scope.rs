@@ -0,0 +1,146 @@
+//! Structured concurrency: Child [`Future`]s that may borrow from the enclosing stack frame, and all finish before the scope does.
+
+use crate::{
+	dyn_future::{dyn_future, DynFuture},
+	pinned_future_set::PinnedFutureSet,
+};
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Calls `f` to spawn child [`Future`]s, and returns a [`Scope`] that drives them all to completion
+/// and then completes with `f`'s return value.
+///
+/// The children may borrow anything that outlives `'env`, since the [`Scope`] can't outlive it either.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::cell::Cell;
+/// use unpin_choices_dsa::{block_on::block_on, scope::scope, yield_now::yield_now};
+///
+/// let total = Cell::new(0);
+/// let numbers = [1, 2, 3];
+///
+/// let spawned = block_on(scope(|s| {
+///     for number in &numbers {
+///         let total = &total;
+///         s.spawn(async move {
+///             yield_now().await;
+///             total.set(total.get() + number);
+///         });
+///     }
+///     numbers.len()
+/// }));
+///
+/// assert_eq!(spawned, 3);
+/// assert_eq!(total.get(), 6);
+/// ```
+pub fn scope<'env, T>(f: impl FnOnce(&mut Spawner<'env>) -> T) -> Scope<'env, T> {
+	let mut spawner = Spawner {
+		children: PinnedFutureSet::new(),
+	};
+	let output = f(&mut spawner);
+	Scope {
+		children: spawner.children,
+		output: Some(output),
+	}
+}
+
+/// Spawns child [`Future`]s into a [`Scope`]. See [`scope`].
+pub struct Spawner<'env> {
+	children: PinnedFutureSet<DynFuture<'env, ()>>,
+}
+
+impl<'env> Spawner<'env> {
+	/// Adds `future` as child of the [`Scope`].
+	///
+	/// It's first polled when the [`Scope`] is.
+	pub fn spawn(&mut self, future: impl 'env + Future<Output = ()>) {
+		self.children.push(dyn_future(future));
+	}
+
+	/// The number of children spawned so far.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.children.len()
+	}
+
+	/// Whether no children were spawned so far.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.children.is_empty()
+	}
+}
+
+/// Shows only the number of children, since they aren't [`Debug`](`core::fmt::Debug`).
+impl core::fmt::Debug for Spawner<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Spawner")
+			.field("len", &self.children.len())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes once all its children have, with the value returned by the closure passed to [`scope`].
+///
+/// Dropping it drops the remaining children in place, so either way none of them outlives it.
+///
+/// > Since the children are stored inside the [`Scope`] instead of being spawned onto an executor,
+/// > leaking the [`Scope`] also leaks them without running them again. That's what makes borrowing sound here.
+pub struct Scope<'env, T> {
+	children: PinnedFutureSet<DynFuture<'env, ()>>,
+	/// [`None`] once completed.
+	output: Option<T>,
+}
+
+impl<T> Scope<'_, T> {
+	/// The number of children that haven't completed yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.children.len()
+	}
+
+	/// Whether all children have completed.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.children.is_empty()
+	}
+}
+
+impl<T> Unpin for Scope<'_, T> {}
+
+impl<T> Future for Scope<'_, T> {
+	type Output = T;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		assert!(self.output.is_some(), "`Scope` was previously completed.");
+		loop {
+			match self.children.poll_next(cx) {
+				Poll::Ready(Some(_)) => (),
+				Poll::Ready(None) => return Poll::Ready(self.output.take().expect("unreachable")),
+				Poll::Pending => return Poll::Pending,
+			}
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> FusedFuture for Scope<'_, T> {
+	fn is_terminated(&self) -> bool {
+		self.output.is_none()
+	}
+}
+
+/// Shows only the progress, since neither `T` nor the children are necessarily [`Debug`](`core::fmt::Debug`).
+impl<T> core::fmt::Debug for Scope<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Scope")
+			.field("len", &self.children.len())
+			.field("done", &self.output.is_none())
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,239 @@
+//! A [`Future`] that interlaces fallible [`Future`]s, until one succeeds or all fail.
+
+use crate::{any_future, pinned_pin::PinnedPin};
+#[cfg(feature = "alloc")]
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Creates a [`Future`] that completes when any [`Future`] in `futures` succeeds, with that [`Ok`] value,
+/// or when all of them have failed, with all of their errors in order.
+pub fn select_ok<Fs: Futures>(futures: Fs) -> SelectOk<Fs> {
+	SelectOk::new(futures)
+}
+
+/// Like [`select_ok`], but for a dynamically-sized `futures` that are pinned in place behind a box,
+/// so they don't have to be [`Unpin`].
+#[cfg(feature = "alloc")]
+pub fn select_ok_boxed<F, T, E>(futures: impl Into<Box<[F]>>) -> SelectOk<Pin<Box<[F]>>>
+where
+	F: Future<Output = Result<T, E>>,
+{
+	SelectOk::new(futures.into().into())
+}
+
+/// A [`Future`] that completes as soon as any [`Future`] in `futures` succeeds, or once all of them failed.
+///
+/// [`Future`]s that failed aren't polled again.
+/// The remaining ones aren't polled anymore after one succeeds, and are dropped along with the [`SelectOk`].
+///
+/// > Unlike [`AnyFuture`](`crate::any_future::AnyFuture`), this always starts polling at the first [`Future`],
+/// > since failed ones drop out anyway.
+#[pin_project]
+pub struct SelectOk<Fs: Futures> {
+	/// [`None`] once completed.
+	errors: Option<Fs::PartialErrors>,
+	#[pin]
+	futures: PinnedPin<Fs>,
+}
+
+impl<Fs: Futures> SelectOk<Fs> {
+	/// Creates a new instance of [`SelectOk`] from the given `futures`.
+	#[must_use]
+	pub fn new(futures: Fs) -> Self {
+		Self {
+			errors: Some(futures.partial_errors()),
+			futures: futures.into(),
+		}
+	}
+
+	/// The number of constituent [`Future`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.futures.0.len()
+	}
+
+	/// Whether there are no constituent [`Future`]s.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.futures.0.is_empty()
+	}
+
+	/// The number of constituent [`Future`]s that have failed so far.
+	///
+	/// This is `0` after completion.
+	#[must_use]
+	pub fn failed_count(&self) -> usize {
+		self.errors.as_ref().map_or(0, |errors| {
+			Fs::errors(errors).iter().filter(|e| e.is_some()).count()
+		})
+	}
+}
+
+impl<Fs: Futures> Future for SelectOk<Fs> {
+	type Output = Result<Fs::Value, Fs::Errors>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		let mut futures = this.futures;
+		let errors = this
+			.errors
+			.as_mut()
+			.expect("`SelectOk` was previously completed.");
+
+		let mut pending = false;
+		for index in 0..futures.0.len() {
+			if Fs::errors_mut(errors)[index].is_some() {
+				continue;
+			}
+			match <Fs as any_future::Futures>::poll_at(futures.as_mut(), index, cx)
+				.map(Fs::into_result)
+			{
+				Poll::Pending => pending = true,
+				Poll::Ready(Ok(value)) => {
+					*this.errors = None;
+					return Poll::Ready(Ok(value));
+				}
+				Poll::Ready(Err(error)) => Fs::errors_mut(errors)[index] = Some(error),
+			}
+		}
+
+		if pending {
+			Poll::Pending
+		} else {
+			let errors = this.errors.take().expect("unreachable");
+			Poll::Ready(Err(Fs::into_errors(errors)))
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<Fs: Futures> FusedFuture for SelectOk<Fs> {
+	fn is_terminated(&self) -> bool {
+		self.errors.is_none()
+	}
+}
+
+/// Neither the [`Future`]s nor their errors are necessarily [`Debug`](`core::fmt::Debug`), so only the progress is shown.
+impl<Fs: Futures> core::fmt::Debug for SelectOk<Fs> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("SelectOk")
+			.field("len", &self.len())
+			.field("failed", &self.failed_count())
+			.field("done", &self.errors.is_none())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Collections of fallible [`Future`]s that can be used with [`SelectOk`].
+///
+/// Compare and contrast [`crate::any_future::Futures`], which this builds on.
+pub trait Futures: any_future::Futures {
+	/// The success type shared by the constituent [`Future`]s.
+	type Value;
+
+	/// The error type shared by the constituent [`Future`]s.
+	type Error;
+
+	/// All errors, once each constituent [`Future`] failed.
+	type Errors;
+
+	/// Storage for the errors collected so far.
+	type PartialErrors;
+
+	/// Unpacks a constituent [`Future`]'s output.
+	///
+	/// > This is always the identity function, but can't be expressed as a supertrait bound without a cycle.
+	///
+	/// # Errors
+	///
+	/// Iff `output` is an error.
+	fn into_result(output: Self::Output) -> Result<Self::Value, Self::Error>;
+
+	/// Creates empty error storage for `self`.
+	fn partial_errors(&self) -> Self::PartialErrors;
+
+	/// Views `errors` as one slot per constituent [`Future`].
+	fn errors(errors: &Self::PartialErrors) -> &[Option<Self::Error>];
+
+	/// Views `errors` as one slot per constituent [`Future`], exclusively.
+	fn errors_mut(errors: &mut Self::PartialErrors) -> &mut [Option<Self::Error>];
+
+	/// Unwraps the completed `errors`.
+	///
+	/// # Panics
+	///
+	/// Iff any slot in `errors` is [`None`].
+	fn into_errors(errors: Self::PartialErrors) -> Self::Errors;
+}
+
+impl<F, T, E, const N: usize> Futures for [F; N]
+where
+	F: Future<Output = Result<T, E>>,
+{
+	type Value = T;
+	type Error = E;
+	type Errors = [E; N];
+	type PartialErrors = [Option<E>; N];
+
+	fn into_result(output: Self::Output) -> Result<T, E> {
+		output
+	}
+
+	fn partial_errors(&self) -> Self::PartialErrors {
+		[(); N].map(|()| None)
+	}
+
+	fn errors(errors: &Self::PartialErrors) -> &[Option<E>] {
+		errors
+	}
+
+	fn errors_mut(errors: &mut Self::PartialErrors) -> &mut [Option<E>] {
+		errors
+	}
+
+	fn into_errors(errors: Self::PartialErrors) -> Self::Errors {
+		errors.map(|error| error.expect("Incomplete errors."))
+	}
+}
+
+#[cfg(feature = "alloc")]
+impl<F, T, E> Futures for Pin<Box<[F]>>
+where
+	F: Future<Output = Result<T, E>>,
+{
+	type Value = T;
+	type Error = E;
+	type Errors = Vec<E>;
+	type PartialErrors = Box<[Option<E>]>;
+
+	fn into_result(output: Self::Output) -> Result<T, E> {
+		output
+	}
+
+	fn partial_errors(&self) -> Self::PartialErrors {
+		(0..self.len()).map(|_| None).collect()
+	}
+
+	fn errors(errors: &Self::PartialErrors) -> &[Option<E>] {
+		errors
+	}
+
+	fn errors_mut(errors: &mut Self::PartialErrors) -> &mut [Option<E>] {
+		errors
+	}
+
+	fn into_errors(errors: Self::PartialErrors) -> Self::Errors {
+		errors
+			.into_vec()
+			.into_iter()
+			.map(|error| error.expect("Incomplete errors."))
+			.collect()
+	}
+}
@@ -0,0 +1,294 @@
+//! An asynchronous counting semaphore that queues waiting [`Future`]s intrusively.
+
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	future::Future,
+	mem,
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// An asynchronous counting semaphore, which hands out up to a certain number of permits at a time.
+///
+/// Waiting [`Acquire`] [`Future`]s are served in FIFO order without allocating,
+/// so a large request isn't starved by smaller ones that arrive after it.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, semaphore::Semaphore};
+///
+/// let semaphore = Semaphore::new(3);
+/// let two = block_on(semaphore.acquire(2));
+/// assert!(semaphore.try_acquire(2).is_none());
+///
+/// let one = semaphore.try_acquire(1).unwrap();
+/// assert_eq!(semaphore.available_permits(), 0);
+///
+/// drop((one, two));
+/// assert_eq!(semaphore.available_permits(), 3);
+/// ```
+pub struct Semaphore {
+	state: SpinLock<State>,
+}
+
+struct State {
+	available: usize,
+	waiters: WaiterList,
+}
+
+impl Semaphore {
+	/// Creates a new [`Semaphore`] with `permits` available permits.
+	#[must_use]
+	pub const fn new(permits: usize) -> Self {
+		Self {
+			state: SpinLock::new(State {
+				available: permits,
+				waiters: WaiterList::new(),
+			}),
+		}
+	}
+
+	/// The number of permits that are currently available.
+	#[must_use]
+	pub fn available_permits(&self) -> usize {
+		self.state.lock().available
+	}
+
+	/// Returns a [`Future`] that completes with a [`Permit`] for `n` permits once they are available.
+	#[must_use]
+	pub fn acquire(&self, n: usize) -> Acquire<'_> {
+		Acquire {
+			semaphore: self,
+			n,
+			waiter: Waiter::with_demand(n),
+			registered: false,
+			done: false,
+		}
+	}
+
+	/// Acquires `n` permits only if they are available and nobody is waiting already.
+	#[must_use]
+	pub fn try_acquire(&self, n: usize) -> Option<Permit<'_>> {
+		Self::try_take(&mut self.state.lock(), n).then(|| Permit { semaphore: self, n })
+	}
+
+	/// Makes `n` more permits available, possibly to waiting [`Acquire`]s.
+	///
+	/// # Panics
+	///
+	/// Iff the number of available permits would overflow.
+	pub fn add_permits(&self, n: usize) {
+		let mut state = self.state.lock();
+		state.available = state
+			.available
+			.checked_add(n)
+			.expect("Too many `Semaphore` permits.");
+		drop(state);
+		self.wake_granted()
+	}
+
+	fn try_take(state: &mut State, n: usize) -> bool {
+		let taken = state.waiters.len() == 0 && state.available >= n;
+		if taken {
+			state.available -= n;
+		}
+		taken
+	}
+
+	/// Grants permits to the front waiter if there are enough, and returns its [`Waker`].
+	fn grant_next(state: &mut State) -> Option<Waker> {
+		let n = state.waiters.front_demand()?;
+		if n > state.available {
+			return None;
+		}
+		state.available -= n;
+		state.waiters.notify_one()
+	}
+
+	/// Grants permits as far as possible, waking each granted waiter outside the lock.
+	fn wake_granted(&self) {
+		loop {
+			let next = Self::grant_next(&mut self.state.lock());
+			match next {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+}
+
+impl core::fmt::Debug for Semaphore {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let state = self.state.lock();
+		let (available, waiting) = (state.available, state.waiters.len());
+		drop(state);
+		f.debug_struct("Semaphore")
+			.field("available", &available)
+			.field("waiting", &waiting)
+			.finish()
+	}
+}
+
+/// A [`Future`] that completes with a [`Permit`] once enough permits are available. See [`Semaphore::acquire`].
+pub struct Acquire<'a> {
+	semaphore: &'a Semaphore,
+	n: usize,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked or was granted permits, to skip locking on drop otherwise.
+	registered: bool,
+	done: bool,
+}
+
+impl<'a> Future for Acquire<'a> {
+	type Output = Permit<'a>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+		assert!(!this.done, "`Acquire` was previously completed.");
+
+		let mut state = this.semaphore.state.lock();
+		let acquired = if this.registered {
+			unsafe {
+				//SAFETY: `waiter` is only used with this list, and removed on drop.
+				state.waiters.is_notified(waiter)
+			}
+		} else {
+			Semaphore::try_take(&mut state, this.n)
+		};
+
+		if acquired {
+			if this.registered {
+				unsafe {
+					//SAFETY: As above.
+					state.waiters.remove(waiter);
+				}
+				this.registered = false;
+			}
+			this.done = true;
+			Poll::Ready(Permit {
+				semaphore: this.semaphore,
+				n: this.n,
+			})
+		} else {
+			unsafe {
+				//SAFETY: As above. This keeps the place in the queue if already linked.
+				state.waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl FusedFuture for Acquire<'_> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+impl Drop for Acquire<'_> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			let mut state = self.semaphore.state.lock();
+			let notified = unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				state.waiters.remove(waiter)
+			};
+			// Returns permits that were granted to this `Acquire`.
+			if notified {
+				state.available += self.n;
+			}
+			drop(state);
+			// Either way, waiters behind this one may be able to go now.
+			self.semaphore.wake_granted()
+		}
+	}
+}
+
+impl core::fmt::Debug for Acquire<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Acquire")
+			.field("n", &self.n)
+			.field("waiting", &self.registered)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Permits acquired from a [`Semaphore`], which are returned when this is dropped.
+pub struct Permit<'a> {
+	semaphore: &'a Semaphore,
+	n: usize,
+}
+
+impl Permit<'_> {
+	/// The number of permits held.
+	#[must_use]
+	pub fn count(&self) -> usize {
+		self.n
+	}
+
+	/// Keeps the permits from being returned, reducing the [`Semaphore`]'s capacity for good.
+	pub fn forget(mut self) {
+		self.n = 0;
+	}
+
+	/// Splits off `n` of the held permits into a separate [`Permit`].
+	///
+	/// Returns [`None`] if fewer are held.
+	#[must_use]
+	pub fn split(&mut self, n: usize) -> Option<Self> {
+		self.n = self.n.checked_sub(n)?;
+		Some(Self {
+			semaphore: self.semaphore,
+			n,
+		})
+	}
+
+	/// Merges `other` into this [`Permit`].
+	///
+	/// # Panics
+	///
+	/// Iff `other` is from a different [`Semaphore`].
+	pub fn merge(&mut self, mut other: Self) {
+		assert!(
+			core::ptr::eq(self.semaphore, other.semaphore),
+			"Tried to merge `Permit`s from different `Semaphore`s."
+		);
+		self.n += mem::take(&mut other.n);
+	}
+}
+
+impl Drop for Permit<'_> {
+	fn drop(&mut self) {
+		if self.n > 0 {
+			self.semaphore.add_permits(self.n)
+		}
+	}
+}
+
+impl core::fmt::Debug for Permit<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Permit")
+			.field("count", &self.n)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,259 @@
+//! A [`Future`] that can be cloned and awaited by several tasks across threads, each getting a clone of the output.
+
+use crate::{
+	spin_lock::SpinLock,
+	waker::{waker_from_arc, ArcWake},
+	waker_set::WakerSet,
+};
+use alloc::sync::Arc;
+use core::{
+	cell::UnsafeCell,
+	future::Future,
+	mem,
+	pin::Pin,
+	sync::atomic::{AtomicU8, Ordering},
+	task::{Context, Poll, Waker},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Wraps `future` into a [`Shared`], which can be cloned to await it from several tasks, on any thread.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, shared::shared};
+///
+/// let response = shared(async { String::from("response") });
+/// let other = response.clone();
+///
+/// let thread = std::thread::spawn(move || block_on(other));
+/// assert_eq!(block_on(response), "response");
+/// assert_eq!(thread.join().unwrap(), "response");
+/// ```
+pub fn shared<F: Future>(future: F) -> Shared<F>
+where
+	F::Output: Clone,
+{
+	Shared::new(future)
+}
+
+const IDLE: u8 = 0;
+const POLLING: u8 = 1;
+const COMPLETE: u8 = 2;
+const POISONED: u8 = 3;
+
+struct Inner<F: Future> {
+	state: AtomicU8,
+	/// Accessed exclusively only while `state` is [`POLLING`], and shared once it's [`COMPLETE`].
+	/// Pinned, structurally.
+	slot: UnsafeCell<Slot<F>>,
+	notifier: Arc<Notifier>,
+	/// Wakes `notifier`, and is what the inner [`Future`] is polled with.
+	waker: Waker,
+}
+
+enum Slot<F: Future> {
+	Pending(F),
+	Done(F::Output),
+}
+
+/// Wakes all waiting clones when the inner [`Future`] is woken.
+struct Notifier {
+	wakers: SpinLock<WakerSet>,
+}
+
+impl ArcWake for Notifier {
+	fn wake_by_ref(arc_self: &Arc<Self>) {
+		// Taken first, so they're woken outside the lock.
+		let mut wakers = mem::take(&mut *arc_self.wakers.lock());
+		wakers.wake_all();
+	}
+}
+
+/// A [`Future`] that shares the output of an inner [`Future`] between all its clones, which may be on different threads.
+///
+/// At most one clone polls the inner [`Future`] at a time, with a [`Waker`] that wakes all waiting clones.
+/// Once it completes, the output is cloned for each clone that polls.
+/// If a waiting clone is dropped, the others are woken, so that one of them takes over.
+///
+/// Compare [`LocalShared`](`crate::local_shared::LocalShared`), which doesn't need atomics.
+///
+/// # Panics
+///
+/// If the inner [`Future`] panics, all clones panic when polled afterwards.
+pub struct Shared<F: Future> {
+	inner: Arc<Inner<F>>,
+	/// Whether this clone's task may be waiting in `inner.notifier`.
+	waiting: bool,
+}
+
+/// # Safety notes
+///
+/// The inner [`Future`] is only accessed by the clone that moved `state` to `POLLING`,
+/// and the output is only shared (and cloned from) once `state` is `COMPLETE`.
+unsafe impl<F: Future + Send> Send for Shared<F> where F::Output: Send + Sync {}
+unsafe impl<F: Future + Send> Sync for Shared<F> where F::Output: Send + Sync {}
+
+impl<F: Future> Shared<F>
+where
+	F::Output: Clone,
+{
+	/// Creates a new instance of [`Shared`] around `future`.
+	#[must_use]
+	pub fn new(future: F) -> Self {
+		let notifier = Arc::new(Notifier {
+			wakers: SpinLock::new(WakerSet::new()),
+		});
+		Self {
+			inner: Arc::new(Inner {
+				state: AtomicU8::new(IDLE),
+				slot: UnsafeCell::new(Slot::Pending(future)),
+				waker: waker_from_arc(Arc::clone(&notifier)),
+				notifier,
+			}),
+			waiting: false,
+		}
+	}
+
+	/// A clone of the output, if the inner [`Future`] has completed.
+	#[must_use]
+	pub fn peek(&self) -> Option<F::Output> {
+		(self.inner.state.load(Ordering::Acquire) == COMPLETE).then(|| self.output())
+	}
+
+	/// The number of clones of this [`Shared`], including itself.
+	#[must_use]
+	pub fn clone_count(&self) -> usize {
+		Arc::strong_count(&self.inner)
+	}
+
+	/// Only valid once `state` is [`COMPLETE`].
+	fn output(&self) -> F::Output {
+		match unsafe {
+			//SAFETY: Shared-only once complete.
+			&*self.inner.slot.get()
+		} {
+			Slot::Done(output) => output.clone(),
+			Slot::Pending(_) => unreachable!(),
+		}
+	}
+}
+
+impl<F: Future> Clone for Shared<F> {
+	fn clone(&self) -> Self {
+		Self {
+			inner: Arc::clone(&self.inner),
+			waiting: false,
+		}
+	}
+}
+
+impl<F: Future> Unpin for Shared<F> {}
+
+/// Poisons the [`Shared`] if the inner [`Future`] panics, unless [forgotten](`mem::forget`).
+struct PoisonOnUnwind<'a, F: Future>(&'a Inner<F>);
+
+impl<F: Future> Drop for PoisonOnUnwind<'_, F> {
+	fn drop(&mut self) {
+		self.0.state.store(POISONED, Ordering::Release);
+		self.0.waker.wake_by_ref();
+	}
+}
+
+impl<F: Future> Future for Shared<F>
+where
+	F::Output: Clone,
+{
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		if this.inner.state.load(Ordering::Acquire) == COMPLETE {
+			this.waiting = false;
+			return Poll::Ready(this.output());
+		}
+
+		// Registered before trying to poll, so that wake-ups during another clone's poll aren't missed.
+		this.inner.notifier.wakers.lock().insert(cx.waker());
+		this.waiting = true;
+
+		match this
+			.inner
+			.state
+			.compare_exchange(IDLE, POLLING, Ordering::Acquire, Ordering::Acquire)
+			.unwrap_or_else(|state| state)
+		{
+			IDLE => (),
+			COMPLETE => {
+				this.waiting = false;
+				return Poll::Ready(this.output());
+			}
+			POLLING => return Poll::Pending,
+			_ => panic!("The inner `Future` of `Shared` panicked."),
+		}
+
+		let inner = &*this.inner;
+		let poison = PoisonOnUnwind(inner);
+		let slot = unsafe {
+			//SAFETY: Exclusive while `POLLING`.
+			&mut *inner.slot.get()
+		};
+		let future = match slot {
+			Slot::Pending(future) => unsafe {
+				//SAFETY: Structurally pinned, as `inner` is never moved out of the `Arc`.
+				Pin::new_unchecked(future)
+			},
+			Slot::Done(_) => unreachable!(),
+		};
+		let polled = future.poll(&mut Context::from_waker(&inner.waker));
+		mem::forget(poison);
+
+		match polled {
+			Poll::Ready(output) => {
+				// Drops the inner `Future` in place.
+				*slot = Slot::Done(output);
+				inner.state.store(COMPLETE, Ordering::Release);
+				inner.waker.wake_by_ref();
+				this.waiting = false;
+				Poll::Ready(this.output())
+			}
+			Poll::Pending => {
+				inner.state.store(IDLE, Ordering::Release);
+				Poll::Pending
+			}
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for Shared<F>
+where
+	F::Output: Clone,
+{
+	/// Never, since each clone can be polled again to get another clone of the output.
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+impl<F: Future> Drop for Shared<F> {
+	fn drop(&mut self) {
+		if self.waiting {
+			// This clone's task may have been the only one to receive the inner `Future`'s last wake-up.
+			self.inner.waker.wake_by_ref();
+		}
+	}
+}
+
+/// Shows only the progress, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F: Future> core::fmt::Debug for Shared<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Shared")
+			.field(
+				"done",
+				&(self.inner.state.load(Ordering::Acquire) == COMPLETE),
+			)
+			.field("clone_count", &Arc::strong_count(&self.inner))
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,192 @@
+//! [`Future`]s that complete after a delay, measured by a [`Timer`] or a globally registered [`Delay`] driver.
+
+use crate::timer::Timer;
+use core::{
+	cell::UnsafeCell,
+	fmt::{self, Display, Formatter},
+	future::Future,
+	ops::Add,
+	pin::Pin,
+	sync::atomic::{AtomicU8, Ordering},
+	task::{Context, Poll},
+	time::Duration,
+};
+
+/// A [`Timer`] that counts [`Duration`]s from an arbitrary epoch (like boot), and can be used as global driver.
+///
+/// This is implemented for all fitting [`Timer`]s. Register one with [`set_driver`].
+pub trait Delay: Timer<Instant = Duration> + Sync {}
+impl<T: Timer<Instant = Duration> + Sync + ?Sized> Delay for T {}
+
+/// The error returned by [`set_driver`] if a driver was registered before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DriverAlreadySet;
+
+impl Display for DriverAlreadySet {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("a `Delay` driver was already registered")
+	}
+}
+
+const UNSET: u8 = 0;
+const SETTING: u8 = 1;
+const SET: u8 = 2;
+
+struct DriverCell {
+	state: AtomicU8,
+	/// Written once while `state` is [`SETTING`], and only read once it's [`SET`].
+	driver: UnsafeCell<Option<&'static dyn Delay>>,
+}
+
+/// # Safety notes
+///
+/// Access to `driver` is synchronised through `state`.
+unsafe impl Sync for DriverCell {}
+
+static DRIVER: DriverCell = DriverCell {
+	state: AtomicU8::new(UNSET),
+	driver: UnsafeCell::new(None),
+};
+
+/// Registers `driver` as the [`Timer`] used by [`sleep`] and [`sleep_until`].
+///
+/// This can be done only once, usually during start-up.
+///
+/// > Like `log::set_logger`, this is a set-once global, so that libraries can sleep without threading a [`Timer`] through.
+///
+/// # Errors
+///
+/// Iff a driver was registered before, in which case it stays in place.
+pub fn set_driver(driver: &'static dyn Delay) -> Result<(), DriverAlreadySet> {
+	DRIVER
+		.state
+		.compare_exchange(UNSET, SETTING, Ordering::Acquire, Ordering::Relaxed)
+		.map_err(|_| DriverAlreadySet)?;
+	unsafe {
+		//SAFETY: Exclusive, since only one thread can observe `UNSET` above, and readers wait for `SET`.
+		*DRIVER.driver.get() = Some(driver);
+	}
+	DRIVER.state.store(SET, Ordering::Release);
+	Ok(())
+}
+
+/// The driver registered with [`set_driver`], if any.
+#[must_use]
+pub fn driver() -> Option<&'static dyn Delay> {
+	if DRIVER.state.load(Ordering::Acquire) == SET {
+		unsafe {
+			//SAFETY: Written before `SET` was released, and never again.
+			*DRIVER.driver.get()
+		}
+	} else {
+		None
+	}
+}
+
+pub(crate) fn expect_driver() -> &'static dyn Delay {
+	driver()
+		.expect("No `Delay` driver was registered. (See `unpin_choices_dsa::sleep::set_driver`.)")
+}
+
+/// Creates a [`Sleep`] that completes once `duration` has passed on the registered driver.
+///
+/// # Panics
+///
+/// Iff no driver was registered with [`set_driver`].
+#[must_use]
+pub fn sleep(duration: Duration) -> Sleep {
+	let driver = expect_driver();
+	let deadline = driver.now() + duration;
+	Sleep::new(driver, deadline)
+}
+
+/// Creates a [`Sleep`] that completes once `deadline` has passed on the registered driver.
+///
+/// # Panics
+///
+/// Iff no driver was registered with [`set_driver`].
+#[must_use]
+pub fn sleep_until(deadline: Duration) -> Sleep {
+	Sleep::new(expect_driver(), deadline)
+}
+
+/// Creates a [`Sleep`] that completes once `duration` has passed on `timer`.
+///
+/// `D` is whatever can be added to `timer`'s instants.
+pub fn sleep_on<T: Timer, D>(timer: T, duration: D) -> Sleep<T>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	let deadline = timer.now() + duration;
+	Sleep::new(timer, deadline)
+}
+
+/// A [`Future`] that completes once its deadline has passed on a [`Timer`].
+///
+/// Polling it again after that completes again right away.
+///
+/// By default, it uses the driver registered with [`set_driver`].
+///
+/// > All the timekeeping is deferred to the [`Timer`], so this is just a deadline check and a wake-up request.
+pub struct Sleep<T: Timer = &'static dyn Delay> {
+	timer: T,
+	deadline: T::Instant,
+}
+
+impl<T: Timer> Sleep<T> {
+	/// Creates a new instance of [`Sleep`] that completes once `deadline` has passed on `timer`.
+	#[must_use]
+	pub fn new(timer: T, deadline: T::Instant) -> Self {
+		Self { timer, deadline }
+	}
+
+	/// The point in time after which this [`Sleep`] completes.
+	#[must_use]
+	pub fn deadline(&self) -> T::Instant {
+		self.deadline
+	}
+
+	/// The [`Timer`] this [`Sleep`] consults.
+	#[must_use]
+	pub fn timer(&self) -> &T {
+		&self.timer
+	}
+
+	/// Whether the deadline has passed.
+	#[must_use]
+	pub fn is_elapsed(&self) -> bool {
+		self.timer.now() >= self.deadline
+	}
+
+	/// Moves the deadline, even if it had passed already.
+	pub fn reset(&mut self, deadline: T::Instant) {
+		self.deadline = deadline;
+	}
+}
+
+impl<T: Timer> Unpin for Sleep<T> {}
+
+impl<T: Timer> Future for Sleep<T> {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.is_elapsed() {
+			Poll::Ready(())
+		} else {
+			self.timer.wake_at(self.deadline, cx.waker());
+			Poll::Pending
+		}
+	}
+}
+
+/// Shows only the deadline, since `T` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T: Timer> core::fmt::Debug for Sleep<T>
+where
+	T::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Sleep")
+			.field("deadline", &self.deadline)
+			.finish_non_exhaustive()
+	}
+}
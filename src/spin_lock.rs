@@ -0,0 +1,81 @@
+//! A minimal spin lock, for the short critical sections of the synchronisation primitives.
+
+use core::{
+	cell::UnsafeCell,
+	hint,
+	ops::{Deref, DerefMut},
+	sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Guards `T` by spinning until it's available.
+///
+/// > This is only used around a few pointer updates, never while polling or waking.
+/// > It must not be locked from an interrupt handler that may have interrupted its holder, which would deadlock.
+pub(crate) struct SpinLock<T> {
+	locked: AtomicBool,
+	value: UnsafeCell<T>,
+}
+
+/// # Safety notes
+///
+/// Access to `value` is synchronised through `locked`.
+unsafe impl<T: Send> Send for SpinLock<T> {}
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+	pub(crate) const fn new(value: T) -> Self {
+		Self {
+			locked: AtomicBool::new(false),
+			value: UnsafeCell::new(value),
+		}
+	}
+
+	pub(crate) fn get_mut(&mut self) -> &mut T {
+		self.value.get_mut()
+	}
+
+	pub(crate) fn into_inner(self) -> T {
+		self.value.into_inner()
+	}
+
+	pub(crate) fn lock(&self) -> SpinLockGuard<'_, T> {
+		while self
+			.locked
+			.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+			.is_err()
+		{
+			while self.locked.load(Ordering::Relaxed) {
+				hint::spin_loop()
+			}
+		}
+		SpinLockGuard(self)
+	}
+}
+
+pub(crate) struct SpinLockGuard<'a, T>(&'a SpinLock<T>);
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		unsafe {
+			//SAFETY: The lock is held.
+			&*self.0.value.get()
+		}
+	}
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		unsafe {
+			//SAFETY: The lock is held exclusively.
+			&mut *self.0.value.get()
+		}
+	}
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+	fn drop(&mut self) {
+		self.0.locked.store(false, Ordering::Release)
+	}
+}
@@ -0,0 +1,436 @@
+//! Fanning one [`Stream`] out to multiple consumers, by cloning or routing its items.
+
+use crate::waker_set::WakerSet;
+use alloc::{boxed::Box, collections::VecDeque, rc::Rc, vec::Vec};
+use core::{
+	cell::RefCell,
+	mem,
+	num::NonZeroUsize,
+	pin::Pin,
+	task::{Context, Poll, Waker},
+};
+use futures_core::{FusedStream, Stream};
+
+/// Splits `stream` into `consumers` [`SplitStream`]s that each receive a clone of every item.
+///
+/// Each consumer buffers up to `capacity` items.
+///
+/// # Panics
+///
+/// Iff `consumers` is `0`.
+pub fn tee<S: Stream>(
+	stream: S,
+	consumers: usize,
+	capacity: NonZeroUsize,
+) -> Vec<SplitStream<S, Tee>>
+where
+	S::Item: Clone,
+{
+	SplitStream::new(stream, consumers, capacity, Tee)
+}
+
+/// Splits `stream` into `consumers` [`SplitStream`]s, sending each item only to the one at the index returned by `route`.
+///
+/// Each consumer buffers up to `capacity` items.
+/// Items routed to an out-of-range index or a dropped consumer are dropped.
+///
+/// # Panics
+///
+/// Iff `consumers` is `0`.
+pub fn split_by<S: Stream, F: FnMut(&S::Item) -> usize>(
+	stream: S,
+	consumers: usize,
+	capacity: NonZeroUsize,
+	route: F,
+) -> Vec<SplitStream<S, ByIndex<F>>> {
+	SplitStream::new(stream, consumers, capacity, ByIndex(route))
+}
+
+/// Decides which consumers of a [`SplitStream`] receive an item.
+pub trait Route<T> {
+	/// Hands `item` to any number of the `consumers` through `send`.
+	fn route(&mut self, item: T, consumers: usize, send: &mut dyn FnMut(usize, T));
+}
+
+/// Sends a clone of each item to every consumer. See [`tee`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Tee;
+
+impl<T: Clone> Route<T> for Tee {
+	fn route(&mut self, item: T, consumers: usize, send: &mut dyn FnMut(usize, T)) {
+		for index in 1..consumers {
+			send(index, item.clone())
+		}
+		send(0, item)
+	}
+}
+
+/// Sends each item to the consumer at the index returned by the wrapped closure. See [`split_by`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByIndex<F>(pub F);
+
+impl<T, F: FnMut(&T) -> usize> Route<T> for ByIndex<F> {
+	fn route(&mut self, item: T, _consumers: usize, send: &mut dyn FnMut(usize, T)) {
+		send((self.0)(&item), item)
+	}
+}
+
+/// One consumer handle of a split [`Stream`].
+///
+/// The source [`Stream`] is polled by whichever consumer runs out of buffered items first,
+/// but only while every remaining consumer has room in its buffer.
+/// That way, the slowest consumer applies backpressure to all others, and memory use stays bounded.
+///
+/// The shared state is never borrowed while the source [`Stream`] is polled, items are routed or dropped,
+/// or [`Waker`]s are woken, so any of these may use the sibling consumers.
+///
+/// > The consumers share their state through an [`Rc`], so they can't be sent across threads.
+/// > That's fine for the single-threaded executors this is mainly meant for.
+pub struct SplitStream<S: Stream, R> {
+	shared: Rc<RefCell<Shared<S, R>>>,
+	index: usize,
+}
+
+struct Shared<S: Stream, R> {
+	source: Source<S, R>,
+	capacity: usize,
+	queues: Box<[Queue<S::Item>]>,
+	/// Consumers waiting for items, room or the source. All of them are woken on any change.
+	wakers: WakerSet,
+}
+
+enum Source<S, R> {
+	Idle(Pin<Box<S>>, R),
+	/// Taken out by the consumer that's currently polling it.
+	Polling,
+	Exhausted,
+}
+
+struct Queue<T> {
+	items: VecDeque<T>,
+	dropped: bool,
+}
+
+impl<S: Stream, R: Route<S::Item>> SplitStream<S, R> {
+	/// Splits `stream` into `consumers` [`SplitStream`]s with a custom [`Route`].
+	///
+	/// # Panics
+	///
+	/// Iff `consumers` is `0`.
+	pub fn new(stream: S, consumers: usize, capacity: NonZeroUsize, route: R) -> Vec<Self> {
+		assert!(consumers > 0, "Can't split a `Stream` into zero consumers.");
+		let shared = Rc::new(RefCell::new(Shared {
+			source: Source::Idle(Box::pin(stream), route),
+			capacity: capacity.get(),
+			queues: (0..consumers)
+				.map(|_| Queue {
+					items: VecDeque::with_capacity(capacity.get()),
+					dropped: false,
+				})
+				.collect(),
+			wakers: WakerSet::new(),
+		}));
+		(0..consumers)
+			.map(|index| Self {
+				shared: shared.clone(),
+				index,
+			})
+			.collect()
+	}
+}
+
+impl<S: Stream, R> SplitStream<S, R> {
+	/// This consumer's index among its siblings.
+	#[must_use]
+	pub fn index(&self) -> usize {
+		self.index
+	}
+
+	/// The number of items currently buffered for this consumer.
+	#[must_use]
+	pub fn buffered(&self) -> usize {
+		self.shared.borrow().queues[self.index].items.len()
+	}
+}
+
+/// What [`SplitStream::poll_next`] decided while borrowing the shared state.
+enum Step<S, R, T> {
+	Item(T, WakerSet),
+	Exhausted,
+	Pending,
+	Poll(Pin<Box<S>>, R),
+}
+
+impl<S: Stream, R> Shared<S, R> {
+	fn step(&mut self, index: usize, waker: &Waker) -> Step<S, R, S::Item> {
+		let queue = &mut self.queues[index];
+		if let Some(item) = queue.items.pop_front() {
+			// This may have been the bottleneck.
+			let wakers = if queue.items.len() + 1 == self.capacity {
+				mem::take(&mut self.wakers)
+			} else {
+				WakerSet::new()
+			};
+			return Step::Item(item, wakers);
+		}
+
+		match self.source {
+			Source::Idle(..) => (),
+			Source::Polling => {
+				// The consumer that's polling the source wakes everyone once it's done.
+				self.wakers.insert(waker);
+				return Step::Pending;
+			}
+			Source::Exhausted => return Step::Exhausted,
+		}
+
+		let capacity = self.capacity;
+		if self
+			.queues
+			.iter()
+			.any(|queue| !queue.dropped && queue.items.len() >= capacity)
+		{
+			self.wakers.insert(waker);
+			return Step::Pending;
+		}
+
+		match mem::replace(&mut self.source, Source::Polling) {
+			Source::Idle(stream, route) => Step::Poll(stream, route),
+			Source::Polling | Source::Exhausted => unreachable!(),
+		}
+	}
+}
+
+impl<S: Stream, R> Unpin for SplitStream<S, R> {}
+
+impl<S: Stream, R: Route<S::Item>> Stream for SplitStream<S, R> {
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let index = self.index;
+		loop {
+			// Each borrow ends with its statement, before anything that could call back into a sibling.
+			let step = self.shared.borrow_mut().step(index, cx.waker());
+			let (mut stream, mut route) = match step {
+				Step::Item(item, mut wakers) => {
+					wakers.wake_all();
+					return Poll::Ready(Some(item));
+				}
+				Step::Exhausted => return Poll::Ready(None),
+				Step::Pending => return Poll::Pending,
+				Step::Poll(stream, route) => (stream, route),
+			};
+
+			match stream.as_mut().poll_next(cx) {
+				Poll::Ready(Some(item)) => {
+					let consumers = self.shared.borrow().queues.len();
+					let mut routed = Vec::new();
+					route.route(item, consumers, &mut |target, item| {
+						routed.push((target, item))
+					});
+
+					let (rejected, mut wakers) = {
+						let mut shared = self.shared.borrow_mut();
+						let shared = &mut *shared;
+						shared.source = Source::Idle(stream, route);
+						let mut rejected = Vec::new();
+						for (target, item) in routed {
+							match shared.queues.get_mut(target) {
+								Some(queue) if !queue.dropped => queue.items.push_back(item),
+								_ => rejected.push(item),
+							}
+						}
+						(rejected, mem::take(&mut shared.wakers))
+					};
+					wakers.wake_all();
+					drop(rejected);
+				}
+				Poll::Ready(None) => {
+					let mut wakers = {
+						let mut shared = self.shared.borrow_mut();
+						shared.source = Source::Exhausted;
+						mem::take(&mut shared.wakers)
+					};
+					wakers.wake_all();
+					drop((stream, route));
+				}
+				Poll::Pending => {
+					// Whichever consumer polls the source last gets woken by it, and then wakes the others as needed.
+					let mut wakers = {
+						let mut shared = self.shared.borrow_mut();
+						shared.source = Source::Idle(stream, route);
+						// Siblings that found the source busy meanwhile may poll it themselves now.
+						mem::take(&mut shared.wakers)
+					};
+					wakers.wake_all();
+					return Poll::Pending;
+				}
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let shared = self.shared.borrow();
+		let buffered = shared.queues[self.index].items.len();
+		match shared.source {
+			Source::Idle(..) | Source::Polling => (buffered, None),
+			Source::Exhausted => (buffered, Some(buffered)),
+		}
+	}
+}
+
+impl<S: Stream, R: Route<S::Item>> FusedStream for SplitStream<S, R> {
+	fn is_terminated(&self) -> bool {
+		let shared = self.shared.borrow();
+		matches!(shared.source, Source::Exhausted) && shared.queues[self.index].items.is_empty()
+	}
+}
+
+/// Drops this consumer's buffered items and stops routing new ones to it,
+/// so that it doesn't hold back its siblings.
+impl<S: Stream, R> Drop for SplitStream<S, R> {
+	fn drop(&mut self) {
+		let (items, mut wakers) = {
+			let mut shared = self.shared.borrow_mut();
+			let queue = &mut shared.queues[self.index];
+			queue.dropped = true;
+			let items = mem::take(&mut queue.items);
+			// Another consumer may have to take over polling the source, or may have been held back.
+			(items, mem::take(&mut shared.wakers))
+		};
+		// Outside the borrow, in case an item's destructor or a wake-up touches a sibling.
+		wakers.wake_all();
+		drop(items)
+	}
+}
+
+impl<S: Stream, R> core::fmt::Debug for SplitStream<S, R> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let shared = self.shared.borrow();
+		f.debug_struct("SplitStream")
+			.field("index", &self.index)
+			.field("buffered", &shared.queues[self.index].items.len())
+			.field("capacity", &shared.capacity)
+			.field("exhausted", &matches!(shared.source, Source::Exhausted))
+			.finish_non_exhaustive()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{split_by, tee};
+	use crate::waker::noop_context;
+	use alloc::{boxed::Box, rc::Rc};
+	use core::{
+		cell::{Cell, RefCell},
+		num::NonZeroUsize,
+		pin::Pin,
+		task::{Context, Poll},
+	};
+	use futures_core::Stream;
+
+	struct Iter<I>(I);
+
+	impl<I: Iterator + Unpin> Stream for Iter<I> {
+		type Item = I::Item;
+
+		fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+			Poll::Ready(self.0.next())
+		}
+	}
+
+	fn next<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+		Pin::new(stream).poll_next(&mut noop_context())
+	}
+
+	const ONE: NonZeroUsize = match NonZeroUsize::new(1) {
+		Some(one) => one,
+		None => unreachable!(),
+	};
+
+	#[test]
+	fn tee_backpressure() {
+		let mut consumers = tee(Iter(0..3), 2, ONE);
+		let mut second = consumers.pop().unwrap();
+		let mut first = consumers.pop().unwrap();
+
+		assert_eq!(next(&mut first), Poll::Ready(Some(0)));
+		// `second` is full.
+		assert_eq!(next(&mut first), Poll::Pending);
+		assert_eq!(next(&mut second), Poll::Ready(Some(0)));
+		assert_eq!(next(&mut second), Poll::Ready(Some(1)));
+		drop(second);
+		assert_eq!(next(&mut first), Poll::Ready(Some(1)));
+		assert_eq!(next(&mut first), Poll::Ready(Some(2)));
+		assert_eq!(next(&mut first), Poll::Ready(None));
+	}
+
+	type Probe = Rc<RefCell<Option<Box<dyn Fn() -> usize>>>>;
+
+	#[test]
+	fn route_may_inspect_siblings() {
+		let probe: Probe = Rc::default();
+		let seen = Rc::new(Cell::new(0));
+
+		let mut consumers = split_by(Iter(0..4_usize), 2, ONE, {
+			let probe = probe.clone();
+			let seen = seen.clone();
+			move |item: &usize| {
+				seen.set(seen.get() + probe.borrow().as_ref().map_or(0, |probe| probe()));
+				item % 2
+			}
+		});
+		let sibling = consumers.pop().unwrap();
+		let mut first = consumers.pop().unwrap();
+		let sibling = Rc::new(sibling);
+		*probe.borrow_mut() = Some(Box::new({
+			let sibling = Rc::downgrade(&sibling);
+			move || {
+				sibling
+					.upgrade()
+					.map_or(0, |sibling| sibling.buffered() + 1)
+			}
+		}));
+
+		assert_eq!(next(&mut first), Poll::Ready(Some(0)));
+		// `1` went to the sibling, which is now full.
+		assert_eq!(next(&mut first), Poll::Pending);
+		assert_eq!(sibling.buffered(), 1);
+		assert_eq!(seen.get(), 1 + 1);
+	}
+
+	#[test]
+	fn rejected_items_may_inspect_siblings() {
+		struct Item(usize, Probe);
+		impl Drop for Item {
+			fn drop(&mut self) {
+				if let Some(probe) = &*self.1.borrow() {
+					probe();
+				}
+			}
+		}
+
+		let probe: Probe = Rc::default();
+		let items = alloc::vec![Item(9, probe.clone()), Item(0, probe.clone())];
+		let mut consumers = split_by(Iter(items.into_iter()), 2, ONE, |item: &Item| item.0);
+		let sibling = Rc::new(consumers.pop().unwrap());
+		let mut first = consumers.pop().unwrap();
+		let probed = Rc::new(Cell::new(0));
+		*probe.borrow_mut() = Some(Box::new({
+			let sibling = Rc::downgrade(&sibling);
+			let probed = probed.clone();
+			move || {
+				probed.set(probed.get() + 1);
+				sibling.upgrade().map_or(0, |sibling| sibling.buffered())
+			}
+		}));
+
+		// The first item is out of range and dropped on the way.
+		match next(&mut first) {
+			Poll::Ready(Some(Item(0, _))) => (),
+			_ => panic!("Expected the second item."),
+		}
+		assert_eq!(probed.get(), 2);
+		*probe.borrow_mut() = None;
+	}
+}
@@ -0,0 +1,34 @@
+//! A reference to channel state that is either borrowed or shared through an [`Arc`](`alloc::sync::Arc`).
+
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+/// Lets the channel halves work the same way whether the caller owns the state or it's heap-allocated.
+pub(crate) enum StateRef<'a, S> {
+	Borrowed(&'a S),
+	#[cfg(feature = "alloc")]
+	Shared(Arc<S>),
+}
+
+impl<S> Clone for StateRef<'_, S> {
+	fn clone(&self) -> Self {
+		match self {
+			StateRef::Borrowed(state) => StateRef::Borrowed(state),
+			#[cfg(feature = "alloc")]
+			StateRef::Shared(state) => StateRef::Shared(Arc::clone(state)),
+		}
+	}
+}
+
+impl<S> Deref for StateRef<'_, S> {
+	type Target = S;
+
+	fn deref(&self) -> &Self::Target {
+		match self {
+			StateRef::Borrowed(state) => state,
+			#[cfg(feature = "alloc")]
+			StateRef::Shared(state) => state,
+		}
+	}
+}
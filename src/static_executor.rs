@@ -0,0 +1,223 @@
+//! An executor with a fixed number of task slots, which are reused once their task completes.
+
+use crate::{superloop::TaskFlags, waker::flag_waker};
+use core::{
+	future::Future,
+	hint,
+	marker::PhantomPinned,
+	pin::Pin,
+	sync::atomic::Ordering,
+	task::{Context, Poll},
+};
+
+/// The error returned by [`StaticExecutor::spawn`] if all slots are taken, with the task that didn't fit.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExecutorFull<F>(pub F);
+
+/// Doesn't show the task, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F> core::fmt::Debug for ExecutorFull<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("ExecutorFull").finish_non_exhaustive()
+	}
+}
+
+impl<F> core::fmt::Display for ExecutorFull<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str("all `StaticExecutor` slots are taken")
+	}
+}
+
+/// Identifies a task spawned on a [`StaticExecutor`], even after its slot was reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskHandle {
+	index: usize,
+	generation: u32,
+}
+
+impl TaskHandle {
+	/// The index of the slot the task runs in.
+	#[must_use]
+	pub fn index(&self) -> usize {
+		self.index
+	}
+}
+
+/// Runs up to `N` tasks of type `F`, each pinned in its own slot.
+///
+/// Each slot has a dirty flag in `flags` which its [`Waker`](`core::task::Waker`) sets,
+/// so only woken tasks are polled. Completed and [cancelled](`StaticExecutor::cancel`) tasks are dropped in place,
+/// after which their slot can be reused by [`.spawn(…)`](`StaticExecutor::spawn`).
+///
+/// Use a common task type to mix different [`Future`]s,
+/// like [`DynFuture<'_, ()>`](`crate::dyn_future::DynFuture`) or an enum.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::cell::Cell;
+/// use unpin_choices_dsa::{
+///     dyn_future::{dyn_future, DynFuture},
+///     static_executor::StaticExecutor,
+///     superloop::TaskFlags,
+/// };
+///
+/// static FLAGS: TaskFlags<2> = TaskFlags::new();
+///
+/// let count = Cell::new(0);
+/// let mut executor = Box::pin(StaticExecutor::<DynFuture<()>, 2>::new(&FLAGS));
+///
+/// let first = executor.as_mut().spawn(dyn_future(async { count.set(count.get() + 1) })).unwrap();
+/// executor.as_mut().spawn(dyn_future(async { count.set(count.get() + 1) })).unwrap();
+/// assert!(executor.as_mut().spawn(dyn_future(async {})).is_err());
+///
+/// executor.as_mut().run();
+/// assert_eq!(count.get(), 2);
+/// assert!(!executor.is_running(first));
+///
+/// // The slots are free again.
+/// executor.as_mut().spawn(dyn_future(async { count.set(count.get() + 1) })).unwrap();
+/// executor.as_mut().run();
+/// assert_eq!(count.get(), 3);
+/// ```
+///
+/// > Tasks can't spawn other tasks here, since that would need shared access to the slots.
+/// > A wake-up meant for a completed task may cause one spurious poll of the slot's next task, which is harmless.
+pub struct StaticExecutor<F, const N: usize> {
+	/// Pinned, structurally.
+	slots: [Option<F>; N],
+	generations: [u32; N],
+	flags: &'static TaskFlags<N>,
+	_pinned: PhantomPinned,
+}
+
+impl<F: Future<Output = ()>, const N: usize> StaticExecutor<F, N> {
+	/// Creates a new empty [`StaticExecutor`], which wakes its tasks through `flags`.
+	#[must_use]
+	pub fn new(flags: &'static TaskFlags<N>) -> Self {
+		Self {
+			slots: core::array::from_fn(|_| None),
+			generations: [0; N],
+			flags,
+			_pinned: PhantomPinned,
+		}
+	}
+
+	/// The number of tasks that haven't completed yet.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.slots.iter().filter(|slot| slot.is_some()).count()
+	}
+
+	/// Whether all tasks have completed.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.slots.iter().all(Option::is_none)
+	}
+
+	/// Whether the task identified by `handle` is still running, i.e. hasn't completed or been cancelled.
+	#[must_use]
+	pub fn is_running(&self, handle: TaskHandle) -> bool {
+		self.generations[handle.index] == handle.generation && self.slots[handle.index].is_some()
+	}
+
+	/// Places `task` into the first free slot, to be polled in the next round.
+	///
+	/// # Errors
+	///
+	/// Iff all slots are taken, in which case `task` is handed back.
+	pub fn spawn(self: Pin<&mut Self>, task: F) -> Result<TaskHandle, ExecutorFull<F>> {
+		let this = unsafe {
+			//SAFETY: Slots are only ever written or dropped in place below.
+			self.get_unchecked_mut()
+		};
+		let index = match this.slots.iter().position(Option::is_none) {
+			Some(index) => index,
+			None => return Err(ExecutorFull(task)),
+		};
+
+		this.slots[index] = Some(task);
+		this.flags.get(index).store(true, Ordering::Release);
+		Ok(TaskHandle {
+			index,
+			generation: this.generations[index],
+		})
+	}
+
+	/// Drops the task identified by `handle` in place, if it's still running.
+	///
+	/// Returns whether it was.
+	#[must_use]
+	pub fn cancel(self: Pin<&mut Self>, handle: TaskHandle) -> bool {
+		if !self.is_running(handle) {
+			return false;
+		}
+
+		let this = unsafe {
+			//SAFETY: The slot is dropped in place.
+			self.get_unchecked_mut()
+		};
+		this.slots[handle.index] = None;
+		this.generations[handle.index] = this.generations[handle.index].wrapping_add(1);
+		true
+	}
+
+	/// Polls each woken task once, in slot order, and drops those that complete.
+	///
+	/// Returns how many tasks were polled.
+	#[must_use]
+	pub fn poll_round(self: Pin<&mut Self>) -> usize {
+		let this = unsafe {
+			//SAFETY: Slots are only ever pinned or dropped in place below.
+			self.get_unchecked_mut()
+		};
+
+		let mut polled = 0;
+		for (index, slot) in this.slots.iter_mut().enumerate() {
+			let flag = &this.flags.get(index);
+			let task = match slot {
+				Some(task) if flag.swap(false, Ordering::Acquire) => task,
+				_ => continue,
+			};
+
+			let waker = flag_waker(flag);
+			let task = unsafe {
+				//SAFETY: Structurally pinned.
+				Pin::new_unchecked(task)
+			};
+			if let Poll::Ready(()) = task.poll(&mut Context::from_waker(&waker)) {
+				*slot = None;
+				this.generations[index] = this.generations[index].wrapping_add(1);
+			}
+			polled += 1;
+		}
+		polled
+	}
+
+	/// Runs rounds until all tasks have completed, spinning while none are woken.
+	pub fn run(self: Pin<&mut Self>) {
+		self.run_with(hint::spin_loop)
+	}
+
+	/// Runs rounds until all tasks have completed, calling `relax` whenever a round found no woken task.
+	///
+	/// On microcontrollers, `relax` is usually a wait-for-interrupt instruction.
+	pub fn run_with(mut self: Pin<&mut Self>, mut relax: impl FnMut()) {
+		while !self.is_empty() {
+			if self.as_mut().poll_round() == 0 {
+				relax()
+			}
+		}
+	}
+}
+
+/// Shows only which slots are taken, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F, const N: usize> core::fmt::Debug for StaticExecutor<F, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("StaticExecutor")
+			.field(
+				"len",
+				&self.slots.iter().filter(|slot| slot.is_some()).count(),
+			)
+			.field("generations", &self.generations)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,197 @@
+//! A fixed-capacity set of [`Future`]s that are pinned in place, without allocation.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::{FusedStream, Stream};
+
+/// A set of up to `N` [`Future`]s that are driven together, yielding each output along with its key as soon as it's available.
+///
+/// The [`Future`]s are stored inline, so the set has to be pinned before use, but they may be [`!Unpin`](`Unpin`).
+/// A slot is reused by [`.try_push(…)`](`StaticFutureSet::try_push`) once its [`Future`] completed or was removed.
+///
+/// Each poll starts at the slot after the one the previous poll started at, wrapping around.
+///
+/// > Without allocation, there's nowhere to put a waker per slot, so all occupied slots are polled each time.
+/// > That's fine for the handful of concurrent operations bare-metal firmware usually has.
+/// > See [`PinnedFutureSet`](`crate::pinned_future_set::PinnedFutureSet`) for a growable alternative that only polls woken entries.
+pub struct StaticFutureSet<F, const N: usize> {
+	len: usize,
+	/// Where the next poll starts.
+	cursor: usize,
+	/// Pinned structurally. [`None`] marks a vacant slot.
+	slots: [Option<F>; N],
+}
+
+impl<F, const N: usize> StaticFutureSet<F, N> {
+	/// Creates a new empty [`StaticFutureSet`].
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			len: 0,
+			cursor: 0,
+			slots: [(); N].map(|()| None),
+		}
+	}
+
+	/// The number of occupied slots.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Whether all slots are vacant.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Whether all slots are occupied.
+	#[must_use]
+	pub fn is_full(&self) -> bool {
+		self.len == N
+	}
+
+	/// The maximum number of [`Future`]s this set can hold at once, `N`.
+	#[must_use]
+	pub fn capacity(&self) -> usize {
+		N
+	}
+
+	/// Whether the slot at `key` is occupied.
+	///
+	/// # Panics
+	///
+	/// Iff `key >= N`.
+	#[must_use]
+	pub fn is_occupied(&self, key: usize) -> bool {
+		self.slots[key].is_some()
+	}
+
+	fn slot(self: Pin<&mut Self>, key: usize) -> Pin<&mut Option<F>> {
+		unsafe {
+			//SAFETY: Plain structural pin projection. Slots are only ever dropped in place.
+			self.map_unchecked_mut(|this| &mut this.slots[key])
+		}
+	}
+
+	/// Places `future` into a vacant slot and returns its key.
+	///
+	/// # Errors
+	///
+	/// Iff the set is full, in which case `future` is handed back.
+	pub fn try_push(mut self: Pin<&mut Self>, future: F) -> Result<usize, F> {
+		match self.slots.iter().position(Option::is_none) {
+			Some(key) => {
+				self.as_mut().slot(key).set(Some(future));
+				unsafe {
+					//SAFETY: Not a pinned field.
+					self.get_unchecked_mut().len += 1
+				}
+				Ok(key)
+			}
+			None => Err(future),
+		}
+	}
+
+	/// Drops the [`Future`] at `key` in place, without yielding its output.
+	///
+	/// Returns whether the slot was occupied.
+	///
+	/// # Panics
+	///
+	/// Iff `key >= N`.
+	pub fn remove(mut self: Pin<&mut Self>, key: usize) -> bool {
+		if self.slots[key].is_none() {
+			return false;
+		}
+		self.as_mut().slot(key).set(None);
+		unsafe {
+			//SAFETY: Not a pinned field.
+			self.get_unchecked_mut().len -= 1
+		}
+		true
+	}
+}
+
+impl<F: Future, const N: usize> StaticFutureSet<F, N> {
+	/// Yields the key and output of the next [`Future`] that completed, vacating its slot.
+	///
+	/// Returns [`Poll::Ready(None)`](`Poll::Ready`) iff the set is empty.
+	pub fn poll_next(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+	) -> Poll<Option<(usize, F::Output)>> {
+		if self.is_empty() {
+			return Poll::Ready(None);
+		}
+
+		let start = self.cursor;
+		unsafe {
+			//SAFETY: Not a pinned field.
+			self.as_mut().get_unchecked_mut().cursor = (start + 1) % N;
+		}
+
+		for key in (start..N).chain(0..start) {
+			let output = match self.as_mut().slot(key).as_pin_mut() {
+				Some(future) => match future.poll(cx) {
+					Poll::Ready(output) => output,
+					Poll::Pending => continue,
+				},
+				None => continue,
+			};
+			self.as_mut().remove(key);
+			return Poll::Ready(Some((key, output)));
+		}
+		Poll::Pending
+	}
+}
+
+impl<F, const N: usize> Default for StaticFutureSet<F, N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Yields [`None`] whenever the set is empty, but can be resumed after pushing more [`Future`]s.
+#[cfg(feature = "futures-core")]
+impl<F: Future, const N: usize> Stream for StaticFutureSet<F, N> {
+	type Item = (usize, F::Output);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		StaticFutureSet::poll_next(self, cx)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.len, Some(self.len))
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, const N: usize> FusedStream for StaticFutureSet<F, N> {
+	fn is_terminated(&self) -> bool {
+		self.is_empty()
+	}
+}
+
+/// Shows which slots are occupied, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F, const N: usize> core::fmt::Debug for StaticFutureSet<F, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		struct Occupied<'a, F>(&'a [Option<F>]);
+		impl<F> core::fmt::Debug for Occupied<'_, F> {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				f.debug_list()
+					.entries(self.0.iter().map(Option::is_some))
+					.finish()
+			}
+		}
+
+		f.debug_struct("StaticFutureSet")
+			.field("len", &self.len)
+			.field("occupied", &Occupied(&self.slots))
+			.finish_non_exhaustive()
+	}
+}
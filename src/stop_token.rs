@@ -0,0 +1,283 @@
+//! Cooperative cancellation: A [`StopSource`] hands out [`StopToken`]s, which tasks can wait on or check.
+
+#[cfg(feature = "combinators")]
+use crate::ready_or_never::Cancelled;
+use crate::{
+	spin_lock::SpinLock,
+	waiter_list::{Waiter, WaiterList},
+};
+use core::{
+	future::Future,
+	pin::Pin,
+	sync::atomic::{AtomicBool, Ordering},
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+#[cfg(feature = "combinators")]
+use pin_project::pin_project;
+
+/// The owning side of a cancellation signal. Stopping it is permanent.
+///
+/// [`StopToken`]s borrow it, so put it in a `static` or in an enclosing task to share it widely.
+/// This needs no allocation.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{
+///     block_on::block_on,
+///     join_future::join,
+///     stop_token::{with_cancel, StopSource},
+/// };
+///
+/// let source = StopSource::new();
+/// let token = source.token();
+///
+/// // Each child of a combinator can be cancelled individually by wrapping it.
+/// let output = block_on(join((
+///     with_cancel(async { 1 }, token),
+///     async {
+///         source.stop();
+///         2
+///     },
+///     with_cancel(core::future::pending::<()>(), token),
+/// )));
+/// assert!(matches!(output, (Ok(1), 2, Err(_))));
+/// ```
+pub struct StopSource {
+	stopped: AtomicBool,
+	waiters: SpinLock<WaiterList>,
+}
+
+impl StopSource {
+	/// Creates a new instance of [`StopSource`] that hasn't been stopped yet.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			stopped: AtomicBool::new(false),
+			waiters: SpinLock::new(WaiterList::new()),
+		}
+	}
+
+	/// Creates a [`StopToken`] that observes this [`StopSource`].
+	#[must_use]
+	pub fn token(&self) -> StopToken<'_> {
+		StopToken { source: self }
+	}
+
+	/// Whether [`.stop()`](`StopSource::stop`) was called.
+	#[must_use]
+	pub fn is_stopped(&self) -> bool {
+		self.stopped.load(Ordering::Acquire)
+	}
+
+	/// Signals all [`StopToken`]s and wakes all tasks waiting on them.
+	///
+	/// Returns whether this call stopped the [`StopSource`], i.e. `false` iff it was stopped before.
+	pub fn stop(&self) -> bool {
+		if self.stopped.swap(true, Ordering::AcqRel) {
+			return false;
+		}
+
+		// Woken one by one, outside the lock.
+		while let Some(waker) = self.waiters.lock().notify_one() {
+			waker.wake()
+		}
+		true
+	}
+}
+
+impl Default for StopSource {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only whether it's stopped, to not lock while formatting.
+impl core::fmt::Debug for StopSource {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("StopSource")
+			.field("stopped", &self.is_stopped())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A shared view of a [`StopSource`], which can be copied freely.
+#[derive(Debug, Clone, Copy)]
+pub struct StopToken<'a> {
+	source: &'a StopSource,
+}
+
+impl<'a> StopToken<'a> {
+	/// Whether the [`StopSource`] was stopped.
+	#[must_use]
+	pub fn is_stopped(&self) -> bool {
+		self.source.is_stopped()
+	}
+
+	/// Creates a [`Future`] that completes once the [`StopSource`] is stopped.
+	#[must_use]
+	pub fn stopped(self) -> Stopped<'a> {
+		Stopped {
+			source: self.source,
+			waiter: Waiter::new(),
+			registered: false,
+		}
+	}
+}
+
+/// A [`Future`] that completes once its [`StopSource`] is stopped.
+///
+/// Polling it again after that completes again right away.
+pub struct Stopped<'a> {
+	source: &'a StopSource,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked, to skip locking on drop otherwise.
+	registered: bool,
+}
+
+impl Stopped<'_> {
+	fn waiter(self: Pin<&Self>) -> Pin<&Waiter> {
+		unsafe {
+			//SAFETY: Structurally pinned.
+			self.map_unchecked(|this| &this.waiter)
+		}
+	}
+}
+
+impl Future for Stopped<'_> {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.source.is_stopped() {
+			return Poll::Ready(());
+		}
+
+		let mut waiters = self.source.waiters.lock();
+		unsafe {
+			//SAFETY: `waiter` is only used with this list, and removed on drop.
+			waiters.register(self.as_ref().waiter(), cx.waker());
+		}
+		// Checked again under the lock, in case `stop` drained the list in between.
+		let stopped = self.source.is_stopped();
+		if stopped {
+			unsafe {
+				//SAFETY: As above.
+				waiters.remove(self.as_ref().waiter());
+			}
+		}
+		drop(waiters);
+
+		unsafe {
+			//SAFETY: Doesn't move anything.
+			self.as_mut().get_unchecked_mut().registered = !stopped;
+		}
+		if stopped {
+			Poll::Ready(())
+		} else {
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl FusedFuture for Stopped<'_> {
+	/// Never, since it completes again right away.
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+impl Drop for Stopped<'_> {
+	fn drop(&mut self) {
+		if self.registered {
+			let this = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&*self)
+			};
+			unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				this.source.waiters.lock().remove(this.waiter());
+			}
+		}
+	}
+}
+
+/// Shows only whether it's stopped, to not lock while formatting.
+impl core::fmt::Debug for Stopped<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Stopped")
+			.field("stopped", &self.source.is_stopped())
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a [`WithCancel`] that completes with the output of `future`,
+/// or with [`Cancelled`] as soon as `token`'s [`StopSource`] is stopped, dropping `future`.
+#[cfg(feature = "combinators")]
+pub fn with_cancel<F: Future>(future: F, token: StopToken<'_>) -> WithCancel<'_, F> {
+	WithCancel {
+		future: Some(future),
+		stopped: token.stopped(),
+	}
+}
+
+/// A [`Future`] that completes with the output of `future`, or with [`Cancelled`] once its [`StopSource`] is stopped.
+///
+/// Cancellation is checked before each poll of the inner [`Future`], which is dropped in place on completion.
+#[cfg(feature = "combinators")]
+#[pin_project]
+pub struct WithCancel<'a, F> {
+	/// [`None`] once completed.
+	#[pin]
+	future: Option<F>,
+	#[pin]
+	stopped: Stopped<'a>,
+}
+
+#[cfg(feature = "combinators")]
+impl<F: Future> Future for WithCancel<'_, F> {
+	type Output = Result<F::Output, Cancelled>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mut this = self.project();
+		assert!(
+			this.future.is_some(),
+			"`WithCancel` was previously completed."
+		);
+
+		if this.stopped.poll(cx).is_ready() {
+			this.future.set(None);
+			return Poll::Ready(Err(Cancelled));
+		}
+
+		let output = this
+			.future
+			.as_mut()
+			.as_pin_mut()
+			.expect("unreachable")
+			.poll(cx);
+		if output.is_ready() {
+			this.future.set(None);
+		}
+		output.map(Ok)
+	}
+}
+
+#[cfg(all(feature = "combinators", feature = "futures-core"))]
+impl<F: Future> FusedFuture for WithCancel<'_, F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_none()
+	}
+}
+
+/// Shows only the progress, since `F` isn't necessarily [`Debug`](`core::fmt::Debug`).
+#[cfg(feature = "combinators")]
+impl<F> core::fmt::Debug for WithCancel<'_, F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("WithCancel")
+			.field("done", &self.future.is_none())
+			.finish_non_exhaustive()
+	}
+}
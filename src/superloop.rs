@@ -0,0 +1,149 @@
+//! A round-robin executor over a fixed, pinned array of tasks, for bare-metal main loops.
+
+use crate::{pinned_pin::PinnedPin, waker::flag_waker};
+use core::{
+	future::Future,
+	hint,
+	pin::Pin,
+	sync::atomic::{AtomicBool, Ordering},
+	task::{Context, Poll},
+};
+
+/// One "dirty" flag per task of a [`Superloop`] or [`StaticExecutor`](`crate::static_executor::StaticExecutor`), which its [`Waker`](`core::task::Waker`)s set when woken.
+///
+/// [`Waker`](`core::task::Waker`)s can't borrow, so this has to be a `static`.
+#[derive(Debug)]
+pub struct TaskFlags<const N: usize>([AtomicBool; N]);
+
+impl<const N: usize> TaskFlags<N> {
+	/// Creates a new instance of [`TaskFlags`], with all flags clear.
+	#[must_use]
+	pub const fn new() -> Self {
+		#[allow(clippy::declare_interior_mutable_const)]
+		const CLEAR: AtomicBool = AtomicBool::new(false);
+		Self([CLEAR; N])
+	}
+
+	pub(crate) fn get(&self, index: usize) -> &AtomicBool {
+		&self.0[index]
+	}
+}
+
+impl<const N: usize> Default for TaskFlags<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Polls a fixed set of tasks in index order, each only after it was woken.
+///
+/// Tasks may be anything that completes with `()`. Use a common type to mix different [`Future`]s,
+/// like [`Pin<&mut dyn Future<Output = ()>>`](`Pin`) for inline tasks
+/// or [`DynFuture<'_, ()>`](`crate::dyn_future::DynFuture`) for boxed ones.
+///
+/// Completed tasks stay in place, but aren't polled again.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::{cell::RefCell, future::Future, pin::Pin};
+/// use unpin_choices_dsa::{
+///     pinned_pin::PinnedPin,
+///     superloop::{Superloop, TaskFlags},
+///     yield_now::yield_now,
+/// };
+///
+/// static FLAGS: TaskFlags<2> = TaskFlags::new();
+///
+/// let log = RefCell::new(Vec::new());
+/// let mut tasks: PinnedPin<[Pin<Box<dyn Future<Output = ()>>>; 2]> = PinnedPin([
+///     Box::pin(async {
+///         log.borrow_mut().push(1);
+///         yield_now().await;
+///         log.borrow_mut().push(3);
+///     }),
+///     Box::pin(async { log.borrow_mut().push(2) }),
+/// ]);
+///
+/// Superloop::new(Pin::new(&mut tasks), &FLAGS).run();
+/// assert_eq!(*log.borrow(), [1, 2, 3]);
+/// ```
+///
+/// > A fixed array means no allocation and no spawning, which is usually all a firmware needs.
+pub struct Superloop<'a, T, const N: usize> {
+	tasks: Pin<&'a mut PinnedPin<[T; N]>>,
+	flags: &'static TaskFlags<N>,
+	done: [bool; N],
+}
+
+impl<'a, T: Future<Output = ()>, const N: usize> Superloop<'a, T, N> {
+	/// Creates a new instance of [`Superloop`] over `tasks`, which are woken through `flags`.
+	///
+	/// All tasks are polled in the first round.
+	#[must_use]
+	pub fn new(tasks: Pin<&'a mut PinnedPin<[T; N]>>, flags: &'static TaskFlags<N>) -> Self {
+		for flag in &flags.0 {
+			flag.store(true, Ordering::Release)
+		}
+		Self {
+			tasks,
+			flags,
+			done: [false; N],
+		}
+	}
+
+	/// Whether all tasks have completed.
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.done.iter().all(|done| *done)
+	}
+
+	/// Polls each woken task that hasn't completed yet once, in index order.
+	///
+	/// Returns how many tasks were polled.
+	pub fn poll_round(&mut self) -> usize {
+		let mut polled = 0;
+		for ((task, flag), done) in self
+			.tasks
+			.as_mut()
+			.into_iter()
+			.zip(&self.flags.0)
+			.zip(&mut self.done)
+		{
+			if *done || !flag.swap(false, Ordering::Acquire) {
+				continue;
+			}
+
+			let waker = flag_waker(flag);
+			if let Poll::Ready(()) = task.poll(&mut Context::from_waker(&waker)) {
+				*done = true;
+			}
+			polled += 1;
+		}
+		polled
+	}
+
+	/// Runs rounds until all tasks have completed, spinning while none are woken.
+	pub fn run(&mut self) {
+		self.run_with(hint::spin_loop)
+	}
+
+	/// Runs rounds until all tasks have completed, calling `relax` whenever a round found no woken task.
+	///
+	/// On microcontrollers, `relax` is usually a wait-for-interrupt instruction.
+	pub fn run_with(&mut self, mut relax: impl FnMut()) {
+		while !self.is_done() {
+			if self.poll_round() == 0 {
+				relax()
+			}
+		}
+	}
+}
+
+/// Shows only the progress, since `T` isn't necessarily [`Debug`](`core::fmt::Debug`).
+impl<T, const N: usize> core::fmt::Debug for Superloop<'_, T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Superloop")
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,98 @@
+//! A manually advanced [`Timer`], for testing time-based combinators deterministically.
+
+use crate::timer::{Clock, Timer};
+use alloc::vec::Vec;
+use core::{
+	cell::{Cell, RefCell},
+	mem,
+	task::Waker,
+	time::Duration,
+};
+
+/// A [`Timer`] whose time only moves when told to, counting [`Duration`]s from `0`.
+///
+/// Wakers registered through [`Timer::wake_at`] are woken during [`.advance(…)`](`TestClock::advance`)
+/// once their deadline has been reached.
+///
+/// Share it by reference, as `&TestClock` implements [`Timer`] too.
+///
+/// > This isn't [`Sync`], so it can't be registered as global [`Delay`](`crate::sleep::Delay`) driver.
+/// > Use the `_on` variants of the combinators with it instead, like [`timeout`](`crate::timeout::timeout`).
+#[derive(Debug, Default)]
+pub struct TestClock {
+	now: Cell<Duration>,
+	wakers: RefCell<Vec<(Duration, Waker)>>,
+}
+
+impl TestClock {
+	/// Creates a new instance of [`TestClock`] at time `0`.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Moves time forward by `by`, waking all wakers that are due.
+	pub fn advance(&self, by: Duration) {
+		self.set(self.now.get() + by);
+	}
+
+	/// Moves time forward to `to`, waking all wakers that are due.
+	///
+	/// # Panics
+	///
+	/// Iff `to` is earlier than the current time.
+	pub fn set(&self, to: Duration) {
+		assert!(to >= self.now.get(), "`TestClock` can't go back in time.");
+		self.now.set(to);
+
+		let due = {
+			let mut wakers = self.wakers.borrow_mut();
+			let (due, later) = mem::take(&mut *wakers)
+				.into_iter()
+				.partition::<Vec<_>, _>(|&(deadline, _)| deadline <= to);
+			*wakers = later;
+			due
+		};
+		// Wakers may call back into the clock, so they are woken without holding the borrow.
+		for (_, waker) in due {
+			waker.wake()
+		}
+	}
+
+	/// The number of wakers that haven't been woken yet.
+	#[must_use]
+	pub fn pending(&self) -> usize {
+		self.wakers.borrow().len()
+	}
+
+	/// The earliest deadline any waker is waiting for, if any.
+	///
+	/// Advancing to this deadline in a loop runs a test as quickly as possible.
+	#[must_use]
+	pub fn next_deadline(&self) -> Option<Duration> {
+		self.wakers
+			.borrow()
+			.iter()
+			.map(|&(deadline, _)| deadline)
+			.min()
+	}
+}
+
+impl Clock for TestClock {
+	type Instant = Duration;
+
+	fn now(&self) -> Self::Instant {
+		self.now.get()
+	}
+}
+
+/// Wakes right away if `deadline` has passed already.
+impl Timer for TestClock {
+	fn wake_at(&self, deadline: Self::Instant, waker: &Waker) {
+		if deadline <= self.now.get() {
+			waker.wake_by_ref();
+		} else {
+			self.wakers.borrow_mut().push((deadline, waker.clone()));
+		}
+	}
+}
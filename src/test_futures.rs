@@ -0,0 +1,216 @@
+//! Deterministic and instrumented [`Future`]s for testing combinators.
+
+use core::{
+	future::Future,
+	marker::PhantomData,
+	pin::Pin,
+	sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::{pin_project, pinned_drop};
+
+/// Creates a [`ReadyAfter`] that returns [`Poll::Pending`] `pending_polls` times and then completes with `value`.
+pub fn ready_after<T>(pending_polls: usize, value: T) -> ReadyAfter<T> {
+	ReadyAfter {
+		remaining: pending_polls,
+		value: Some(value),
+	}
+}
+
+/// A [`Future`] that returns [`Poll::Pending`] a set number of times and then completes with a value.
+///
+/// It wakes itself each time it's pending, so it makes progress with any executor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReadyAfter<T> {
+	remaining: usize,
+	/// [`None`] once completed.
+	value: Option<T>,
+}
+
+impl<T> ReadyAfter<T> {
+	/// How many more times this [`ReadyAfter`] will return [`Poll::Pending`].
+	#[must_use]
+	pub fn remaining(&self) -> usize {
+		self.remaining
+	}
+}
+
+impl<T> Unpin for ReadyAfter<T> {}
+
+impl<T> Future for ReadyAfter<T> {
+	type Output = T;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.remaining > 0 {
+			self.remaining -= 1;
+			cx.waker().wake_by_ref();
+			Poll::Pending
+		} else {
+			Poll::Ready(
+				self.value
+					.take()
+					.expect("`ReadyAfter` was previously completed."),
+			)
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> FusedFuture for ReadyAfter<T> {
+	fn is_terminated(&self) -> bool {
+		self.value.is_none()
+	}
+}
+
+/// Creates a [`PendingForever`].
+#[must_use]
+pub fn pending_forever<T>() -> PendingForever<T> {
+	PendingForever(PhantomData)
+}
+
+/// A [`Future`] that never completes and never wakes its task.
+///
+/// > Unlike [`core::future::Pending`], this one implements [`FusedFuture`](`futures_core::FusedFuture`) (as never terminated),
+/// > so it can stand in for any fused constituent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PendingForever<T>(PhantomData<fn() -> T>);
+
+impl<T> Future for PendingForever<T> {
+	type Output = T;
+
+	fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+		Poll::Pending
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<T> FusedFuture for PendingForever<T> {
+	fn is_terminated(&self) -> bool {
+		false
+	}
+}
+
+/// Wraps `future` in a [`PanicOnSecondPoll`].
+pub fn panic_on_second_poll<F: Future>(future: F) -> PanicOnSecondPoll<F> {
+	PanicOnSecondPoll {
+		future,
+		done: false,
+	}
+}
+
+/// A [`Future`] that panics if it's polled again after completing.
+///
+/// Wrap constituents in this to assert that a combinator doesn't poll them after they completed.
+/// (For a [`Future`] that is ready right away, that means panicking on the second poll.)
+#[pin_project]
+#[derive(Debug)]
+pub struct PanicOnSecondPoll<F> {
+	#[pin]
+	future: F,
+	done: bool,
+}
+
+impl<F> PanicOnSecondPoll<F> {
+	/// Whether the inner [`Future`] has completed.
+	#[must_use]
+	pub fn is_done(&self) -> bool {
+		self.done
+	}
+}
+
+impl<F: Future> Future for PanicOnSecondPoll<F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		assert!(
+			!*this.done,
+			"`PanicOnSecondPoll` was polled after completing."
+		);
+		let output = this.future.poll(cx);
+		*this.done = output.is_ready();
+		output
+	}
+}
+
+/// Reports the completion, so that fuse-aware combinators can be tested with it too.
+#[cfg(feature = "futures-core")]
+impl<F: Future> FusedFuture for PanicOnSecondPoll<F> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+/// Wraps `future` in a [`PollCounter`] that increments `polls` each time it's polled.
+pub fn poll_counter<F: Future>(future: F, polls: &AtomicUsize) -> PollCounter<'_, F> {
+	PollCounter { future, polls }
+}
+
+/// A transparent [`Future`] wrapper that counts how often it's polled, in a counter outside of it.
+///
+/// The counter stays accessible while the [`PollCounter`] is owned by a combinator.
+/// (See [`Instrumented`](`crate::instrumented::Instrumented`) for a wrapper that keeps its statistics inside.)
+#[pin_project]
+#[derive(Debug)]
+pub struct PollCounter<'a, F> {
+	#[pin]
+	future: F,
+	polls: &'a AtomicUsize,
+}
+
+impl<F: Future> Future for PollCounter<'_, F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		this.polls.fetch_add(1, Ordering::Relaxed);
+		this.future.poll(cx)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: FusedFuture> FusedFuture for PollCounter<'_, F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_terminated()
+	}
+}
+
+/// Wraps `future` in a [`DropTracker`] that sets `dropped` when it's dropped.
+pub fn drop_tracker<F: Future>(future: F, dropped: &AtomicBool) -> DropTracker<'_, F> {
+	DropTracker { future, dropped }
+}
+
+/// A transparent [`Future`] wrapper that sets a flag outside of it when it's dropped.
+///
+/// Use this to assert that a combinator drops its constituents promptly, for example once they lost a race.
+#[pin_project(PinnedDrop)]
+#[derive(Debug)]
+pub struct DropTracker<'a, F> {
+	#[pin]
+	future: F,
+	dropped: &'a AtomicBool,
+}
+
+#[pinned_drop]
+impl<F> PinnedDrop for DropTracker<'_, F> {
+	fn drop(self: Pin<&mut Self>) {
+		self.dropped.store(true, Ordering::Relaxed)
+	}
+}
+
+impl<F: Future> Future for DropTracker<'_, F> {
+	type Output = F::Output;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.project().future.poll(cx)
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: FusedFuture> FusedFuture for DropTracker<'_, F> {
+	fn is_terminated(&self) -> bool {
+		self.future.is_terminated()
+	}
+}
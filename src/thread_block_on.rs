@@ -0,0 +1,47 @@
+//! A `block_on` that parks the current thread while waiting, for use with the standard library.
+
+use crate::waker::{waker_from_arc, ArcWake};
+use alloc::sync::Arc;
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+use std::thread::{self, Thread};
+
+struct Unparker(Thread);
+
+impl ArcWake for Unparker {
+	fn wake_by_ref(arc_self: &Arc<Self>) {
+		arc_self.0.unpark()
+	}
+}
+
+/// Drives `future` to completion on the current thread, which is parked until `future`'s [`Waker`](`core::task::Waker`) is woken.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::thread_block_on::block_on;
+///
+/// assert_eq!(block_on(async { 1 + 1 }), 2);
+/// ```
+///
+/// > Unlike [`crate::block_on::block_on`], this doesn't use any CPU time while waiting.
+/// > [`thread::park`]'s token makes sure that a wake-up between the poll and parking isn't lost.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+	let mut future = future;
+	let mut future = unsafe {
+		//SAFETY: Shadowed, so it can't be moved again.
+		Pin::new_unchecked(&mut future)
+	};
+
+	let waker = waker_from_arc(Arc::new(Unparker(thread::current())));
+	let mut cx = Context::from_waker(&waker);
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+			return output;
+		}
+		// Spurious wake-ups just lead to another poll.
+		thread::park()
+	}
+}
@@ -0,0 +1,178 @@
+//! A [`Timer`] backed by a background thread, for use with the standard library.
+
+use crate::timer::{Clock, Timer};
+use alloc::{collections::BinaryHeap, sync::Arc, vec::Vec};
+use core::{
+	cmp::{Ordering, Reverse},
+	task::Waker,
+};
+use std::{
+	sync::{Condvar, Mutex, MutexGuard, PoisonError},
+	thread::{self, JoinHandle},
+	time::Instant,
+};
+
+/// A [`Timer`] on [`std::time::Instant`] that wakes tasks from a dedicated thread.
+///
+/// The thread is started by [`ThreadTimer::new`] and stopped and joined when the [`ThreadTimer`] is dropped.
+/// Share it by reference, as `&ThreadTimer` implements [`Timer`] too.
+///
+/// > This is the simplest thing that works, with one mutex around a deadline queue.
+/// > Async runtimes have much more scalable timer wheels, so prefer theirs where available.
+#[derive(Debug)]
+pub struct ThreadTimer {
+	shared: Arc<Shared>,
+	thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+	state: Mutex<State>,
+	condvar: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	queue: BinaryHeap<Reverse<Deadline>>,
+	shutdown: bool,
+}
+
+/// Ordered only by its instant.
+#[derive(Debug)]
+struct Deadline(Instant, Waker);
+
+impl PartialEq for Deadline {
+	fn eq(&self, other: &Self) -> bool {
+		self.0 == other.0
+	}
+}
+
+impl Eq for Deadline {}
+
+impl PartialOrd for Deadline {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Deadline {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.0.cmp(&other.0)
+	}
+}
+
+impl Shared {
+	/// Panics while the lock is held can only come from the allocator, so poisoning is ignored.
+	fn lock(&self) -> MutexGuard<'_, State> {
+		self.state.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+
+	fn run(&self) {
+		let mut state = self.lock();
+		let mut due = Vec::new();
+		loop {
+			if state.shutdown {
+				return;
+			}
+
+			let now = Instant::now();
+			while matches!(state.queue.peek(), Some(Reverse(Deadline(deadline, _))) if *deadline <= now)
+			{
+				let Reverse(Deadline(_, waker)) = state.queue.pop().expect("unreachable");
+				due.push(waker);
+			}
+
+			if !due.is_empty() {
+				// Wakers may call back into the timer, so they are woken without holding the lock.
+				drop(state);
+				due.drain(..).for_each(Waker::wake);
+				state = self.lock();
+				continue;
+			}
+
+			state = match state.queue.peek() {
+				Some(Reverse(Deadline(deadline, _))) => {
+					let timeout = deadline.saturating_duration_since(now);
+					self.condvar
+						.wait_timeout(state, timeout)
+						.unwrap_or_else(PoisonError::into_inner)
+						.0
+				}
+				None => self
+					.condvar
+					.wait(state)
+					.unwrap_or_else(PoisonError::into_inner),
+			};
+		}
+	}
+}
+
+impl ThreadTimer {
+	/// Creates a new instance of [`ThreadTimer`], starting its thread.
+	///
+	/// # Panics
+	///
+	/// Iff the thread can't be spawned.
+	#[must_use]
+	pub fn new() -> Self {
+		let shared = Arc::new(Shared::default());
+		let thread = thread::Builder::new()
+			.name("unpin-choices-dsa ThreadTimer".into())
+			.spawn({
+				let shared = Arc::clone(&shared);
+				move || shared.run()
+			})
+			.expect("Failed to spawn the `ThreadTimer` thread.");
+		Self {
+			shared,
+			thread: Some(thread),
+		}
+	}
+
+	/// The number of pending wake-ups.
+	#[must_use]
+	pub fn pending(&self) -> usize {
+		self.shared.lock().queue.len()
+	}
+}
+
+impl Default for ThreadTimer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Drop for ThreadTimer {
+	/// Stops the thread without waking the remaining wakers.
+	fn drop(&mut self) {
+		self.shared.lock().shutdown = true;
+		self.shared.condvar.notify_one();
+		if let Some(thread) = self.thread.take() {
+			// The thread doesn't panic by itself, but wakers might.
+			thread.join().ok();
+		}
+	}
+}
+
+impl Clock for ThreadTimer {
+	type Instant = Instant;
+
+	fn now(&self) -> Self::Instant {
+		Instant::now()
+	}
+}
+
+impl Timer for ThreadTimer {
+	fn wake_at(&self, deadline: Self::Instant, waker: &Waker) {
+		let mut state = self.shared.lock();
+		let earliest = state
+			.queue
+			.peek()
+			.map_or(true, |Reverse(Deadline(next, _))| deadline < *next);
+		state.queue.push(Reverse(Deadline(deadline, waker.clone())));
+		drop(state);
+		if earliest {
+			self.shared.condvar.notify_one();
+		}
+	}
+}
@@ -0,0 +1,124 @@
+//! A [`Stream`] adaptor that spaces out items.
+
+use crate::{
+	sleep::{self, Delay},
+	timer::Timer,
+};
+use core::{
+	ops::Add,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+use futures_core::{FusedStream, Stream};
+use pin_project::pin_project;
+
+/// Creates a [`Throttle`] that yields the items of `stream` at least `min_interval` apart,
+/// measured on the driver registered with [`set_driver`](`crate::sleep::set_driver`).
+///
+/// # Panics
+///
+/// Iff no driver was registered.
+pub fn throttle<S: Stream>(stream: S, min_interval: Duration) -> Throttle<S> {
+	Throttle::new(stream, sleep::expect_driver(), min_interval)
+}
+
+/// Creates a [`Throttle`] that yields the items of `stream` at least `min_interval` apart, measured on `timer`.
+pub fn throttle_on<S: Stream, T: Timer, D: Copy>(
+	timer: T,
+	stream: S,
+	min_interval: D,
+) -> Throttle<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	Throttle::new(stream, timer, min_interval)
+}
+
+/// A [`Stream`] that yields the items of `stream` at least `min_interval` apart.
+///
+/// Items aren't dropped. Instead, the inner [`Stream`] isn't polled until the interval has passed,
+/// so that it can apply backpressure.
+#[pin_project]
+pub struct Throttle<S, T: Timer = &'static dyn Delay, D = Duration> {
+	#[pin]
+	stream: S,
+	timer: T,
+	min_interval: D,
+	/// When the next item may be yielded, if one was yielded before.
+	next: Option<T::Instant>,
+}
+
+impl<S, T: Timer, D> Throttle<S, T, D> {
+	/// Creates a new instance of [`Throttle`] that yields the items of `stream` at least `min_interval` apart, measured on `timer`.
+	#[must_use]
+	pub fn new(stream: S, timer: T, min_interval: D) -> Self {
+		Self {
+			stream,
+			timer,
+			min_interval,
+			next: None,
+		}
+	}
+
+	/// Gives pinning exclusive access to the inner [`Stream`].
+	#[must_use]
+	pub fn stream_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+		self.project().stream
+	}
+
+	/// Unwraps the inner [`Stream`].
+	#[must_use]
+	pub fn into_inner(self) -> S {
+		self.stream
+	}
+}
+
+impl<S: Stream, T: Timer, D: Copy> Stream for Throttle<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(next) = *this.next {
+			if this.timer.now() < next {
+				this.timer.wake_at(next, cx.waker());
+				return Poll::Pending;
+			}
+		}
+
+		let item = this.stream.poll_next(cx);
+		if let Poll::Ready(Some(_)) = item {
+			*this.next = Some(this.timer.now() + *this.min_interval);
+		}
+		item
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.stream.size_hint()
+	}
+}
+
+impl<S: FusedStream, T: Timer, D: Copy> FusedStream for Throttle<S, T, D>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	fn is_terminated(&self) -> bool {
+		self.stream.is_terminated()
+	}
+}
+
+/// Shows only the schedule, since `S` and `T` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<S, T: Timer, D: core::fmt::Debug> core::fmt::Debug for Throttle<S, T, D>
+where
+	T::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Throttle")
+			.field("min_interval", &self.min_interval)
+			.field("next", &self.next)
+			.finish_non_exhaustive()
+	}
+}
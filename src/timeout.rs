@@ -0,0 +1,128 @@
+//! A [`Future`] adaptor that gives up on another after a deadline.
+
+use crate::timer::{TimedOut, Timer};
+use core::{
+	future::Future,
+	ops::Add,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+use pin_project::pin_project;
+
+/// Creates a [`Future`] that completes with the output of `future`,
+/// or with [`TimedOut`] if `duration` passes on `timer` first.
+///
+/// The deadline is calculated right away, not when the [`Timeout`] is first polled.
+///
+/// `D` is whatever can be added to `timer`'s instants, like [`Duration`](`core::time::Duration`) for
+/// [`ThreadTimer`](`crate::thread_timer::ThreadTimer`) or a plain tick count for many HAL timers.
+pub fn timeout<F: Future, T: Timer, D>(timer: T, duration: D, future: F) -> Timeout<F, T>
+where
+	T::Instant: Add<D, Output = T::Instant>,
+{
+	let deadline = timer.now() + duration;
+	Timeout::new(future, timer, deadline)
+}
+
+/// Creates a [`Future`] that completes with the output of `future`,
+/// or with [`TimedOut`] if `deadline` passes on `timer` first.
+pub fn timeout_at<F: Future, T: Timer>(timer: T, deadline: T::Instant, future: F) -> Timeout<F, T> {
+	Timeout::new(future, timer, deadline)
+}
+
+/// A [`Future`] that completes with the output of `future`, or with [`TimedOut`] once `deadline` has passed.
+///
+/// `future` is always polled before the deadline is checked,
+/// so a [`Future`] that is ready in time wins even if the [`Timeout`] is polled late.
+///
+/// > The inner [`Future`] isn't dropped on time-out, only when the [`Timeout`] is.
+/// > Use [`.into_inner()`](`Timeout::into_inner`) to retry with a new deadline without losing its progress.
+#[pin_project]
+pub struct Timeout<F, T: Timer> {
+	#[pin]
+	future: F,
+	timer: T,
+	deadline: T::Instant,
+	done: bool,
+}
+
+impl<F, T: Timer> Timeout<F, T> {
+	/// Creates a new instance of [`Timeout`] that gives up on `future` once `deadline` passes on `timer`.
+	#[must_use]
+	pub fn new(future: F, timer: T, deadline: T::Instant) -> Self {
+		Self {
+			future,
+			timer,
+			deadline,
+			done: false,
+		}
+	}
+
+	/// The point in time after which this [`Timeout`] gives up.
+	#[must_use]
+	pub fn deadline(&self) -> T::Instant {
+		self.deadline
+	}
+
+	/// The [`Timer`] this [`Timeout`] consults.
+	#[must_use]
+	pub fn timer(&self) -> &T {
+		&self.timer
+	}
+
+	/// Gives pinning exclusive access to the inner [`Future`].
+	#[must_use]
+	pub fn future_pin_mut(self: Pin<&mut Self>) -> Pin<&mut F> {
+		self.project().future
+	}
+
+	/// Unwraps the inner [`Future`].
+	#[must_use]
+	pub fn into_inner(self) -> F {
+		self.future
+	}
+}
+
+impl<F: Future, T: Timer> Future for Timeout<F, T> {
+	type Output = Result<F::Output, TimedOut>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.project();
+		assert!(!*this.done, "`Timeout` was previously completed.");
+
+		if let Poll::Ready(output) = this.future.poll(cx) {
+			*this.done = true;
+			return Poll::Ready(Ok(output));
+		}
+
+		if this.timer.now() >= *this.deadline {
+			*this.done = true;
+			Poll::Ready(Err(TimedOut))
+		} else {
+			this.timer.wake_at(*this.deadline, cx.waker());
+			Poll::Pending
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl<F: Future, T: Timer> FusedFuture for Timeout<F, T> {
+	fn is_terminated(&self) -> bool {
+		self.done
+	}
+}
+
+/// Shows only the deadline and progress, since `F` and `T` aren't necessarily [`Debug`](`core::fmt::Debug`).
+impl<F, T: Timer> core::fmt::Debug for Timeout<F, T>
+where
+	T::Instant: core::fmt::Debug,
+{
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Timeout")
+			.field("deadline", &self.deadline)
+			.field("done", &self.done)
+			.finish_non_exhaustive()
+	}
+}
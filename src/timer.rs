@@ -0,0 +1,63 @@
+//! A minimal timekeeping abstraction, so that time-aware combinators don't depend on any particular runtime.
+
+use core::{
+	fmt::{self, Display, Formatter},
+	task::Waker,
+};
+
+/// A source of time.
+///
+/// This is all that [`Instrumented`](`crate::instrumented::Instrumented`) needs.
+/// Combinators that wait for a deadline need a [`Timer`] instead.
+///
+/// > There is no `Duration` in here on purpose, since monotonic tick counts are more common on embedded targets.
+pub trait Clock {
+	/// A point in time. Later instants must compare as greater.
+	type Instant: Ord + Copy;
+
+	/// The current point in time.
+	fn now(&self) -> Self::Instant;
+}
+
+/// A [`Clock`] that can wake tasks at a deadline.
+///
+/// Implement this on top of your HAL timer or an OS timer thread.
+/// For tests, [`TestClock`](`crate::test_clock::TestClock`) lets you advance time manually.
+pub trait Timer: Clock {
+	/// Arranges for `waker` to be woken once `deadline` has passed.
+	///
+	/// Spurious or early wake-ups are fine, as callers check [`.now()`](`Clock::now`) again when polled.
+	/// Repeated calls with the same [`Waker`] may be coalesced.
+	fn wake_at(&self, deadline: Self::Instant, waker: &Waker);
+}
+
+/// A [`Clock`] that doesn't keep time, for where measuring it is optional.
+impl Clock for () {
+	type Instant = ();
+
+	fn now(&self) -> Self::Instant {}
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+	type Instant = C::Instant;
+
+	fn now(&self) -> Self::Instant {
+		(**self).now()
+	}
+}
+
+impl<T: Timer + ?Sized> Timer for &T {
+	fn wake_at(&self, deadline: Self::Instant, waker: &Waker) {
+		(**self).wake_at(deadline, waker)
+	}
+}
+
+/// The error returned when a deadline passed before an operation completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct TimedOut;
+
+impl Display for TimedOut {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("deadline has elapsed")
+	}
+}
@@ -0,0 +1,195 @@
+//! An intrusive list of waiting [`Future`](`core::future::Future`)s, for the synchronisation primitives.
+
+use core::{cell::UnsafeCell, marker::PhantomPinned, pin::Pin, ptr::NonNull, task::Waker};
+
+/// A FIFO queue of pinned [`Waiter`]s, each of which is stored inside the [`Future`](`core::future::Future`) that waits.
+///
+/// This needs no allocation and, unlike a [`WakerSet`](`crate::waker_set::WakerSet`),
+/// tells apart several waiting [`Future`]s of the same task.
+/// Each [`Waiter`] unlinks itself exactly once, when its [`Future`] is dropped.
+///
+/// `&mut WaiterList` is taken as proof that the lock guarding it is held,
+/// which also guards all [`Waiter`]s linked into it.
+///
+/// > Wakers are always returned rather than woken, so that they can be woken after the lock is released.
+pub(crate) struct WaiterList {
+	head: Option<NonNull<Waiter>>,
+	tail: Option<NonNull<Waiter>>,
+	len: usize,
+}
+
+/// # Safety notes
+///
+/// The [`Waiter`]s are only accessed through the list while its lock is held.
+unsafe impl Send for WaiterList {}
+
+/// A node in a [`WaiterList`]. It must be pinned while linked.
+pub(crate) struct Waiter {
+	node: UnsafeCell<Node>,
+	_pinned: PhantomPinned,
+}
+
+/// # Safety notes
+///
+/// `node` is only accessed while the lock of the [`WaiterList`] it's used with is held.
+unsafe impl Send for Waiter {}
+unsafe impl Sync for Waiter {}
+
+struct Node {
+	waker: Option<Waker>,
+	prev: Option<NonNull<Waiter>>,
+	next: Option<NonNull<Waiter>>,
+	linked: bool,
+	notified: bool,
+	/// What this [`Waiter`] is waiting for, for primitives that grant in portions.
+	demand: usize,
+}
+
+impl Waiter {
+	pub(crate) const fn new() -> Self {
+		Self::with_demand(0)
+	}
+
+	pub(crate) const fn with_demand(demand: usize) -> Self {
+		Self {
+			node: UnsafeCell::new(Node {
+				waker: None,
+				prev: None,
+				next: None,
+				linked: false,
+				notified: false,
+				demand,
+			}),
+			_pinned: PhantomPinned,
+		}
+	}
+}
+
+impl WaiterList {
+	pub(crate) const fn new() -> Self {
+		Self {
+			head: None,
+			tail: None,
+			len: 0,
+		}
+	}
+
+	/// The number of linked [`Waiter`]s.
+	pub(crate) fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Links `waiter` at the back of the queue, or updates its [`Waker`] if it's already linked.
+	///
+	/// This clears its notified flag.
+	///
+	/// # Safety
+	///
+	/// `waiter` must be used only with this [`WaiterList`],
+	/// and must be [removed](`WaiterList::remove`) before it's dropped.
+	pub(crate) unsafe fn register(&mut self, waiter: Pin<&Waiter>, waker: &Waker) {
+		let pointer = NonNull::from(&*waiter);
+		let node = &mut *waiter.node.get();
+		match &node.waker {
+			Some(registered) if registered.will_wake(waker) => (),
+			_ => node.waker = Some(waker.clone()),
+		}
+		node.notified = false;
+
+		if !node.linked {
+			node.linked = true;
+			node.prev = self.tail;
+			node.next = None;
+			match self.tail {
+				Some(tail) => (*tail.as_ref().node.get()).next = Some(pointer),
+				None => self.head = Some(pointer),
+			}
+			self.tail = Some(pointer);
+			self.len += 1;
+		}
+	}
+
+	/// Unlinks `waiter` if it's linked and drops its [`Waker`].
+	///
+	/// Returns whether it was notified since last registered,
+	/// so that the notification can be passed on if it wasn't consumed.
+	///
+	/// # Safety
+	///
+	/// `waiter` must be used only with this [`WaiterList`].
+	pub(crate) unsafe fn remove(&mut self, waiter: Pin<&Waiter>) -> bool {
+		if (*waiter.node.get()).linked {
+			self.unlink(NonNull::from(&*waiter));
+		}
+		let node = &mut *waiter.node.get();
+		node.waker = None;
+		core::mem::take(&mut node.notified)
+	}
+
+	/// # Safety
+	///
+	/// `waiter` must be linked into this [`WaiterList`].
+	unsafe fn unlink(&mut self, waiter: NonNull<Waiter>) {
+		let node = &mut *waiter.as_ref().node.get();
+		match node.prev {
+			Some(prev) => (*prev.as_ref().node.get()).next = node.next,
+			None => self.head = node.next,
+		}
+		match node.next {
+			Some(next) => (*next.as_ref().node.get()).prev = node.prev,
+			None => self.tail = node.prev,
+		}
+		node.linked = false;
+		node.prev = None;
+		node.next = None;
+		self.len -= 1;
+	}
+
+	/// Whether `waiter` was notified since last registered, without clearing that.
+	///
+	/// # Safety
+	///
+	/// `waiter` must be used only with this [`WaiterList`].
+	#[allow(clippy::unused_self)] // Taken as proof that the lock is held.
+	pub(crate) unsafe fn is_notified(&self, waiter: Pin<&Waiter>) -> bool {
+		(*waiter.node.get()).notified
+	}
+
+	/// The demand of the front [`Waiter`], if any.
+	pub(crate) fn front_demand(&self) -> Option<usize> {
+		let head = self.head?;
+		unsafe {
+			//SAFETY: Linked `Waiter`s are pinned and alive, and guarded by the same lock.
+			Some((*head.as_ref().node.get()).demand)
+		}
+	}
+
+	/// Unlinks the front [`Waiter`], marks it as notified and returns its [`Waker`] to be woken.
+	///
+	/// Returns [`None`] iff the queue is empty.
+	pub(crate) fn notify_one(&mut self) -> Option<Waker> {
+		self.notify_first(|_| true)
+	}
+
+	/// Like [`.notify_one()`](`WaiterList::notify_one`), but for the front-most [`Waiter`] whose demand matches `predicate`.
+	pub(crate) fn notify_first(
+		&mut self,
+		mut predicate: impl FnMut(usize) -> bool,
+	) -> Option<Waker> {
+		let mut current = self.head;
+		while let Some(waiter) = current {
+			unsafe {
+				//SAFETY: Linked `Waiter`s are pinned and alive, and guarded by the same lock.
+				let node = waiter.as_ref().node.get();
+				if predicate((*node).demand) {
+					self.unlink(waiter);
+					let node = &mut *node;
+					node.notified = true;
+					return node.waker.take();
+				}
+				current = (*node).next;
+			}
+		}
+		None
+	}
+}
@@ -0,0 +1,170 @@
+//! A fixed-capacity queue of [`Waker`]s that interrupt handlers can push to and a main loop drains.
+
+use core::{cell::RefCell, task::Waker};
+use critical_section::Mutex;
+
+/// The error returned by [`WakeQueue::push`] if the queue is full, with the [`Waker`] that didn't fit.
+#[derive(Debug, Clone)]
+pub struct WakeQueueFull(pub Waker);
+
+impl core::fmt::Display for WakeQueueFull {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str("the `WakeQueue` is full")
+	}
+}
+
+/// A multi-producer, single-consumer queue of up to `N` [`Waker`]s, guarded by a critical section.
+///
+/// Interrupt handlers (or other threads) [`.push(…)`](`WakeQueue::push`) wake-ups,
+/// and the main-loop executor [`.wake_all()`](`WakeQueue::wake_all`)s them outside of interrupt context.
+/// [`Waker`]s that [will wake](`Waker::will_wake`) a queued one are skipped, so each task is queued at most once.
+///
+/// Each critical section is short, and queued [`Waker`]s are woken only after leaving it.
+///
+/// > This needs a `critical-section` implementation for the target,
+/// > which is usually provided by the HAL or the architecture support crate.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+/// use unpin_choices_dsa::{wake_queue::WakeQueue, waker::counting_waker};
+///
+/// static QUEUE: WakeQueue<4> = WakeQueue::new();
+/// static WAKES: AtomicUsize = AtomicUsize::new(0);
+///
+/// // In the interrupt handler:
+/// let waker = counting_waker(&WAKES);
+/// QUEUE.push_by_ref(&waker).unwrap();
+/// QUEUE.push_by_ref(&waker).unwrap();
+///
+/// // In the main loop:
+/// assert_eq!(QUEUE.wake_all(), 1);
+/// assert_eq!(WAKES.load(Ordering::Acquire), 1);
+/// ```
+pub struct WakeQueue<const N: usize> {
+	ring: Mutex<RefCell<Ring<N>>>,
+}
+
+struct Ring<const N: usize> {
+	/// Entries `head..head + len` (wrapping) are [`Some`], the rest [`None`].
+	slots: [Option<Waker>; N],
+	head: usize,
+	len: usize,
+}
+
+impl<const N: usize> Ring<N> {
+	fn iter(&self) -> impl Iterator<Item = &Waker> {
+		(0..self.len).filter_map(|i| self.slots[(self.head + i) % N].as_ref())
+	}
+}
+
+impl<const N: usize> WakeQueue<N> {
+	/// Creates a new empty [`WakeQueue`].
+	#[must_use]
+	pub const fn new() -> Self {
+		const NONE: Option<Waker> = None;
+		Self {
+			ring: Mutex::new(RefCell::new(Ring {
+				slots: [NONE; N],
+				head: 0,
+				len: 0,
+			})),
+		}
+	}
+
+	/// Queues `waker` to be woken by the consumer, unless an equivalent one is queued already.
+	///
+	/// # Errors
+	///
+	/// Iff the queue is full, in which case `waker` is handed back.
+	pub fn push(&self, waker: Waker) -> Result<(), WakeQueueFull> {
+		critical_section::with(|cs| {
+			let ring = &mut *self.ring.borrow_ref_mut(cs);
+			if ring.iter().any(|queued| queued.will_wake(&waker)) {
+				Ok(())
+			} else if ring.len == N {
+				Err(WakeQueueFull(waker))
+			} else {
+				let tail = (ring.head + ring.len) % N;
+				ring.slots[tail] = Some(waker);
+				ring.len += 1;
+				Ok(())
+			}
+		})
+	}
+
+	/// Like [`.push(…)`](`WakeQueue::push`), but clones `waker` only if it's queued.
+	///
+	/// # Errors
+	///
+	/// Iff the queue is full, in which case a clone of `waker` is handed back.
+	pub fn push_by_ref(&self, waker: &Waker) -> Result<(), WakeQueueFull> {
+		critical_section::with(|cs| {
+			let ring = &mut *self.ring.borrow_ref_mut(cs);
+			if ring.iter().any(|queued| queued.will_wake(waker)) {
+				Ok(())
+			} else if ring.len == N {
+				Err(WakeQueueFull(waker.clone()))
+			} else {
+				let tail = (ring.head + ring.len) % N;
+				ring.slots[tail] = Some(waker.clone());
+				ring.len += 1;
+				Ok(())
+			}
+		})
+	}
+
+	/// Removes the earliest queued [`Waker`], without waking it.
+	#[must_use]
+	pub fn pop(&self) -> Option<Waker> {
+		critical_section::with(|cs| {
+			let ring = &mut *self.ring.borrow_ref_mut(cs);
+			if ring.len == 0 {
+				return None;
+			}
+			let waker = ring.slots[ring.head].take();
+			ring.head = (ring.head + 1) % N;
+			ring.len -= 1;
+			waker
+		})
+	}
+
+	/// Wakes queued [`Waker`]s in order until the queue is empty, each outside of the critical section.
+	///
+	/// Returns how many were woken, which includes any pushed while this runs.
+	pub fn wake_all(&self) -> usize {
+		let mut count = 0;
+		while let Some(waker) = self.pop() {
+			waker.wake();
+			count += 1;
+		}
+		count
+	}
+
+	/// The number of queued [`Waker`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		critical_section::with(|cs| self.ring.borrow_ref(cs).len)
+	}
+
+	/// Whether no [`Waker`]s are queued.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl<const N: usize> Default for WakeQueue<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Shows only the length, to not enter a critical section for longer than necessary.
+impl<const N: usize> core::fmt::Debug for WakeQueue<N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("WakeQueue")
+			.field("len", &self.len())
+			.finish_non_exhaustive()
+	}
+}
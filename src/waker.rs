@@ -0,0 +1,421 @@
+//! [`Waker`] utilities.
+
+#[cfg(feature = "alloc")]
+use alloc::rc::Rc;
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use core::mem::ManuallyDrop;
+#[cfg(target_has_atomic = "ptr")]
+use core::sync::atomic::AtomicUsize;
+use core::{
+	ptr,
+	sync::atomic::{AtomicBool, Ordering},
+	task::{Context, RawWaker, RawWakerVTable, Waker},
+};
+
+const NOOP_VTABLE: RawWakerVTable =
+	RawWakerVTable::new(|_| noop_raw_waker(), |_| (), |_| (), |_| ());
+
+/// Creates a [`RawWaker`] that does nothing when woken.
+///
+/// This can be used in `const` contexts.
+#[must_use]
+pub const fn noop_raw_waker() -> RawWaker {
+	RawWaker::new(ptr::null(), &NOOP_VTABLE)
+}
+
+/// Creates a [`Waker`] that does nothing when woken.
+///
+/// > `Waker::noop` isn't available on this crate's minimum supported Rust version yet.
+#[must_use]
+pub fn noop_waker() -> Waker {
+	unsafe {
+		//SAFETY: None of the vtable functions touch the data pointer.
+		Waker::from_raw(noop_raw_waker())
+	}
+}
+
+/// A reference to a static [`Waker`] that does nothing when woken.
+///
+/// This avoids creating a new [`Waker`] where a reference is enough.
+#[must_use]
+pub fn noop_waker_ref() -> &'static Waker {
+	struct SyncRawWaker(RawWaker);
+
+	/// # Safety notes
+	///
+	/// The data pointer is null and never dereferenced.
+	unsafe impl Sync for SyncRawWaker {}
+
+	static NOOP_RAW_WAKER: SyncRawWaker = SyncRawWaker(noop_raw_waker());
+
+	unsafe {
+		//SAFETY: `Waker` is `#[repr(transparent)]` over its `RawWaker`,
+		// and none of the vtable functions touch the data pointer.
+		&*ptr::addr_of!(NOOP_RAW_WAKER.0).cast::<Waker>()
+	}
+}
+
+/// Creates a [`Context`] with a [`Waker`] that does nothing when woken.
+///
+/// Use this to poll [`Future`](`core::future::Future`)s outside of any task,
+/// for example ones that are about to be dropped anyway.
+#[must_use]
+pub fn noop_context() -> Context<'static> {
+	Context::from_waker(noop_waker_ref())
+}
+
+/// A `static` rather than a `const`, so that all clones point to the same vtable and [`Waker::will_wake`] recognises them.
+static FLAG_VTABLE: RawWakerVTable = RawWakerVTable::new(
+	|data| RawWaker::new(data, &FLAG_VTABLE),
+	|data| unsafe {
+		//SAFETY: `data` came from a `&'static AtomicBool` in `flag_waker`.
+		(*data.cast::<AtomicBool>()).store(true, Ordering::Release);
+	},
+	|data| unsafe {
+		//SAFETY: As above.
+		(*data.cast::<AtomicBool>()).store(true, Ordering::Release);
+	},
+	|_| (),
+);
+
+/// Creates a [`Waker`] that sets `flag` to `true` when woken.
+///
+/// Use this in tests to check whether a specific [`Waker`] was woken, by giving each child its own flag.
+///
+/// > [`Waker`]s can't borrow, so the flag has to be a `static`.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use unpin_choices_dsa::waker::flag_waker;
+///
+/// static WOKEN: AtomicBool = AtomicBool::new(false);
+///
+/// let waker = flag_waker(&WOKEN);
+/// assert!(!WOKEN.load(Ordering::Acquire));
+///
+/// waker.wake();
+/// assert!(WOKEN.load(Ordering::Acquire));
+/// ```
+#[must_use]
+pub fn flag_waker(flag: &'static AtomicBool) -> Waker {
+	unsafe {
+		//SAFETY: The vtable functions only access `flag`, which lives forever.
+		Waker::from_raw(RawWaker::new(
+			(flag as *const AtomicBool).cast(),
+			&FLAG_VTABLE,
+		))
+	}
+}
+
+/// See [`FLAG_VTABLE`].
+#[cfg(target_has_atomic = "ptr")]
+static COUNTING_VTABLE: RawWakerVTable = RawWakerVTable::new(
+	|data| RawWaker::new(data, &COUNTING_VTABLE),
+	|data| unsafe {
+		//SAFETY: `data` came from a `&'static AtomicUsize` in `counting_waker`.
+		(*data.cast::<AtomicUsize>()).fetch_add(1, Ordering::AcqRel);
+	},
+	|data| unsafe {
+		//SAFETY: As above.
+		(*data.cast::<AtomicUsize>()).fetch_add(1, Ordering::AcqRel);
+	},
+	|_| (),
+);
+
+/// Creates a [`Waker`] that increments `counter` each time it's woken.
+///
+/// Clones share the `counter`, so this counts wake-ups through any of them.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::sync::atomic::{AtomicUsize, Ordering};
+/// use unpin_choices_dsa::waker::counting_waker;
+///
+/// static WAKES: AtomicUsize = AtomicUsize::new(0);
+///
+/// let waker = counting_waker(&WAKES);
+/// waker.wake_by_ref();
+/// waker.clone().wake();
+/// assert_eq!(WAKES.load(Ordering::Acquire), 2);
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+#[must_use]
+pub fn counting_waker(counter: &'static AtomicUsize) -> Waker {
+	unsafe {
+		//SAFETY: The vtable functions only access `counter`, which lives forever.
+		Waker::from_raw(RawWaker::new(
+			(counter as *const AtomicUsize).cast(),
+			&COUNTING_VTABLE,
+		))
+	}
+}
+
+/// Some `data` with the function that wakes through it, for [`static_waker`].
+///
+/// [`RawWaker`] has room for only one data pointer, so both have to live together in a `static`.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use core::sync::atomic::{AtomicBool, Ordering};
+/// use unpin_choices_dsa::waker::{static_waker, StaticWake};
+///
+/// // For example set from an interrupt handler, and checked by the main loop.
+/// static PENDING: StaticWake<AtomicBool> =
+///     StaticWake::new(AtomicBool::new(false), |pending| pending.store(true, Ordering::Release));
+///
+/// let waker = static_waker(&PENDING);
+/// waker.wake_by_ref();
+/// assert!(PENDING.data().swap(false, Ordering::Acquire));
+/// ```
+pub struct StaticWake<T> {
+	data: T,
+	wake: fn(&T),
+}
+
+impl<T> StaticWake<T> {
+	/// Creates a new instance of [`StaticWake`] that calls `wake` with `data` whenever a [`Waker`] from it is woken.
+	#[must_use]
+	pub const fn new(data: T, wake: fn(&T)) -> Self {
+		Self { data, wake }
+	}
+
+	/// The `data` that is passed to the wake function.
+	#[must_use]
+	pub fn data(&self) -> &T {
+		&self.data
+	}
+}
+
+/// Shows only the data, since function pointers aren't very informative.
+impl<T: core::fmt::Debug> core::fmt::Debug for StaticWake<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("StaticWake")
+			.field("data", &self.data)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a [`Waker`] that calls `target`'s wake function with its data, without any allocation or reference counting.
+///
+/// Cloning and dropping the [`Waker`] does nothing, and waking it may happen from any thread or interrupt context,
+/// so the wake function should be short and must not block.
+///
+/// > This is meant for microcontrollers without a global allocator,
+/// > where a few hardware events each get their own `static`.
+#[must_use]
+pub fn static_waker<T: Sync>(target: &'static StaticWake<T>) -> Waker {
+	unsafe {
+		//SAFETY: `target` lives forever and is `Sync`, so the vtable functions may use it from anywhere.
+		Waker::from_raw(RawWaker::new(
+			(target as *const StaticWake<T>).cast(),
+			static_vtable::<T>(),
+		))
+	}
+}
+
+fn static_vtable<T: Sync>() -> &'static RawWakerVTable {
+	&RawWakerVTable::new(
+		|data| RawWaker::new(data, static_vtable::<T>()),
+		static_wake::<T>,
+		static_wake::<T>,
+		|_| (),
+	)
+}
+
+unsafe fn static_wake<T: Sync>(data: *const ()) {
+	//SAFETY: `data` came from a `&'static StaticWake<T>` in `static_waker`.
+	let target = &*data.cast::<StaticWake<T>>();
+	(target.wake)(&target.data)
+}
+
+/// Shared state that can be woken through an [`Arc`], and turned into a [`Waker`] with [`waker_from_arc`].
+///
+/// > This is the same shape as `futures::task::ArcWake`, but without the dependency.
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+pub trait ArcWake: Send + Sync {
+	/// Wakes the task this belongs to, consuming one reference.
+	///
+	/// Defaults to [`ArcWake::wake_by_ref`].
+	fn wake(self: Arc<Self>) {
+		Self::wake_by_ref(&self)
+	}
+
+	/// Wakes the task this belongs to.
+	fn wake_by_ref(arc_self: &Arc<Self>);
+}
+
+/// Creates a [`Waker`] that owns `wake` and calls its [`ArcWake`] implementation.
+///
+/// Cloning the [`Waker`] clones the [`Arc`].
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+/// use unpin_choices_dsa::waker::{waker_from_arc, ArcWake};
+///
+/// struct Task {
+///     wakes: AtomicUsize,
+/// }
+///
+/// impl ArcWake for Task {
+///     fn wake_by_ref(arc_self: &Arc<Self>) {
+///         arc_self.wakes.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let task = Arc::new(Task { wakes: AtomicUsize::new(0) });
+/// let waker = waker_from_arc(task.clone());
+/// waker.wake_by_ref();
+/// waker.wake();
+/// assert_eq!(task.wakes.load(Ordering::Relaxed), 2);
+/// assert_eq!(Arc::strong_count(&task), 1);
+/// ```
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+#[must_use]
+pub fn waker_from_arc<W: ArcWake + 'static>(wake: Arc<W>) -> Waker {
+	unsafe {
+		//SAFETY: The data pointer owns one strong reference, which the vtable functions account for.
+		Waker::from_raw(RawWaker::new(Arc::into_raw(wake).cast(), arc_vtable::<W>()))
+	}
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+fn arc_vtable<W: ArcWake + 'static>() -> &'static RawWakerVTable {
+	&RawWakerVTable::new(
+		arc_clone::<W>,
+		arc_wake::<W>,
+		arc_wake_by_ref::<W>,
+		arc_drop::<W>,
+	)
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe fn arc_clone<W: ArcWake + 'static>(data: *const ()) -> RawWaker {
+	//SAFETY: `data` owns a strong reference, which stays in place.
+	Arc::increment_strong_count(data.cast::<W>());
+	RawWaker::new(data, arc_vtable::<W>())
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe fn arc_wake<W: ArcWake + 'static>(data: *const ()) {
+	//SAFETY: Takes over the strong reference owned by `data`.
+	ArcWake::wake(Arc::from_raw(data.cast::<W>()));
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe fn arc_wake_by_ref<W: ArcWake + 'static>(data: *const ()) {
+	//SAFETY: Borrows the strong reference owned by `data` without releasing it.
+	let arc = ManuallyDrop::new(Arc::from_raw(data.cast::<W>()));
+	ArcWake::wake_by_ref(&*arc);
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+unsafe fn arc_drop<W: ArcWake + 'static>(data: *const ()) {
+	//SAFETY: Releases the strong reference owned by `data`.
+	drop(Arc::from_raw(data.cast::<W>()));
+}
+
+/// Like [`ArcWake`], but for single-threaded task state behind an [`Rc`].
+///
+/// Turn it into a [`Waker`] with [`waker_from_rc`].
+#[cfg(feature = "alloc")]
+pub trait LocalWake {
+	/// Wakes the task this belongs to, consuming one reference.
+	///
+	/// Defaults to [`LocalWake::wake_by_ref`].
+	fn wake(self: Rc<Self>) {
+		Self::wake_by_ref(&self)
+	}
+
+	/// Wakes the task this belongs to.
+	fn wake_by_ref(rc_self: &Rc<Self>);
+}
+
+/// Creates a [`Waker`] that owns `wake` and calls its [`LocalWake`] implementation, without any atomic operations.
+///
+/// Cloning the [`Waker`] clones the [`Rc`].
+///
+/// > Single-threaded executors can use this to avoid atomic reference counting,
+/// > but [`Waker`] is [`Send`] and [`Sync`] regardless, so the compiler can't check this use.
+///
+/// # Safety
+///
+/// The returned [`Waker`] and all its clones must be cloned, woken and dropped only on the current thread.
+///
+/// In particular, they must not be handed to [`Future`](`core::future::Future`)s that may send them elsewhere,
+/// like ones that register with a timer thread.
+///
+/// # Example
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use std::{cell::Cell, rc::Rc};
+/// use unpin_choices_dsa::waker::{waker_from_rc, LocalWake};
+///
+/// struct Task {
+///     woken: Cell<bool>,
+/// }
+///
+/// impl LocalWake for Task {
+///     fn wake_by_ref(rc_self: &Rc<Self>) {
+///         rc_self.woken.set(true);
+///     }
+/// }
+///
+/// let task = Rc::new(Task { woken: Cell::new(false) });
+/// let waker = unsafe {
+///     // The `Waker` stays on this thread.
+///     waker_from_rc(task.clone())
+/// };
+/// waker.wake();
+/// assert!(task.woken.get());
+/// ```
+#[cfg(feature = "alloc")]
+#[must_use]
+pub unsafe fn waker_from_rc<W: LocalWake + 'static>(wake: Rc<W>) -> Waker {
+	//SAFETY: The data pointer owns one strong reference, which the vtable functions account for.
+	// Thread confinement is up to the caller.
+	Waker::from_raw(RawWaker::new(Rc::into_raw(wake).cast(), rc_vtable::<W>()))
+}
+
+#[cfg(feature = "alloc")]
+fn rc_vtable<W: LocalWake + 'static>() -> &'static RawWakerVTable {
+	&RawWakerVTable::new(
+		rc_clone::<W>,
+		rc_wake::<W>,
+		rc_wake_by_ref::<W>,
+		rc_drop::<W>,
+	)
+}
+
+#[cfg(feature = "alloc")]
+unsafe fn rc_clone<W: LocalWake + 'static>(data: *const ()) -> RawWaker {
+	//SAFETY: `data` owns a strong reference, which stays in place.
+	Rc::increment_strong_count(data.cast::<W>());
+	RawWaker::new(data, rc_vtable::<W>())
+}
+
+#[cfg(feature = "alloc")]
+unsafe fn rc_wake<W: LocalWake + 'static>(data: *const ()) {
+	//SAFETY: Takes over the strong reference owned by `data`.
+	LocalWake::wake(Rc::from_raw(data.cast::<W>()));
+}
+
+#[cfg(feature = "alloc")]
+unsafe fn rc_wake_by_ref<W: LocalWake + 'static>(data: *const ()) {
+	//SAFETY: Borrows the strong reference owned by `data` without releasing it.
+	let rc = ManuallyDrop::new(Rc::from_raw(data.cast::<W>()));
+	LocalWake::wake_by_ref(&*rc);
+}
+
+#[cfg(feature = "alloc")]
+unsafe fn rc_drop<W: LocalWake + 'static>(data: *const ()) {
+	//SAFETY: Releases the strong reference owned by `data`.
+	drop(Rc::from_raw(data.cast::<W>()));
+}
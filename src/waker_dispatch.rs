@@ -0,0 +1,200 @@
+//! Index-tagging [`Waker`]s, which record which child of a combinator was woken.
+
+use crate::atomic_waker::AtomicWaker;
+use alloc::{boxed::Box, sync::Arc};
+use core::{
+	iter,
+	mem::ManuallyDrop,
+	sync::atomic::{AtomicUsize, Ordering},
+	task::{RawWaker, RawWakerVTable, Waker},
+};
+
+/// The number of children per word of dirty flags.
+pub(crate) const BITS: usize = usize::BITS as usize;
+
+/// Hands out one [`Waker`] per child index and tracks which of them were woken ("dirty") since last checked.
+///
+/// The parent's [`Waker`] is woken along with each child's.
+///
+/// > This has to be reference-counted, since [`Waker`]s may outlive the combinator they were taken from.
+/// > Inline storage in the (pinned) combinator would only be sound with a way to revoke all child [`Waker`]s on drop,
+/// > which [`Waker`] doesn't offer.
+#[derive(Debug)]
+pub(crate) struct WakerDispatch {
+	shared: Arc<Shared>,
+	children: Box<[Waker]>,
+}
+
+#[derive(Debug)]
+struct Shared {
+	parent: AtomicWaker,
+	dirty: Box<[AtomicUsize]>,
+}
+
+/// The target of each child [`Waker`].
+struct Child {
+	shared: Arc<Shared>,
+	index: usize,
+}
+
+impl WakerDispatch {
+	/// Creates a new [`WakerDispatch`] for `len` children, all of which start out dirty.
+	pub(crate) fn new(len: usize) -> Self {
+		let shared = Arc::new(Shared {
+			parent: AtomicWaker::new(),
+			dirty: (0..(len + BITS - 1) / BITS)
+				.map(|_| AtomicUsize::new(usize::MAX))
+				.collect(),
+		});
+		let children = (0..len)
+			.map(|index| {
+				Child::into_waker(Arc::new(Child {
+					shared: shared.clone(),
+					index,
+				}))
+			})
+			.collect();
+		Self { shared, children }
+	}
+
+	/// Registers the parent's [`Waker`]. Call this before [`.drain_dirty_from(…)`](`WakerDispatch::drain_dirty_from`)
+	/// to not miss any wake-ups.
+	pub(crate) fn register(&self, parent: &Waker) {
+		self.shared.parent.register(parent)
+	}
+
+	/// The [`Waker`] to poll the child at `index` with.
+	pub(crate) fn waker(&self, index: usize) -> &Waker {
+		&self.children[index]
+	}
+
+	/// Marks the child at `index` as woken, so that it is polled again.
+	pub(crate) fn mark_dirty(&self, index: usize) {
+		self.shared.dirty[index / BITS].fetch_or(1 << (index % BITS), Ordering::Release);
+	}
+
+	/// Marks all children as woken, so that they are polled again.
+	pub(crate) fn mark_all_dirty(&self) {
+		for word in self.shared.dirty.iter() {
+			word.store(usize::MAX, Ordering::Release)
+		}
+	}
+
+	/// Calls `f` with the index of each child that was woken since the last call,
+	/// in ascending order but starting at `start` and wrapping around.
+	///
+	/// Iff `f` returns `false`, its index and all remaining ones are kept dirty and this method returns `true`.
+	pub(crate) fn drain_dirty_from(&self, start: usize, mut f: impl FnMut(usize) -> bool) -> bool {
+		let dirty = &self.shared.dirty;
+		if dirty.is_empty() {
+			return false;
+		}
+
+		let (start_word, start_bit) = (start / BITS, start % BITS);
+		let high = usize::MAX << start_bit;
+		// The word containing `start` is visited in two parts, first and last.
+		let segments = iter::once((start_word, high))
+			.chain(
+				(start_word + 1..dirty.len())
+					.chain(0..start_word)
+					.map(|word| (word, usize::MAX)),
+			)
+			.chain(iter::once((start_word, !high)));
+
+		for (word, mask) in segments {
+			if mask == 0 {
+				continue;
+			}
+
+			let mut bits = dirty[word].fetch_and(!mask, Ordering::Acquire) & mask;
+			while bits != 0 {
+				if !f(word * BITS + bits.trailing_zeros() as usize) {
+					dirty[word].fetch_or(bits, Ordering::Release);
+					return true;
+				}
+				bits &= bits - 1;
+			}
+		}
+		false
+	}
+}
+
+impl Child {
+	fn wake_by_ref(&self) {
+		self.shared.dirty[self.index / BITS].fetch_or(1 << (self.index % BITS), Ordering::Release);
+		self.shared.parent.wake()
+	}
+
+	fn into_waker(this: Arc<Self>) -> Waker {
+		unsafe {
+			//SAFETY: The vtable functions below treat the data pointer as `Arc<Child>`.
+			Waker::from_raw(RawWaker::new(Arc::into_raw(this).cast(), &VTABLE))
+		}
+	}
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_raw, wake_raw, wake_by_ref_raw, drop_raw);
+
+unsafe fn clone_raw(data: *const ()) -> RawWaker {
+	Arc::increment_strong_count(data.cast::<Child>());
+	RawWaker::new(data, &VTABLE)
+}
+
+unsafe fn wake_raw(data: *const ()) {
+	Arc::from_raw(data.cast::<Child>()).wake_by_ref()
+}
+
+unsafe fn wake_by_ref_raw(data: *const ()) {
+	ManuallyDrop::new(Arc::from_raw(data.cast::<Child>())).wake_by_ref()
+}
+
+unsafe fn drop_raw(data: *const ()) {
+	Arc::from_raw(data.cast::<Child>());
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{WakerDispatch, BITS};
+	use crate::waker::counting_waker;
+	use alloc::vec::Vec;
+	use core::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Like the combinators, this ignores the padding bits past `len`.
+	fn drain_all(dispatch: &WakerDispatch, len: usize, start: usize) -> Vec<usize> {
+		let mut drained = Vec::new();
+		assert!(!dispatch.drain_dirty_from(start, |index| {
+			if index < len {
+				drained.push(index)
+			}
+			true
+		}));
+		drained
+	}
+
+	#[test]
+	fn only_woken_children_are_drained() {
+		static PARENT_WAKES: AtomicUsize = AtomicUsize::new(0);
+
+		let dispatch = WakerDispatch::new(3);
+		dispatch.register(&counting_waker(&PARENT_WAKES));
+		assert_eq!(drain_all(&dispatch, 3, 0), [0, 1, 2]);
+		assert_eq!(drain_all(&dispatch, 3, 0), []);
+
+		dispatch.waker(1).wake_by_ref();
+		assert_eq!(PARENT_WAKES.load(Ordering::Acquire), 1);
+		assert_eq!(drain_all(&dispatch, 3, 0), [1]);
+		assert_eq!(drain_all(&dispatch, 3, 0), []);
+	}
+
+	#[test]
+	fn drain_wraps_around_across_words() {
+		let len = BITS + 3;
+		let dispatch = WakerDispatch::new(len);
+		assert_eq!(drain_all(&dispatch, len, 0).len(), len);
+
+		for index in [1, BITS - 1, BITS + 2] {
+			dispatch.mark_dirty(index);
+		}
+		assert_eq!(drain_all(&dispatch, len, BITS), [BITS + 2, 1, BITS - 1]);
+	}
+}
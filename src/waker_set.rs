@@ -0,0 +1,199 @@
+//! A small set of [`Waker`]s, for primitives with more than one waiting task.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::task::Waker;
+
+/// A set of [`Waker`]s, deduplicated by [`Waker::will_wake`] and woken in the order they were first inserted.
+///
+/// Up to `N` [`Waker`]s are stored inline. With the `"alloc"` feature, any more spill onto the heap.
+/// Without it, inserting into a full set wakes the earliest entry to make room,
+/// which is a spurious but harmless wake-up: The evicted task polls again and re-registers.
+///
+/// This isn't synchronised by itself, so shared primitives keep it behind their lock.
+/// Dropping it drops the [`Waker`]s without waking them.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{waker::noop_waker_ref, waker_set::WakerSet};
+///
+/// let mut wakers = WakerSet::<2>::new();
+/// assert!(wakers.insert(noop_waker_ref()));
+/// assert!(!wakers.insert(noop_waker_ref()));
+/// assert_eq!(wakers.len(), 1);
+///
+/// assert_eq!(wakers.wake_all(), 1);
+/// assert!(wakers.is_empty());
+/// ```
+pub struct WakerSet<const N: usize = 4> {
+	/// Entries `..inline_len` are [`Some`], the rest [`None`].
+	inline: [Option<Waker>; N],
+	inline_len: usize,
+	/// Only used once `inline` is full.
+	#[cfg(feature = "alloc")]
+	spill: Vec<Waker>,
+}
+
+impl<const N: usize> WakerSet<N> {
+	/// Creates a new empty [`WakerSet`].
+	#[must_use]
+	pub const fn new() -> Self {
+		const NONE: Option<Waker> = None;
+		Self {
+			inline: [NONE; N],
+			inline_len: 0,
+			#[cfg(feature = "alloc")]
+			spill: Vec::new(),
+		}
+	}
+
+	/// The number of stored [`Waker`]s.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		#[cfg(feature = "alloc")]
+		return self.inline_len + self.spill.len();
+		#[cfg(not(feature = "alloc"))]
+		return self.inline_len;
+	}
+
+	/// Whether no [`Waker`]s are stored.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.inline_len == 0
+	}
+
+	/// Whether a [`Waker`] that [will wake](`Waker::will_wake`) the same task as `waker` is stored.
+	#[must_use]
+	pub fn contains(&self, waker: &Waker) -> bool {
+		self.iter().any(|stored| stored.will_wake(waker))
+	}
+
+	/// Stores a clone of `waker`, unless it's already [contained](`WakerSet::contains`).
+	///
+	/// Returns whether `waker` was newly inserted.
+	///
+	/// > Without `"alloc"` and with `N == 0`, `waker` is woken right away instead and this returns `false`.
+	pub fn insert(&mut self, waker: &Waker) -> bool {
+		if self.contains(waker) {
+			return false;
+		}
+
+		if self.inline_len < N {
+			self.inline[self.inline_len] = Some(waker.clone());
+			self.inline_len += 1;
+			return true;
+		}
+
+		#[cfg(feature = "alloc")]
+		{
+			self.spill.push(waker.clone());
+			true
+		}
+		#[cfg(not(feature = "alloc"))]
+		{
+			if let Some(evicted) = self.pop_front() {
+				evicted.wake();
+				self.inline[self.inline_len] = Some(waker.clone());
+				self.inline_len += 1;
+				true
+			} else {
+				waker.wake_by_ref();
+				false
+			}
+		}
+	}
+
+	/// Removes the [`Waker`] that [will wake](`Waker::will_wake`) the same task as `waker`, without waking it.
+	///
+	/// Returns whether one was stored.
+	///
+	/// Call this when a waiting [`Future`](`core::future::Future`) is dropped, so it's not woken needlessly.
+	pub fn remove(&mut self, waker: &Waker) -> bool {
+		if let Some(index) = self.inline[..self.inline_len]
+			.iter()
+			.position(|stored| matches!(stored, Some(stored) if stored.will_wake(waker)))
+		{
+			self.remove_inline(index);
+			return true;
+		}
+
+		#[cfg(feature = "alloc")]
+		if let Some(index) = self.spill.iter().position(|stored| stored.will_wake(waker)) {
+			self.spill.remove(index);
+			return true;
+		}
+
+		false
+	}
+
+	/// Wakes and removes the earliest inserted [`Waker`].
+	///
+	/// Returns whether there was one.
+	pub fn wake_one(&mut self) -> bool {
+		self.pop_front().map(Waker::wake).is_some()
+	}
+
+	/// Wakes and removes all stored [`Waker`]s, in insertion order.
+	///
+	/// Returns how many were woken.
+	pub fn wake_all(&mut self) -> usize {
+		let count = self.len();
+		self.inline[..self.inline_len]
+			.iter_mut()
+			.filter_map(Option::take)
+			.for_each(Waker::wake);
+		self.inline_len = 0;
+		#[cfg(feature = "alloc")]
+		self.spill.drain(..).for_each(Waker::wake);
+		count
+	}
+
+	/// Removes all stored [`Waker`]s without waking them.
+	pub fn clear(&mut self) {
+		self.inline[..self.inline_len]
+			.iter_mut()
+			.for_each(|stored| *stored = None);
+		self.inline_len = 0;
+		#[cfg(feature = "alloc")]
+		self.spill.clear();
+	}
+
+	fn iter(&self) -> impl Iterator<Item = &Waker> {
+		let inline = self.inline[..self.inline_len].iter().flatten();
+		#[cfg(feature = "alloc")]
+		return inline.chain(self.spill.iter());
+		#[cfg(not(feature = "alloc"))]
+		return inline;
+	}
+
+	fn pop_front(&mut self) -> Option<Waker> {
+		(self.inline_len > 0).then(|| self.remove_inline(0))
+	}
+
+	/// Removes the inline entry at `index`, keeping the order and refilling from the spill-over.
+	fn remove_inline(&mut self, index: usize) -> Waker {
+		let removed = self.inline[index].take().expect("unreachable");
+		self.inline[index..self.inline_len].rotate_left(1);
+		self.inline_len -= 1;
+
+		#[cfg(feature = "alloc")]
+		if !self.spill.is_empty() {
+			self.inline[self.inline_len] = Some(self.spill.remove(0));
+			self.inline_len += 1;
+		}
+
+		removed
+	}
+}
+
+impl<const N: usize> Default for WakerSet<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<const N: usize> core::fmt::Debug for WakerSet<N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_set().entries(self.iter()).finish()
+	}
+}
@@ -0,0 +1,394 @@
+//! A channel that keeps only the latest value, which [`Receiver`]s can borrow and wait for changes of.
+
+use crate::{
+	spin_lock::{SpinLock, SpinLockGuard},
+	state_ref::StateRef,
+	waiter_list::{Waiter, WaiterList},
+};
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+use core::{
+	fmt::{self, Display, Formatter},
+	future::Future,
+	mem,
+	ops::Deref,
+	pin::Pin,
+	task::{Context, Poll},
+};
+
+/// The error returned by [`Receiver::changed`] and [`Receiver::has_changed`] once the [`Sender`] was dropped.
+///
+/// The last value can still be borrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RecvError;
+
+impl Display for RecvError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("the `Sender` was dropped")
+	}
+}
+
+/// The state of a watch channel, which [`Sender`] and [`Receiver`]s borrow.
+///
+/// ```
+/// # #![allow(deprecated)]
+/// use unpin_choices_dsa::{block_on::block_on, watch::{RecvError, Watch}};
+///
+/// let mut watch = Watch::new("initial");
+/// let (sender, mut receiver) = watch.split();
+/// assert_eq!(*receiver.borrow(), "initial");
+///
+/// sender.send("first");
+/// sender.send("second");
+/// assert_eq!(block_on(receiver.changed()), Ok(()));
+/// assert_eq!(*receiver.borrow(), "second");
+///
+/// drop(sender);
+/// assert_eq!(block_on(receiver.changed()), Err(RecvError));
+/// assert_eq!(*receiver.borrow(), "second");
+/// ```
+pub struct Watch<T> {
+	state: SpinLock<State<T>>,
+}
+
+struct State<T> {
+	value: T,
+	/// Incremented with each change.
+	version: u64,
+	closed: bool,
+	receivers: usize,
+	waiters: WaiterList,
+}
+
+impl<T> Watch<T> {
+	/// Creates a new [`Watch`] holding `initial`.
+	#[must_use]
+	pub const fn new(initial: T) -> Self {
+		Self {
+			state: SpinLock::new(State {
+				value: initial,
+				version: 0,
+				closed: false,
+				receivers: 0,
+				waiters: WaiterList::new(),
+			}),
+		}
+	}
+
+	/// Splits this [`Watch`] into a [`Sender`] and a [`Receiver`] that has seen the current value.
+	///
+	/// Use [`Sender::subscribe`] or clone the [`Receiver`] for more consumers.
+	pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
+		let state = self.state.get_mut();
+		state.closed = false;
+		state.receivers = 1;
+		let seen = state.version;
+		let this = &*self;
+		(
+			Sender {
+				watch: StateRef::Borrowed(this),
+			},
+			Receiver {
+				watch: StateRef::Borrowed(this),
+				seen,
+			},
+		)
+	}
+
+	/// Unwraps the current value.
+	pub fn into_inner(self) -> T {
+		self.state.into_inner().value
+	}
+
+	/// Wakes up to `count` waiting [`Receiver`]s, one by one outside the lock.
+	///
+	/// > Limited to those that were waiting before, since woken ones may re-register right away.
+	fn wake(&self, count: usize) {
+		for _ in 0..count {
+			match self.state.lock().waiters.notify_one() {
+				Some(waker) => waker.wake(),
+				None => break,
+			}
+		}
+	}
+}
+
+impl<T: Default> Default for Watch<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+/// Shows only the version, to not require `T: Debug` and to not hold the lock while formatting.
+impl<T> core::fmt::Debug for Watch<T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Watch")
+			.field("version", &self.state.lock().version)
+			.finish_non_exhaustive()
+	}
+}
+
+/// Creates a heap-allocated watch channel holding `initial`, whose halves can be moved anywhere independently.
+#[cfg(feature = "alloc")]
+#[must_use]
+pub fn channel<T>(initial: T) -> (Sender<'static, T>, Receiver<'static, T>) {
+	let watch = Arc::new(Watch::new(initial));
+	watch.state.lock().receivers = 1;
+	(
+		Sender {
+			watch: StateRef::Shared(Arc::clone(&watch)),
+		},
+		Receiver {
+			watch: StateRef::Shared(watch),
+			seen: 0,
+		},
+	)
+}
+
+/// A borrow of the latest value in a watch channel.
+///
+/// > This holds the channel's lock, so keep it short. Sending and borrowing elsewhere spin until it's dropped.
+pub struct Ref<'r, T> {
+	state: SpinLockGuard<'r, State<T>>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.state.value
+	}
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Ref<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		core::fmt::Debug::fmt(&**self, f)
+	}
+}
+
+/// The sending half of a watch channel.
+///
+/// Dropping it closes the channel, but the last value stays available.
+pub struct Sender<'a, T> {
+	watch: StateRef<'a, Watch<T>>,
+}
+
+impl<'a, T> Sender<'a, T> {
+	/// Replaces the value and wakes all waiting [`Receiver`]s.
+	///
+	/// Returns the previous value.
+	pub fn send(&self, value: T) -> T {
+		let mut previous = value;
+		self.send_modify(|value| mem::swap(value, &mut previous));
+		previous
+	}
+
+	/// Modifies the value in place and wakes all waiting [`Receiver`]s.
+	///
+	/// > `modify` runs while the lock is held, so it mustn't use this channel.
+	pub fn send_modify(&self, modify: impl FnOnce(&mut T)) {
+		let mut state = self.watch.state.lock();
+		modify(&mut state.value);
+		state.version += 1;
+		let waiting = state.waiters.len();
+		drop(state);
+		self.watch.wake(waiting);
+	}
+
+	/// Borrows the current value.
+	#[must_use]
+	pub fn borrow(&self) -> Ref<'_, T> {
+		Ref {
+			state: self.watch.state.lock(),
+		}
+	}
+
+	/// Creates a new [`Receiver`] that has seen the current value.
+	#[must_use]
+	pub fn subscribe(&self) -> Receiver<'a, T> {
+		let mut state = self.watch.state.lock();
+		state.receivers += 1;
+		Receiver {
+			seen: state.version,
+			watch: self.watch.clone(),
+		}
+	}
+
+	/// Whether all [`Receiver`]s were dropped.
+	#[must_use]
+	pub fn is_closed(&self) -> bool {
+		self.watch.state.lock().receivers == 0
+	}
+}
+
+impl<T> Drop for Sender<'_, T> {
+	fn drop(&mut self) {
+		let mut state = self.watch.state.lock();
+		state.closed = true;
+		let waiting = state.waiters.len();
+		drop(state);
+		self.watch.wake(waiting);
+	}
+}
+
+/// Shows only the version, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Sender<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Sender")
+			.field("version", &self.watch.state.lock().version)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A receiving half of a watch channel, which remembers which version of the value it has seen.
+///
+/// Cloning it clones that too.
+pub struct Receiver<'a, T> {
+	watch: StateRef<'a, Watch<T>>,
+	seen: u64,
+}
+
+impl<'a, T> Receiver<'a, T> {
+	/// Borrows the latest value, without marking it as seen.
+	#[must_use]
+	pub fn borrow(&self) -> Ref<'_, T> {
+		Ref {
+			state: self.watch.state.lock(),
+		}
+	}
+
+	/// Borrows the latest value and marks it as seen.
+	#[must_use]
+	pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+		let state = self.watch.state.lock();
+		self.seen = state.version;
+		Ref { state }
+	}
+
+	/// Whether there is a value that wasn't seen yet.
+	///
+	/// # Errors
+	///
+	/// Iff there isn't and the [`Sender`] was dropped.
+	pub fn has_changed(&self) -> Result<bool, RecvError> {
+		let state = self.watch.state.lock();
+		if state.version != self.seen {
+			Ok(true)
+		} else if state.closed {
+			Err(RecvError)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Returns a [`Future`] that completes once there is a value that wasn't seen yet, and marks it as seen.
+	///
+	/// Only the latest value can be borrowed afterwards, so intermediate changes may be skipped.
+	/// It completes with [`RecvError`] if there is none and the [`Sender`] was dropped.
+	#[must_use]
+	pub fn changed(&mut self) -> Changed<'_, 'a, T> {
+		Changed {
+			receiver: self,
+			waiter: Waiter::new(),
+			registered: false,
+		}
+	}
+}
+
+impl<T> Clone for Receiver<'_, T> {
+	fn clone(&self) -> Self {
+		self.watch.state.lock().receivers += 1;
+		Self {
+			watch: self.watch.clone(),
+			seen: self.seen,
+		}
+	}
+}
+
+impl<T> Drop for Receiver<'_, T> {
+	fn drop(&mut self) {
+		self.watch.state.lock().receivers -= 1;
+	}
+}
+
+/// Shows only the seen version, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Receiver<'_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Receiver")
+			.field("seen", &self.seen)
+			.finish_non_exhaustive()
+	}
+}
+
+/// A [`Future`] that completes once the value changed. See [`Receiver::changed`].
+pub struct Changed<'r, 'a, T> {
+	receiver: &'r mut Receiver<'a, T>,
+	/// Pinned, structurally.
+	waiter: Waiter,
+	/// Whether `waiter` may be linked, to skip locking on drop otherwise.
+	registered: bool,
+}
+
+impl<T> Future for Changed<'_, '_, T> {
+	type Output = Result<(), RecvError>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = unsafe {
+			//SAFETY: Only `waiter` is structurally pinned, and it's not moved.
+			self.get_unchecked_mut()
+		};
+		let waiter = unsafe {
+			//SAFETY: Structurally pinned.
+			Pin::new_unchecked(&this.waiter)
+		};
+
+		let receiver = &mut *this.receiver;
+		let mut state = receiver.watch.state.lock();
+		let output = if state.version != receiver.seen {
+			receiver.seen = state.version;
+			Ok(())
+		} else if state.closed {
+			Err(RecvError)
+		} else {
+			unsafe {
+				//SAFETY: `waiter` is only used with this list, and removed on drop.
+				state.waiters.register(waiter, cx.waker());
+			}
+			this.registered = true;
+			return Poll::Pending;
+		};
+
+		if this.registered {
+			unsafe {
+				//SAFETY: As above.
+				state.waiters.remove(waiter);
+			}
+			this.registered = false;
+		}
+		Poll::Ready(output)
+	}
+}
+
+impl<T> Drop for Changed<'_, '_, T> {
+	fn drop(&mut self) {
+		if self.registered {
+			let waiter = unsafe {
+				//SAFETY: `Drop` runs in place, so this is still pinned.
+				Pin::new_unchecked(&self.waiter)
+			};
+			unsafe {
+				//SAFETY: `waiter` is only used with this list.
+				self.receiver.watch.state.lock().waiters.remove(waiter);
+			}
+		}
+	}
+}
+
+/// Shows only the seen version, to not require `T: Debug`.
+impl<T> core::fmt::Debug for Changed<'_, '_, T> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("Changed")
+			.field("seen", &self.receiver.seen)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,57 @@
+//! A [`Future`] that lets other tasks run once.
+
+use core::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+};
+#[cfg(feature = "futures-core")]
+use futures_core::FusedFuture;
+
+/// Creates a [`YieldNow`], which returns [`Poll::Pending`] once and then completes.
+///
+/// Await this in long-running loops to let a single-threaded executor run other tasks in between.
+#[must_use]
+pub fn yield_now() -> YieldNow {
+	YieldNow(YieldState::Initial)
+}
+
+/// A [`Future`] that wakes itself and returns [`Poll::Pending`] on its first poll, and completes on the next one.
+///
+/// > Waking before returning means the task is rescheduled right away, but behind the other tasks that are ready already,
+/// > at least with FIFO executors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct YieldNow(YieldState);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum YieldState {
+	Initial,
+	Yielded,
+	Done,
+}
+
+impl Future for YieldNow {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		match self.0 {
+			YieldState::Initial => {
+				self.0 = YieldState::Yielded;
+				cx.waker().wake_by_ref();
+				Poll::Pending
+			}
+			YieldState::Yielded => {
+				self.0 = YieldState::Done;
+				Poll::Ready(())
+			}
+			YieldState::Done => panic!("`YieldNow` was previously completed."),
+		}
+	}
+}
+
+#[cfg(feature = "futures-core")]
+impl FusedFuture for YieldNow {
+	fn is_terminated(&self) -> bool {
+		self.0 == YieldState::Done
+	}
+}
@@ -2,4 +2,4 @@
 
 pub const BRANCH: &str = "develop";
 pub const USER: &str = "Tamschi";
-pub const RUST_VERSION: &str = "1.57";
+pub const RUST_VERSION: &str = "1.64";